@@ -0,0 +1,69 @@
+use age::x25519;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// How every binary age file begins
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+
+static KEY: OnceLock<Result<Option<x25519::Identity>, String>> = OnceLock::new();
+
+/// The key stored files are encrypted with: an age identity
+/// (`AGE-SECRET-KEY-1...`) in `DEVLOG_STORAGE_KEY`, or in the identity file
+/// named by `DEVLOG_STORAGE_KEY_FILE`. None when neither is set, and files are
+/// written in the clear.
+pub fn key() -> Result<Option<&'static x25519::Identity>> {
+    KEY.get_or_init(|| load_key().map_err(|e| format!("{:#}", e)))
+        .as_ref()
+        .map(Option::as_ref)
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+fn load_key() -> Result<Option<x25519::Identity>> {
+    let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+    let secret = if let Some(key) = var("DEVLOG_STORAGE_KEY") {
+        key
+    } else if let Some(path) = var("DEVLOG_STORAGE_KEY_FILE") {
+        let file = fs::read_to_string(&path).with_context(|| format!("Failed to read storage key file {}", path))?;
+        file.lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+            .with_context(|| format!("No AGE-SECRET-KEY in {}", path))?
+            .to_string()
+    } else {
+        return Ok(None);
+    };
+    x25519::Identity::from_str(secret.trim())
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Invalid storage key: {}", e))
+}
+
+/// Whether a stored file's content was encrypted by `seal`
+pub fn is_sealed(content: &[u8]) -> bool {
+    content.starts_with(AGE_MAGIC)
+}
+
+/// Encrypt content about to be stored, if a storage key is set
+pub fn seal(plaintext: Vec<u8>) -> Result<Vec<u8>> {
+    match key()? {
+        Some(key) => age::encrypt(&key.to_public(), &plaintext).context("Failed to encrypt for storage"),
+        None => Ok(plaintext),
+    }
+}
+
+/// The plaintext of stored content, decrypting it if it was sealed
+pub fn open(content: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_sealed(&content) {
+        return Ok(content);
+    }
+    let key = key()?.context("File is encrypted, and neither DEVLOG_STORAGE_KEY nor DEVLOG_STORAGE_KEY_FILE is set")?;
+    age::decrypt(key, &content).context("Failed to decrypt stored file")
+}
+
+/// Read a stored file, decrypting it if it was sealed
+pub fn read(path: &Path) -> Result<Vec<u8>> {
+    let content = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    open(content).with_context(|| format!("Failed to read {}", path.display()))
+}
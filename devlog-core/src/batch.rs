@@ -0,0 +1,17 @@
+use crate::models::DevlogOutput;
+use anyhow::{Context, Result};
+
+/// Parse a batch ingest body: either a JSON array of sessions or NDJSON, one per line
+pub fn parse_batch(body: &str) -> Result<Vec<DevlogOutput>> {
+    if body.trim_start().starts_with('[') {
+        return serde_json::from_str(body).context("Invalid JSON array of sessions");
+    }
+
+    body.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| format!("Invalid session on line {}", i + 1))
+        })
+        .collect()
+}
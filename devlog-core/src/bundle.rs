@@ -0,0 +1,277 @@
+//! Day bundles: the sessions a project stored on one day, rolled by
+//! [`FileStore::bundle_days`](crate::FileStore::bundle_days) into a single
+//! `<YYYY-MM-DD>.bundle` file in their month's directory, so a store of many
+//! small sessions walks and backs up as a few files. A bundle is a line naming
+//! the format, a JSON index line listing each member with where its content
+//! lies and its manifest line, then the members' content, each exactly as it
+//! was stored on its own (compressed or encrypted if it was). A bundled
+//! session is addressed as if its bundle were a directory,
+//! `.../2025/03/2025-03-14.bundle/2025-03-14-093000-abcdef12.json`, which
+//! [`read_session`](crate::store::read_session) and the other readers follow.
+
+use crate::manifest::ManifestEntry;
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::SystemTime;
+
+pub const EXTENSION: &str = "bundle";
+
+/// The first line of every bundle
+const FORMAT: &str = "DEVLOG-BUNDLE 1";
+
+/// When receivers roll their older days into bundles
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BundlePolicy {
+    /// Days are bundled once they are this many days old, so late pushes
+    /// still land as files of their own
+    #[serde(default = "default_after_days")]
+    pub after_days: u32,
+    /// Hours between bundling runs
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+}
+
+fn default_after_days() -> u32 {
+    2
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+impl Default for BundlePolicy {
+    fn default() -> Self {
+        Self {
+            after_days: default_after_days(),
+            interval_hours: default_interval_hours(),
+        }
+    }
+}
+
+impl BundlePolicy {
+    /// The newest day to bundle; today never is
+    pub fn last_day(&self) -> NaiveDate {
+        Utc::now().date_naive() - Duration::days(self.after_days.max(1) as i64)
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_hours.max(1) * 3600)
+    }
+}
+
+/// What a bundling run did
+#[derive(Debug, Default)]
+pub struct BundleReport {
+    pub sessions: usize,
+    pub days: usize,
+}
+
+impl fmt::Display for BundleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bundled {} sessions into {} day bundles", self.sessions, self.days)
+    }
+}
+
+/// A session in a bundle's index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Member {
+    pub name: String,
+    /// Where its content starts, counted from the end of the index
+    pub offset: u64,
+    pub size: u64,
+    /// Its manifest line, which bundles keep in place of `manifest.jsonl`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Index {
+    members: Vec<Member>,
+}
+
+/// A session read out of a bundle, or about to be written into one
+#[derive(Debug, Clone)]
+pub struct Bundled {
+    pub name: String,
+    /// As stored: compressed and encrypted as the session's own file would be
+    pub content: Vec<u8>,
+    pub manifest: Option<ManifestEntry>,
+}
+
+/// Whether a path is a bundle file
+pub fn is_bundle(path: &Path) -> bool {
+    path.extension().map(|e| e == EXTENSION).unwrap_or(false) && path.is_file()
+}
+
+/// The bundle a session path points into, if it is a bundled one
+pub fn containing(path: &Path) -> Option<&Path> {
+    path.parent().filter(|parent| is_bundle(parent))
+}
+
+/// The name of a day's bundle
+pub fn file_name(day: &str) -> String {
+    format!("{}.{}", day, EXTENSION)
+}
+
+/// Size and modification time of a stored session file; a bundled one has its
+/// own size and its bundle's time
+pub fn stat(path: &Path) -> Result<(u64, Option<SystemTime>)> {
+    if let Some(bundle) = containing(path) {
+        let member = member(path)?;
+        let modified = fs::metadata(bundle).ok().and_then(|m| m.modified().ok());
+        return Ok((member.size, modified));
+    }
+    let metadata = path
+        .metadata()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok((metadata.len(), metadata.modified().ok()))
+}
+
+/// The members of a bundle
+pub fn members(bundle: &Path) -> Result<Vec<Member>> {
+    open(bundle).map(|(_, members, _)| members)
+}
+
+/// The manifest lines a bundle keeps for its members, by member name
+pub fn manifest(bundle: &Path) -> Result<HashMap<String, ManifestEntry>> {
+    Ok(members(bundle)?
+        .into_iter()
+        .filter_map(|member| {
+            let size = member.size;
+            member.manifest.filter(|entry| entry.size == size).map(|entry| (member.name, entry))
+        })
+        .collect())
+}
+
+/// The stored content of a bundled session
+pub fn read_member(path: &Path) -> Result<Vec<u8>> {
+    let bundle = containing(path).with_context(|| format!("{} is not in a bundle", path.display()))?;
+    let (mut reader, members, start) = open(bundle)?;
+    let member = find(&members, path)?;
+    read_content(&mut reader, start, member).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Every session in a bundle, with its content
+pub fn read_all(bundle: &Path) -> Result<Vec<Bundled>> {
+    let (mut reader, members, start) = open(bundle)?;
+    members
+        .into_iter()
+        .map(|member| {
+            let content = read_content(&mut reader, start, &member)
+                .with_context(|| format!("Failed to read {} from {}", member.name, bundle.display()))?;
+            Ok(Bundled {
+                name: member.name,
+                content,
+                manifest: member.manifest,
+            })
+        })
+        .collect()
+}
+
+/// Write a bundle holding these sessions, replacing any there was, via a
+/// temporary file renamed into place. With none, the bundle is removed.
+pub fn write(bundle: &Path, sessions: &[Bundled]) -> Result<()> {
+    if sessions.is_empty() {
+        return match fs::remove_file(bundle) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", bundle.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let mut offset = 0;
+    let index = Index {
+        members: sessions
+            .iter()
+            .map(|session| {
+                let member = Member {
+                    name: session.name.clone(),
+                    offset,
+                    size: session.content.len() as u64,
+                    manifest: session.manifest.clone(),
+                };
+                offset += member.size;
+                member
+            })
+            .collect(),
+    };
+
+    let mut content = format!("{}\n", FORMAT).into_bytes();
+    serde_json::to_writer(&mut content, &index).context("Failed to serialize bundle index")?;
+    content.push(b'\n');
+    for session in sessions {
+        content.extend_from_slice(&session.content);
+    }
+
+    let mut tmp = bundle.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, content).with_context(|| format!("Failed to write {}", bundle.display()))?;
+    fs::rename(&tmp, bundle).with_context(|| format!("Failed to write {}", bundle.display()))
+}
+
+/// Put a session into a bundle, in place of any of the same name
+pub fn replace(bundle: &Path, session: Bundled) -> Result<()> {
+    let mut sessions = read_all(bundle)?;
+    match sessions.iter_mut().find(|s| s.name == session.name) {
+        Some(old) => *old = session,
+        None => {
+            sessions.push(session);
+            sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+    write(bundle, &sessions)
+}
+
+/// Take a session out of its bundle, removing the bundle once it is empty
+pub fn remove(path: &Path) -> Result<()> {
+    let bundle = containing(path).with_context(|| format!("{} is not in a bundle", path.display()))?;
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let mut sessions = read_all(bundle)?;
+    sessions.retain(|s| s.name != name);
+    write(bundle, &sessions)
+}
+
+/// A bundle's reader, its members, and where their content starts
+fn open(bundle: &Path) -> Result<(BufReader<File>, Vec<Member>, u64)> {
+    let file = File::open(bundle).with_context(|| format!("Failed to read {}", bundle.display()))?;
+    let mut reader = BufReader::new(file);
+    let (mut format, mut index) = (String::new(), String::new());
+    reader
+        .read_line(&mut format)
+        .and_then(|_| reader.read_line(&mut index))
+        .with_context(|| format!("Failed to read {}", bundle.display()))?;
+    if format.trim_end() != FORMAT {
+        anyhow::bail!("Not a session bundle: {}", bundle.display());
+    }
+    let start = (format.len() + index.len()) as u64;
+    let index: Index =
+        serde_json::from_str(&index).with_context(|| format!("Failed to parse the index of {}", bundle.display()))?;
+    Ok((reader, index.members, start))
+}
+
+fn member(path: &Path) -> Result<Member> {
+    let bundle = containing(path).with_context(|| format!("{} is not in a bundle", path.display()))?;
+    find(&members(bundle)?, path).cloned()
+}
+
+fn find<'a>(members: &'a [Member], path: &Path) -> Result<&'a Member> {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    members
+        .iter()
+        .find(|member| member.name == name)
+        .with_context(|| format!("No such bundled session: {}", path.display()))
+}
+
+fn read_content(reader: &mut BufReader<File>, start: u64, member: &Member) -> std::io::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(start + member.offset))?;
+    let mut content = vec![0; member.size as usize];
+    reader.read_exact(&mut content)?;
+    Ok(content)
+}
@@ -0,0 +1,92 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// How every zstd frame begins
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Appended to the `.json` of a compressed session file
+pub const EXTENSION: &str = "zst";
+
+/// Devlogs compress well; higher levels cost far more time for little gain
+const LEVEL: i32 = 9;
+
+static ENABLED: OnceLock<Result<bool, String>> = OnceLock::new();
+
+/// Whether sessions are stored compressed: `DEVLOG_STORAGE_COMPRESSION` set to
+/// `zstd`. Unset or `none` stores them as plain JSON.
+pub fn enabled() -> Result<bool> {
+    ENABLED
+        .get_or_init(|| {
+            match std::env::var("DEVLOG_STORAGE_COMPRESSION").unwrap_or_default().trim() {
+                "" | "none" => Ok(false),
+                "zstd" => Ok(true),
+                other => Err(format!("Unknown DEVLOG_STORAGE_COMPRESSION {:?}, expected zstd or none", other)),
+            }
+        })
+        .clone()
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+/// What compressing a store's existing sessions did
+#[derive(Debug, Default, Serialize)]
+pub struct CompactReport {
+    pub files: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl fmt::Display for CompactReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "compressed {} sessions from {} to {} bytes",
+            self.files, self.bytes_before, self.bytes_after
+        )
+    }
+}
+
+/// Whether stored content was compressed by `compress`
+pub fn is_compressed(content: &[u8]) -> bool {
+    content.starts_with(ZSTD_MAGIC)
+}
+
+pub fn compress(content: &[u8]) -> Result<Vec<u8>> {
+    zstd::encode_all(content, LEVEL).context("Failed to compress for storage")
+}
+
+/// The uncompressed content, decompressing it if it was compressed
+pub fn decompress(content: Vec<u8>) -> Result<Vec<u8>> {
+    if !is_compressed(&content) {
+        return Ok(content);
+    }
+    zstd::decode_all(content.as_slice()).context("Failed to decompress stored file")
+}
+
+/// Where a `.json` session file is written: with `.zst` added when sessions are
+/// stored compressed
+pub fn storage_path(path: PathBuf) -> Result<PathBuf> {
+    if !enabled()? {
+        return Ok(path);
+    }
+    let mut path = path.into_os_string();
+    path.push(".");
+    path.push(EXTENSION);
+    Ok(path.into())
+}
+
+/// Whether a file is a stored devlog, compressed or not
+pub fn is_session_file(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    name.ends_with(".json") || name.ends_with(".json.zst")
+}
+
+/// A stored devlog's name without `.json` or `.json.zst`
+pub fn session_stem(name: &str) -> &str {
+    name.strip_suffix(".zst")
+        .unwrap_or(name)
+        .strip_suffix(".json")
+        .unwrap_or(name)
+}
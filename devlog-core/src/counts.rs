@@ -0,0 +1,87 @@
+use crate::models::{ContentCounts, ConversationEntry, DevlogOutput};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What `devlog stats` adds up from one session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub prompts: usize,
+    pub tool_calls: usize,
+    pub files_touched: usize,
+    pub prompt_words: usize,
+    pub response_words: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
+impl SessionStats {
+    pub fn of(devlog: &DevlogOutput) -> Self {
+        let mut stats = SessionStats::default();
+
+        for entry in &devlog.conversation {
+            match entry {
+                ConversationEntry::User { .. } => stats.prompts += 1,
+                ConversationEntry::Assistant { usage, .. } => {
+                    if let Some(ref usage) = usage {
+                        stats.input_tokens += usage.input_tokens.unwrap_or(0);
+                        stats.output_tokens += usage.output_tokens.unwrap_or(0);
+                        stats.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
+                        stats.cache_write_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
+                    }
+                }
+                ConversationEntry::ToolSummary { actions } => stats.tool_calls += actions.len(),
+            }
+        }
+
+        // A stats-only session's text is gone, so its counts were kept instead
+        let counts = devlog.stats_only.unwrap_or_else(|| content_counts(devlog));
+        stats.prompt_words = counts.prompt_words;
+        stats.response_words = counts.response_words;
+        stats.files_touched = counts.files_touched;
+        stats
+    }
+}
+
+/// Words in the prompts and responses, and the files the tools touched
+pub fn content_counts(devlog: &DevlogOutput) -> ContentCounts {
+    let mut counts = ContentCounts::default();
+    let mut files: HashSet<String> = HashSet::new();
+    for entry in &devlog.conversation {
+        match entry {
+            ConversationEntry::User { content, .. } => counts.prompt_words += count_words(content),
+            ConversationEntry::Assistant { content, .. } => counts.response_words += count_words(content),
+            ConversationEntry::ToolSummary { actions } => {
+                // Extract file paths from tool actions
+                for action in actions {
+                    if let Some(file) = extract_file_from_action(action) {
+                        files.insert(file);
+                    }
+                }
+            }
+        }
+    }
+    counts.files_touched = files.len();
+    counts
+}
+
+fn count_words(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+fn extract_file_from_action(action: &str) -> Option<String> {
+    file_action(action).map(|(_, file)| file.to_string())
+}
+
+/// The verb and file of a tool action that touched one, e.g. `("edited", "src/main.rs")`
+pub fn file_action(action: &str) -> Option<(&str, &str)> {
+    // Actions look like: "edited src/main.rs", "read config.json", "created foo.txt"
+    let verbs = ["edited", "read", "created"];
+    verbs.into_iter().find_map(|verb| {
+        action
+            .strip_prefix(verb)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .map(|file| (verb, file))
+    })
+}
@@ -0,0 +1,120 @@
+//! Git remote URLs: a canonical form for grouping projects, and links to the web
+//! UI of the forge hosting the repo.
+
+/// Forges whose URL layout is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Forge {
+    GitHub,
+    GitLab,
+}
+
+/// Canonical `host/owner/repo` form of a remote URL, so that
+/// `git@github.com:me/foo.git`, `ssh://git@github.com:22/me/foo` and
+/// `https://github.com/me/foo` all compare equal.
+///
+/// Drops the scheme, credentials, port and a trailing `.git`, and lowercases the
+/// result. The receiver's `normalize_remote` SQL macro applies the same rules.
+pub fn normalize_remote(remote: &str) -> String {
+    let (host, path) = split_remote(remote);
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    if path.is_empty() {
+        host.to_lowercase()
+    } else {
+        format!("{}/{}", host, path).to_lowercase()
+    }
+}
+
+/// Browsable `https://host/owner/repo` for a remote URL, if it is hosted on GitHub or GitLab.
+///
+/// Accepts the scp-like SSH form (`git@github.com:me/foo.git`), `ssh://` and
+/// `http(s)://` URLs, with or without credentials and a trailing `.git`.
+pub fn web_url(remote: &str) -> Option<String> {
+    web_url_and_forge(remote).map(|(url, _)| url)
+}
+
+/// Page for a single commit on the forge
+pub fn commit_url(remote: &str, hash: &str) -> Option<String> {
+    let (base, forge) = web_url_and_forge(remote)?;
+    Some(match forge {
+        Forge::GitHub => format!("{}/commit/{}", base, hash),
+        Forge::GitLab => format!("{}/-/commit/{}", base, hash),
+    })
+}
+
+/// Page for a branch's tree on the forge
+pub fn branch_url(remote: &str, branch: &str) -> Option<String> {
+    let (base, forge) = web_url_and_forge(remote)?;
+    Some(match forge {
+        Forge::GitHub => format!("{}/tree/{}", base, branch),
+        Forge::GitLab => format!("{}/-/tree/{}", base, branch),
+    })
+}
+
+/// Page for an issue, e.g. `#456`, on the forge
+pub fn issue_url(remote: &str, number: &str) -> Option<String> {
+    let (base, forge) = web_url_and_forge(remote)?;
+    Some(match forge {
+        Forge::GitHub => format!("{}/issues/{}", base, number),
+        Forge::GitLab => format!("{}/-/issues/{}", base, number),
+    })
+}
+
+/// Owner and name of a repository hosted on GitHub, for its API
+pub fn github_repo(remote: &str) -> Option<(String, String)> {
+    let (_, forge) = web_url_and_forge(remote)?;
+    if forge != Forge::GitHub {
+        return None;
+    }
+    let canonical = normalize_remote(remote);
+    let (_, path) = canonical.split_once('/')?;
+    let (owner, repo) = path.split_once('/')?;
+    Some((owner.to_string(), repo.to_string()))
+}
+
+fn web_url_and_forge(remote: &str) -> Option<(String, Forge)> {
+    let canonical = normalize_remote(remote);
+    let (host, path) = canonical.split_once('/')?;
+
+    let forge = if host == "github.com" || host.starts_with("github.") {
+        Forge::GitHub
+    } else if host.contains("gitlab") {
+        Forge::GitLab
+    } else {
+        return None;
+    };
+
+    if path.is_empty() {
+        return None;
+    }
+
+    Some((format!("https://{}", canonical), forge))
+}
+
+/// Host and path of a remote URL, without scheme, credentials or port
+fn split_remote(remote: &str) -> (&str, &str) {
+    let mut rest = remote.trim();
+    if let Some((_, after_scheme)) = rest.split_once("://") {
+        rest = after_scheme;
+    }
+    if let Some((user, after_user)) = rest.split_once('@') {
+        if !user.contains('/') {
+            rest = after_user;
+        }
+    }
+
+    match rest.find(['/', ':']) {
+        Some(i) if rest[i..].starts_with(':') => {
+            // `host:22/owner/repo` (ssh:// with a port) or scp-like `host:owner/repo`
+            let after = &rest[i + 1..];
+            let path = match after.split_once('/') {
+                Some((port, path)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => path,
+                _ => after,
+            };
+            (&rest[..i], path)
+        }
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, ""),
+    }
+}
@@ -0,0 +1,54 @@
+use crate::forge::normalize_remote;
+use crate::models::{DevlogOutput, GitInfo};
+use crate::store::project_name;
+
+/// What makes two sessions part of the same project in stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProjectGrouping {
+    /// The project directory's name, as laid out in storage
+    #[default]
+    Directory,
+    /// The canonical remote URL, so clones in differently named folders count
+    /// as one project; sessions without a remote fall back to the directory name
+    Remote,
+}
+
+impl ProjectGrouping {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "remote" => Self::Remote,
+            _ => Self::Directory,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Directory => "directory",
+            Self::Remote => "remote",
+        }
+    }
+
+    /// The project a session counts towards
+    pub fn project_key(&self, output: &DevlogOutput) -> String {
+        self.key(output.project_path(), output.git.as_ref())
+    }
+
+    /// The project a session in `project_path`, with these git details, counts towards
+    pub fn key(&self, project_path: &str, git: Option<&GitInfo>) -> String {
+        let directory = || project_name(project_path);
+        match self {
+            Self::Directory => directory(),
+            Self::Remote => remote_key(git).unwrap_or_else(directory),
+        }
+    }
+}
+
+/// Canonical form of `origin`, or of the first remote when there is no origin
+fn remote_key(git: Option<&GitInfo>) -> Option<String> {
+    let git = git?;
+    let url = git
+        .remote
+        .as_deref()
+        .or_else(|| git.remotes.first().map(|r| r.url.as_str()))?;
+    Some(normalize_remote(url))
+}
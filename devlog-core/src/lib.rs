@@ -0,0 +1,38 @@
+//! The devlog session format and the pieces of storage every receiver shares.
+//!
+//! The CLI writes [`DevlogOutput`] files and pushes them; both receivers accept
+//! exactly these types, so clients never need to know which one they talk to.
+
+pub mod at_rest;
+pub mod batch;
+pub mod bundle;
+pub mod compression;
+pub mod counts;
+pub mod forge;
+pub mod grouping;
+pub mod manifest;
+pub mod metrics;
+pub mod models;
+pub mod purge;
+pub mod receipt;
+pub mod retention;
+pub mod store;
+pub mod tls;
+pub mod validate;
+pub mod webhook;
+
+pub use batch::parse_batch;
+pub use bundle::{BundlePolicy, BundleReport};
+pub use counts::SessionStats;
+pub use grouping::ProjectGrouping;
+pub use manifest::ManifestEntry;
+pub use metrics::Metrics;
+pub use models::{
+    CommitInfo, ContentCounts, ConversationEntry, DevlogOutput, EncryptedSession, GitAuthor, GitInfo, GitRemote, Note, ParentRepo,
+    RepoKind, SessionHeader, TicketLink, TokenUsage, TranscriptParams,
+};
+pub use purge::{PurgeReport, PurgeRequest, PurgedSession};
+pub use receipt::{Receipt, ReceiptSigner};
+pub use retention::{PruneReport, RetentionPolicy, SessionFile};
+pub use store::FileStore;
+pub use webhook::SessionEvent;
@@ -0,0 +1,173 @@
+//! `manifest.jsonl`, kept in each directory of sessions of a
+//! [`FileStore`](crate::FileStore): a line per devlog, appended as it is
+//! stored, holding what listings, filters and stats need from it. A line is
+//! only trusted while its file is still there at the size it records; other
+//! files are opened as before, so a missing or stale manifest costs time, not
+//! results.
+
+use crate::at_rest;
+use crate::bundle;
+use crate::counts::SessionStats;
+use crate::models::{DevlogOutput, GitInfo, SessionHeader};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "manifest.jsonl";
+
+/// One stored devlog, as its manifest line describes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// The devlog's file name, within the manifest's directory
+    pub file: String,
+    /// Size of the file when the line was written
+    pub size: u64,
+    #[serde(flatten)]
+    pub header: SessionHeader,
+    /// The session's git details, without its commits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitInfo>,
+    pub entry_count: usize,
+    pub stats: SessionStats,
+}
+
+impl ManifestEntry {
+    pub fn new(file: String, size: u64, output: &DevlogOutput) -> Self {
+        Self {
+            file,
+            size,
+            header: output.header(),
+            git: output.git.as_ref().map(|git| GitInfo {
+                commits: Vec::new(),
+                ..git.clone()
+            }),
+            entry_count: output.conversation.len(),
+            stats: SessionStats::of(output),
+        }
+    }
+
+    /// The line for the devlog stored at `path`
+    pub fn for_file(path: &Path, output: &DevlogOutput) -> Result<Self> {
+        let file = path.file_name().context("Devlog path has no file name")?;
+        let (size, _) = bundle::stat(path)?;
+        Ok(Self::new(file.to_string_lossy().to_string(), size, output))
+    }
+
+    /// As [`DevlogOutput::project_path`]
+    pub fn project_path(&self) -> &str {
+        self.git
+            .as_ref()
+            .and_then(|g| g.parent_repo.as_ref())
+            .map(|p| p.path.as_str())
+            .unwrap_or(&self.header.project_dir)
+    }
+}
+
+/// Note a devlog just written to `path` in its directory's manifest
+pub fn record(path: &Path, output: &DevlogOutput) -> Result<()> {
+    remember(path, &ManifestEntry::for_file(path, output)?)
+}
+
+/// Add the line for the devlog at `path` to its directory's manifest, e.g. once
+/// a session missing from it has been read anyway. Nothing is kept while
+/// sessions are encrypted at rest, as the manifest would hold their tags and
+/// tickets in plain text. Bundles are left alone; they get their members'
+/// lines as they are written.
+pub fn remember(path: &Path, entry: &ManifestEntry) -> Result<()> {
+    if at_rest::key()?.is_some() || bundle::containing(path).is_some() {
+        return Ok(());
+    }
+    match path.parent() {
+        Some(dir) => append(dir, entry),
+        None => Ok(()),
+    }
+}
+
+/// Add a line to a directory's manifest
+pub fn append(dir: &Path, entry: &ManifestEntry) -> Result<()> {
+    let mut line = serde_json::to_string(entry).context("Failed to serialize manifest entry")?;
+    line.push('\n');
+    let manifest = dir.join(MANIFEST_FILE);
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&manifest)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to write {}", manifest.display()))
+}
+
+/// Replace a directory's manifest with these lines, removing it when there are none
+pub fn write<'a>(dir: &Path, entries: impl IntoIterator<Item = &'a ManifestEntry>) -> Result<()> {
+    let manifest = dir.join(MANIFEST_FILE);
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry).context("Failed to serialize manifest entry")?);
+        content.push('\n');
+    }
+    if content.is_empty() {
+        return match fs::remove_file(&manifest) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", manifest.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+    fs::write(&manifest, content).with_context(|| format!("Failed to write {}", manifest.display()))
+}
+
+/// The latest line for each file in a directory's manifest; none when it has
+/// no manifest. Lines that don't parse, such as one cut short, are passed over.
+pub fn read(dir: &Path) -> Result<HashMap<String, ManifestEntry>> {
+    let path = dir.join(MANIFEST_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<ManifestEntry>(line).ok())
+        .map(|entry| (entry.file.clone(), entry))
+        .collect())
+}
+
+/// Drop the lines of files no longer in a directory, such as deleted or
+/// pruned sessions, so the manifest keeps nothing of them
+pub fn forget_missing(dir: &Path) -> Result<()> {
+    let entries = read(dir)?;
+    if entries.values().all(|entry| dir.join(&entry.file).exists()) {
+        return Ok(());
+    }
+    write(dir, entries.values().filter(|entry| dir.join(&entry.file).exists()))
+}
+
+/// Manifests read so far, by directory, so a walk over many files reads each
+/// directory's manifest once
+#[derive(Default)]
+pub struct Manifests {
+    dirs: HashMap<PathBuf, HashMap<String, ManifestEntry>>,
+}
+
+impl Manifests {
+    /// The manifest line for a devlog file, if there is one and the file is
+    /// still the size it records. A bundled session's line comes from its
+    /// bundle's index.
+    pub fn entry(&mut self, path: &Path) -> Option<&ManifestEntry> {
+        let dir = path.parent()?;
+        let file = path.file_name()?.to_string_lossy();
+        let bundled = bundle::containing(path).is_some();
+        if !self.dirs.contains_key(dir) {
+            let lines = if bundled { bundle::manifest(dir) } else { read(dir) };
+            self.dirs.insert(dir.to_path_buf(), lines.unwrap_or_default());
+        }
+        let entry = self.dirs.get(dir)?.get(file.as_ref())?;
+        if bundled {
+            return Some(entry);
+        }
+        let size = fs::metadata(path).ok()?.len();
+        (entry.size == size).then_some(entry)
+    }
+}
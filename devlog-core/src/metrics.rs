@@ -0,0 +1,102 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the insert latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Counters kept by both receivers and served from `/metrics` in the Prometheus
+/// text format, so either can be scraped with the same dashboards and alerts
+#[derive(Debug, Default)]
+pub struct Metrics {
+    ingest_requests: AtomicU64,
+    sessions_ingested: AtomicU64,
+    ingest_errors: AtomicU64,
+    ingest_rejected: AtomicU64,
+    sessions_duplicate: AtomicU64,
+    sessions_pruned: AtomicU64,
+    /// Cumulative counts per `LATENCY_BUCKETS` entry
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_count: AtomicU64,
+    latency_sum_micros: AtomicU64,
+}
+
+impl Metrics {
+    /// A successful `/ingest` or `/ingest/batch` request storing `sessions` sessions
+    pub fn record_ingest(&self, sessions: usize, elapsed: Duration) {
+        self.ingest_requests.fetch_add(1, Ordering::Relaxed);
+        self.sessions_ingested.fetch_add(sessions as u64, Ordering::Relaxed);
+
+        let seconds = elapsed.as_secs_f64();
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_ingest_error(&self) {
+        self.ingest_requests.fetch_add(1, Ordering::Relaxed);
+        self.ingest_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// An ingest request turned away because the server was too busy
+    pub fn record_ingest_rejected(&self) {
+        self.ingest_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sessions skipped because identical content was already stored
+    pub fn record_duplicates(&self, sessions: usize) {
+        self.sessions_duplicate.fetch_add(sessions as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_pruned(&self, sessions: usize) {
+        self.sessions_pruned.fetch_add(sessions as u64, Ordering::Relaxed);
+    }
+
+    /// Render every metric; `storage_bytes` is the current size of the store
+    pub fn render(&self, storage_bytes: u64) -> String {
+        let mut out = String::new();
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        let counters = [
+            ("devlog_ingest_requests_total", "Ingest requests received", &self.ingest_requests),
+            ("devlog_sessions_ingested_total", "Sessions stored by ingest requests", &self.sessions_ingested),
+            ("devlog_ingest_errors_total", "Ingest requests that failed", &self.ingest_errors),
+            ("devlog_ingest_rejected_total", "Ingest requests turned away while the server was busy", &self.ingest_rejected),
+            ("devlog_sessions_duplicate_total", "Sessions skipped as already stored", &self.sessions_duplicate),
+            ("devlog_sessions_pruned_total", "Sessions removed by retention", &self.sessions_pruned),
+        ];
+        for (name, help, counter) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, load(counter));
+        }
+
+        let name = "devlog_insert_duration_seconds";
+        let _ = writeln!(out, "# HELP {} Time taken to store ingested sessions", name);
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, load(bucket));
+        }
+        let count = load(&self.latency_count);
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let _ = writeln!(
+            out,
+            "{}_sum {}",
+            name,
+            load(&self.latency_sum_micros) as f64 / 1_000_000.0
+        );
+        let _ = writeln!(out, "{}_count {}", name, count);
+
+        let name = "devlog_storage_bytes";
+        let _ = writeln!(out, "# HELP {} Size of the session store on disk", name);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "{} {}", name, storage_bytes);
+
+        out
+    }
+}
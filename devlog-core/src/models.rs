@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A captured session, as written to `.devlog/` and sent to `/ingest`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DevlogOutput {
+    pub schema_version: String,
+    pub session_id: String,
+    pub timestamp: String,
+    pub machine_id: String,
+    pub project_dir: String,
+    pub git: Option<GitInfo>,
+    /// Labels added with `devlog tag`, e.g. `bug-hunt` or `refactor`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Annotations added afterwards with `devlog note`, apart from the conversation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    /// Issue-tracker tickets the prompts refer to, e.g. `PROJ-123` or `#456`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tickets: Vec<TicketLink>,
+    pub conversation: Vec<ConversationEntry>,
+    /// The Claude Code JSONL this was parsed from, on the ingesting machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_path: Option<String>,
+    /// Set when the session was captured stats-only: its text was hashed or
+    /// dropped, and these counts stand in for what stats read from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_only: Option<ContentCounts>,
+}
+
+impl DevlogOutput {
+    /// SHA-256 of the serialized session; re-pushing an unchanged session gives the same hash
+    pub fn content_hash(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        Sha256::digest(&json)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// The session's metadata, as filters look at it
+    pub fn header(&self) -> SessionHeader {
+        SessionHeader {
+            session_id: self.session_id.clone(),
+            timestamp: self.timestamp.clone(),
+            machine_id: self.machine_id.clone(),
+            project_dir: self.project_dir.clone(),
+            tags: self.tags.clone(),
+            tickets: self.tickets.clone(),
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Whether the prompts refer to `ticket`, ignoring case; `456` also finds `#456`
+    pub fn has_ticket(&self, ticket: &str) -> bool {
+        refers_to(&self.tickets, ticket)
+    }
+
+    /// Directory the session is attributed to: the main repository when it ran in
+    /// a linked worktree or a submodule, otherwise `project_dir`
+    pub fn project_path(&self) -> &str {
+        self.git
+            .as_ref()
+            .and_then(|g| g.parent_repo.as_ref())
+            .map(|p| p.path.as_str())
+            .unwrap_or(&self.project_dir)
+    }
+}
+
+/// The fields of a [`DevlogOutput`] that listings and filters look at. Read
+/// with serde skipping everything else, so a session can be passed over
+/// without building its conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionHeader {
+    pub session_id: String,
+    pub timestamp: String,
+    pub machine_id: String,
+    pub project_dir: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub tickets: Vec<TicketLink>,
+}
+
+impl SessionHeader {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// As [`DevlogOutput::has_ticket`]
+    pub fn has_ticket(&self, ticket: &str) -> bool {
+        refers_to(&self.tickets, ticket)
+    }
+}
+
+fn refers_to(tickets: &[TicketLink], ticket: &str) -> bool {
+    let ticket = ticket.trim();
+    tickets
+        .iter()
+        .any(|t| t.id.eq_ignore_ascii_case(ticket) || t.id.strip_prefix('#') == Some(ticket))
+}
+
+/// A ticket in an issue tracker, as a prompt referred to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TicketLink {
+    /// As written, e.g. `PROJ-123` or `#456`
+    pub id: String,
+    /// The ticket's page, when the tracker is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Query parameters of `POST /ingest/transcript`, whose body is the raw JSONL
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranscriptParams {
+    pub machine_id: String,
+    pub project_dir: String,
+    pub session_id: String,
+}
+
+/// A session encrypted by the client, as `POST /ingest/encrypted` takes them.
+/// The server sees only what it needs to file it; the devlog itself is an
+/// ASCII-armored age file that only the holders of the recipients' keys can read.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptedSession {
+    pub session_id: String,
+    pub machine_id: String,
+    /// Directory name of the project, not its full path
+    pub project: String,
+    pub timestamp: String,
+    pub ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitInfo {
+    /// URL of `origin`; every configured remote is listed in `remotes`
+    pub remote: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remotes: Vec<GitRemote>,
+    pub branch: String,
+    pub commit: String,
+    /// Whether the working tree had uncommitted changes when the session was ingested
+    #[serde(default)]
+    pub dirty: bool,
+    /// Changed tracked files (staged or not), relative to the repository root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_files: Vec<String>,
+    /// Untracked files; untracked directories are listed once, with a trailing `/`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub untracked_files: Vec<String>,
+    /// Commits made between the session's first and last entry, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commits: Vec<CommitInfo>,
+    /// Set when the session ran in a linked worktree or inside a submodule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_repo: Option<ParentRepo>,
+    /// `user.name`/`user.email` from git config; only recorded when enabled in `[git]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<GitAuthor>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitAuthor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoKind {
+    Worktree,
+    Submodule,
+}
+
+/// The repository a worktree or submodule belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentRepo {
+    pub kind: RepoKind,
+    /// Working directory of the main repository or superproject
+    pub path: String,
+    /// Where the session's directory sits within `path`; empty at its root
+    pub sub_path: String,
+}
+
+/// A named remote such as `origin`, `upstream` or a fork
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRemote {
+    pub name: String,
+    pub url: String,
+}
+
+/// A commit created while the session was running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub subject: String,
+    pub timestamp: String,
+    /// Paths changed relative to the first parent
+    pub files: Vec<String>,
+}
+
+/// A human annotation on a session, e.g. how it turned out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    /// When the note was added (ISO 8601)
+    pub timestamp: String,
+    pub text: String,
+}
+
+/// What a session's text amounted to, kept when the text itself is not
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentCounts {
+    pub prompt_words: usize,
+    pub response_words: usize,
+    pub files_touched: usize,
+}
+
+/// Token usage information from Claude API
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenUsage {
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub cache_creation_input_tokens: Option<u64>,
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+/// Output conversation entry
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type")]
+pub enum ConversationEntry {
+    #[serde(rename = "user")]
+    User {
+        timestamp: Option<String>,
+        content: String,
+        /// The transcript entry's `uuid`; absent in devlogs written before it was kept
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uuid: Option<String>,
+    },
+    #[serde(rename = "assistant")]
+    Assistant {
+        timestamp: Option<String>,
+        content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        usage: Option<TokenUsage>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        uuid: Option<String>,
+    },
+    #[serde(rename = "tool_summary")]
+    ToolSummary { actions: Vec<String> },
+}
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// What to delete for a data removal request: every session matching all of the
+/// fields set. At least one must be set, so an empty request cannot wipe a store.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PurgeRequest {
+    pub machine_id: Option<String>,
+    /// A project's name, or its full directory
+    pub project: Option<String>,
+    /// Sessions from this date (`YYYY-MM-DD`) or time (RFC 3339) on
+    pub since: Option<String>,
+    /// Sessions before this date or time
+    pub until: Option<String>,
+    /// List what would be removed without removing it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl PurgeRequest {
+    /// Check the request can be carried out before anything is deleted
+    pub fn validate(&self) -> Result<()> {
+        if self.machine_id.is_none() && self.project.is_none() && self.since.is_none() && self.until.is_none() {
+            anyhow::bail!("A purge needs at least one of machine_id, project, since or until");
+        }
+        if let (Some(since), Some(until)) = (self.since()?, self.until()?) {
+            if since >= until {
+                anyhow::bail!("since must be before until");
+            }
+        }
+        Ok(())
+    }
+
+    /// The `since` time, as UTC
+    pub fn since(&self) -> Result<Option<DateTime<Utc>>> {
+        self.since.as_deref().map(parse_time).transpose().context("Invalid since")
+    }
+
+    /// The `until` time, as UTC
+    pub fn until(&self) -> Result<Option<DateTime<Utc>>> {
+        self.until.as_deref().map(parse_time).transpose().context("Invalid until")
+    }
+
+    /// Whether the request names a project with this directory (or, in file
+    /// stores, this directory name)
+    pub fn matches_project(&self, project_dir: &str) -> bool {
+        match self.project {
+            Some(ref project) => {
+                project_dir == project || crate::store::project_name(project_dir) == crate::store::project_name(project)
+            }
+            None => true,
+        }
+    }
+
+    /// Whether a session from `timestamp` falls in the `since`..`until` range
+    pub fn matches_time(&self, timestamp: DateTime<Utc>) -> Result<bool> {
+        let (since, until) = (self.since()?, self.until()?);
+        Ok(since.map(|s| timestamp >= s).unwrap_or(true) && until.map(|u| timestamp < u).unwrap_or(true))
+    }
+}
+
+impl fmt::Display for PurgeRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields = [
+            ("machine_id", &self.machine_id),
+            ("project", &self.project),
+            ("since", &self.since),
+            ("until", &self.until),
+        ];
+        let set: Vec<String> = fields
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|v| format!("{}={}", name, v)))
+            .collect();
+        write!(f, "{}", set.join(" "))?;
+        if self.dry_run {
+            write!(f, " (dry run)")?;
+        }
+        Ok(())
+    }
+}
+
+/// A `YYYY-MM-DD` date (midnight UTC) or an RFC 3339 time
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .with_context(|| format!("Expected YYYY-MM-DD or an RFC 3339 time, got {:?}", value))
+}
+
+/// A session a purge removed, or would remove
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PurgedSession {
+    pub machine_id: String,
+    pub session_id: String,
+    pub project_dir: String,
+    pub timestamp: String,
+}
+
+/// What a purge removed, or with `dry_run` would remove
+#[derive(Debug, Default, Serialize)]
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub sessions: Vec<PurgedSession>,
+    /// Session, transcript and encrypted files
+    pub files: Vec<PathBuf>,
+    pub bytes: u64,
+}
+
+impl PurgeReport {
+    /// Add what another store removed, listing each session once
+    pub fn merge(&mut self, other: PurgeReport) {
+        self.sessions.extend(other.sessions);
+        self.dedup_sessions();
+        self.files.extend(other.files);
+        self.bytes += other.bytes;
+    }
+
+    /// One entry per machine and session, however many copies were stored
+    pub(crate) fn dedup_sessions(&mut self) {
+        self.sessions.sort();
+        self.sessions
+            .dedup_by(|a, b| a.machine_id == b.machine_id && a.session_id == b.session_id);
+    }
+}
+
+impl fmt::Display for PurgeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run { "would delete" } else { "deleted" };
+        write!(
+            f,
+            "{} {} sessions in {} files ({} bytes)",
+            verb,
+            self.sessions.len(),
+            self.files.len(),
+            self.bytes
+        )
+    }
+}
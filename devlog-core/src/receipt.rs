@@ -0,0 +1,140 @@
+use crate::DevlogOutput;
+use anyhow::{Context, Result};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// What a receiver signs to acknowledge a stored session: which session, its
+/// content hash as received, and when. Clients keep it next to the devlog, so
+/// they can later show the session was archived exactly as it is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub session_id: String,
+    pub machine_id: String,
+    /// [`DevlogOutput::content_hash`] of the session the receiver stored
+    pub content_hash: String,
+    /// RFC 3339, UTC
+    pub received_at: String,
+    /// The receiver's Ed25519 public key, hex
+    pub public_key: String,
+    /// Ed25519 signature over [`Receipt::message`], hex
+    pub signature: String,
+}
+
+impl Receipt {
+    /// The bytes signed: every field but the key and signature, one per line
+    fn message(&self) -> Vec<u8> {
+        format!(
+            "devlog-receipt-v1\n{}\n{}\n{}\n{}\n",
+            self.session_id, self.machine_id, self.content_hash, self.received_at
+        )
+        .into_bytes()
+    }
+
+    /// Check the signature against the receipt's own public key
+    pub fn verify(&self) -> Result<()> {
+        let public_key = hex_decode(&self.public_key).context("Invalid receipt public key")?;
+        let signature = hex_decode(&self.signature).context("Invalid receipt signature")?;
+        UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(&self.message(), &signature)
+            .map_err(|_| anyhow::anyhow!("Receipt signature does not match"))
+    }
+
+    /// Check the receipt is validly signed and acknowledges exactly this session
+    pub fn verify_for(&self, output: &DevlogOutput) -> Result<()> {
+        self.verify()?;
+        if self.session_id != output.session_id || self.machine_id != output.machine_id {
+            anyhow::bail!("Receipt is for session {} of {}", self.session_id, self.machine_id);
+        }
+        if self.content_hash != output.content_hash() {
+            anyhow::bail!("Session has changed since the receipt was issued");
+        }
+        Ok(())
+    }
+}
+
+/// A receiver's signing key
+pub struct ReceiptSigner {
+    key_pair: Ed25519KeyPair,
+}
+
+impl ReceiptSigner {
+    /// Read a PKCS#8 Ed25519 key, creating it (readable only by its owner) if
+    /// the file does not exist yet
+    pub fn load_or_generate(path: &Path) -> Result<Self> {
+        let pkcs8 = match fs::read(path) {
+            Ok(pkcs8) => pkcs8,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let document = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                    .map_err(|_| anyhow::anyhow!("Failed to generate a receipt key"))?;
+                let mut options = fs::OpenOptions::new();
+                options.write(true).create_new(true);
+                #[cfg(unix)]
+                std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+                options
+                    .open(path)
+                    .and_then(|mut file| file.write_all(document.as_ref()))
+                    .with_context(|| format!("Failed to write receipt key {}", path.display()))?;
+                document.as_ref().to_vec()
+            }
+            Err(e) => return Err(e).with_context(|| format!("Failed to read receipt key {}", path.display())),
+        };
+        let key_pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .map_err(|e| anyhow::anyhow!("Invalid receipt key {}: {}", path.display(), e))?;
+        Ok(Self { key_pair })
+    }
+
+    /// The public key clients can pin, hex
+    pub fn public_key(&self) -> String {
+        hex_encode(self.key_pair.public_key().as_ref())
+    }
+
+    /// Acknowledge a session as received now
+    pub fn sign(&self, output: &DevlogOutput) -> Receipt {
+        let mut receipt = Receipt {
+            session_id: output.session_id.clone(),
+            machine_id: output.machine_id.clone(),
+            content_hash: output.content_hash(),
+            received_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            public_key: self.public_key(),
+            signature: String::new(),
+        };
+        receipt.signature = hex_encode(self.key_pair.sign(&receipt.message()).as_ref());
+        receipt
+    }
+}
+
+static SIGNER: OnceLock<Result<Option<ReceiptSigner>, String>> = OnceLock::new();
+
+/// The signer for the key file named by `DEVLOG_RECEIPT_KEY_FILE`, created on
+/// first use. None when it is not set, and no receipts are issued.
+pub fn signer() -> Result<Option<&'static ReceiptSigner>> {
+    SIGNER
+        .get_or_init(|| {
+            let path = std::env::var("DEVLOG_RECEIPT_KEY_FILE").ok().filter(|p| !p.trim().is_empty());
+            path.map(|p| ReceiptSigner::load_or_generate(Path::new(&p)))
+                .transpose()
+                .map_err(|e| format!("{:#}", e))
+        })
+        .as_ref()
+        .map(Option::as_ref)
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hex_decode(hex: &str) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        anyhow::bail!("Not a hex string");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).context("Not a hex string"))
+        .collect()
+}
@@ -0,0 +1,129 @@
+use crate::bundle;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// How long receivers keep sessions; limits left unset are not enforced
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RetentionPolicy {
+    /// Prune sessions older than this many days
+    pub max_age_days: Option<u32>,
+    /// Prune the oldest sessions until the store fits in this many megabytes
+    pub max_total_mb: Option<u64>,
+    /// Move pruned sessions here instead of deleting them
+    pub archive_dir: Option<PathBuf>,
+    /// Hours between pruning runs
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u64,
+}
+
+fn default_interval_hours() -> u64 {
+    24
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_total_mb: None,
+            archive_dir: None,
+            interval_hours: default_interval_hours(),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_total_mb.is_some()
+    }
+
+    /// Sessions older than this are pruned
+    pub fn cutoff(&self) -> Option<DateTime<Utc>> {
+        self.max_age_days
+            .map(|days| Utc::now() - Duration::days(days as i64))
+    }
+
+    pub fn max_total_bytes(&self) -> Option<u64> {
+        self.max_total_mb.map(|mb| mb * 1024 * 1024)
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval_hours.max(1) * 3600)
+    }
+
+    /// The files outside the policy, oldest first: everything older than the cutoff,
+    /// then the oldest until the rest fits the size budget. Nothing is deleted.
+    pub fn select(&self, mut files: Vec<SessionFile>) -> Vec<SessionFile> {
+        // Newest first, so the size budget is spent on recent sessions
+        files.sort_by_key(|f| std::cmp::Reverse(f.timestamp));
+
+        let cutoff = self.cutoff();
+        let budget = self.max_total_bytes();
+
+        let mut kept_bytes = 0u64;
+        let mut selected = Vec::new();
+        for file in files {
+            let too_old = cutoff.map(|c| file.timestamp < c).unwrap_or(false);
+            let over_budget = budget.map(|b| kept_bytes + file.size > b).unwrap_or(false);
+
+            if too_old || over_budget {
+                selected.push(file);
+            } else {
+                kept_bytes += file.size;
+            }
+        }
+
+        selected.reverse();
+        selected
+    }
+}
+
+/// A devlog file as retention sees it
+#[derive(Debug, Clone)]
+pub struct SessionFile {
+    pub path: PathBuf,
+    pub timestamp: DateTime<Utc>,
+    pub size: u64,
+}
+
+impl SessionFile {
+    /// Dated by its `YYYY-MM-DD-HHMMSS-...` file name, or else its modification time
+    pub fn read(path: PathBuf) -> Result<Self> {
+        let (size, modified) = bundle::stat(&path)?;
+        let timestamp = filename_timestamp(&path)
+            .or_else(|| modified.map(DateTime::<Utc>::from))
+            .unwrap_or_else(Utc::now);
+        Ok(Self { path, timestamp, size })
+    }
+}
+
+/// The time encoded at the start of a devlog file name
+fn filename_timestamp(path: &Path) -> Option<DateTime<Utc>> {
+    let name = path.file_name()?.to_str()?;
+    let date_part = name.get(..17)?;
+    NaiveDateTime::parse_from_str(date_part, "%Y-%m-%d-%H%M%S")
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+/// What a pruning run removed
+#[derive(Debug, Default)]
+pub struct PruneReport {
+    pub sessions: usize,
+    pub bytes: u64,
+    /// Where the pruned sessions went, if they were archived rather than deleted
+    pub archived_to: Option<PathBuf>,
+}
+
+impl fmt::Display for PruneReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.archived_to.is_some() { "archived" } else { "deleted" };
+        write!(f, "{} {} sessions ({} bytes)", verb, self.sessions, self.bytes)?;
+        if let Some(ref dir) = self.archived_to {
+            write!(f, " to {}", dir.display())?;
+        }
+        Ok(())
+    }
+}
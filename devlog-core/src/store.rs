@@ -0,0 +1,886 @@
+use crate::at_rest;
+use crate::bundle::{self, BundleReport, Bundled};
+use crate::compression::{self, CompactReport};
+use crate::manifest::{self, ManifestEntry};
+use crate::models::{DevlogOutput, EncryptedSession, SessionHeader};
+use crate::purge::{PurgeReport, PurgeRequest, PurgedSession};
+use crate::retention::{PruneReport, RetentionPolicy, SessionFile};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sessions stored as `<root>/<machine_id>/<project>/<YYYY>/<MM>/<date>-<session>.json`,
+/// the layout `devlog search --storage` and `devlog stats` read. Sessions
+/// stored before they were sharded by month sit directly in the project
+/// directory, and are read there too until [`FileStore::shard_existing`] moves them. Older
+/// days may be rolled into a bundle per day (see [`bundle`]). With a storage
+/// key set (see [`at_rest::key`]) sessions and transcripts are written
+/// encrypted; read them with [`read_session`].
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn store(&self, output: &DevlogOutput) -> Result<PathBuf> {
+        let output_path = compression::storage_path(self.path_for(output)?)?;
+
+        let json = serde_json::to_vec_pretty(output)?;
+        fs::write(&output_path, encode(json)?)
+            .with_context(|| format!("Failed to write {}", output_path.display()))?;
+        manifest::record(&output_path, output)?;
+
+        Ok(output_path)
+    }
+
+    /// Write a batch via temporary files and rename them into place only once all are written
+    pub fn store_batch<'a>(&self, outputs: impl IntoIterator<Item = &'a DevlogOutput>) -> Result<usize> {
+        let mut staged = Vec::new();
+
+        let result = outputs.into_iter().try_for_each(|output| {
+            let path = compression::storage_path(self.path_for(output)?)?;
+            let tmp_path = tmp_path(&path);
+            fs::write(&tmp_path, encode(serde_json::to_vec_pretty(output)?)?)?;
+            staged.push((tmp_path, path, output));
+            anyhow::Ok(())
+        });
+
+        if let Err(e) = result {
+            for (tmp_path, _, _) in &staged {
+                let _ = fs::remove_file(tmp_path);
+            }
+            return Err(e);
+        }
+
+        for (tmp_path, path, _) in &staged {
+            fs::rename(tmp_path, path)?;
+        }
+        for (_, path, output) in &staged {
+            manifest::record(path, output)?;
+        }
+
+        Ok(staged.len())
+    }
+
+    /// Keep sessions encrypted by their clients as
+    /// `<machine>/<project>/<YYYY>/<MM>/<date>-<session>.age`, next to where their devlogs
+    /// would go. Search, stats and retention only read `.json` files, so they
+    /// pass these by. Returns the number stored.
+    pub fn store_encrypted(&self, sessions: &[EncryptedSession]) -> Result<usize> {
+        // Check them all first, so a plaintext devlog sent by mistake stores nothing
+        for session in sessions {
+            if !session.ciphertext.trim_start().starts_with(AGE_ARMOR_HEADER) {
+                anyhow::bail!("Session {} is not an armored age file", session.session_id);
+            }
+        }
+
+        for session in sessions {
+            let file_name = storage_filename(&session.session_id, &session.timestamp);
+            let dir = self
+                .root
+                .join(safe_component(&session.machine_id)?)
+                .join(safe_component(&session.project)?)
+                .join(shard(&file_name).unwrap_or_default());
+            fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+            let path = dir.join(file_name).with_extension("age");
+            fs::write(&path, &session.ciphertext)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        Ok(sessions.len())
+    }
+
+    /// Keep the raw Claude Code transcript of a session next to its devlog, as
+    /// `<machine>/<project>/transcripts/<session_id>.jsonl`, so it can be re-parsed later
+    pub fn store_transcript(
+        &self,
+        machine_id: &str,
+        project_dir: &str,
+        session_id: &str,
+        transcript: &[u8],
+    ) -> Result<PathBuf> {
+        let dir = self
+            .root
+            .join(safe_component(machine_id)?)
+            .join(safe_component(&project_name(project_dir))?)
+            .join("transcripts");
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let path = dir.join(format!("{}.jsonl", safe_component(session_id)?));
+        fs::write(&path, at_rest::seal(transcript.to_vec())?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    /// Delete (or move to the archive dir) sessions outside the retention policy:
+    /// everything older than the cutoff, then the oldest until the size budget fits
+    pub fn prune(&self, policy: &RetentionPolicy) -> Result<PruneReport> {
+        let mut report = PruneReport {
+            archived_to: policy.archive_dir.clone(),
+            ..Default::default()
+        };
+
+        let mut dirs = BTreeSet::new();
+        for file in policy.select(self.stored_files()?) {
+            let bundled = bundle::containing(&file.path);
+            match policy.archive_dir {
+                Some(ref archive_dir) => {
+                    // A bundled session is archived as a file of its own, beside where its bundle was
+                    let unbundled = match (bundled, file.path.file_name()) {
+                        (Some(bundle), Some(name)) => bundle.with_file_name(name),
+                        _ => file.path.clone(),
+                    };
+                    let relative = unbundled.strip_prefix(&self.root).unwrap_or(&unbundled);
+                    let target = archive_dir.join(relative);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    if bundled.is_some() {
+                        fs::write(&target, bundle::read_member(&file.path)?)
+                            .with_context(|| format!("Failed to archive {}", file.path.display()))?;
+                        bundle::remove(&file.path)?;
+                    // Rename fails across filesystems; fall back to copying
+                    } else if fs::rename(&file.path, &target).is_err() {
+                        fs::copy(&file.path, &target).with_context(|| {
+                            format!("Failed to archive {}", file.path.display())
+                        })?;
+                        fs::remove_file(&file.path)?;
+                    }
+                }
+                None => remove_stored(&file.path)?,
+            }
+
+            report.sessions += 1;
+            report.bytes += file.size;
+            if bundled.is_none() {
+                dirs.extend(file.path.parent().map(Path::to_path_buf));
+            }
+        }
+        for dir in &dirs {
+            manifest::forget_missing(dir)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Delete every stored copy of a session from a machine, and its transcript.
+    /// Returns the number of session files removed.
+    pub fn delete_session(&self, machine_id: &str, session_id: &str) -> Result<usize> {
+        let machine_dir = self.root.join(safe_component(machine_id)?);
+        let session_id = safe_component(session_id)?;
+        if !machine_dir.is_dir() {
+            return Ok(0);
+        }
+
+        // File names carry only the first 8 characters of the ID, so confirm by content
+        let short_id: String = session_id.chars().take(8).collect();
+        let suffix = format!("-{}", short_id);
+        let mut deleted = 0;
+        for project in fs::read_dir(&machine_dir)? {
+            let project = project?.path();
+            if !project.is_dir() {
+                continue;
+            }
+            for dir in session_dirs(&project, None)? {
+                for path in session_files(&dir)? {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    if !compression::session_stem(name).ends_with(&suffix) {
+                        continue;
+                    }
+                    let matches = read_session(&path)
+                        .ok()
+                        .map(|output| output.session_id == session_id)
+                        .unwrap_or(false);
+                    if matches {
+                        remove_stored(&path)?;
+                        manifest::forget_missing(&dir)?;
+                        deleted += 1;
+                    }
+                }
+            }
+
+            let transcript = project.join("transcripts").join(format!("{}.jsonl", session_id));
+            if transcript.exists() {
+                fs::remove_file(&transcript)
+                    .with_context(|| format!("Failed to delete {}", transcript.display()))?;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// The files that may hold a session from a machine, found by the short
+    /// session ID in their names without reading any; callers confirm by
+    /// content or manifest. Names unfit for storage hold nothing.
+    pub fn session_candidates(&self, machine_id: &str, session_id: &str) -> Result<Vec<PathBuf>> {
+        let (Ok(machine), Ok(session_id)) = (safe_component(machine_id), safe_component(session_id)) else {
+            return Ok(Vec::new());
+        };
+        let machine_dir = self.root.join(machine);
+        if !machine_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let short_id: String = session_id.chars().take(8).collect();
+        let suffix = format!("-{}", short_id);
+        let mut candidates = Vec::new();
+        for project in fs::read_dir(&machine_dir)? {
+            let project = project?.path();
+            if !project.is_dir() {
+                continue;
+            }
+            for dir in session_dirs(&project, None)? {
+                candidates.extend(session_files(&dir)?.into_iter().filter(|path| {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    compression::session_stem(name).ends_with(&suffix)
+                }));
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Delete the sessions a data removal request matches, with their
+    /// transcripts and any client-encrypted copies, then the directories left
+    /// empty. Projects match by directory name, the only part stored. With
+    /// `dry_run` nothing is deleted.
+    pub fn purge(&self, request: &PurgeRequest) -> Result<PurgeReport> {
+        request.validate()?;
+        let dated = request.since.is_some() || request.until.is_some();
+        let mut report = PurgeReport {
+            dry_run: request.dry_run,
+            ..Default::default()
+        };
+        if !self.root.is_dir() {
+            return Ok(report);
+        }
+
+        let mut dirs = Vec::new();
+        for machine in fs::read_dir(&self.root)? {
+            let machine = machine?.path();
+            let machine_name = machine.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if !machine.is_dir() || request.machine_id.as_deref().map(|m| m != machine_name).unwrap_or(false) {
+                continue;
+            }
+            for project in fs::read_dir(&machine)? {
+                let project = project?.path();
+                let project_name = project.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if !project.is_dir() || !request.matches_project(project_name) {
+                    continue;
+                }
+
+                let mut session_ids = BTreeSet::new();
+                let mut stored_short_ids = BTreeSet::new();
+                let mut shards = Vec::new();
+                for dir in session_dirs(&project, None)? {
+                    let mut paths = session_files(&dir)?;
+                    for entry in fs::read_dir(&dir)? {
+                        let path = entry?.path();
+                        if path.extension().map(|e| e == "age").unwrap_or(false) {
+                            paths.push(path);
+                        }
+                    }
+                    for path in paths {
+                        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                        let stem = compression::session_stem(name.strip_suffix(".age").unwrap_or(name));
+                        if let Some(short_id) = stem.get(18..) {
+                            stored_short_ids.insert(short_id.to_string());
+                        }
+                        let file = SessionFile::read(path)?;
+                        if !request.matches_time(file.timestamp)? {
+                            continue;
+                        }
+                        // Client-encrypted and unreadable files are removed without being listed as sessions
+                        if let Ok(output) = read_session(&file.path) {
+                            session_ids.insert(output.session_id.clone());
+                            report.sessions.push(PurgedSession {
+                                machine_id: output.machine_id.clone(),
+                                session_id: output.session_id.clone(),
+                                project_dir: output.project_dir.clone(),
+                                timestamp: output.timestamp.clone(),
+                            });
+                        }
+                        report.bytes += file.size;
+                        report.files.push(file.path);
+                    }
+                    if dir != project {
+                        shards.push(dir);
+                    }
+                }
+                // Months, then the years holding them
+                let years: BTreeSet<PathBuf> = shards.iter().filter_map(|dir| dir.parent().map(Path::to_path_buf)).collect();
+                dirs.extend(shards);
+                dirs.extend(years);
+
+                // Transcripts carry no date: a dated purge takes those of the
+                // sessions it removes, and dates any without a stored session
+                // (as in a receiver's transcript store) by when they were written
+                let transcripts = project.join("transcripts");
+                if transcripts.is_dir() {
+                    for entry in fs::read_dir(&transcripts)? {
+                        let path = entry?.path();
+                        let session_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+                        let short_id: String = session_id.chars().take(8).collect();
+                        let orphan_in_range = !stored_short_ids.contains(&short_id)
+                            && request.matches_time(SessionFile::read(path.clone())?.timestamp)?;
+                        if !dated || session_ids.contains(session_id) || orphan_in_range {
+                            report.bytes += path.metadata().map(|m| m.len()).unwrap_or(0);
+                            report.files.push(path);
+                        }
+                    }
+                    dirs.push(transcripts);
+                }
+                dirs.push(project);
+            }
+            dirs.push(machine);
+        }
+
+        report.dedup_sessions();
+        if request.dry_run {
+            return Ok(report);
+        }
+
+        for path in &report.files {
+            remove_stored(path)?;
+        }
+        // Innermost first; directories still holding other sessions stay
+        for dir in &dirs {
+            manifest::forget_missing(dir)?;
+            let _ = fs::remove_dir(dir);
+        }
+        Ok(report)
+    }
+
+    /// Encrypt the sessions and transcripts stored before the storage key was
+    /// set. Each file is replaced only once its encrypted copy is written, and
+    /// each bundle once all its sessions are. Returns the number encrypted.
+    pub fn seal_existing(&self) -> Result<usize> {
+        if at_rest::key()?.is_none() {
+            return Ok(0);
+        }
+        let mut paths = Vec::new();
+        let mut bundles = BTreeSet::new();
+        for file in self.stored_files()? {
+            match bundle::containing(&file.path) {
+                Some(bundle) => {
+                    bundles.insert(bundle.to_path_buf());
+                }
+                None => paths.push(file.path),
+            }
+        }
+        for project in self.project_dirs()? {
+            let transcripts = project.join("transcripts");
+            if !transcripts.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&transcripts)? {
+                paths.push(entry?.path());
+            }
+        }
+
+        let mut sealed = 0;
+        for path in paths {
+            let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            if at_rest::is_sealed(&content) {
+                continue;
+            }
+            let tmp_path = path.with_extension("sealing.tmp");
+            fs::write(&tmp_path, at_rest::seal(content)?)
+                .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+            fs::rename(&tmp_path, &path)?;
+            sealed += 1;
+        }
+        for bundle in bundles {
+            let mut sessions = bundle::read_all(&bundle)?;
+            let mut sealed_here = 0;
+            for session in sessions.iter_mut().filter(|s| !at_rest::is_sealed(&s.content)) {
+                session.content = at_rest::seal(std::mem::take(&mut session.content))?;
+                if let Some(ref mut entry) = session.manifest {
+                    entry.size = session.content.len() as u64;
+                }
+                sealed_here += 1;
+            }
+            if sealed_here > 0 {
+                bundle::write(&bundle, &sessions)?;
+            }
+            sealed += sealed_here;
+        }
+        Ok(sealed)
+    }
+
+    /// Path of a stored session addressed by its directory and file names, as
+    /// listed by search and stats
+    pub fn session_path(&self, machine: &str, project: &str, file: &str) -> Result<PathBuf> {
+        if !compression::is_session_file(Path::new(file)) {
+            anyhow::bail!("Not a session file: {:?}", file);
+        }
+        let project_dir = self.root.join(safe_component(machine)?).join(safe_component(project)?);
+        let file = safe_component(file)?;
+        // Stored before sessions were sharded, or in its month, or in its day's bundle
+        let flat = project_dir.join(file);
+        let Some(shard) = shard(file).filter(|_| !flat.exists()) else {
+            return Ok(flat);
+        };
+        let month = project_dir.join(shard);
+        let bundle = month.join(bundle::file_name(file.get(..10).unwrap_or("")));
+        if !month.join(file).exists() && bundle.is_file() {
+            return Ok(bundle.join(file));
+        }
+        Ok(month.join(file))
+    }
+
+    /// Total size of the stored session files
+    pub fn total_bytes(&self) -> Result<u64> {
+        Ok(self.stored_files()?.iter().map(|f| f.size).sum())
+    }
+
+    /// Every stored session file, bundled ones included, dated by its file
+    /// name (or modification time)
+    pub fn stored_files(&self) -> Result<Vec<SessionFile>> {
+        let mut files = Vec::new();
+        for project in self.project_dirs()? {
+            for dir in session_dirs(&project, None)? {
+                for path in session_files(&dir)? {
+                    files.push(SessionFile::read(path)?);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Compress the stored sessions that are plain JSON into `.json.zst` files,
+    /// keeping encrypted ones encrypted and their manifest lines current. Each
+    /// file is removed only once its compressed copy is in place, and each
+    /// bundle is rewritten once all its sessions are compressed.
+    pub fn compress_existing(&self) -> Result<CompactReport> {
+        let mut report = CompactReport::default();
+        let mut manifests: HashMap<PathBuf, HashMap<String, ManifestEntry>> = HashMap::new();
+        let mut bundles = BTreeSet::new();
+        for file in self.stored_files()? {
+            if file.path.extension().map(|e| e != "json").unwrap_or(true) {
+                continue;
+            }
+            if let Some(bundle) = bundle::containing(&file.path) {
+                bundles.insert(bundle.to_path_buf());
+                continue;
+            }
+            let content = fs::read(&file.path).with_context(|| format!("Failed to read {}", file.path.display()))?;
+            let compressed = recompress(content).with_context(|| format!("Failed to read {}", file.path.display()))?;
+
+            let target = {
+                let mut name = file.path.as_os_str().to_owned();
+                name.push(".");
+                name.push(compression::EXTENSION);
+                PathBuf::from(name)
+            };
+            let tmp = tmp_path(&target);
+            fs::write(&tmp, &compressed).with_context(|| format!("Failed to write {}", tmp.display()))?;
+            fs::rename(&tmp, &target)?;
+            fs::remove_file(&file.path).with_context(|| format!("Failed to remove {}", file.path.display()))?;
+
+            if let Some(dir) = file.path.parent() {
+                let lines = match manifests.entry(dir.to_path_buf()) {
+                    Entry::Occupied(lines) => lines.into_mut(),
+                    Entry::Vacant(lines) => lines.insert(manifest::read(dir)?),
+                };
+                let name = file.path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if let Some(mut entry) = lines.remove(name) {
+                    entry.file = format!("{}.{}", name, compression::EXTENSION);
+                    entry.size = compressed.len() as u64;
+                    manifest::append(dir, &entry)?;
+                }
+            }
+            report.files += 1;
+            report.bytes_before += file.size;
+            report.bytes_after += compressed.len() as u64;
+        }
+        for dir in manifests.keys() {
+            manifest::forget_missing(dir)?;
+        }
+
+        for bundle in bundles {
+            let mut sessions = bundle::read_all(&bundle)?;
+            for session in sessions.iter_mut().filter(|s| s.name.ends_with(".json")) {
+                let size = session.content.len() as u64;
+                session.content = recompress(std::mem::take(&mut session.content))
+                    .with_context(|| format!("Failed to read {} from {}", session.name, bundle.display()))?;
+                session.name = format!("{}.{}", session.name, compression::EXTENSION);
+                if let Some(ref mut entry) = session.manifest {
+                    entry.file = session.name.clone();
+                    entry.size = session.content.len() as u64;
+                }
+                report.files += 1;
+                report.bytes_before += size;
+                report.bytes_after += session.content.len() as u64;
+            }
+            sessions.sort_by(|a, b| a.name.cmp(&b.name));
+            bundle::write(&bundle, &sessions)?;
+        }
+        Ok(report)
+    }
+
+    /// Roll the sessions of each project's days up to `last_day` into one
+    /// bundle per day in their month's directory, adding them to the bundle
+    /// already there if a session of that day was stored after it was made.
+    /// Their manifest lines move into the bundle with them. Each file is
+    /// removed only once its bundle is in place.
+    pub fn bundle_days(&self, last_day: NaiveDate) -> Result<BundleReport> {
+        // Kept out of bundles as out of manifest.jsonl while sessions are encrypted
+        let keep_manifest = at_rest::key()?.is_none();
+        let mut report = BundleReport::default();
+        for project in self.project_dirs()? {
+            let dirs = session_dirs(&project, None)?;
+            let mut days: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+            for dir in &dirs {
+                for entry in fs::read_dir(dir)? {
+                    let path = entry?.path();
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                    let day = name.get(..10).filter(|day| {
+                        NaiveDate::parse_from_str(day, "%Y-%m-%d").map(|d| d <= last_day).unwrap_or(false)
+                    });
+                    if let Some(day) = day.filter(|_| compression::is_session_file(&path)) {
+                        days.entry(day.to_string()).or_default().push(path);
+                    }
+                }
+            }
+
+            let mut manifests: HashMap<PathBuf, HashMap<String, ManifestEntry>> = HashMap::new();
+            for (day, paths) in days {
+                let Some(shard) = shard(&day) else {
+                    continue;
+                };
+                let month = project.join(shard);
+                fs::create_dir_all(&month).with_context(|| format!("Failed to create {}", month.display()))?;
+                let bundle_path = month.join(bundle::file_name(&day));
+                let mut sessions = if bundle_path.exists() { bundle::read_all(&bundle_path)? } else { Vec::new() };
+
+                for path in &paths {
+                    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+                    let content = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+                    let size = content.len() as u64;
+                    let manifest = match path.parent() {
+                        Some(dir) if keep_manifest => {
+                            let lines = match manifests.entry(dir.to_path_buf()) {
+                                Entry::Occupied(lines) => lines.into_mut(),
+                                Entry::Vacant(lines) => lines.insert(manifest::read(dir)?),
+                            };
+                            lines.remove(&name).filter(|entry| entry.size == size).or_else(|| {
+                                read_session(path).ok().map(|output| ManifestEntry::new(name.clone(), size, &output))
+                            })
+                        }
+                        _ => None,
+                    };
+                    // Stored again since its day was bundled: the newer copy wins
+                    sessions.retain(|s| s.name != name);
+                    sessions.push(Bundled { name, content, manifest });
+                }
+                sessions.sort_by(|a, b| a.name.cmp(&b.name));
+                bundle::write(&bundle_path, &sessions)?;
+
+                for path in &paths {
+                    fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+                report.sessions += paths.len();
+                report.days += 1;
+            }
+            for dir in &dirs {
+                manifest::forget_missing(dir)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Move sessions stored directly in their project directory, as they were
+    /// before sessions were sharded by month, into their `<YYYY>/<MM>`
+    /// directory, taking their manifest lines along. Returns the number moved.
+    pub fn shard_existing(&self) -> Result<usize> {
+        let mut moved = 0;
+        for project in self.project_dirs()? {
+            let mut entries = manifest::read(&project)?;
+            let mut moved_here = 0;
+            for entry in fs::read_dir(&project)? {
+                let path = entry?.path();
+                let is_session = compression::is_session_file(&path) || path.extension().map(|e| e == "age").unwrap_or(false);
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+                let Some(shard) = shard(&name).filter(|_| is_session && path.is_file()) else {
+                    continue;
+                };
+
+                let dir = project.join(shard);
+                fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+                let target = dir.join(&name);
+                if target.exists() {
+                    anyhow::bail!("Both {} and {} exist", path.display(), target.display());
+                }
+                fs::rename(&path, &target).with_context(|| format!("Failed to move {}", path.display()))?;
+                if let Some(line) = entries.remove(&name) {
+                    manifest::append(&dir, &line)?;
+                }
+                moved_here += 1;
+            }
+            if moved_here > 0 {
+                manifest::write(&project, entries.values())?;
+            }
+            moved += moved_here;
+        }
+        Ok(moved)
+    }
+
+    /// Every `<machine>/<project>` directory
+    fn project_dirs(&self) -> Result<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        if !self.root.exists() {
+            return Ok(dirs);
+        }
+        for machine in fs::read_dir(&self.root)? {
+            let machine = machine?.path();
+            if !machine.is_dir() {
+                continue;
+            }
+            for project in fs::read_dir(&machine)? {
+                let project = project?.path();
+                if project.is_dir() {
+                    dirs.push(project);
+                }
+            }
+        }
+        Ok(dirs)
+    }
+
+    /// Where a devlog is stored, creating its machine/project directory
+    fn path_for(&self, output: &DevlogOutput) -> Result<PathBuf> {
+        let project_dir = self
+            .root
+            .join(safe_component(&output.machine_id)?)
+            .join(safe_component(&project_name(output.project_path()))?);
+        let file_name = storage_filename(&output.session_id, &output.timestamp);
+        let dir = project_dir.join(shard(&file_name).unwrap_or_default());
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        Ok(dir.join(file_name))
+    }
+}
+
+/// A stored devlog's JSON, decrypted and decompressed as needed
+fn read_stored(path: &Path) -> Result<Vec<u8>> {
+    let content = match bundle::containing(path) {
+        Some(_) => bundle::read_member(path)?,
+        None => fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?,
+    };
+    at_rest::open(content)
+        .and_then(compression::decompress)
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Stored content compressed, encrypted again if it was
+fn recompress(content: Vec<u8>) -> Result<Vec<u8>> {
+    let sealed = at_rest::is_sealed(&content);
+    let compressed = compression::compress(&at_rest::open(content)?)?;
+    if sealed {
+        at_rest::seal(compressed)
+    } else {
+        Ok(compressed)
+    }
+}
+
+/// Delete a stored session file, or take a bundled session out of its bundle
+fn remove_stored(path: &Path) -> Result<()> {
+    match bundle::containing(path) {
+        Some(_) => bundle::remove(path),
+        None => fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display())),
+    }
+}
+
+/// Compress a devlog's JSON when sessions are stored compressed, then encrypt
+/// it when a storage key is set
+fn encode(json: Vec<u8>) -> Result<Vec<u8>> {
+    let content = if compression::enabled()? { compression::compress(&json)? } else { json };
+    at_rest::seal(content)
+}
+
+/// Where a file is written before being renamed into place
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    tmp.into()
+}
+
+/// Read a devlog, decrypting and decompressing it as needed
+pub fn read_session(path: &Path) -> Result<DevlogOutput> {
+    let json = read_stored(path)?;
+    serde_json::from_slice(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// A devlog read from disk with only its header parsed. Filter on the header,
+/// then [`parse`](Self::parse) the sessions that are still wanted.
+pub struct StoredSession {
+    pub header: SessionHeader,
+    path: PathBuf,
+    json: Vec<u8>,
+}
+
+impl StoredSession {
+    /// The whole session, conversation and all
+    pub fn parse(self) -> Result<DevlogOutput> {
+        serde_json::from_slice(&self.json).with_context(|| format!("Failed to parse {}", self.path.display()))
+    }
+}
+
+/// Read a devlog's header, decrypting it if it was stored encrypted
+pub fn read_session_header(path: &Path) -> Result<StoredSession> {
+    let json = read_stored(path)?;
+    let header = serde_json::from_slice(&json).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(StoredSession {
+        header,
+        path: path.to_path_buf(),
+        json,
+    })
+}
+
+/// Replace a devlog file with `output`, keeping it encrypted and compressed if it was
+pub fn rewrite_session(path: &Path, output: &DevlogOutput) -> Result<()> {
+    let mut content = serde_json::to_vec_pretty(output).context("Failed to serialize output")?;
+    if path.extension().map(|e| e == compression::EXTENSION).unwrap_or(false) {
+        content = compression::compress(&content)?;
+    }
+    if let Some(bundle) = bundle::containing(path) {
+        if at_rest::is_sealed(&bundle::read_member(path)?) {
+            content = at_rest::seal(content)?;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("").to_string();
+        let manifest = at_rest::key()?
+            .is_none()
+            .then(|| ManifestEntry::new(name.clone(), content.len() as u64, output));
+        return bundle::replace(bundle, Bundled { name, content, manifest });
+    }
+    let was_sealed = fs::read(path).map(|old| at_rest::is_sealed(&old)).unwrap_or(false);
+    if was_sealed {
+        content = at_rest::seal(content)?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write devlog file: {}", path.display()))?;
+    // Stored sessions are listed in their directory's manifest; local ones are not
+    if path.with_file_name(manifest::MANIFEST_FILE).exists() {
+        manifest::record(path, output)?;
+    }
+    Ok(())
+}
+
+/// First line of an ASCII-armored age file
+const AGE_ARMOR_HEADER: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Client-supplied names become directory names; refuse any that could escape the store
+fn safe_component(name: &str) -> Result<&str> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        anyhow::bail!("Invalid name for storage: {:?}", name);
+    }
+    Ok(name)
+}
+
+/// Extract project name from a path, handling both Windows and Unix separators
+pub fn project_name(path: &str) -> String {
+    // Split by both Windows and Unix separators, take the last non-empty component
+    path.split(['/', '\\'])
+        .rfind(|s| !s.is_empty())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+/// The `<YYYY>/<MM>` directory a stored session file belongs in, from the date
+/// its name starts with
+pub fn shard(file_name: &str) -> Option<PathBuf> {
+    let year = file_name.get(..4).filter(|y| y.bytes().all(|b| b.is_ascii_digit()))?;
+    let month = file_name.get(5..7).filter(|m| matches!(m.parse::<u32>(), Ok(1..=12)))?;
+    (file_name.as_bytes().get(4) == Some(&b'-')).then(|| Path::new(year).join(month))
+}
+
+/// The directories of a stored project that hold sessions: its `<YYYY>/<MM>`
+/// shards, oldest first, then the project directory itself for sessions stored
+/// before sharding. With `since`, months ending before it are not listed, so
+/// their files are never looked at.
+pub fn session_dirs(project_dir: &Path, since: Option<DateTime<Utc>>) -> Result<Vec<PathBuf>> {
+    let since = since.map(|s| (s.year(), s.month()));
+    let numbered = |dir: &Path| -> Result<Vec<(u32, PathBuf)>> {
+        let mut numbered = Vec::new();
+        for entry in fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))? {
+            let path = entry?.path();
+            let number = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.parse::<u32>().ok());
+            if let Some(number) = number.filter(|_| path.is_dir()) {
+                numbered.push((number, path));
+            }
+        }
+        numbered.sort();
+        Ok(numbered)
+    };
+
+    let mut dirs = Vec::new();
+    for (year, year_dir) in numbered(project_dir)? {
+        if since.map(|(y, _)| (year as i32) < y).unwrap_or(false) {
+            continue;
+        }
+        for (month, month_dir) in numbered(&year_dir)? {
+            if since.map(|s| ((year as i32), month) < s).unwrap_or(false) {
+                continue;
+            }
+            dirs.push(month_dir);
+        }
+    }
+    dirs.push(project_dir.to_path_buf());
+    Ok(dirs)
+}
+
+/// The session files in one of the [`session_dirs`]: its `.json` and
+/// `.json.zst` files, then the sessions in its day bundles, addressed as
+/// `<bundle>/<file>`. A session stored again after its day was bundled is
+/// listed once, as its own file. An unreadable bundle is passed over, as
+/// readers pass over an unreadable session.
+pub fn session_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read directory: {}", dir.display()))? {
+        let path = entry?.path();
+        if compression::is_session_file(&path) {
+            files.push(path);
+        } else if bundle::is_bundle(&path) {
+            bundles.push(path);
+        }
+    }
+
+    let loose: HashSet<PathBuf> = files.iter().filter_map(|f| f.file_name().map(PathBuf::from)).collect();
+    for bundle in bundles {
+        let Ok(members) = bundle::members(&bundle) else {
+            continue;
+        };
+        for member in members {
+            if !loose.contains(Path::new(&member.name)) {
+                files.push(bundle.join(&member.name));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// `YYYY-MM-DD-HHMMSS-<session_id_short>.json`, dated by the session's timestamp
+pub fn storage_filename(session_id: &str, timestamp: &str) -> String {
+    // Try to parse the timestamp for the date part
+    let date_part = chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("%Y-%m-%d-%H%M%S").to_string())
+        .unwrap_or_else(|_| chrono::Utc::now().format("%Y-%m-%d-%H%M%S").to_string());
+
+    // Shorten session_id for filename
+    let short_id: String = session_id.chars().take(8).collect();
+
+    format!("{}-{}.json", date_part, short_id)
+}
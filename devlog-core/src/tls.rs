@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use rustls::crypto::ring::default_provider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A TLS server config for the certificate chain and key in PEM files. With
+/// `client_ca`, clients must present a certificate issued by one of the CAs in
+/// it, so only machines holding one can connect at all.
+pub fn server_config(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<ServerConfig> {
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .with_context(|| format!("Failed to read certificates from {}", cert.display()))?;
+    if chain.is_empty() {
+        anyhow::bail!("No certificates in {}", cert.display());
+    }
+    let key = PrivateKeyDer::from_pem_file(key).with_context(|| format!("Failed to read private key {}", key.display()))?;
+
+    let provider = Arc::new(default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .context("Failed to set up TLS")?;
+    let builder = match client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for ca in CertificateDer::pem_file_iter(client_ca)
+                .with_context(|| format!("Failed to read client CA {}", client_ca.display()))?
+            {
+                let ca = ca.with_context(|| format!("Failed to read client CA {}", client_ca.display()))?;
+                roots.add(ca).with_context(|| format!("Invalid client CA in {}", client_ca.display()))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .context("Failed to set up client certificate verification")?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    builder
+        .with_single_cert(chain, key)
+        .context("TLS certificate and key do not match")
+}
+
+/// The TLS config from `DEVLOG_TLS_CERT` and `DEVLOG_TLS_KEY`, requiring client
+/// certificates from the CAs in `DEVLOG_TLS_CLIENT_CA` when that is set. None
+/// when no certificate is set, and the server speaks plain HTTP.
+pub fn from_env() -> Result<Option<ServerConfig>> {
+    let var = |name| std::env::var(name).ok().filter(|v: &String| !v.trim().is_empty());
+    match (var("DEVLOG_TLS_CERT"), var("DEVLOG_TLS_KEY"), var("DEVLOG_TLS_CLIENT_CA")) {
+        (Some(cert), Some(key), client_ca) => {
+            server_config(Path::new(&cert), Path::new(&key), client_ca.as_deref().map(Path::new)).map(Some)
+        }
+        (None, None, None) => Ok(None),
+        (_, _, Some(_)) => anyhow::bail!("DEVLOG_TLS_CLIENT_CA needs DEVLOG_TLS_CERT and DEVLOG_TLS_KEY"),
+        _ => anyhow::bail!("DEVLOG_TLS_CERT and DEVLOG_TLS_KEY must be set together"),
+    }
+}
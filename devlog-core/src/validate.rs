@@ -0,0 +1,372 @@
+//! Checks a devlog's JSON against SCHEMA.md, reporting every problem rather than
+//! stopping at the first field serde cannot read.
+
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Schema versions this build knows how to check
+pub const SCHEMA_VERSIONS: &[&str] = &["1.0"];
+
+const ROOT_FIELDS: &[&str] = &[
+    "schema_version",
+    "session_id",
+    "timestamp",
+    "machine_id",
+    "project_dir",
+    "git",
+    "tags",
+    "notes",
+    "tickets",
+    "conversation",
+    "transcript_path",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The file cannot be read as a devlog, or breaks the schema
+    Error,
+    /// Readable, but not as the schema describes
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Where in the document, e.g. `conversation[3].timestamp`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        if self.path.is_empty() {
+            write!(f, "{}: {}", severity, self.message)
+        } else {
+            write!(f, "{}: {}: {}", severity, self.path, self.message)
+        }
+    }
+}
+
+/// Check raw devlog JSON text
+pub fn validate_str(json: &str) -> Vec<Issue> {
+    match serde_json::from_str::<Value>(json) {
+        Ok(value) => validate(&value),
+        Err(e) => vec![Issue {
+            severity: Severity::Error,
+            path: String::new(),
+            message: format!("not valid JSON: {}", e),
+        }],
+    }
+}
+
+/// Check a devlog document against the schema of its `schema_version`
+pub fn validate(value: &Value) -> Vec<Issue> {
+    let mut checker = Checker::default();
+    let Some(root) = value.as_object() else {
+        checker.error("", "not a JSON object");
+        return checker.issues;
+    };
+
+    match root.get("schema_version") {
+        Some(Value::String(version)) if !SCHEMA_VERSIONS.contains(&version.as_str()) => checker.warning(
+            "schema_version",
+            &format!(
+                "unknown version {:?}; checked against {}",
+                version,
+                SCHEMA_VERSIONS[SCHEMA_VERSIONS.len() - 1]
+            ),
+        ),
+        Some(Value::String(_)) => {}
+        Some(_) => checker.error("schema_version", "not a string"),
+        None => checker.error("schema_version", "missing"),
+    }
+
+    checker.required_string(root, "", "session_id");
+    checker.required_string(root, "", "machine_id");
+    checker.required_string(root, "", "project_dir");
+    if checker.required_string(root, "", "timestamp") {
+        checker.timestamp(&root["timestamp"], "timestamp");
+    }
+
+    match root.get("git") {
+        None => checker.warning("git", "missing; read as null"),
+        Some(Value::Null) => {}
+        Some(Value::Object(git)) => checker.git(git),
+        Some(_) => checker.error("git", "not an object or null"),
+    }
+
+    if let Some(tags) = checker.optional_array(root, "", "tags") {
+        for (i, tag) in tags.iter().enumerate() {
+            if !tag.is_string() {
+                checker.error(&format!("tags[{}]", i), "not a string");
+            }
+        }
+    }
+    if let Some(notes) = checker.optional_array(root, "", "notes") {
+        for (i, note) in notes.iter().enumerate() {
+            let path = format!("notes[{}]", i);
+            let Some(note) = note.as_object() else {
+                checker.error(&path, "not an object");
+                continue;
+            };
+            if checker.required_string(note, &path, "timestamp") {
+                checker.timestamp(&note["timestamp"], &format!("{}.timestamp", path));
+            }
+            checker.required_string(note, &path, "text");
+        }
+    }
+    if let Some(tickets) = checker.optional_array(root, "", "tickets") {
+        for (i, ticket) in tickets.iter().enumerate() {
+            let path = format!("tickets[{}]", i);
+            let Some(ticket) = ticket.as_object() else {
+                checker.error(&path, "not an object");
+                continue;
+            };
+            checker.required_string(ticket, &path, "id");
+            if ticket.get("url").map(|url| !url.is_string()).unwrap_or(false) {
+                checker.error(&format!("{}.url", path), "not a string");
+            }
+        }
+    }
+
+    match root.get("conversation") {
+        Some(Value::Array(entries)) => checker.conversation(entries),
+        Some(_) => checker.error("conversation", "not an array"),
+        None => checker.error("conversation", "missing"),
+    }
+
+    if let Some(path) = root.get("transcript_path") {
+        if !path.is_string() {
+            checker.error("transcript_path", "not a string");
+        }
+    }
+
+    for key in root.keys() {
+        if !ROOT_FIELDS.contains(&key.as_str()) {
+            checker.warning(key, "unknown field");
+        }
+    }
+
+    checker.issues
+}
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<Issue>,
+}
+
+impl Checker {
+    fn error(&mut self, path: &str, message: &str) {
+        self.issues.push(Issue {
+            severity: Severity::Error,
+            path: path.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    fn warning(&mut self, path: &str, message: &str) {
+        self.issues.push(Issue {
+            severity: Severity::Warning,
+            path: path.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    /// Whether `object[key]` is a string; reports it otherwise
+    fn required_string(&mut self, object: &Map<String, Value>, parent: &str, key: &str) -> bool {
+        let path = join(parent, key);
+        match object.get(key) {
+            Some(Value::String(_)) => true,
+            Some(_) => {
+                self.error(&path, "not a string");
+                false
+            }
+            None => {
+                self.error(&path, "missing");
+                false
+            }
+        }
+    }
+
+    fn optional_array<'a>(&mut self, object: &'a Map<String, Value>, parent: &str, key: &str) -> Option<&'a Vec<Value>> {
+        match object.get(key) {
+            Some(Value::Array(items)) => Some(items),
+            Some(_) => {
+                self.error(&join(parent, key), "not an array");
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn string_array(&mut self, object: &Map<String, Value>, parent: &str, key: &str) {
+        if let Some(items) = self.optional_array(object, parent, key) {
+            for (i, item) in items.iter().enumerate() {
+                if !item.is_string() {
+                    self.error(&format!("{}[{}]", join(parent, key), i), "not a string");
+                }
+            }
+        }
+    }
+
+    /// Whether the value is an ISO 8601 (RFC 3339) datetime; reports it otherwise
+    fn timestamp(&mut self, value: &Value, path: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let text = value.as_str()?;
+        match chrono::DateTime::parse_from_rfc3339(text) {
+            Ok(dt) => Some(dt),
+            Err(_) => {
+                self.error(path, &format!("not an ISO 8601 datetime: {:?}", text));
+                None
+            }
+        }
+    }
+
+    fn git(&mut self, git: &Map<String, Value>) {
+        match git.get("remote") {
+            None | Some(Value::Null) | Some(Value::String(_)) => {}
+            Some(_) => self.error("git.remote", "not a string or null"),
+        }
+        self.required_string(git, "git", "branch");
+        if self.required_string(git, "git", "commit") {
+            let commit = git["commit"].as_str().unwrap_or_default();
+            if commit.len() != 40 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                self.warning("git.commit", "not a full SHA");
+            }
+        }
+        if let Some(dirty) = git.get("dirty") {
+            if !dirty.is_boolean() {
+                self.error("git.dirty", "not a boolean");
+            }
+        }
+        self.string_array(git, "git", "modified_files");
+        self.string_array(git, "git", "untracked_files");
+
+        if let Some(remotes) = self.optional_array(git, "git", "remotes") {
+            for (i, remote) in remotes.iter().enumerate() {
+                let path = format!("git.remotes[{}]", i);
+                match remote.as_object() {
+                    Some(remote) => {
+                        self.required_string(remote, &path, "name");
+                        self.required_string(remote, &path, "url");
+                    }
+                    None => self.error(&path, "not an object"),
+                }
+            }
+        }
+
+        if let Some(commits) = self.optional_array(git, "git", "commits") {
+            for (i, commit) in commits.iter().enumerate() {
+                let path = format!("git.commits[{}]", i);
+                let Some(commit) = commit.as_object() else {
+                    self.error(&path, "not an object");
+                    continue;
+                };
+                self.required_string(commit, &path, "hash");
+                self.required_string(commit, &path, "subject");
+                if self.required_string(commit, &path, "timestamp") {
+                    self.timestamp(&commit["timestamp"], &format!("{}.timestamp", path));
+                }
+                match commit.get("files") {
+                    Some(Value::Array(_)) => self.string_array(commit, &path, "files"),
+                    Some(_) => self.error(&format!("{}.files", path), "not an array"),
+                    None => self.error(&format!("{}.files", path), "missing"),
+                }
+            }
+        }
+
+        match git.get("parent_repo") {
+            None => {}
+            Some(Value::Object(parent)) => {
+                match parent.get("kind").and_then(Value::as_str) {
+                    Some("worktree") | Some("submodule") => {}
+                    Some(kind) => self.error("git.parent_repo.kind", &format!("unknown kind {:?}", kind)),
+                    None => self.error("git.parent_repo.kind", "missing"),
+                }
+                self.required_string(parent, "git.parent_repo", "path");
+                self.required_string(parent, "git.parent_repo", "sub_path");
+            }
+            Some(_) => self.error("git.parent_repo", "not an object"),
+        }
+
+        match git.get("author") {
+            None => {}
+            Some(Value::Object(author)) => {
+                for key in ["name", "email"] {
+                    if author.get(key).map(|v| !v.is_string()).unwrap_or(false) {
+                        self.error(&format!("git.author.{}", key), "not a string");
+                    }
+                }
+            }
+            Some(_) => self.error("git.author", "not an object"),
+        }
+    }
+
+    fn conversation(&mut self, entries: &[Value]) {
+        let mut last_time = None;
+        for (i, entry) in entries.iter().enumerate() {
+            let path = format!("conversation[{}]", i);
+            let Some(entry) = entry.as_object() else {
+                self.error(&path, "not an object");
+                continue;
+            };
+
+            match entry.get("type").and_then(Value::as_str) {
+                Some("user") | Some("assistant") => {
+                    self.required_string(entry, &path, "content");
+                    let timestamp_path = format!("{}.timestamp", path);
+                    match entry.get("timestamp") {
+                        None | Some(Value::Null) => {}
+                        Some(timestamp @ Value::String(_)) => {
+                            if let Some(time) = self.timestamp(timestamp, &timestamp_path) {
+                                if last_time.map(|last| time < last).unwrap_or(false) {
+                                    self.warning(&timestamp_path, "earlier than the entry before it");
+                                }
+                                last_time = Some(time);
+                            }
+                        }
+                        Some(_) => self.error(&timestamp_path, "not a string or null"),
+                    }
+                    if entry.get("uuid").map(|v| !v.is_string()).unwrap_or(false) {
+                        self.error(&format!("{}.uuid", path), "not a string");
+                    }
+                    if let Some(usage) = entry.get("usage") {
+                        self.usage(usage, &format!("{}.usage", path));
+                    }
+                }
+                Some("tool_summary") => match entry.get("actions") {
+                    Some(Value::Array(_)) => self.string_array(entry, &path, "actions"),
+                    Some(_) => self.error(&format!("{}.actions", path), "not an array"),
+                    None => self.error(&format!("{}.actions", path), "missing"),
+                },
+                Some(other) => self.error(&format!("{}.type", path), &format!("unknown entry type {:?}", other)),
+                None => self.error(&format!("{}.type", path), "missing"),
+            }
+        }
+    }
+
+    fn usage(&mut self, usage: &Value, path: &str) {
+        let Some(usage) = usage.as_object() else {
+            if !usage.is_null() {
+                self.error(path, "not an object");
+            }
+            return;
+        };
+        for (key, value) in usage {
+            if !value.is_null() && !value.is_u64() {
+                self.error(&format!("{}.{}", path, key), "not a token count");
+            }
+        }
+    }
+}
+
+fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::models::{ConversationEntry, DevlogOutput};
+use crate::store::project_name;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Waits before each retry of a failed delivery; after the last one the event is dropped
+pub const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(30),
+];
+
+/// Whether a delivery answered with `status` is worth another try: the
+/// receiving end was down or busy, rather than refusing the event
+pub fn is_retryable(status: u16) -> bool {
+    status == 408 || status == 429 || status >= 500
+}
+
+/// What both receivers POST to generic webhooks about each session they store:
+/// who, where and how much, without the conversation itself
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvent {
+    /// Always `session.stored`, leaving room for other events
+    pub event: &'static str,
+    pub session_id: String,
+    pub machine_id: String,
+    pub project: String,
+    pub project_dir: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    pub tags: Vec<String>,
+    /// Ticket IDs, e.g. `PROJ-123` or `#456`
+    pub tickets: Vec<String>,
+    pub prompts: usize,
+    pub tool_actions: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// The first line of the first prompt, at most 200 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_prompt: Option<String>,
+    /// The session's page, when the receiver knows its own address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl SessionEvent {
+    pub fn new(output: &DevlogOutput) -> Self {
+        let mut event = Self {
+            event: "session.stored",
+            session_id: output.session_id.clone(),
+            machine_id: output.machine_id.clone(),
+            project: project_name(output.project_path()),
+            project_dir: output.project_dir.clone(),
+            timestamp: output.timestamp.clone(),
+            branch: output.git.as_ref().map(|git| git.branch.clone()),
+            remote: output.git.as_ref().and_then(|git| git.remote.clone()),
+            tags: output.tags.clone(),
+            tickets: output.tickets.iter().map(|t| t.id.clone()).collect(),
+            prompts: 0,
+            tool_actions: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            first_prompt: None,
+            url: None,
+        };
+        for entry in &output.conversation {
+            match entry {
+                ConversationEntry::User { content, .. } => {
+                    event.prompts += 1;
+                    if event.first_prompt.is_none() {
+                        let line = content.trim().lines().next().unwrap_or("");
+                        event.first_prompt = Some(line.chars().take(200).collect());
+                    }
+                }
+                ConversationEntry::Assistant { usage: Some(usage), .. } => {
+                    event.input_tokens += usage.input_tokens.unwrap_or(0);
+                    event.output_tokens += usage.output_tokens.unwrap_or(0);
+                }
+                ConversationEntry::Assistant { .. } => {}
+                ConversationEntry::ToolSummary { actions } => event.tool_actions += actions.len(),
+            }
+        }
+        event
+    }
+}
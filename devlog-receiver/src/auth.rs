@@ -0,0 +1,151 @@
+use crate::{db, with_db, AppState};
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header,
+    middleware::Next,
+    web, Error, HttpResponse,
+};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+
+/// Tokens are stored as hex SHA-256 digests, never in plain text
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Register tokens from `DEVLOG_API_TOKENS` and admin tokens from
+/// `DEVLOG_ADMIN_TOKENS` (both "name:token,name:token") and report whether any
+/// tokens exist, i.e. whether requests must authenticate. When either variable
+/// is set, the two together are the full list: stored tokens missing from both
+/// are revoked.
+pub fn init_tokens(pool: &db::DbPool) -> Result<bool> {
+    let conn = pool.get().context("Failed to get a database connection")?;
+
+    let mut configured = None;
+    for (var, admin) in [("DEVLOG_API_TOKENS", false), ("DEVLOG_ADMIN_TOKENS", true)] {
+        let Ok(spec) = std::env::var(var) else {
+            continue;
+        };
+        let hashes = configured.get_or_insert_with(Vec::new);
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, token) = entry
+                .split_once(':')
+                .with_context(|| format!("{} entries must look like name:token", var))?;
+            let token_hash = hash_token(token.trim());
+            db::upsert_token(&conn, name.trim(), &token_hash, admin)?;
+            info!("Registered {} token '{}'", if admin { "admin" } else { "API" }, name.trim());
+            hashes.push(token_hash);
+        }
+    }
+
+    if let Some(hashes) = configured {
+        for name in db::delete_tokens_except(&conn, &hashes)? {
+            info!("Revoked API token '{}', no longer configured", name);
+        }
+    }
+
+    let count = db::token_count(&conn)?;
+    if count == 0 {
+        warn!("No API tokens configured; all endpoints are open. Set DEVLOG_API_TOKENS to require auth");
+    }
+    if db::admin_token_count(&conn)? == 0 {
+        warn!("No admin tokens configured; /admin endpoints are disabled. Set DEVLOG_ADMIN_TOKENS to enable them");
+    }
+
+    Ok(count > 0)
+}
+
+/// The bearer token a request carries, if any
+fn bearer(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|t| t.trim().to_string())
+}
+
+/// The registered token a request carries, if any
+async fn registered(state: &web::Data<AppState>, req: &ServiceRequest) -> Option<db::ApiToken> {
+    let token_hash = hash_token(&bearer(req)?);
+    with_db(state, move |conn| db::find_token(conn, &token_hash))
+        .await
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to check API token: {}", e);
+            None
+        })
+}
+
+/// Middleware rejecting requests without a registered `Authorization: Bearer` token
+pub async fn require_token<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .cloned()
+        .expect("AppState is registered");
+
+    if !state.auth_required {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+
+    match registered(&state, &req).await {
+        Some(_) => next.call(req).await.map(ServiceResponse::map_into_left_body),
+        None => {
+            let response = HttpResponse::Unauthorized()
+                .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                .json(serde_json::json!({
+                    "status": "error",
+                    "error": "Missing or invalid bearer token"
+                }));
+            Ok(req.into_response(response).map_into_right_body())
+        }
+    }
+}
+
+/// Middleware letting only admin tokens through. Unlike [`require_token`] it
+/// never falls back to open access: with no admin token registered, the
+/// routes it guards answer 403 to everyone.
+pub async fn require_admin<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .cloned()
+        .expect("AppState is registered");
+
+    let token = registered(&state, &req).await;
+    if token.as_ref().map(|t| t.admin).unwrap_or(false) {
+        return next.call(req).await.map(ServiceResponse::map_into_left_body);
+    }
+
+    let admins = with_db(&state, |conn| db::admin_token_count(conn)).await.unwrap_or_else(|e| {
+        eprintln!("Failed to count admin tokens: {}", e);
+        0
+    });
+    let response = if admins == 0 {
+        HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "error": "Admin endpoints are disabled; set DEVLOG_ADMIN_TOKENS to enable them"
+        }))
+    } else if token.is_some() {
+        HttpResponse::Forbidden().json(serde_json::json!({
+            "status": "error",
+            "error": "This token may not use admin endpoints"
+        }))
+    } else {
+        HttpResponse::Unauthorized()
+            .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+            .json(serde_json::json!({
+                "status": "error",
+                "error": "Missing or invalid admin bearer token"
+            }))
+    };
+    Ok(req.into_response(response).map_into_right_body())
+}
@@ -0,0 +1,59 @@
+use crate::{db, with_db, AppState};
+use actix_web::web;
+use anyhow::Result;
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Snapshot the database into the backup dir, then drop all but the newest `backup_keep`
+pub async fn run_backup(app_state: &web::Data<AppState>) -> Result<PathBuf> {
+    let backup_root = app_state.backup_dir.clone();
+    let keep = app_state.backup_keep;
+
+    with_db(app_state, move |conn| {
+        let dir = db::backup_database(conn, &backup_root)?;
+        remove_old_backups(&backup_root, keep)?;
+        Ok(dir)
+    })
+    .await
+}
+
+/// Take a backup every `interval`, starting one interval after startup
+pub async fn backup_periodically(app_state: web::Data<AppState>, interval: Duration) {
+    loop {
+        actix_web::rt::time::sleep(interval).await;
+        match run_backup(&app_state).await {
+            Ok(dir) => info!("Scheduled backup written to {}", dir.display()),
+            Err(e) => warn!("Scheduled backup failed: {:#}", e),
+        }
+    }
+}
+
+/// Backup directory names sort by time, so the newest are last
+fn remove_old_backups(backup_root: &Path, keep: usize) -> Result<()> {
+    if keep == 0 {
+        return Ok(());
+    }
+
+    let mut backups: Vec<PathBuf> = fs::read_dir(backup_root)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with("backup-"))
+                    .unwrap_or(false)
+        })
+        .collect();
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(keep);
+    for old in &backups[..excess] {
+        fs::remove_dir_all(old)?;
+        info!("Removed old backup {}", old.display());
+    }
+
+    Ok(())
+}
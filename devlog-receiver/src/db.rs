@@ -1,14 +1,67 @@
-use crate::models::DevlogSession;
+use crate::models::{
+    ProjectStats, SearchHit, SessionFilter, SessionSummary, StatsReport, StoredSession,
+};
 use anyhow::{Context, Result};
-use duckdb::Connection;
+use devlog_core::forge::normalize_remote;
+use devlog_core::{
+    ConversationEntry, DevlogOutput, FileStore, GitRemote, ProjectGrouping, PruneReport,
+    PurgeReport, PurgeRequest, PurgedSession, RetentionPolicy,
+};
+use duckdb::{params_from_iter, Connection, DuckdbConnectionManager, OptionalExt, Row};
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn init_database(db_path: &str) -> Result<Connection> {
-    let conn = Connection::open(db_path)
-        .with_context(|| format!("Failed to open DuckDB database at {}", db_path))?;
+/// Connections share one database instance; DuckDB handles concurrency between them
+pub type DbPool = r2d2::Pool<DuckdbConnectionManager>;
 
-    // Create sessions table
-    conn.execute(
-        r#"
+/// Joins each row of `messages` to its session's metadata, so the session filters apply
+const MESSAGES_CTE: &str = r#"
+    WITH session_messages AS (
+        SELECT
+            s.id, s.session_id, s.machine_id, s.project_dir, s.timestamp, s.git_branch, s.git_remote, s.git, s.tags, s.tickets,
+            m.idx AS entry_index, m.role, m.timestamp AS entry_timestamp, m.content, m.tool_name
+        FROM messages m
+        JOIN sessions s ON s.id = m.session_fk
+    )
+"#;
+
+/// Whether a session's tickets include the one bound to `?`; case and a leading
+/// `#` are ignored, so `456` finds `#456`
+const TICKET_CONDITION: &str = "list_contains(\
+    list_transform(json_extract_string(tickets, '$[*].id'), id -> lower(ltrim(id, '#'))), \
+    lower(ltrim(CAST(? AS VARCHAR), '#')))";
+
+/// Default and maximum page sizes for `list_sessions`
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+/// Columns read into a `SessionSummary`, in the order `summary_from_row` expects.
+/// Timestamps are formatted in SQL so no chrono conversion is needed on the way out.
+const SUMMARY_COLUMNS: &str = r#"
+    session_id, machine_id, project_dir,
+    strftime(timestamp, '%Y-%m-%dT%H:%M:%SZ'),
+    git_remote, git_branch, git_commit,
+    CAST(json_array_length(conversation) AS BIGINT),
+    strftime(received_at, '%Y-%m-%dT%H:%M:%SZ'),
+    CAST(to_json(tags) AS VARCHAR)
+"#;
+
+/// A schema change applied once, in version order, inside its own transaction
+struct Migration {
+    version: i32,
+    name: &'static str,
+    sql: &'static str,
+}
+
+/// Ordered schema history. Append new steps; never edit one that has shipped.
+/// DuckDB refuses some ALTERs on indexed tables, so steps that alter `sessions`
+/// should drop and recreate its secondary indexes around the change.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create sessions",
+        sql: r#"
         CREATE TABLE IF NOT EXISTS sessions (
             id INTEGER PRIMARY KEY,
             session_id VARCHAR NOT NULL,
@@ -22,37 +75,321 @@ pub fn init_database(db_path: &str) -> Result<Connection> {
             conversation JSON NOT NULL,
             received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             UNIQUE(machine_id, session_id)
-        )
+        );
+        CREATE INDEX IF NOT EXISTS idx_machine_timestamp ON sessions(machine_id, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_project ON sessions(project_dir);
+        CREATE INDEX IF NOT EXISTS idx_git_remote ON sessions(git_remote);
         "#,
-        [],
-    )
-    .context("Failed to create sessions table")?;
+    },
+    Migration {
+        // DuckDB does not auto-assign INTEGER PRIMARY KEY values
+        version: 2,
+        name: "sessions id sequence",
+        sql: "CREATE SEQUENCE IF NOT EXISTS sessions_id_seq START 1;",
+    },
+    Migration {
+        version: 3,
+        name: "api tokens",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS api_tokens (
+            token_hash VARCHAR PRIMARY KEY,
+            name VARCHAR NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        "#,
+    },
+    Migration {
+        // Rows are filled by `insert_session`; existing sessions by `backfill_messages`
+        version: 4,
+        name: "messages",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS messages (
+            session_fk INTEGER NOT NULL,
+            idx INTEGER NOT NULL,
+            role VARCHAR NOT NULL,
+            timestamp TIMESTAMP,
+            content VARCHAR NOT NULL,
+            tool_name VARCHAR
+        );
+        CREATE INDEX IF NOT EXISTS idx_messages_session ON messages(session_fk, idx);
+        CREATE INDEX IF NOT EXISTS idx_messages_role ON messages(role);
+        CREATE INDEX IF NOT EXISTS idx_messages_tool ON messages(tool_name);
+        "#,
+    },
+    Migration {
+        // A separate table because DuckDB upserts cannot update indexed columns of `sessions`
+        version: 5,
+        name: "session content hashes",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS session_hashes (
+            content_hash VARCHAR PRIMARY KEY,
+            session_fk INTEGER NOT NULL,
+            received_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_hashes_session ON session_hashes(session_fk);
+        "#,
+    },
+    Migration {
+        // The full git object, including working-tree status, beyond the indexed columns
+        version: 6,
+        name: "sessions git json",
+        sql: r#"
+        DROP INDEX IF EXISTS idx_machine_timestamp;
+        DROP INDEX IF EXISTS idx_project;
+        DROP INDEX IF EXISTS idx_git_remote;
+        ALTER TABLE sessions ADD COLUMN git JSON;
+        CREATE INDEX idx_machine_timestamp ON sessions(machine_id, timestamp);
+        CREATE INDEX idx_project ON sessions(project_dir);
+        CREATE INDEX idx_git_remote ON sessions(git_remote);
+        "#,
+    },
+    Migration {
+        // Every named remote, not just origin; rows are filled by `insert_session`
+        version: 7,
+        name: "session remotes",
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS session_remotes (
+            session_fk INTEGER NOT NULL,
+            name VARCHAR NOT NULL,
+            url VARCHAR NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_remotes_session ON session_remotes(session_fk);
+        CREATE INDEX IF NOT EXISTS idx_session_remotes_url ON session_remotes(url);
+        "#,
+    },
+    Migration {
+        // Mirrors `devlog_core::forge::normalize_remote`: scp-like, ssh:// and https://
+        // forms of one repository reduce to the same lowercase `host/owner/repo`
+        version: 8,
+        name: "normalize_remote macro",
+        sql: r#"
+        CREATE OR REPLACE MACRO normalize_remote(url) AS lower(
+            regexp_replace(
+                regexp_replace(
+                    regexp_replace(
+                        regexp_replace(trim(url), '^[^/@:]*://', ''),
+                        '^[^@/]*@', ''),
+                    '^([^/:]*):([0-9]+/)?', '\1/'),
+                '(\.git)?/*$', '')
+        );
+        "#,
+    },
+    Migration {
+        // Labels added on the client with `devlog tag`
+        version: 9,
+        name: "sessions tags",
+        sql: r#"
+        DROP INDEX IF EXISTS idx_machine_timestamp;
+        DROP INDEX IF EXISTS idx_project;
+        DROP INDEX IF EXISTS idx_git_remote;
+        ALTER TABLE sessions ADD COLUMN tags VARCHAR[] DEFAULT [];
+        CREATE INDEX idx_machine_timestamp ON sessions(machine_id, timestamp);
+        CREATE INDEX idx_project ON sessions(project_dir);
+        CREATE INDEX idx_git_remote ON sessions(git_remote);
+        "#,
+    },
+    Migration {
+        // Annotations added on the client with `devlog note`
+        version: 10,
+        name: "sessions notes",
+        sql: r#"
+        DROP INDEX IF EXISTS idx_machine_timestamp;
+        DROP INDEX IF EXISTS idx_project;
+        DROP INDEX IF EXISTS idx_git_remote;
+        ALTER TABLE sessions ADD COLUMN notes JSON;
+        CREATE INDEX idx_machine_timestamp ON sessions(machine_id, timestamp);
+        CREATE INDEX idx_project ON sessions(project_dir);
+        CREATE INDEX idx_git_remote ON sessions(git_remote);
+        "#,
+    },
+    Migration {
+        // Issue-tracker tickets the client found in the prompts
+        version: 11,
+        name: "sessions tickets",
+        sql: r#"
+        DROP INDEX IF EXISTS idx_machine_timestamp;
+        DROP INDEX IF EXISTS idx_project;
+        DROP INDEX IF EXISTS idx_git_remote;
+        ALTER TABLE sessions ADD COLUMN tickets JSON;
+        CREATE INDEX idx_machine_timestamp ON sessions(machine_id, timestamp);
+        CREATE INDEX idx_project ON sessions(project_dir);
+        CREATE INDEX idx_git_remote ON sessions(git_remote);
+        "#,
+    },
+    Migration {
+        // Tokens from DEVLOG_ADMIN_TOKENS may also use the /admin endpoints.
+        // The table is rebuilt rather than altered, as its primary key is an index.
+        version: 12,
+        name: "api_tokens admin",
+        sql: r#"
+        CREATE TABLE api_tokens_new (
+            token_hash VARCHAR PRIMARY KEY,
+            name VARCHAR NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            admin BOOLEAN NOT NULL DEFAULT false
+        );
+        INSERT INTO api_tokens_new (token_hash, name, created_at)
+            SELECT token_hash, name, created_at FROM api_tokens;
+        DROP TABLE api_tokens;
+        ALTER TABLE api_tokens_new RENAME TO api_tokens;
+        "#,
+    },
+];
 
-    // Create indexes for common queries
+pub fn init_database(db_path: &str, pool_size: u32) -> Result<DbPool> {
+    let manager = DuckdbConnectionManager::file(db_path)
+        .with_context(|| format!("Failed to open DuckDB database at {}", db_path))?;
+
+    let pool = r2d2::Pool::builder()
+        .max_size(pool_size)
+        .build(manager)
+        .context("Failed to create database connection pool")?;
+
+    let mut conn = pool.get().context("Failed to get a database connection")?;
+    run_migrations(&mut conn)?;
+    backfill_messages(&mut conn)?;
+
+    Ok(pool)
+}
+
+/// Bring the schema up to date, recording each applied step in `schema_migrations`
+fn run_migrations(conn: &mut Connection) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_machine_timestamp ON sessions(machine_id, timestamp)",
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name VARCHAR NOT NULL,
+            applied_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
         [],
     )
-    .context("Failed to create machine_timestamp index")?;
+    .context("Failed to create schema_migrations table")?;
+
+    let current: i32 = conn
+        .query_row(
+            "SELECT coalesce(max(version), 0) FROM schema_migrations",
+            [],
+            |row| row.get(0),
+        )
+        .context("Failed to read schema version")?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        info!("Applying migration {}: {}", migration.version, migration.name);
 
+        let tx = conn.transaction().context("Failed to start migration transaction")?;
+        tx.execute_batch(migration.sql)
+            .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.name))?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name) VALUES (?, ?)",
+            duckdb::params![migration.version, migration.name],
+        )
+        .context("Failed to record migration")?;
+        tx.commit()
+            .with_context(|| format!("Failed to commit migration {}", migration.version))?;
+    }
+
+    Ok(())
+}
+
+/// Register a token by its SHA-256 hash; re-registering renames it
+pub fn upsert_token(conn: &Connection, name: &str, token_hash: &str, admin: bool) -> Result<()> {
     conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_project ON sessions(project_dir)",
-        [],
+        r#"
+        INSERT INTO api_tokens (token_hash, name, admin) VALUES (?, ?, ?)
+        ON CONFLICT (token_hash) DO UPDATE SET name = excluded.name, admin = excluded.admin
+        "#,
+        duckdb::params![token_hash, name, admin],
     )
-    .context("Failed to create project index")?;
+    .context("Failed to store API token")?;
+    Ok(())
+}
 
-    conn.execute(
-        "CREATE INDEX IF NOT EXISTS idx_git_remote ON sessions(git_remote)",
-        [],
+pub fn token_count(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT count(*) FROM api_tokens", [], |row| row.get(0))
+        .context("Failed to count API tokens")
+}
+
+/// Delete every token whose hash is not in `keep`, returning their names
+pub fn delete_tokens_except(conn: &Connection, keep: &[String]) -> Result<Vec<String>> {
+    let filter = if keep.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE token_hash NOT IN ({})", vec!["?"; keep.len()].join(", "))
+    };
+    let mut stmt = conn
+        .prepare(&format!("DELETE FROM api_tokens{} RETURNING name", filter))
+        .context("Failed to prepare token deletion")?;
+    let names = stmt
+        .query_map(params_from_iter(keep.iter()), |row| row.get(0))
+        .context("Failed to delete API tokens")?
+        .collect::<duckdb::Result<Vec<String>>>()
+        .context("Failed to read deleted API tokens")?;
+    Ok(names)
+}
+
+/// Delete the tokens registered under `name`, returning how many there were
+pub fn revoke_token(conn: &Connection, name: &str) -> Result<usize> {
+    conn.execute("DELETE FROM api_tokens WHERE name = ?", duckdb::params![name])
+        .context("Failed to revoke API token")
+}
+
+pub fn admin_token_count(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT count(*) FROM api_tokens WHERE admin", [], |row| row.get(0))
+        .context("Failed to count admin tokens")
+}
+
+/// A registered API token
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    pub name: String,
+    /// Whether it may use the /admin endpoints
+    pub admin: bool,
+}
+
+/// The token with this hash, if it is registered
+pub fn find_token(conn: &Connection, token_hash: &str) -> Result<Option<ApiToken>> {
+    conn.query_row(
+        "SELECT name, admin FROM api_tokens WHERE token_hash = ?",
+        duckdb::params![token_hash],
+        |row| {
+            Ok(ApiToken {
+                name: row.get(0)?,
+                admin: row.get(1)?,
+            })
+        },
     )
-    .context("Failed to create git_remote index")?;
+    .optional()
+    .context("Failed to look up API token")
+}
 
-    Ok(conn)
+/// Whether an ingested session was written or already stored with identical content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertOutcome {
+    Stored,
+    Duplicate,
 }
 
-pub fn insert_session(conn: &Connection, session: &DevlogSession) -> Result<()> {
+/// Insert or update a session, skipping it if identical content was already received
+pub fn insert_session(conn: &Connection, session: &DevlogOutput) -> Result<InsertOutcome> {
+    let content_hash = session.content_hash();
+    let seen: bool = conn
+        .query_row(
+            "SELECT count(*) > 0 FROM session_hashes WHERE content_hash = ?",
+            duckdb::params![content_hash],
+            |row| row.get(0),
+        )
+        .context("Failed to check content hash")?;
+    if seen {
+        return Ok(InsertOutcome::Duplicate);
+    }
+
     let conversation_json = serde_json::to_string(&session.conversation)
         .context("Failed to serialize conversation")?;
+    let git_json = serde_json::to_string(&session.git).context("Failed to serialize git info")?;
+    let tags_json = serde_json::to_string(&session.tags).context("Failed to serialize tags")?;
+    let notes_json = serde_json::to_string(&session.notes).context("Failed to serialize notes")?;
+    let tickets_json = serde_json::to_string(&session.tickets).context("Failed to serialize tickets")?;
 
     let timestamp = chrono::DateTime::parse_from_rfc3339(&session.timestamp)
         .context("Failed to parse timestamp")?
@@ -61,10 +398,10 @@ pub fn insert_session(conn: &Connection, session: &DevlogSession) -> Result<()>
     conn.execute(
         r#"
         INSERT INTO sessions (
-            session_id, machine_id, project_dir, timestamp,
+            id, session_id, machine_id, project_dir, timestamp,
             schema_version, git_remote, git_branch, git_commit,
-            conversation
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            conversation, git, tags, notes, tickets
+        ) VALUES (nextval('sessions_id_seq'), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CAST(CAST(? AS JSON) AS VARCHAR[]), ?, ?)
         ON CONFLICT (machine_id, session_id) DO UPDATE SET
             timestamp = excluded.timestamp,
             project_dir = excluded.project_dir,
@@ -73,6 +410,10 @@ pub fn insert_session(conn: &Connection, session: &DevlogSession) -> Result<()>
             git_branch = excluded.git_branch,
             git_commit = excluded.git_commit,
             conversation = excluded.conversation,
+            git = excluded.git,
+            tags = excluded.tags,
+            notes = excluded.notes,
+            tickets = excluded.tickets,
             received_at = CURRENT_TIMESTAMP
         "#,
         [
@@ -82,12 +423,914 @@ pub fn insert_session(conn: &Connection, session: &DevlogSession) -> Result<()>
             &timestamp,
             &session.schema_version,
             &session.git.as_ref().and_then(|g| g.remote.as_ref()),
-            &session.git.as_ref().and_then(|g| g.branch.as_ref()),
-            &session.git.as_ref().and_then(|g| g.commit.as_ref()),
+            &session.git.as_ref().map(|g| &g.branch),
+            &session.git.as_ref().map(|g| &g.commit),
             &conversation_json,
+            &git_json,
+            &tags_json,
+            &notes_json,
+            &tickets_json,
         ],
     )
     .context("Failed to insert session into database")?;
 
+    let session_fk: i64 = conn
+        .query_row(
+            "SELECT CAST(id AS BIGINT) FROM sessions WHERE machine_id = ? AND session_id = ?",
+            duckdb::params![session.machine_id, session.session_id],
+            |row| row.get(0),
+        )
+        .context("Failed to read stored session id")?;
+
+    insert_messages(conn, session_fk, &session.conversation)?;
+    let remotes = session.git.as_ref().map(|g| g.remotes.as_slice()).unwrap_or_default();
+    insert_remotes(conn, session_fk, remotes)?;
+
+    conn.execute(
+        "INSERT INTO session_hashes (content_hash, session_fk) VALUES (?, ?) ON CONFLICT DO NOTHING",
+        duckdb::params![content_hash, session_fk],
+    )
+    .context("Failed to record content hash")?;
+
+    Ok(InsertOutcome::Stored)
+}
+
+/// Replace a session's `session_remotes` rows
+fn insert_remotes(conn: &Connection, session_fk: i64, remotes: &[GitRemote]) -> Result<()> {
+    conn.execute("DELETE FROM session_remotes WHERE session_fk = ?", duckdb::params![session_fk])
+        .context("Failed to clear previous remotes")?;
+
+    let mut stmt = conn
+        .prepare_cached("INSERT INTO session_remotes (session_fk, name, url) VALUES (?, ?, ?)")
+        .context("Failed to prepare remote insert")?;
+    for remote in remotes {
+        stmt.execute(duckdb::params![session_fk, remote.name, remote.url])
+            .context("Failed to insert remote")?;
+    }
+
+    Ok(())
+}
+
+/// One row of the `messages` table
+struct MessageRow {
+    role: &'static str,
+    timestamp: Option<chrono::NaiveDateTime>,
+    content: String,
+    tool_name: Option<String>,
+}
+
+/// Replace a session's `messages` rows: one per prompt or response, and one per tool
+/// call. `idx` is the entry's position in the conversation, so the tool calls of one
+/// summary share it.
+fn insert_messages(conn: &Connection, session_fk: i64, conversation: &[ConversationEntry]) -> Result<()> {
+    conn.execute("DELETE FROM messages WHERE session_fk = ?", duckdb::params![session_fk])
+        .context("Failed to clear previous messages")?;
+
+    let mut stmt = conn
+        .prepare_cached(
+            "INSERT INTO messages (session_fk, idx, role, timestamp, content, tool_name) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .context("Failed to prepare message insert")?;
+
+    for (idx, entry) in conversation.iter().enumerate() {
+        for row in message_rows(entry) {
+            stmt.execute(duckdb::params![
+                session_fk,
+                idx as i64,
+                row.role,
+                row.timestamp,
+                row.content,
+                row.tool_name
+            ])
+            .context("Failed to insert message")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn message_rows(entry: &ConversationEntry) -> Vec<MessageRow> {
+    let parse_timestamp = |ts: &Option<String>| {
+        ts.as_deref()
+            .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.naive_utc())
+    };
+
+    match entry {
+        ConversationEntry::User { timestamp, content, .. } => vec![MessageRow {
+            role: "user",
+            timestamp: parse_timestamp(timestamp),
+            content: content.clone(),
+            tool_name: None,
+        }],
+        ConversationEntry::Assistant {
+            timestamp, content, ..
+        } => vec![MessageRow {
+            role: "assistant",
+            timestamp: parse_timestamp(timestamp),
+            content: content.clone(),
+            tool_name: None,
+        }],
+        ConversationEntry::ToolSummary { actions } => actions
+            .iter()
+            .map(|action| MessageRow {
+                role: "tool",
+                timestamp: None,
+                content: action.clone(),
+                tool_name: tool_name(action),
+            })
+            .collect(),
+    }
+}
+
+/// Recover the tool behind a summarized action such as "edited src/main.rs"
+fn tool_name(action: &str) -> Option<String> {
+    let (verb, rest) = action.split_once(' ').unwrap_or((action, ""));
+    let name = match verb {
+        "edited" => "Edit",
+        "created" => "Write",
+        "read" => "Read",
+        "ran" => "Bash",
+        "searched" => "Search",
+        "fetched" => "WebFetch",
+        "updated" if rest == "todo list" => "TodoWrite",
+        "used" if rest == "subagent" => "Task",
+        "used" => rest,
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Fill `messages` for sessions stored before the table existed
+fn backfill_messages(conn: &mut Connection) -> Result<()> {
+    let pending: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT CAST(id AS BIGINT), CAST(conversation AS VARCHAR)
+            FROM sessions
+            WHERE id NOT IN (SELECT DISTINCT session_fk FROM messages)
+              AND json_array_length(conversation) > 0
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()
+            .context("Failed to read sessions for messages backfill")?
+    };
+
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    info!("Backfilling messages for {} sessions", pending.len());
+
+    let tx = conn.transaction().context("Failed to start backfill transaction")?;
+    for (session_fk, conversation_json) in pending {
+        match serde_json::from_str::<Vec<ConversationEntry>>(&conversation_json) {
+            Ok(conversation) => insert_messages(&tx, session_fk, &conversation)?,
+            Err(e) => warn!("Skipping messages for session {}: {}", session_fk, e),
+        }
+    }
+    tx.commit().context("Failed to commit messages backfill")?;
+
+    Ok(())
+}
+
+/// Insert or update one session in its own transaction, committed only once
+/// `persist` has written a stored session elsewhere (the file store). If that
+/// fails nothing is recorded, so the client's retry is stored again rather
+/// than answered as a duplicate.
+pub fn store_session<F>(conn: &mut Connection, session: &DevlogOutput, persist: F) -> Result<InsertOutcome>
+where
+    F: FnOnce(&DevlogOutput) -> Result<()>,
+{
+    let tx = conn.transaction().context("Failed to start ingest transaction")?;
+    let outcome = insert_session(&tx, session)?;
+    if outcome == InsertOutcome::Stored {
+        persist(session)?;
+    }
+    tx.commit().context("Failed to commit ingest transaction")?;
+
+    Ok(outcome)
+}
+
+/// Insert or update a batch of sessions in one transaction, so a bad entry stores nothing.
+/// As in [`store_session`], the transaction is committed only once `persist`
+/// has written the sessions that were stored. Returns the outcome for each
+/// session, in order.
+pub fn insert_sessions<F>(conn: &mut Connection, sessions: &[DevlogOutput], persist: F) -> Result<Vec<InsertOutcome>>
+where
+    F: FnOnce(Vec<&DevlogOutput>) -> Result<()>,
+{
+    let tx = conn.transaction().context("Failed to start ingest transaction")?;
+    let outcomes = sessions
+        .iter()
+        .map(|session| {
+            insert_session(&tx, session)
+                .with_context(|| format!("Failed to store session {}", session.session_id))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    persist(
+        sessions
+            .iter()
+            .zip(&outcomes)
+            .filter(|(_, outcome)| **outcome == InsertOutcome::Stored)
+            .map(|(session, _)| session)
+            .collect(),
+    )?;
+    tx.commit().context("Failed to commit ingest transaction")?;
+
+    Ok(outcomes)
+}
+
+/// Sessions inserted per transaction by `import_file_store`
+const IMPORT_CHUNK: usize = 500;
+
+/// What loading a `devlog serve` storage directory did
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub stored: usize,
+    pub duplicates: usize,
+    /// Files that are not readable devlogs
+    pub skipped: usize,
+}
+
+/// Insert every session under a `devlog serve` storage directory
+/// (`<machine>/<project>/<YYYY>/<MM>/*.json`), oldest file first so the latest copy of a
+/// session wins. Sessions already received are skipped as duplicates, so an
+/// interrupted import can simply be run again.
+pub fn import_file_store(conn: &mut Connection, store: &FileStore) -> Result<ImportReport> {
+    let mut files = store.stored_files()?;
+    files.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.path.cmp(&b.path)));
+    info!("Importing {} session files from {}", files.len(), store.root().display());
+
+    let mut report = ImportReport::default();
+    for chunk in files.chunks(IMPORT_CHUNK) {
+        let mut sessions = Vec::with_capacity(chunk.len());
+        for file in chunk {
+            let session = devlog_core::store::read_session(&file.path).and_then(|session| {
+                // A bad timestamp would abort the whole transaction
+                chrono::DateTime::parse_from_rfc3339(&session.timestamp).context("Failed to parse timestamp")?;
+                Ok(session)
+            });
+            match session {
+                Ok(session) => sessions.push(session),
+                Err(e) => {
+                    warn!("Skipping {}: {:#}", file.path.display(), e);
+                    report.skipped += 1;
+                }
+            }
+        }
+
+        for outcome in insert_sessions(conn, &sessions, |_| Ok(()))? {
+            match outcome {
+                InsertOutcome::Stored => report.stored += 1,
+                InsertOutcome::Duplicate => report.duplicates += 1,
+            }
+        }
+        info!(
+            "Imported {} of {} files",
+            report.stored + report.duplicates + report.skipped,
+            files.len()
+        );
+    }
+
+    Ok(report)
+}
+
+/// Pass each session the filter matches to `each`, newest first, as DuckDB's
+/// cursor reads them, so a listing is never held in memory whole
+pub fn list_sessions<F>(conn: &Connection, filter: &SessionFilter, mut each: F) -> Result<()>
+where
+    F: FnMut(SessionSummary) -> Result<()>,
+{
+    let (conditions, params) = filter_conditions(filter);
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", conditions.join(" AND "))
+    };
+
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = filter.offset.unwrap_or(0);
+
+    let sql = format!(
+        "SELECT {} FROM sessions {} ORDER BY timestamp DESC, machine_id, session_id LIMIT {} OFFSET {}",
+        SUMMARY_COLUMNS, where_clause, limit, offset
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare session query")?;
+    let mut rows = stmt
+        .query(params_from_iter(params.iter()))
+        .context("Failed to query sessions")?;
+    while let Some(row) = rows.next().context("Failed to read session row")? {
+        each(summary_from_row(row).context("Failed to read session row")?)?;
+    }
+
     Ok(())
 }
+
+/// Case-insensitive substring search over the content of every stored conversation entry
+pub fn search_messages(conn: &Connection, query: &str, filter: &SessionFilter) -> Result<Vec<SearchHit>> {
+    let (mut conditions, mut params) = filter_conditions(filter);
+    conditions.push("contains(lower(content), lower(?))");
+    params.push(query.to_string());
+
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    let sql = format!(
+        r#"
+        {}
+        SELECT session_id, machine_id, project_dir,
+               strftime(timestamp, '%Y-%m-%dT%H:%M:%SZ'),
+               CAST(entry_index AS BIGINT), role, content
+        FROM session_messages
+        WHERE {}
+        ORDER BY timestamp DESC, machine_id, session_id, entry_index
+        LIMIT {}
+        "#,
+        MESSAGES_CTE,
+        conditions.join(" AND "),
+        limit
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare search query")?;
+    let hits = stmt
+        .query_map(params_from_iter(params.iter()), |row| {
+            let content: String = row.get(6)?;
+            Ok(SearchHit {
+                session_id: row.get(0)?,
+                machine_id: row.get(1)?,
+                project_dir: row.get(2)?,
+                timestamp: row.get(3)?,
+                entry_index: row.get(4)?,
+                role: row.get(5)?,
+                snippet: create_snippet(&content, query),
+            })
+        })
+        .context("Failed to run search")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read search row")?;
+
+    Ok(hits)
+}
+
+/// Per machine/project activity over the last `days` days, computed entirely in DuckDB
+pub fn project_stats(
+    conn: &Connection,
+    days: u32,
+    grouping: ProjectGrouping,
+    tag: Option<&str>,
+    ticket: Option<&str>,
+) -> Result<StatsReport> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+    let cutoff = cutoff.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    // Worktree and submodule sessions count towards their main repository
+    let directory = "coalesce(json_extract_string(git, '$.parent_repo.path'), project_dir)";
+    let project_key = match grouping {
+        ProjectGrouping::Directory => directory.to_string(),
+        // Origin, else the first remote, as in `ProjectGrouping::project_key`
+        ProjectGrouping::Remote => format!(
+            "coalesce(normalize_remote(coalesce(git_remote, json_extract_string(git, '$.remotes[0].url'))), {})",
+            directory
+        ),
+    };
+
+    let sql = format!(
+        r#"
+        WITH recent AS (
+            SELECT
+                machine_id,
+                {project_key} AS project_dir,
+                timestamp, conversation
+            FROM sessions
+            WHERE timestamp >= CAST(? AS TIMESTAMP)
+              AND (CAST(? AS VARCHAR) IS NULL OR list_contains(tags, CAST(? AS VARCHAR)))
+              AND (CAST(? AS VARCHAR) IS NULL OR {ticket_condition})
+        ),
+        entries AS (
+            SELECT machine_id, project_dir, unnest(json_extract(conversation, '$[*]')) AS entry
+            FROM recent
+        ),
+        entry_stats AS (
+            SELECT
+                machine_id, project_dir,
+                count(*) FILTER (
+                    WHERE coalesce(json_extract_string(entry, '$.type'), json_extract_string(entry, '$.role')) = 'user'
+                ) AS prompts,
+                sum(json_array_length(json_extract(entry, '$.actions'))) AS tool_calls,
+                sum(TRY_CAST(json_extract_string(entry, '$.usage.input_tokens') AS BIGINT)) AS input_tokens,
+                sum(TRY_CAST(json_extract_string(entry, '$.usage.output_tokens') AS BIGINT)) AS output_tokens
+            FROM entries
+            GROUP BY machine_id, project_dir
+        ),
+        session_stats AS (
+            SELECT machine_id, project_dir, count(*) AS sessions, max(timestamp) AS last_activity
+            FROM recent
+            GROUP BY machine_id, project_dir
+        )
+        SELECT
+            s.machine_id,
+            regexp_extract(s.project_dir, '([^/\\]+)[/\\]*$', 1),
+            s.project_dir,
+            CAST(s.sessions AS BIGINT),
+            CAST(coalesce(e.prompts, 0) AS BIGINT),
+            CAST(coalesce(e.tool_calls, 0) AS BIGINT),
+            CAST(coalesce(e.input_tokens, 0) AS BIGINT),
+            CAST(coalesce(e.output_tokens, 0) AS BIGINT),
+            strftime(s.last_activity, '%Y-%m-%dT%H:%M:%SZ')
+        FROM session_stats s
+        LEFT JOIN entry_stats e USING (machine_id, project_dir)
+        ORDER BY 5 DESC, 1, 2
+    "#,
+        ticket_condition = TICKET_CONDITION
+    );
+
+    let mut stmt = conn.prepare(&sql).context("Failed to prepare stats query")?;
+    let projects = stmt
+        .query_map(duckdb::params![cutoff, tag, tag, ticket, ticket], |row| {
+            Ok(ProjectStats {
+                machine_id: row.get(0)?,
+                project: row.get(1)?,
+                project_dir: row.get(2)?,
+                sessions: row.get(3)?,
+                prompts: row.get(4)?,
+                tool_calls: row.get(5)?,
+                input_tokens: row.get(6)?,
+                output_tokens: row.get(7)?,
+                last_activity: row.get(8)?,
+            })
+        })
+        .context("Failed to compute stats")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to read stats row")?;
+
+    Ok(StatsReport {
+        days,
+        group: grouping.as_str(),
+        tag: tag.map(str::to_string),
+        ticket: ticket.map(str::to_string),
+        sessions: projects.iter().map(|p| p.sessions).sum(),
+        prompts: projects.iter().map(|p| p.prompts).sum(),
+        tool_calls: projects.iter().map(|p| p.tool_calls).sum(),
+        input_tokens: projects.iter().map(|p| p.input_tokens).sum(),
+        output_tokens: projects.iter().map(|p| p.output_tokens).sum(),
+        projects,
+    })
+}
+
+/// Write `sessions.parquet` and a flattened `messages.parquet` into `dir`.
+/// Both files are read from the same snapshot.
+pub fn export_parquet(conn: &Connection, dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create export directory: {}", dir.display()))?;
+
+    let sessions_path = dir.join("sessions.parquet");
+    let messages_path = dir.join("messages.parquet");
+
+    // COPY does not take bound parameters, so the paths are inlined as string literals
+    let sql = format!(
+        r#"
+        BEGIN TRANSACTION;
+        COPY (
+            SELECT * REPLACE (CAST(conversation AS VARCHAR) AS conversation)
+            FROM sessions
+            ORDER BY timestamp
+        ) TO {} (FORMAT PARQUET);
+        COPY (
+            {}
+            SELECT session_id, machine_id, project_dir, timestamp AS session_timestamp,
+                   CAST(entry_index AS BIGINT) AS entry_index, role, entry_timestamp, content, tool_name
+            FROM session_messages
+            ORDER BY session_timestamp, machine_id, session_id, entry_index
+        ) TO {} (FORMAT PARQUET);
+        COMMIT;
+        "#,
+        sql_string_literal(&sessions_path),
+        MESSAGES_CTE,
+        sql_string_literal(&messages_path)
+    );
+
+    conn.execute_batch(&sql).context("Failed to export Parquet files")?;
+
+    Ok(vec![sessions_path, messages_path])
+}
+
+/// Write a consistent snapshot of the whole database to a new `backup-<time>` directory
+/// under `backup_root`, restorable with `IMPORT DATABASE '<dir>'`
+pub fn backup_database(conn: &Connection, backup_root: &Path) -> Result<PathBuf> {
+    fs::create_dir_all(backup_root)
+        .with_context(|| format!("Failed to create backup directory: {}", backup_root.display()))?;
+
+    let dir = backup_root.join(format!(
+        "backup-{}",
+        chrono::Utc::now().format("%Y-%m-%d-%H%M%S")
+    ));
+
+    // EXPORT DATABASE reads a single snapshot, so ingests may continue meanwhile
+    conn.execute_batch(&format!(
+        "CHECKPOINT; EXPORT DATABASE {} (FORMAT PARQUET);",
+        sql_string_literal(&dir)
+    ))
+    .context("Failed to export database backup")?;
+
+    Ok(dir)
+}
+
+/// Remove sessions outside the retention policy, with their messages: everything older
+/// than the cutoff, then the oldest until the stored conversations fit the size budget.
+/// With an archive dir, pruned sessions are first written there as Parquet.
+pub fn prune_sessions(conn: &Connection, policy: &RetentionPolicy) -> Result<PruneReport> {
+    let cutoff = policy
+        .cutoff()
+        .map(|c| c.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    let budget = policy.max_total_bytes().map(|b| b as i64);
+
+    // Running size from the newest session backwards decides what fits the budget
+    let mut stmt = conn
+        .prepare(
+            r#"
+            WITH sized AS (
+                SELECT
+                    CAST(id AS BIGINT) AS id,
+                    timestamp,
+                    CAST(strlen(CAST(conversation AS VARCHAR)) AS BIGINT) AS bytes,
+                    sum(strlen(CAST(conversation AS VARCHAR)))
+                        OVER (ORDER BY timestamp DESC, id DESC) AS running_bytes
+                FROM sessions
+            )
+            SELECT id, bytes FROM sized
+            WHERE (CAST(? AS TIMESTAMP) IS NOT NULL AND timestamp < CAST(? AS TIMESTAMP))
+               OR (CAST(? AS BIGINT) IS NOT NULL AND running_bytes > CAST(? AS BIGINT))
+            "#,
+        )
+        .context("Failed to prepare retention query")?;
+    let pruned: Vec<(i64, i64)> = stmt
+        .query_map(duckdb::params![cutoff, cutoff, budget, budget], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .context("Failed to select sessions to prune")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read retention row")?;
+
+    let mut report = PruneReport {
+        sessions: pruned.len(),
+        bytes: pruned.iter().map(|(_, bytes)| *bytes as u64).sum(),
+        ..Default::default()
+    };
+    if pruned.is_empty() {
+        return Ok(report);
+    }
+
+    let ids = pruned
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let archive = match policy.archive_dir {
+        Some(ref dir) => {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create archive directory: {}", dir.display()))?;
+            let path = dir.join(format!(
+                "sessions-{}.parquet",
+                chrono::Utc::now().format("%Y-%m-%d-%H%M%S")
+            ));
+            let sql = format!(
+                "COPY (SELECT * REPLACE (CAST(conversation AS VARCHAR) AS conversation) FROM sessions WHERE id IN ({})) TO {} (FORMAT PARQUET);",
+                ids,
+                sql_string_literal(&path)
+            );
+            report.archived_to = Some(path);
+            sql
+        }
+        None => String::new(),
+    };
+
+    // Ids are integers read back from the database, so inlining them is safe
+    conn.execute_batch(&format!(
+        r#"
+        BEGIN TRANSACTION;
+        {archive}
+        DELETE FROM messages WHERE session_fk IN ({ids});
+        DELETE FROM session_hashes WHERE session_fk IN ({ids});
+        DELETE FROM session_remotes WHERE session_fk IN ({ids});
+        DELETE FROM sessions WHERE id IN ({ids});
+        COMMIT;
+        "#
+    ))
+    .context("Failed to prune sessions")?;
+
+    Ok(report)
+}
+
+/// Delete the sessions a data removal request matches, with their messages,
+/// hashes and remotes, in one transaction. With `dry_run` only lists them.
+pub fn purge_sessions(conn: &mut Connection, request: &PurgeRequest) -> Result<PurgeReport> {
+    request.validate()?;
+
+    let mut conditions: Vec<&'static str> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+    if let Some(ref machine) = request.machine_id {
+        conditions.push("machine_id = ?");
+        params.push(machine.clone());
+    }
+    if let Some(ref project) = request.project {
+        conditions.push("(project_dir = ? OR ends_with(project_dir, '/' || ?) OR ends_with(project_dir, '\\' || ?))");
+        params.extend([project.clone(), project.clone(), project.clone()]);
+    }
+    if let Some(since) = request.since()? {
+        conditions.push("timestamp >= CAST(? AS TIMESTAMP)");
+        params.push(since.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    if let Some(until) = request.until()? {
+        conditions.push("timestamp < CAST(? AS TIMESTAMP)");
+        params.push(until.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    let tx = conn.transaction().context("Failed to start transaction")?;
+    let sql = format!(
+        "SELECT CAST(id AS BIGINT), machine_id, session_id, project_dir, strftime(timestamp, '%Y-%m-%dT%H:%M:%SZ'), \
+         CAST(strlen(CAST(conversation AS VARCHAR)) AS BIGINT) \
+         FROM sessions WHERE {} ORDER BY timestamp",
+        conditions.join(" AND ")
+    );
+    let rows: Vec<(i64, PurgedSession, i64)> = tx
+        .prepare(&sql)
+        .context("Failed to prepare purge query")?
+        .query_map(params_from_iter(params.iter()), |row| {
+            Ok((
+                row.get(0)?,
+                PurgedSession {
+                    machine_id: row.get(1)?,
+                    session_id: row.get(2)?,
+                    project_dir: row.get(3)?,
+                    timestamp: row.get(4)?,
+                },
+                row.get(5)?,
+            ))
+        })
+        .context("Failed to select sessions to purge")?
+        .collect::<Result<_, _>>()
+        .context("Failed to read purge row")?;
+
+    let report = PurgeReport {
+        dry_run: request.dry_run,
+        bytes: rows.iter().map(|(_, _, bytes)| *bytes as u64).sum(),
+        sessions: rows.iter().map(|(_, session, _)| session.clone()).collect(),
+        files: Vec::new(),
+    };
+    if request.dry_run || rows.is_empty() {
+        return Ok(report);
+    }
+
+    // Ids are integers read back from the database, so inlining them is safe
+    let ids = rows
+        .iter()
+        .map(|(id, _, _)| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    tx.execute_batch(&format!(
+        r#"
+        DELETE FROM messages WHERE session_fk IN ({ids});
+        DELETE FROM session_hashes WHERE session_fk IN ({ids});
+        DELETE FROM session_remotes WHERE session_fk IN ({ids});
+        DELETE FROM sessions WHERE id IN ({ids});
+        "#
+    ))
+    .context("Failed to purge sessions")?;
+    tx.commit().context("Failed to commit purge")?;
+
+    Ok(report)
+}
+
+/// Remove one session and its messages; false when there was no such session
+pub fn delete_session(conn: &mut Connection, machine_id: &str, session_id: &str) -> Result<bool> {
+    let tx = conn.transaction().context("Failed to start transaction")?;
+    let session_fk: Option<i64> = tx
+        .query_row(
+            "SELECT CAST(id AS BIGINT) FROM sessions WHERE machine_id = ? AND session_id = ?",
+            duckdb::params![machine_id, session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to look up session")?;
+    let Some(session_fk) = session_fk else {
+        return Ok(false);
+    };
+
+    for table_sql in [
+        "DELETE FROM messages WHERE session_fk = ?",
+        "DELETE FROM session_hashes WHERE session_fk = ?",
+        "DELETE FROM session_remotes WHERE session_fk = ?",
+        "DELETE FROM sessions WHERE id = ?",
+    ] {
+        tx.execute(table_sql, duckdb::params![session_fk])
+            .context("Failed to delete session")?;
+    }
+    tx.commit().context("Failed to commit deletion")?;
+    Ok(true)
+}
+
+fn sql_string_literal(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "''"))
+}
+
+/// SQL conditions and their parameters for the filters shared by listing and search
+fn filter_conditions(filter: &SessionFilter) -> (Vec<&'static str>, Vec<String>) {
+    let mut conditions: Vec<&'static str> = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+
+    if let Some(ref machine) = filter.machine {
+        conditions.push("machine_id = ?");
+        params.push(machine.clone());
+    }
+    if let Some(ref project) = filter.project {
+        // Worktree and submodule sessions also match by their main repository
+        conditions.push(
+            "(project_dir = ? OR ends_with(project_dir, '/' || ?) OR ends_with(project_dir, '\\' || ?) \
+             OR ends_with(json_extract_string(git, '$.parent_repo.path'), '/' || ?))",
+        );
+        params.extend([project.clone(), project.clone(), project.clone(), project.clone()]);
+    }
+    if let Some(ref branch) = filter.branch {
+        conditions.push("git_branch = ?");
+        params.push(branch.clone());
+    }
+    if let Some(ref remote) = filter.remote {
+        // Compared in canonical form, so SSH and HTTPS spellings of a remote match alike
+        conditions.push(
+            "(normalize_remote(git_remote) LIKE '%' || ? || '%' \
+             OR id IN (SELECT session_fk FROM session_remotes WHERE normalize_remote(url) LIKE '%' || ? || '%'))",
+        );
+        let remote = normalize_remote(remote);
+        params.extend([remote.clone(), remote]);
+    }
+    if let Some(ref tag) = filter.tag {
+        conditions.push("list_contains(tags, ?)");
+        params.push(tag.clone());
+    }
+    if let Some(ref ticket) = filter.ticket {
+        conditions.push(TICKET_CONDITION);
+        params.push(ticket.clone());
+    }
+    if let Some(days) = filter.days {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        conditions.push("timestamp >= CAST(? AS TIMESTAMP)");
+        params.push(cutoff.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    (conditions, params)
+}
+
+pub fn get_session(
+    conn: &Connection,
+    machine_id: &str,
+    session_id: &str,
+) -> Result<Option<StoredSession>> {
+    let sql = format!(
+        "SELECT {}, schema_version, CAST(conversation AS VARCHAR), CAST(git AS VARCHAR), CAST(notes AS VARCHAR), \
+         CAST(tickets AS VARCHAR) FROM sessions WHERE machine_id = ? AND session_id = ?",
+        SUMMARY_COLUMNS
+    );
+
+    let row = conn
+        .query_row(&sql, duckdb::params![machine_id, session_id], |row| {
+            Ok((
+                summary_from_row(row)?,
+                row.get::<_, Option<String>>(10)?,
+                row.get::<_, String>(11)?,
+                row.get::<_, Option<String>>(12)?,
+                row.get::<_, Option<String>>(13)?,
+                row.get::<_, Option<String>>(14)?,
+            ))
+        })
+        .optional()
+        .context("Failed to query session")?;
+
+    match row {
+        Some((summary, schema_version, conversation_json, git_json, notes_json, tickets_json)) => {
+            let conversation = serde_json::from_str(&conversation_json)
+                .context("Stored conversation is not valid JSON")?;
+            // Sessions stored before the git column existed have none
+            let git = git_json
+                .map(|g| serde_json::from_str(&g))
+                .transpose()
+                .context("Stored git info is not valid JSON")?
+                .unwrap_or(serde_json::Value::Null);
+            let notes = notes_json
+                .map(|n| serde_json::from_str(&n))
+                .transpose()
+                .context("Stored notes are not valid JSON")?
+                .unwrap_or_else(|| serde_json::json!([]));
+            let tickets = tickets_json
+                .map(|t| serde_json::from_str(&t))
+                .transpose()
+                .context("Stored tickets are not valid JSON")?
+                .unwrap_or_else(|| serde_json::json!([]));
+            Ok(Some(StoredSession {
+                summary,
+                schema_version,
+                git,
+                notes,
+                tickets,
+                conversation,
+            }))
+        }
+        None => Ok(None),
+    }
+}
+
+fn summary_from_row(row: &Row) -> duckdb::Result<SessionSummary> {
+    Ok(SessionSummary {
+        session_id: row.get(0)?,
+        machine_id: row.get(1)?,
+        project_dir: row.get(2)?,
+        timestamp: row.get(3)?,
+        git_remote: row.get(4)?,
+        git_branch: row.get(5)?,
+        git_commit: row.get(6)?,
+        entry_count: row.get(7)?,
+        received_at: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+        // Read as JSON text; NULL for sessions stored before tags existed
+        tags: row
+            .get::<_, Option<String>>(9)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// Up to ~160 characters of context around the first case-insensitive match
+fn create_snippet(content: &str, query: &str) -> String {
+    let context_chars = 80;
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = content.to_lowercase().chars().collect();
+    let needle: Vec<char> = query.to_lowercase().chars().collect();
+
+    // Lowercasing can change the char count for a few scripts; fall back to the start
+    let match_pos = if lower.len() == chars.len() && !needle.is_empty() {
+        lower
+            .windows(needle.len())
+            .position(|w| w == needle.as_slice())
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let start = match_pos.saturating_sub(context_chars);
+    let end = (match_pos + needle.len() + context_chars).min(chars.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push_str("...");
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push_str("...");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> DevlogOutput {
+        serde_json::from_value(serde_json::json!({
+            "schema_version": "1.0",
+            "session_id": "retry-session",
+            "timestamp": "2025-06-01T12:00:00Z",
+            "machine_id": "laptop",
+            "project_dir": "/home/dev/api",
+            "git": null,
+            "conversation": [
+                {"type": "user", "content": "add retries", "timestamp": "2025-06-01T12:00:00Z"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn failed_file_store_write_is_not_recorded() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_migrations(&mut conn).unwrap();
+
+        // A store rooted at a plain file cannot create its directories
+        let blocked = std::env::temp_dir().join(format!("devlog-receiver-blocked-{}", std::process::id()));
+        std::fs::write(&blocked, b"").unwrap();
+        let result = store_session(&mut conn, &session(), |s| FileStore::new(blocked.clone()).store(s).map(drop));
+        std::fs::remove_file(&blocked).unwrap();
+        assert!(result.is_err());
+
+        let sessions: i64 = conn.query_row("SELECT count(*) FROM sessions", [], |row| row.get(0)).unwrap();
+        let hashes: i64 = conn.query_row("SELECT count(*) FROM session_hashes", [], |row| row.get(0)).unwrap();
+        assert_eq!((sessions, hashes), (0, 0));
+
+        // The client's retry is stored, not answered as a duplicate
+        let outcome = store_session(&mut conn, &session(), |_| Ok(())).unwrap();
+        assert_eq!(outcome, InsertOutcome::Stored);
+        let outcome = store_session(&mut conn, &session(), |_| Ok(())).unwrap();
+        assert_eq!(outcome, InsertOutcome::Duplicate);
+    }
+}
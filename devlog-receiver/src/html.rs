@@ -0,0 +1,144 @@
+use crate::models::StatsReport;
+
+pub fn render_stats(report: &StatsReport) -> String {
+    let mut filter_param = report
+        .tag
+        .as_deref()
+        .map(|t| format!("&tag={}", url_encode(t)))
+        .unwrap_or_default();
+    if let Some(ref ticket) = report.ticket {
+        filter_param.push_str(&format!("&ticket={}", url_encode(ticket)));
+    }
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>Devlog Stats</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
+h1 {{ color: #00d9ff; }}
+table {{ border-collapse: collapse; width: 100%; max-width: 1100px; }}
+th, td {{ padding: 0.5rem 1rem; text-align: left; border-bottom: 1px solid #333; }}
+th {{ background: #16213e; color: #00d9ff; }}
+tr:hover {{ background: #16213e; }}
+.number {{ text-align: right; font-variant-numeric: tabular-nums; }}
+a {{ color: #00d9ff; }}
+.filter {{ margin-bottom: 1rem; display: flex; gap: 0.5rem; }}
+.filter a {{ padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.filter a:hover, .filter a.active {{ background: #00d9ff; color: #1a1a2e; }}
+.total {{ margin-top: 1rem; color: #888; }}
+</style>
+</head>
+<body>
+<h1>Project Activity</h1>
+<div class="filter">
+  <a href="stats?format=html&days=1&group={group}{filter}" {}>Today</a>
+  <a href="stats?format=html&days=7&group={group}{filter}" {}>7 days</a>
+  <a href="stats?format=html&days=30&group={group}{filter}" {}>30 days</a>
+  <a href="stats?format=html&days=90&group={group}{filter}" {}>90 days</a>
+</div>
+<div class="filter">
+  <a href="stats?format=html&days={days}&group=directory{filter}" {}>By directory</a>
+  <a href="stats?format=html&days={days}&group=remote{filter}" {}>By remote</a>
+</div>
+"#,
+        active(report.days == 1),
+        active(report.days == 7),
+        active(report.days == 30),
+        active(report.days == 90),
+        active(report.group == "directory"),
+        active(report.group == "remote"),
+        group = report.group,
+        days = report.days,
+        filter = html_escape(&filter_param),
+    );
+
+    if report.tag.is_some() || report.ticket.is_some() {
+        let mut filters = Vec::new();
+        if let Some(ref tag) = report.tag {
+            filters.push(format!("Tagged <strong>{}</strong>", html_escape(tag)));
+        }
+        if let Some(ref ticket) = report.ticket {
+            filters.push(format!("Ticket <strong>{}</strong>", html_escape(ticket)));
+        }
+        html.push_str(&format!(
+            "<div class=\"filter\"><span>{}</span><a href=\"stats?format=html&days={}&group={}\">Clear</a></div>\n",
+            filters.join(" · "),
+            report.days,
+            report.group
+        ));
+    }
+
+    if report.projects.is_empty() {
+        html.push_str(&format!("<p>No activity in the last {} days</p>", report.days));
+    } else {
+        html.push_str(
+            r#"<table>
+<tr><th>Machine</th><th>Project</th><th class="number">Sessions</th><th class="number">Prompts</th><th class="number">Tools</th><th class="number">In Tokens</th><th class="number">Out Tokens</th><th>Last Activity</th></tr>
+"#,
+        );
+
+        for p in &report.projects {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td title=\"{}\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                html_escape(&p.machine_id),
+                html_escape(&p.project_dir),
+                html_escape(&p.project),
+                p.sessions,
+                p.prompts,
+                p.tool_calls,
+                format_tokens(p.input_tokens),
+                format_tokens(p.output_tokens),
+                html_escape(&p.last_activity.replace('T', " ").replace('Z', "")),
+            ));
+        }
+
+        html.push_str("</table>");
+        html.push_str(&format!(
+            "<p class=\"total\">{} sessions, {} prompts, {} tool calls, {} in, {} out</p>",
+            report.sessions,
+            report.prompts,
+            report.tool_calls,
+            format_tokens(report.input_tokens),
+            format_tokens(report.output_tokens),
+        ));
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+fn active(is_active: bool) -> &'static str {
+    if is_active {
+        "class=\"active\""
+    } else {
+        ""
+    }
+}
+
+/// Percent-encode a query-string value
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_tokens(tokens: i64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}K", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
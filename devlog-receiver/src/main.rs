@@ -1,13 +1,64 @@
+mod auth;
+mod backup;
 mod db;
+mod html;
 mod models;
+mod telemetry;
+mod webhooks;
 
-use actix_web::{web, App, HttpResponse, HttpServer, Responder};
-use anyhow::Result;
-use log::info;
-use std::sync::Mutex;
+use actix_web::{middleware::from_fn, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use anyhow::{Context, Result};
+use devlog_core::{
+    BundlePolicy, DevlogOutput, EncryptedSession, FileStore, Metrics, ProjectGrouping, PurgeRequest,
+    RetentionPolicy, SessionEvent, TranscriptParams,
+};
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::future::ready;
+use futures::{stream, SinkExt, StreamExt};
+use log::{info, warn};
+use std::path::PathBuf;
+use tracing::Instrument;
+
+/// Largest accepted `/ingest/batch` body
+const BATCH_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Sessions a listing reads ahead of what the client has received
+const LIST_CHUNKS: usize = 64;
 
 struct AppState {
-    db: Mutex<duckdb::Connection>,
+    pool: db::DbPool,
+    db_path: PathBuf,
+    metrics: Metrics,
+    export_dir: PathBuf,
+    /// False only when no API tokens have been registered
+    auth_required: bool,
+    /// Also keep sessions as JSON files, in the layout of `devlog serve`
+    file_store: Option<FileStore>,
+    /// Raw JSONL transcripts uploaded by clients
+    transcript_store: FileStore,
+    /// Sessions clients encrypted before pushing, which only they can read
+    encrypted_store: FileStore,
+    backup_dir: PathBuf,
+    /// Number of backups kept; 0 keeps all of them
+    backup_keep: usize,
+    webhooks: Option<webhooks::Webhooks>,
+}
+
+/// Run a database call on actix's blocking thread pool with a pooled connection,
+/// so slow queries never stall the async workers or each other
+async fn with_db<T, F>(app_state: &web::Data<AppState>, f: F) -> Result<T>
+where
+    F: FnOnce(&mut duckdb::Connection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = app_state.pool.clone();
+    web::block(move || {
+        let mut conn = pool.get().context("Failed to get a database connection")?;
+        f(&mut conn)
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Database task failed: {}", e))?
 }
 
 async fn health_check() -> impl Responder {
@@ -15,27 +66,75 @@ async fn health_check() -> impl Responder {
 }
 
 async fn ingest_session(
-    data: web::Json<models::DevlogSession>,
+    req: HttpRequest,
+    data: web::Json<DevlogOutput>,
     app_state: web::Data<AppState>,
 ) -> impl Responder {
     let session = data.into_inner();
+    let span = tracing::info_span!(
+        "ingest",
+        session_id = %session.session_id,
+        machine_id = %session.machine_id
+    );
+    telemetry::continue_trace(&span, req.headers());
+    store_session(session, app_state).instrument(span).await
+}
 
+async fn store_session(session: DevlogOutput, app_state: web::Data<AppState>) -> HttpResponse {
     info!(
         "Received session {} from machine {} (project: {})",
         session.session_id, session.machine_id, session.project_dir
     );
 
-    let db = app_state.db.lock().unwrap();
+    let session_id = session.session_id.clone();
+    let event = SessionEvent::new(&session);
+    let file_store = app_state.file_store.clone();
+    let started = std::time::Instant::now();
+    // Duplicates are acknowledged too: the server holds exactly this content
+    let receipt = match devlog_core::receipt::signer() {
+        Ok(signer) => signer.map(|s| s.sign(&session)),
+        Err(e) => {
+            eprintln!("Failed to load receipt key: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }));
+        }
+    };
 
-    match db::insert_session(&db, &session) {
-        Ok(_) => {
-            info!("Session {} stored successfully", session.session_id);
+    match with_db(&app_state, move |conn| {
+        db::store_session(conn, &session, |session| match file_store {
+            Some(store) => store.store(session).map(drop),
+            None => Ok(()),
+        })
+    })
+    .await
+    {
+        Ok(db::InsertOutcome::Stored) => {
+            app_state.metrics.record_ingest(1, started.elapsed());
+            info!("Session {} stored successfully", session_id);
+            if let Some(ref webhooks) = app_state.webhooks {
+                webhooks.sessions_stored(vec![event]);
+            }
             HttpResponse::Ok().json(serde_json::json!({
                 "status": "success",
-                "session_id": session.session_id
+                "session_id": session_id,
+                "receipt": receipt
+            }))
+        }
+        Ok(db::InsertOutcome::Duplicate) => {
+            app_state.metrics.record_duplicates(1);
+            info!("Session {} unchanged, skipped", session_id);
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "duplicate",
+                "session_id": session_id,
+                "message": "duplicate, skipped",
+                "receipt": receipt
             }))
         }
         Err(e) => {
+            app_state.metrics.record_ingest_error();
+            telemetry::record_error(&e);
             eprintln!("Failed to store session: {}", e);
             HttpResponse::InternalServerError().json(serde_json::json!({
                 "status": "error",
@@ -45,33 +144,766 @@ async fn ingest_session(
     }
 }
 
+/// Store many sessions at once (JSON array or NDJSON), for client backfills
+async fn ingest_batch(req: HttpRequest, body: String, app_state: web::Data<AppState>) -> impl Responder {
+    let span = tracing::info_span!("ingest_batch", bytes = body.len(), sessions = tracing::field::Empty);
+    telemetry::continue_trace(&span, req.headers());
+    store_batch(body, app_state).instrument(span).await
+}
+
+async fn store_batch(body: String, app_state: web::Data<AppState>) -> HttpResponse {
+    let sessions = match devlog_core::parse_batch(&body) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            app_state.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }));
+        }
+    };
+
+    info!("Received batch of {} sessions", sessions.len());
+    tracing::Span::current().record("sessions", sessions.len());
+
+    let file_store = app_state.file_store.clone();
+    let started = std::time::Instant::now();
+
+    match with_db(&app_state, move |conn| {
+        let outcomes = db::insert_sessions(conn, &sessions, |stored| match file_store {
+            Some(store) => store.store_batch(stored).map(drop),
+            None => Ok(()),
+        })?;
+        let (stored, duplicates): (Vec<_>, Vec<_>) = sessions
+            .into_iter()
+            .zip(outcomes)
+            .partition(|(_, outcome)| *outcome == db::InsertOutcome::Stored);
+        Ok((stored.iter().map(|(s, _)| SessionEvent::new(s)).collect::<Vec<_>>(), duplicates.len()))
+    })
+    .await
+    {
+        Ok((events, duplicates)) => {
+            let stored = events.len();
+            app_state.metrics.record_ingest(stored, started.elapsed());
+            app_state.metrics.record_duplicates(duplicates);
+            info!(
+                "Batch stored: {} sessions, {} unchanged and skipped",
+                stored, duplicates
+            );
+            if let Some(ref webhooks) = app_state.webhooks {
+                webhooks.sessions_stored(events);
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "stored": stored,
+                "duplicates": duplicates
+            }))
+        }
+        Err(e) => {
+            app_state.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            eprintln!("Failed to store batch: {:#}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }))
+        }
+    }
+}
+
+/// Keep sessions the client encrypted as `.age` files; the receiver cannot
+/// read them, so they stay out of the database
+async fn ingest_encrypted(req: HttpRequest, body: web::Bytes, app_state: web::Data<AppState>) -> impl Responder {
+    let span = tracing::info_span!("ingest_encrypted", bytes = body.len());
+    telemetry::continue_trace(&span, req.headers());
+    store_encrypted(body, app_state).instrument(span).await
+}
+
+async fn store_encrypted(body: web::Bytes, app_state: web::Data<AppState>) -> HttpResponse {
+    let sessions: Vec<EncryptedSession> = match serde_json::from_slice(&body) {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            app_state.metrics.record_ingest_error();
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "status": "error",
+                "error": format!("Invalid encrypted sessions: {}", e)
+            }));
+        }
+    };
+
+    let store = app_state.encrypted_store.clone();
+    let started = std::time::Instant::now();
+    match web::block(move || store.store_encrypted(&sessions)).await {
+        Ok(Ok(count)) => {
+            app_state.metrics.record_ingest(count, started.elapsed());
+            info!("Stored {} encrypted sessions", count);
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "stored": count
+            }))
+        }
+        Ok(Err(e)) => {
+            app_state.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            eprintln!("Failed to store encrypted sessions: {:#}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }))
+        }
+        Err(e) => {
+            app_state.metrics.record_ingest_error();
+            eprintln!("Failed to store encrypted sessions: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+/// Keep a session's raw JSONL transcript so it can be re-parsed later
+async fn ingest_transcript(
+    req: HttpRequest,
+    query: web::Query<TranscriptParams>,
+    body: web::Bytes,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let params = query.into_inner();
+    let span = tracing::info_span!(
+        "ingest_transcript",
+        session_id = %params.session_id,
+        machine_id = %params.machine_id,
+        bytes = body.len()
+    );
+    telemetry::continue_trace(&span, req.headers());
+    store_transcript(params, body, app_state).instrument(span).await
+}
+
+async fn store_transcript(
+    params: TranscriptParams,
+    body: web::Bytes,
+    app_state: web::Data<AppState>,
+) -> HttpResponse {
+    let store = app_state.transcript_store.clone();
+
+    let result = web::block(move || {
+        store.store_transcript(&params.machine_id, &params.project_dir, &params.session_id, &body)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(path)) => {
+            info!("Stored transcript {}", path.display());
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "path": path
+            }))
+        }
+        Ok(Err(e)) => {
+            telemetry::record_error(&e);
+            eprintln!("Failed to store transcript: {:#}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to store transcript: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+/// Prometheus text exposition, shared with `devlog serve`
+async fn metrics(app_state: web::Data<AppState>) -> impl Responder {
+    // The write-ahead log holds data not yet checkpointed into the main file
+    let wal_path = PathBuf::from(format!("{}.wal", app_state.db_path.display()));
+    let storage_bytes = [&app_state.db_path, &wal_path]
+        .iter()
+        .filter_map(|path| std::fs::metadata(path).ok())
+        .map(|meta| meta.len())
+        .sum();
+
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(app_state.metrics.render(storage_bytes))
+}
+
+async fn list_sessions(
+    query: web::Query<models::SessionFilter>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let filter = query.into_inner();
+    let pool = app_state.pool.clone();
+
+    // Rows are read on a blocking thread and sent on as a chunked JSON array
+    // while DuckDB's cursor moves, so a listing is never held in memory whole
+    let (mut chunks, mut body) = mpsc::channel::<Result<web::Bytes>>(LIST_CHUNKS);
+    actix_web::rt::task::spawn_blocking(move || {
+        let mut opened = false;
+        let listed = pool.get().context("Failed to get a database connection").and_then(|conn| {
+            db::list_sessions(&conn, &filter, |session| {
+                let chunk = format!("{}{}", if opened { "," } else { "[" }, serde_json::to_string(&session)?);
+                opened = true;
+                block_on(chunks.send(Ok(chunk.into()))).context("Client went away")
+            })
+        });
+        let end = match listed {
+            Ok(()) if opened => Ok("]".into()),
+            Ok(()) => Ok("[]".into()),
+            Err(e) => Err(e),
+        };
+        let _ = block_on(chunks.send(end));
+    });
+
+    // A failure before the first session can still be reported as such
+    match body.next().await {
+        Some(Ok(first)) => HttpResponse::Ok()
+            .content_type("application/json")
+            .streaming(stream::once(ready(Ok(first))).chain(body)),
+        Some(Err(e)) => {
+            eprintln!("Failed to list sessions: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+        None => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "error": "Session listing stopped unexpectedly"
+        })),
+    }
+}
+
+async fn get_session(
+    path: web::Path<(String, String)>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let (machine_id, session_id) = path.into_inner();
+    let (m, s) = (machine_id.clone(), session_id.clone());
+
+    match with_db(&app_state, move |conn| db::get_session(conn, &m, &s)).await {
+        Ok(Some(session)) => HttpResponse::Ok().json(session),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "status": "error",
+            "error": format!("No session {} from machine {}", session_id, machine_id)
+        })),
+        Err(e) => {
+            eprintln!("Failed to load session: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+/// Remove a session, its mirrored file and its transcript, e.g. for `devlog prune --remote`
+async fn delete_session(
+    path: web::Path<(String, String)>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let (machine_id, session_id) = path.into_inner();
+    let (m, s) = (machine_id.clone(), session_id.clone());
+
+    match with_db(&app_state, move |conn| db::delete_session(conn, &m, &s)).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "status": "error",
+                "error": format!("No session {} from machine {}", session_id, machine_id)
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to delete session: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }));
+        }
+    }
+
+    let mut stores = vec![app_state.transcript_store.clone()];
+    stores.extend(app_state.file_store.clone());
+    let (m, s) = (machine_id.clone(), session_id.clone());
+    let files = web::block(move || {
+        stores
+            .iter()
+            .try_for_each(|store| store.delete_session(&m, &s).map(|_| ()))
+    })
+    .await;
+    match files {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("Failed to delete files of session {}: {:#}", session_id, e),
+        Err(e) => warn!("Failed to delete files of session {}: {}", session_id, e),
+    }
+
+    info!("Deleted session {} from {}", session_id, machine_id);
+    HttpResponse::Ok().json(serde_json::json!({ "status": "deleted" }))
+}
+
+/// Delete everything held for a machine, project or date range, e.g. to honour
+/// a data removal request: database rows, mirrored files, transcripts and
+/// encrypted sessions. With `dry_run` only lists what would go.
+async fn purge(body: web::Json<PurgeRequest>, app_state: web::Data<AppState>) -> impl Responder {
+    let request = body.into_inner();
+    if let Err(e) = request.validate() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "error": format!("{:#}", e)
+        }));
+    }
+
+    let db_request = request.clone();
+    let mut report = match with_db(&app_state, move |conn| db::purge_sessions(conn, &db_request)).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to purge sessions: {:#}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }));
+        }
+    };
+
+    let mut stores = vec![app_state.transcript_store.clone(), app_state.encrypted_store.clone()];
+    stores.extend(app_state.file_store.clone());
+    let file_request = request.clone();
+    let files = web::block(move || {
+        stores
+            .iter()
+            .map(|store| store.purge(&file_request))
+            .collect::<Result<Vec<_>>>()
+    })
+    .await;
+    match files {
+        Ok(Ok(reports)) => reports.into_iter().for_each(|r| report.merge(r)),
+        Ok(Err(e)) => {
+            eprintln!("Failed to purge stored files: {:#}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }));
+        }
+        Err(e) => {
+            eprintln!("Failed to purge stored files: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }));
+        }
+    }
+
+    info!("Purge ({}): {}", request, report);
+    HttpResponse::Ok().json(report)
+}
+
+async fn search(
+    query: web::Query<models::SearchQuery>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    if query.q.trim().is_empty() {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "error": "Query parameter q must not be empty"
+        }));
+    }
+
+    let query = query.into_inner();
+
+    match with_db(&app_state, move |conn| {
+        db::search_messages(conn, &query.q, &query.filter())
+    })
+    .await
+    {
+        Ok(hits) => HttpResponse::Ok().json(hits),
+        Err(e) => {
+            eprintln!("Search failed: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+async fn stats(
+    query: web::Query<models::StatsQuery>,
+    app_state: web::Data<AppState>,
+) -> impl Responder {
+    let days = query.days.unwrap_or(7);
+    let grouping = ProjectGrouping::parse(query.group.as_deref().unwrap_or_default());
+    let tag = query.tag.clone().filter(|t| !t.is_empty());
+    let ticket = query.ticket.clone().filter(|t| !t.is_empty());
+
+    match with_db(&app_state, move |conn| {
+        db::project_stats(conn, days, grouping, tag.as_deref(), ticket.as_deref())
+    })
+    .await
+    {
+        Ok(report) if query.format.as_deref() == Some("html") => HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(html::render_stats(&report)),
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => {
+            eprintln!("Failed to compute stats: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+/// Export the database to Parquet in a new timestamped directory under the export dir
+async fn export_parquet(app_state: web::Data<AppState>) -> impl Responder {
+    let dir = app_state
+        .export_dir
+        .join(chrono::Utc::now().format("%Y-%m-%d-%H%M%S").to_string());
+
+    match with_db(&app_state, move |conn| db::export_parquet(conn, &dir)).await {
+        Ok(files) => {
+            info!("Exported Parquet files: {:?}", files);
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "files": files
+            }))
+        }
+        Err(e) => {
+            eprintln!("Parquet export failed: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{}", e)
+            }))
+        }
+    }
+}
+
+/// Snapshot the database into the backup directory without stopping the service
+async fn create_backup(app_state: web::Data<AppState>) -> impl Responder {
+    match backup::run_backup(&app_state).await {
+        Ok(dir) => {
+            info!("Backup written to {}", dir.display());
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "success",
+                "backup": dir
+            }))
+        }
+        Err(e) => {
+            eprintln!("Backup failed: {:#}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }))
+        }
+    }
+}
+
+/// Compress the session files under `DEVLOG_STORAGE_DIR` that are still plain JSON
+async fn compact(app_state: web::Data<AppState>) -> impl Responder {
+    let Some(store) = app_state.file_store.clone() else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "status": "error",
+            "error": "No DEVLOG_STORAGE_DIR is set"
+        }));
+    };
+    match web::block(move || store.compress_existing()).await {
+        Ok(Ok(report)) => {
+            info!("Compact: {}", report);
+            HttpResponse::Ok().json(report)
+        }
+        Ok(Err(e)) => {
+            eprintln!("Failed to compact: {:#}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "status": "error",
+                "error": format!("{:#}", e)
+            }))
+        }
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "status": "error",
+            "error": e.to_string()
+        })),
+    }
+}
+
+/// Retention limits from `DEVLOG_RETENTION_*`; `None` when no limit is set
+fn retention_from_env() -> Option<RetentionPolicy> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    let mut policy = RetentionPolicy {
+        max_age_days: var("DEVLOG_RETENTION_DAYS").and_then(|v| v.parse().ok()),
+        max_total_mb: var("DEVLOG_RETENTION_MAX_MB").and_then(|v| v.parse().ok()),
+        archive_dir: var("DEVLOG_RETENTION_ARCHIVE_DIR").map(PathBuf::from),
+        ..Default::default()
+    };
+    if let Some(hours) = var("DEVLOG_RETENTION_INTERVAL_HOURS").and_then(|v| v.parse().ok()) {
+        policy.interval_hours = hours;
+    }
+
+    policy.is_enabled().then_some(policy)
+}
+
+/// Enforce the retention policy now and then every `interval_hours`, on the
+/// database and on the file store mirror if there is one
+async fn prune_periodically(app_state: web::Data<AppState>, policy: RetentionPolicy) {
+    loop {
+        let db_policy = policy.clone();
+        match with_db(&app_state, move |conn| db::prune_sessions(conn, &db_policy)).await {
+            Ok(report) => {
+                app_state.metrics.record_pruned(report.sessions);
+                if report.sessions > 0 {
+                    info!("Retention: {}", report);
+                }
+            }
+            Err(e) => warn!("Retention pruning failed: {:#}", e),
+        }
+
+        if let Some(store) = app_state.file_store.clone() {
+            let file_policy = policy.clone();
+            match web::block(move || store.prune(&file_policy)).await {
+                Ok(Ok(report)) if report.sessions > 0 => info!("Retention (files): {}", report),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => warn!("Retention pruning of files failed: {:#}", e),
+                Err(e) => warn!("Retention pruning of files failed: {}", e),
+            }
+        }
+
+        actix_web::rt::time::sleep(policy.interval()).await;
+    }
+}
+
+/// Day bundling of the file store from `DEVLOG_BUNDLE_*`; `None` unless
+/// `DEVLOG_BUNDLE_AFTER_DAYS` is set
+fn bundles_from_env() -> Option<BundlePolicy> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    let mut policy = BundlePolicy {
+        after_days: var("DEVLOG_BUNDLE_AFTER_DAYS")?.parse().ok()?,
+        ..Default::default()
+    };
+    if let Some(hours) = var("DEVLOG_BUNDLE_INTERVAL_HOURS").and_then(|v| v.parse().ok()) {
+        policy.interval_hours = hours;
+    }
+    Some(policy)
+}
+
+/// Roll the file store's older days into bundles now and then every `interval_hours`
+async fn bundle_periodically(store: FileStore, policy: BundlePolicy) {
+    loop {
+        let (store_ref, last_day) = (store.clone(), policy.last_day());
+        match web::block(move || store_ref.bundle_days(last_day)).await {
+            Ok(Ok(report)) if report.sessions > 0 => info!("Bundles: {}", report),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("Bundling failed: {:#}", e),
+            Err(e) => warn!("Bundling failed: {}", e),
+        }
+        actix_web::rt::time::sleep(policy.interval()).await;
+    }
+}
+
 #[actix_web::main]
 async fn main() -> Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let _telemetry = telemetry::init()?;
 
     info!("Initializing DuckDB database...");
     let db_path = std::env::var("DEVLOG_DB_PATH").unwrap_or_else(|_| "devlog.duckdb".to_string());
-    let conn = db::init_database(&db_path)?;
+    let pool_size = std::env::var("DEVLOG_DB_POOL_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(8);
+    let pool = db::init_database(&db_path, pool_size)?;
+
+    info!("Database initialized at: {} ({} connections)", db_path, pool_size);
+
+    // One-shot commands: `devlog-receiver export-parquet <dir>`, `devlog-receiver backup <dir>`,
+    // `devlog-receiver import <dir>`, `devlog-receiver revoke-token <name>`
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("export-parquet") => {
+            let dir = args
+                .get(2)
+                .map(PathBuf::from)
+                .context("Usage: devlog-receiver export-parquet <dir>")?;
+            let conn = pool.get().context("Failed to get a database connection")?;
+            for file in db::export_parquet(&conn, &dir)? {
+                info!("Wrote {}", file.display());
+            }
+            return Ok(());
+        }
+        Some("backup") => {
+            let dir = args
+                .get(2)
+                .map(PathBuf::from)
+                .context("Usage: devlog-receiver backup <dir>")?;
+            let conn = pool.get().context("Failed to get a database connection")?;
+            let backup = db::backup_database(&conn, &dir)?;
+            info!("Wrote backup to {}", backup.display());
+            return Ok(());
+        }
+        Some("import") => {
+            let dir = args
+                .get(2)
+                .map(PathBuf::from)
+                .context("Usage: devlog-receiver import <dir>")?;
+            if !dir.is_dir() {
+                anyhow::bail!("Not a directory: {}", dir.display());
+            }
+            let mut conn = pool.get().context("Failed to get a database connection")?;
+            let report = db::import_file_store(&mut conn, &FileStore::new(dir))?;
+            info!(
+                "Imported {} sessions ({} duplicates, {} files skipped)",
+                report.stored, report.duplicates, report.skipped
+            );
+            return Ok(());
+        }
+        Some("revoke-token") => {
+            let name = args
+                .get(2)
+                .context("Usage: devlog-receiver revoke-token <name>")?;
+            let conn = pool.get().context("Failed to get a database connection")?;
+            match db::revoke_token(&conn, name)? {
+                0 => anyhow::bail!("No API token named '{}'", name),
+                n => info!("Revoked {} token(s) named '{}'", n, name),
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let export_dir = std::env::var("DEVLOG_EXPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("exports"));
+
+    let auth_required = auth::init_tokens(&pool)?;
+
+    // Files are written encrypted with a storage key; a bad one stops startup
+    if devlog_core::at_rest::key()?.is_some() {
+        info!("Encrypting stored files at rest");
+    }
+    if devlog_core::compression::enabled()? {
+        info!("Storing session files compressed with zstd");
+    }
+    if let Some(signer) = devlog_core::receipt::signer()? {
+        info!("Signing ingest receipts with public key {}", signer.public_key());
+    }
+
+    let file_store = std::env::var("DEVLOG_STORAGE_DIR").ok().map(|dir| {
+        info!("Also storing sessions as files under {}", dir);
+        FileStore::new(dir)
+    });
 
-    info!("Database initialized at: {}", db_path);
+    let transcript_store = FileStore::new(
+        std::env::var("DEVLOG_TRANSCRIPT_DIR").unwrap_or_else(|_| "transcripts".to_string()),
+    );
+    let encrypted_store = FileStore::new(
+        std::env::var("DEVLOG_ENCRYPTED_DIR").unwrap_or_else(|_| "encrypted".to_string()),
+    );
+
+    let backup_dir = std::env::var("DEVLOG_BACKUP_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("backups"));
+    let backup_keep = std::env::var("DEVLOG_BACKUP_KEEP")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(7);
 
     let app_state = web::Data::new(AppState {
-        db: Mutex::new(conn),
+        pool,
+        db_path: PathBuf::from(&db_path),
+        metrics: Metrics::default(),
+        export_dir,
+        auth_required,
+        file_store,
+        transcript_store,
+        encrypted_store,
+        backup_dir,
+        backup_keep,
+        webhooks: webhooks::Webhooks::from_env()?,
     });
 
+    if let Some(policy) = retention_from_env() {
+        info!("Retention enabled: {:?}", policy);
+        actix_web::rt::spawn(prune_periodically(app_state.clone(), policy));
+    }
+
+    if let (Some(policy), Some(store)) = (bundles_from_env(), app_state.file_store.clone()) {
+        info!("Day bundling enabled: {:?}", policy);
+        actix_web::rt::spawn(bundle_periodically(store, policy));
+    }
+
+    let backup_hours = std::env::var("DEVLOG_BACKUP_INTERVAL_HOURS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&h| h > 0);
+    if let Some(hours) = backup_hours {
+        info!("Scheduled backups every {} hours", hours);
+        actix_web::rt::spawn(backup::backup_periodically(
+            app_state.clone(),
+            std::time::Duration::from_secs(hours * 3600),
+        ));
+    }
+
     let bind_addr = std::env::var("DEVLOG_BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
 
-    info!("Starting HTTP server on {}", bind_addr);
+    // A bad certificate stops startup rather than falling back to plain HTTP
+    let tls = devlog_core::tls::from_env()?;
+    info!("Starting HTTP server on {}{}", bind_addr, if tls.is_some() { " (TLS)" } else { "" });
+    if let (Some(_), Ok(client_ca)) = (tls.as_ref(), std::env::var("DEVLOG_TLS_CLIENT_CA")) {
+        info!("Requiring client certificates issued by {}", client_ca);
+    }
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
             .route("/health", web::get().to(health_check))
-            .route("/ingest", web::post().to(ingest_session))
-    })
-    .bind(&bind_addr)?
-    .run()
-    .await?;
+            // Everything else requires a bearer token once tokens are registered
+            .service(
+                web::scope("")
+                    .wrap(from_fn(auth::require_token))
+                    .route("/ingest", web::post().to(ingest_session))
+                    .service(
+                        web::resource("/ingest/batch")
+                            .app_data(web::PayloadConfig::new(BATCH_BODY_LIMIT))
+                            .route(web::post().to(ingest_batch)),
+                    )
+                    .service(
+                        web::resource("/ingest/transcript")
+                            .app_data(web::PayloadConfig::new(BATCH_BODY_LIMIT))
+                            .route(web::post().to(ingest_transcript)),
+                    )
+                    .service(
+                        web::resource("/ingest/encrypted")
+                            .app_data(web::PayloadConfig::new(BATCH_BODY_LIMIT))
+                            .route(web::post().to(ingest_encrypted)),
+                    )
+                    .route("/sessions", web::get().to(list_sessions))
+                    .route("/sessions/{machine}/{id}", web::get().to(get_session))
+                    .route("/sessions/{machine}/{id}", web::delete().to(delete_session))
+                    .route("/search", web::get().to(search))
+                    .route("/stats", web::get().to(stats))
+                    .route("/metrics", web::get().to(metrics))
+                    // Only admin tokens, and never without one registered
+                    .service(
+                        web::scope("/admin")
+                            .wrap(from_fn(auth::require_admin))
+                            .route("/backup", web::post().to(create_backup))
+                            .route("/purge", web::post().to(purge))
+                            .route("/compact", web::post().to(compact))
+                            .route("/export/parquet", web::post().to(export_parquet)),
+                    ),
+            )
+    });
+    let server = match tls {
+        Some(tls) => server.bind_rustls_0_23(&bind_addr, tls)?,
+        None => server.bind(&bind_addr)?,
+    };
+    server.run().await?;
 
     Ok(())
 }
@@ -1,31 +1,137 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct DevlogSession {
-    pub schema_version: String,
+/// Filters accepted by `GET /sessions`
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionFilter {
+    pub machine: Option<String>,
+    /// Matches the last component of `project_dir`
+    pub project: Option<String>,
+    pub branch: Option<String>,
+    /// Substring of any of the session's git remotes, both compared in canonical
+    /// `host/owner/repo` form
+    pub remote: Option<String>,
+    /// Only sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only sessions whose prompts mention this ticket, e.g. `PROJ-123` or `#456`
+    pub ticket: Option<String>,
+    /// Only sessions from the last N days
+    pub days: Option<u32>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Session metadata returned by `GET /sessions`
+#[derive(Debug, Serialize)]
+pub struct SessionSummary {
     pub session_id: String,
-    pub timestamp: String,
     pub machine_id: String,
     pub project_dir: String,
-    pub git: Option<GitInfo>,
-    pub conversation: Vec<ConversationEntry>,
+    pub timestamp: String,
+    pub git_remote: Option<String>,
+    pub git_branch: Option<String>,
+    pub git_commit: Option<String>,
+    pub entry_count: i64,
+    pub received_at: String,
+    pub tags: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct GitInfo {
-    pub remote: Option<String>,
-    pub branch: Option<String>,
-    pub commit: Option<String>,
+/// A full stored session returned by `GET /sessions/{machine}/{id}`
+#[derive(Debug, Serialize)]
+pub struct StoredSession {
+    #[serde(flatten)]
+    pub summary: SessionSummary,
+    pub schema_version: Option<String>,
+    /// The session's full git object, e.g. including working-tree status
+    pub git: serde_json::Value,
+    /// Annotations added with `devlog note`
+    pub notes: serde_json::Value,
+    /// Tickets the prompts refer to, as `{"id", "url"}`
+    pub tickets: serde_json::Value,
+    pub conversation: serde_json::Value,
+}
+
+/// Parameters accepted by `GET /search`
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub machine: Option<String>,
+    pub project: Option<String>,
+    pub tag: Option<String>,
+    pub ticket: Option<String>,
+    pub days: Option<u32>,
+    pub limit: Option<u32>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct ConversationEntry {
+impl SearchQuery {
+    pub fn filter(&self) -> SessionFilter {
+        SessionFilter {
+            machine: self.machine.clone(),
+            project: self.project.clone(),
+            tag: self.tag.clone(),
+            ticket: self.ticket.clone(),
+            days: self.days,
+            limit: self.limit,
+            ..Default::default()
+        }
+    }
+}
+
+/// A conversation entry matching a search, with the session it belongs to
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub machine_id: String,
+    pub project_dir: String,
+    pub timestamp: String,
+    pub entry_index: i64,
     pub role: String,
-    pub timestamp: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub content: Option<String>,
+    pub snippet: String,
+}
+
+/// Parameters accepted by `GET /stats`
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    pub days: Option<u32>,
+    /// `remote` to key projects by canonical git remote instead of directory
+    pub group: Option<String>,
+    /// Only sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only sessions whose prompts mention this ticket
+    pub ticket: Option<String>,
+    /// `html` for a rendered table; JSON otherwise
+    pub format: Option<String>,
+}
+
+/// Activity for one machine/project pair, aggregated in SQL
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    pub machine_id: String,
+    pub project: String,
+    /// The project directory, or the canonical remote when grouped by remote
+    pub project_dir: String,
+    pub sessions: i64,
+    pub prompts: i64,
+    pub tool_calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub last_activity: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsReport {
+    pub days: u32,
+    /// `directory` or `remote`
+    pub group: &'static str,
+    /// The tag sessions were filtered by, if any
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_name: Option<String>,
+    pub tag: Option<String>,
+    /// The ticket sessions were filtered by, if any
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_description: Option<String>,
+    pub ticket: Option<String>,
+    pub sessions: i64,
+    pub prompts: i64,
+    pub tool_calls: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub projects: Vec<ProjectStats>,
 }
@@ -0,0 +1,78 @@
+use actix_web::http::header::{HeaderMap, HeaderName};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::{Status, TracerProvider as _};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Exports spans while it lives; dropping it sends the ones still queued
+pub struct Telemetry(Option<SdkTracerProvider>);
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.0.take() {
+            if let Err(e) = provider.shutdown() {
+                warn!("Failed to export traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Export spans over OTLP/HTTP when `OTEL_EXPORTER_OTLP_ENDPOINT` (or
+/// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) is set, under `OTEL_SERVICE_NAME`
+/// or `devlog-receiver`
+pub fn init() -> Result<Telemetry> {
+    let configured = ["OTEL_EXPORTER_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"]
+        .iter()
+        .any(|var| std::env::var(var).map(|v| !v.is_empty()).unwrap_or(false));
+    if !configured {
+        return Ok(Telemetry(None));
+    }
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .build()
+        .context("Failed to create the OTLP exporter")?;
+    let service_name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "devlog-receiver".to_string());
+    info!("Exporting traces as {}", service_name);
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(service_name).build())
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("devlog-receiver"));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))
+        .context("Failed to install the trace subscriber")?;
+    Ok(Telemetry(Some(provider)))
+}
+
+/// Make `span`, the receiver's span for a request, continue the trace the
+/// client sent in its `traceparent` header, if any
+pub fn continue_trace(span: &Span, headers: &HeaderMap) {
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    // Fails only when no trace is being exported
+    let _ = span.set_parent(parent);
+}
+
+/// Mark the current span failed, so the error shows in the trace
+pub fn record_error(error: &anyhow::Error) {
+    Span::current().set_status(Status::error(format!("{:#}", error)));
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use devlog_core::webhook::{self, SessionEvent};
+use log::{info, warn};
+use std::time::Duration;
+
+/// Endpoints from `DEVLOG_WEBHOOK_URLS` POSTed a [`SessionEvent`] for every
+/// session stored, as `devlog serve` does for `[[notifications.webhooks]]`
+#[derive(Clone)]
+pub struct Webhooks {
+    client: reqwest::Client,
+    urls: Vec<String>,
+}
+
+impl Webhooks {
+    /// `None` when no URLs are set
+    pub fn from_env() -> Result<Option<Self>> {
+        let urls: Vec<String> = std::env::var("DEVLOG_WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(String::from)
+            .collect();
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create HTTP client for webhooks")?;
+        info!("Sending stored sessions to {} webhooks", urls.len());
+        Ok(Some(Self { client, urls }))
+    }
+
+    /// Deliver in the background, so ingest responses never wait on a slow or
+    /// failing endpoint
+    pub fn sessions_stored(&self, events: Vec<SessionEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        for url in &self.urls {
+            let (client, url, events) = (self.client.clone(), url.clone(), events.clone());
+            actix_web::rt::spawn(async move {
+                for event in &events {
+                    if let Err(e) = deliver(&client, &url, event).await {
+                        warn!("Webhook delivery failed: {:#}", e);
+                        // The endpoint is down; the rest would only wait out their retries too
+                        break;
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// POST `event` to `url`, retrying while the endpoint is down or busy
+async fn deliver(client: &reqwest::Client, url: &str, event: &SessionEvent) -> Result<()> {
+    let mut delays = webhook::RETRY_DELAYS.iter();
+    loop {
+        let error = match client.post(url).json(event).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) if !webhook::is_retryable(response.status().as_u16()) => anyhow::bail!(
+                "{} returned {}: {}",
+                url,
+                response.status(),
+                response.text().await.unwrap_or_default()
+            ),
+            Ok(response) => anyhow::anyhow!("{} returned {}", url, response.status()),
+            Err(e) => anyhow::Error::new(e).context(format!("Failed to reach {}", url)),
+        };
+        match delays.next() {
+            Some(delay) => actix_web::rt::time::sleep(*delay).await,
+            None => {
+                return Err(error.context(format!(
+                    "Gave up on session {} after {} retries",
+                    event.session_id,
+                    webhook::RETRY_DELAYS.len()
+                )))
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use crate::config::{Role, UserConfig};
+use crate::server::ServerConfig;
+use anyhow::{Context, Result};
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{header, request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A `devlog serve` account, with its password read from the environment
+#[derive(Clone)]
+pub struct User {
+    pub name: String,
+    password: String,
+    role: Role,
+    projects: Vec<String>,
+}
+
+/// The configured users with their passwords; a password missing from the
+/// environment stops the server rather than locking the user out
+pub fn load_users(users: &BTreeMap<String, UserConfig>) -> Result<Vec<User>> {
+    users
+        .iter()
+        .map(|(name, user)| {
+            let password = std::env::var(&user.password_env)
+                .ok()
+                .filter(|p| !p.is_empty())
+                .with_context(|| format!("{} (the password of user {}) is not set", user.password_env, name))?;
+            Ok(User {
+                name: name.clone(),
+                password,
+                role: user.role,
+                projects: user.projects.clone(),
+            })
+        })
+        .collect()
+}
+
+/// A single name and password for the whole web UI, for servers that need a
+/// login but no per-project limits
+#[derive(Clone)]
+pub struct WebLogin {
+    name: String,
+    password: String,
+}
+
+impl WebLogin {
+    /// `DEVLOG_WEB_PASSWORD`, with `DEVLOG_WEB_USER` as the name or `devlog`
+    /// without it; None while no password is set
+    pub fn from_env() -> Option<Self> {
+        let password = std::env::var("DEVLOG_WEB_PASSWORD").ok().filter(|p| !p.is_empty())?;
+        let name = std::env::var("DEVLOG_WEB_USER")
+            .ok()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| "devlog".to_string());
+        Some(WebLogin { name, password })
+    }
+
+    fn accepts(&self, name: &str, password: &str) -> bool {
+        name == self.name && same_secret(password, &self.password)
+    }
+}
+
+/// Who made a request, and so which projects' sessions they may see
+#[derive(Clone)]
+pub struct Viewer {
+    /// None on a server without users, where everyone sees everything
+    pub name: Option<String>,
+    admin: bool,
+    /// None when every project is visible
+    projects: Option<Vec<String>>,
+}
+
+impl Viewer {
+    /// Allowed to purge: an admin user, or a request with the admin token
+    pub fn is_admin(&self) -> bool {
+        self.admin
+    }
+
+    /// Whether sessions of this project directory name may be shown
+    pub fn can_see(&self, project: &str) -> bool {
+        self.projects
+            .as_deref()
+            .map(|patterns| grants(patterns, project))
+            .unwrap_or(true)
+    }
+
+    /// The project patterns the viewer is limited to, if any
+    pub fn projects(&self) -> Option<&[String]> {
+        self.projects.as_deref()
+    }
+}
+
+/// Users, and the web login, sign in with HTTP basic auth, which browsers
+/// prompt for; API clients such as `devlog sync` can instead send
+/// `name:password` as their bearer token
+#[async_trait]
+impl FromRequestParts<Arc<ServerConfig>> for Viewer {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, config: &Arc<ServerConfig>) -> Result<Self, Response> {
+        let authorization = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok());
+        let bearer = authorization.and_then(|v| v.strip_prefix("Bearer ")).map(str::trim);
+
+        if bearer
+            .zip(config.admin_token.as_deref())
+            .is_some_and(|(token, admin_token)| same_secret(token, admin_token))
+        {
+            return Ok(Viewer {
+                name: Some("admin token".to_string()),
+                admin: true,
+                projects: None,
+            });
+        }
+        if config.users.is_empty() && config.web_login.is_none() {
+            return Ok(Viewer {
+                name: None,
+                admin: false,
+                projects: None,
+            });
+        }
+
+        let credentials = match bearer {
+            Some(token) => token.split_once(':').map(|(name, password)| (name.to_string(), password.to_string())),
+            None => authorization.and_then(basic_credentials),
+        };
+        if let Some((ref name, ref password)) = credentials {
+            if config.web_login.as_ref().is_some_and(|login| login.accepts(name, password)) {
+                return Ok(Viewer {
+                    name: Some(name.clone()),
+                    admin: false,
+                    projects: None,
+                });
+            }
+        }
+        let user = credentials.and_then(|(name, password)| {
+            config
+                .users
+                .iter()
+                .find(|u| u.name == name && same_secret(&password, &u.password))
+        });
+        match user {
+            Some(user) => Ok(Viewer {
+                name: Some(user.name.clone()),
+                admin: user.role == Role::Admin,
+                projects: (user.role != Role::Admin).then(|| user.projects.clone()),
+            }),
+            None => Err((
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Basic realm=\"devlog\"")],
+                "Sign in to see sessions",
+            )
+                .into_response()),
+        }
+    }
+}
+
+/// Turns away ingests without the ingest token, while one is configured. The
+/// web login is not accepted, so signing in to the pages does not let anyone
+/// write sessions and the ingest token reads nothing; a user's `name:password`
+/// is, so `devlog sync` can both upload and download as that user.
+pub async fn require_ingest_token(State(config): State<Arc<ServerConfig>>, request: Request, next: Next) -> Response {
+    if let Some(ref expected) = config.ingest_token {
+        let token = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(str::trim);
+        let user = token.and_then(|token| token.split_once(':')).is_some_and(|(name, password)| {
+            config
+                .users
+                .iter()
+                .any(|u| u.name == name && same_secret(password, &u.password))
+        });
+        if !user && !token.is_some_and(|token| same_secret(token, expected)) {
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                "Ingesting needs the ingest token",
+            )
+                .into_response();
+        }
+    }
+    next.run(request).await
+}
+
+/// Whether a presented secret is the expected one. Their digests are compared
+/// in full, so the time taken says nothing about where they differ.
+fn same_secret(given: &str, expected: &str) -> bool {
+    let (given, expected) = (Sha256::digest(given.as_bytes()), Sha256::digest(expected.as_bytes()));
+    given.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// The user name and password of an `Authorization: Basic` header
+fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    let encoded = authorization.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (name, password) = decoded.split_once(':')?;
+    Some((name.to_string(), password.to_string()))
+}
+
+/// Whether any of the patterns matches a project directory name
+pub fn grants(patterns: &[String], project: &str) -> bool {
+    patterns.iter().any(|pattern| glob_match(pattern, project))
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and how much of the text it has taken so far
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
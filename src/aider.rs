@@ -0,0 +1,247 @@
+use crate::parser::{truncate, ConversationEntry, TokenUsage};
+use crate::source::{ConversationBuilder, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The file Aider appends each chat to, in the root of the repository
+pub const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Aider's chat history
+pub struct Aider;
+
+impl SessionSource for Aider {
+    fn name(&self) -> &'static str {
+        "aider"
+    }
+
+    fn description(&self) -> &'static str {
+        ".aider.chat.history.md, given directly or as the repository holding it"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        path.join(HISTORY_FILE).is_file() || path.file_name().map(|n| n == HISTORY_FILE).unwrap_or(false)
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        let history = if path.is_dir() {
+            path.join(HISTORY_FILE)
+        } else {
+            path.to_path_buf()
+        };
+        let sessions = read_history(&history)?;
+
+        // Aider keeps its history in the root of the repository it worked on
+        let history = history
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", history.display()))?;
+        let dir = history.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+
+        // A chat's end isn't recorded, so it is taken to last until the next one
+        // started, or the history was last written
+        let modified: Option<DateTime<Utc>> = std::fs::metadata(&history)
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::from);
+        let ends: Vec<Option<DateTime<Utc>>> = sessions
+            .iter()
+            .skip(1)
+            .map(|s| Some(s.started))
+            .chain(std::iter::once(modified))
+            .collect();
+
+        Ok(sessions
+            .into_iter()
+            .zip(ends)
+            .map(|(session, end)| Session {
+                // Aider has no session IDs; the start time identifies a chat within a history
+                id: format!("aider-{}", session.started.format("%Y%m%d-%H%M%S")),
+                started: Some(session.started),
+                span: end.map(|end| (session.started, end.max(session.started))),
+                dir: Some(dir.clone()),
+                conversation: session.conversation,
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+const SESSION_HEADER: &str = "# aider chat started at ";
+
+/// One chat from an Aider history file
+pub struct AiderSession {
+    pub started: DateTime<Utc>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+/// Read the chats in an Aider history file, oldest first
+pub fn read_history(path: &Path) -> Result<Vec<AiderSession>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read Aider history: {}", path.display()))?;
+    Ok(parse_history(&text))
+}
+
+/// Split a history into chats at each `# aider chat started at` header. Within a
+/// chat, `#### ` lines are what the user typed, `> ` lines are Aider's own
+/// output and everything else is the model's reply.
+pub fn parse_history(text: &str) -> Vec<AiderSession> {
+    let mut sessions = Vec::new();
+    let mut current: Option<ChatBuilder> = None;
+
+    for line in text.lines() {
+        if let Some(started) = line.strip_prefix(SESSION_HEADER) {
+            if let Some(chat) = current.take() {
+                sessions.push(chat.finish());
+            }
+            // Aider writes the local time, without an offset
+            current = NaiveDateTime::parse_from_str(started.trim(), "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+                .map(|local| ChatBuilder::new(local.with_timezone(&Utc)));
+            continue;
+        }
+        // Lines before the first header, or under one that could not be read
+        let Some(chat) = current.as_mut() else {
+            continue;
+        };
+
+        if let Some(input) = line.strip_prefix("####") {
+            // Aider ends each line with two spaces, a markdown line break
+            chat.user_line(input.strip_prefix(' ').unwrap_or(input).trim_end());
+        } else if let Some(output) = line.strip_prefix('>') {
+            chat.tool_line(output.trim());
+        } else {
+            chat.assistant_line(line);
+        }
+    }
+    if let Some(chat) = current {
+        sessions.push(chat.finish());
+    }
+
+    sessions
+}
+
+#[derive(PartialEq)]
+enum Speaker {
+    User,
+    Assistant,
+}
+
+struct ChatBuilder {
+    started: DateTime<Utc>,
+    conversation: ConversationBuilder,
+    speaker: Option<Speaker>,
+    text: Vec<String>,
+}
+
+impl ChatBuilder {
+    fn new(started: DateTime<Utc>) -> Self {
+        Self {
+            started,
+            conversation: ConversationBuilder::new(),
+            speaker: None,
+            text: Vec::new(),
+        }
+    }
+
+    fn user_line(&mut self, line: &str) {
+        self.speak(Speaker::User);
+        self.text.push(line.to_string());
+    }
+
+    fn assistant_line(&mut self, line: &str) {
+        // Blank lines separate blocks; they only belong inside a reply
+        if line.trim().is_empty() && self.speaker != Some(Speaker::Assistant) {
+            return;
+        }
+        self.speak(Speaker::Assistant);
+        self.text.push(line.to_string());
+    }
+
+    fn tool_line(&mut self, line: &str) {
+        self.flush_text();
+        if let Some(usage) = parse_usage(line) {
+            self.conversation.usage(usage);
+        } else if let Some(action) = summarize_tool_line(line) {
+            self.conversation.tool(action);
+        }
+    }
+
+    /// Start a new block unless `speaker` is already speaking
+    fn speak(&mut self, speaker: Speaker) {
+        if self.speaker.as_ref() == Some(&speaker) {
+            return;
+        }
+        self.flush_text();
+        self.speaker = Some(speaker);
+    }
+
+    fn flush_text(&mut self) {
+        let Some(speaker) = self.speaker.take() else {
+            return;
+        };
+        let content = std::mem::take(&mut self.text).join("\n").trim().to_string();
+        // Only the start of a chat is recorded, so only its first entry has a time
+        let timestamp = self.conversation.is_empty().then(|| self.started.to_rfc3339());
+        match speaker {
+            Speaker::User => self.conversation.user(timestamp, content, None),
+            Speaker::Assistant => self.conversation.assistant(timestamp, content, None, None),
+        }
+    }
+
+    fn finish(mut self) -> AiderSession {
+        self.flush_text();
+        AiderSession {
+            started: self.started,
+            conversation: self.conversation.finish(),
+        }
+    }
+}
+
+/// The same short actions the Claude Code parser records, for the Aider output
+/// lines that report one; prompts, warnings and the startup banner are dropped
+fn summarize_tool_line(line: &str) -> Option<String> {
+    if let Some(path) = line.strip_prefix("Applied edit to ") {
+        Some(format!("edited {}", path))
+    } else if let Some(commit) = line.strip_prefix("Commit ") {
+        let hash = commit.split_whitespace().next()?;
+        Some(format!("committed {}", hash))
+    } else if let Some(command) = line.strip_prefix("Running ") {
+        Some(format!("ran {}", truncate(command, 50)))
+    } else if let Some(added) = line.strip_prefix("Added ") {
+        let path = added.strip_suffix(" to the chat")?;
+        Some(format!("added {} to the chat", path))
+    } else {
+        None
+    }
+}
+
+/// `Tokens: 2.1k sent, 1.5k cache write, 345 received. Cost: ...`
+fn parse_usage(line: &str) -> Option<TokenUsage> {
+    let counts = line.strip_prefix("Tokens: ")?;
+    let pattern = Regex::new(r"([\d.]+)([kM]?) (sent|received|cache write|cache hit)").ok()?;
+
+    let mut usage = TokenUsage {
+        input_tokens: None,
+        output_tokens: None,
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: None,
+    };
+    for caps in pattern.captures_iter(counts) {
+        let number: f64 = caps[1].parse().ok()?;
+        let scale = match &caps[2] {
+            "k" => 1_000.0,
+            "M" => 1_000_000.0,
+            _ => 1.0,
+        };
+        let tokens = Some((number * scale).round() as u64);
+        match &caps[3] {
+            "sent" => usage.input_tokens = tokens,
+            "received" => usage.output_tokens = tokens,
+            "cache write" => usage.cache_creation_input_tokens = tokens,
+            _ => usage.cache_read_input_tokens = tokens,
+        }
+    }
+    Some(usage)
+}
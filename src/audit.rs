@@ -0,0 +1,118 @@
+use crate::access::Viewer;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// What a user did, as recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// A session's conversation, as a page or through the API
+    ReadSession,
+    /// A search, whose results quote session content
+    Search,
+    DeleteSession,
+    Purge,
+    /// Compressing the stored sessions
+    Compact,
+    /// The audit log itself
+    ReadAudit,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 6] = [
+        AuditAction::ReadSession,
+        AuditAction::Search,
+        AuditAction::DeleteSession,
+        AuditAction::Purge,
+        AuditAction::Compact,
+        AuditAction::ReadAudit,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ReadSession => "read_session",
+            AuditAction::Search => "search",
+            AuditAction::DeleteSession => "delete_session",
+            AuditAction::Purge => "purge",
+            AuditAction::Compact => "compact",
+            AuditAction::ReadAudit => "read_audit",
+        }
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: String,
+    /// The signed-in user, `admin token`, or `anonymous` on a server without users
+    pub user: String,
+    pub action: AuditAction,
+    /// What was read or changed, e.g. a session's path or a search query
+    pub target: String,
+}
+
+/// Who read session content and what admins did, one JSON object per line.
+/// Entries are only ever appended.
+pub struct AuditLog {
+    path: PathBuf,
+    /// Keeps concurrent requests from interleaving their lines
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry. Content is only served once its read is recorded, so
+    /// callers fail the request when this fails.
+    pub fn record(&self, viewer: &Viewer, action: AuditAction, target: impl Into<String>) -> Result<()> {
+        let entry = AuditEntry {
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            user: viewer.name.clone().unwrap_or_else(|| "anonymous".to_string()),
+            action,
+            target: target.into(),
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut options = fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write audit log {}", self.path.display()))
+    }
+
+    /// The newest entries first, up to `limit`, optionally only of one user or action
+    pub fn recent(&self, user: Option<&str>, action: Option<AuditAction>, limit: usize) -> Result<Vec<AuditEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read audit log {}", self.path.display())),
+        };
+        Ok(content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| user.map(|u| entry.user == u).unwrap_or(true))
+            .filter(|entry| action.map(|a| entry.action == a).unwrap_or(true))
+            .take(limit)
+            .collect())
+    }
+}
@@ -0,0 +1,236 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use crate::search;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Prompts shorter than this ("yes", "go on") carry no context
+const MIN_PROMPT_CHARS: usize = 15;
+const MAX_PROMPTS: usize = 8;
+const MAX_DECISIONS: usize = 5;
+const MAX_FILES: usize = 20;
+const MAX_OUTCOME_CHARS: usize = 600;
+
+/// Phrases that mark a sentence of a response as a decision worth carrying over
+const DECISION_MARKERS: &[&str] = &[
+    "decided",
+    "decision",
+    "instead of",
+    "going with",
+    "chose ",
+    "opted",
+    "trade-off",
+    "tradeoff",
+];
+
+/// A markdown brief of the last `count` sessions in a `.devlog` directory, oldest
+/// first, to paste into a new Claude Code session
+pub fn build_brief(devlog_dir: &Path, count: usize) -> Result<String> {
+    let sessions = latest_sessions(devlog_dir)?;
+    if sessions.is_empty() {
+        anyhow::bail!("No devlog JSON files found in {}", devlog_dir.display());
+    }
+    let recent = &sessions[sessions.len().saturating_sub(count)..];
+
+    let project = devlog_core::store::project_name(recent[recent.len() - 1].project_path());
+    let mut brief = format!("# Context: {}\n\n", project);
+    brief.push_str(&format!(
+        "Summary of the last {} devlog session{} of this project, to pick up where they left off.",
+        recent.len(),
+        if recent.len() == 1 { "" } else { "s" }
+    ));
+    if let Some(ref git) = recent[recent.len() - 1].git {
+        brief.push_str(&format!(
+            " Most recently on branch `{}` at `{}`{}.",
+            git.branch,
+            short(&git.commit),
+            if git.dirty { " with uncommitted changes" } else { "" }
+        ));
+    }
+    brief.push_str("\n\n");
+
+    for session in recent {
+        brief.push_str(&render_session(session));
+    }
+
+    Ok(brief)
+}
+
+/// Every session once, at its latest ingest, oldest first. A session is ingested
+/// again on each compaction and at its end, so `.devlog` can hold several copies.
+pub fn latest_sessions(devlog_dir: &Path) -> Result<Vec<DevlogOutput>> {
+    let mut latest: HashMap<String, DevlogOutput> = HashMap::new();
+    for file in search::local_files(devlog_dir)? {
+        let output = match search::read_devlog(&file.path) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {}", file.path.display(), e);
+                continue;
+            }
+        };
+        let newer = latest
+            .get(&output.session_id)
+            .map(|seen| output.timestamp > seen.timestamp)
+            .unwrap_or(true);
+        if newer {
+            latest.insert(output.session_id.clone(), output);
+        }
+    }
+
+    let mut sessions: Vec<DevlogOutput> = latest.into_values().collect();
+    sessions.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    Ok(sessions)
+}
+
+fn render_session(output: &DevlogOutput) -> String {
+    let started = chrono::DateTime::parse_from_rfc3339(&output.timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| output.timestamp.clone());
+    let mut md = format!("## {} · session {}", started, short(&output.session_id));
+    if let Some(ref git) = output.git {
+        md.push_str(&format!(" · `{}`", git.branch));
+    }
+    md.push_str("\n\n");
+
+    let prompts: Vec<&str> = output
+        .conversation
+        .iter()
+        .filter_map(|entry| match entry {
+            ConversationEntry::User { content, .. } if content.trim().chars().count() >= MIN_PROMPT_CHARS => {
+                Some(content.trim())
+            }
+            _ => None,
+        })
+        .collect();
+    if !prompts.is_empty() {
+        md.push_str("Prompts:\n");
+        for prompt in prompts.iter().take(MAX_PROMPTS) {
+            md.push_str(&format!("- {}\n", truncate_chars(&first_line(prompt), 200)));
+        }
+        if prompts.len() > MAX_PROMPTS {
+            md.push_str(&format!("- ... and {} more\n", prompts.len() - MAX_PROMPTS));
+        }
+        md.push('\n');
+    }
+
+    let decisions = decisions(output);
+    if !decisions.is_empty() {
+        md.push_str("Decisions:\n");
+        for decision in &decisions {
+            md.push_str(&format!("- {}\n", decision));
+        }
+        md.push('\n');
+    }
+
+    let files = files_touched(output);
+    if !files.is_empty() {
+        let shown: Vec<String> = files.iter().take(MAX_FILES).map(|f| format!("`{}`", f)).collect();
+        md.push_str(&format!("Files touched: {}", shown.join(", ")));
+        if files.len() > MAX_FILES {
+            md.push_str(&format!(" and {} more", files.len() - MAX_FILES));
+        }
+        md.push_str("\n\n");
+    }
+
+    if let Some(ref git) = output.git {
+        if !git.commits.is_empty() {
+            md.push_str("Commits:\n");
+            for commit in &git.commits {
+                md.push_str(&format!("- `{}` {}\n", short(&commit.hash), commit.subject));
+            }
+            md.push('\n');
+        }
+    }
+
+    // The last response usually sums up where the session ended
+    let outcome = output.conversation.iter().rev().find_map(|entry| match entry {
+        ConversationEntry::Assistant { content, .. } if !content.trim().is_empty() => Some(content.trim()),
+        _ => None,
+    });
+    if let Some(outcome) = outcome {
+        md.push_str("Where it ended:\n\n");
+        for line in truncate_chars(outcome, MAX_OUTCOME_CHARS).lines() {
+            md.push_str(&format!("> {}\n", line));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+/// Sentences of responses that mention choosing one approach over another
+fn decisions(output: &DevlogOutput) -> Vec<String> {
+    let mut found = Vec::new();
+    for entry in &output.conversation {
+        let ConversationEntry::Assistant { content, .. } = entry else {
+            continue;
+        };
+        for sentence in content.lines().flat_map(|line| line.split(". ")) {
+            let sentence = sentence.trim().trim_start_matches(['-', '*', ' ']);
+            let lower = sentence.to_lowercase();
+            if DECISION_MARKERS.iter().any(|m| lower.contains(m)) {
+                let sentence = truncate_chars(sentence.trim_end_matches('.'), 200);
+                if !found.contains(&sentence) {
+                    found.push(sentence);
+                }
+                if found.len() == MAX_DECISIONS {
+                    return found;
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Files edited or created by tools, in the order first touched, plus those of
+/// commits made during the session. Paths are relative to the project where possible.
+fn files_touched(output: &DevlogOutput) -> Vec<String> {
+    let root = format!("{}/", output.project_dir.trim_end_matches('/'));
+    let mut files: Vec<String> = Vec::new();
+    let mut add = |file: &str| {
+        let file = file.strip_prefix(root.as_str()).unwrap_or(file);
+        if !files.iter().any(|f| f == file) {
+            files.push(file.to_string());
+        }
+    };
+
+    for entry in &output.conversation {
+        if let ConversationEntry::ToolSummary { actions } = entry {
+            for action in actions {
+                if let Some(file) = action.strip_prefix("edited ").or_else(|| action.strip_prefix("created ")) {
+                    add(file);
+                }
+            }
+        }
+    }
+    if let Some(ref git) = output.git {
+        for file in git.commits.iter().flat_map(|c| &c.files) {
+            add(file);
+        }
+    }
+    files
+}
+
+fn first_line(s: &str) -> String {
+    let mut lines = s.lines();
+    let first = lines.next().unwrap_or("").to_string();
+    if lines.next().is_some() {
+        format!("{} ...", first)
+    } else {
+        first
+    }
+}
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max_chars).collect();
+        format!("{}...", truncated.trim_end())
+    }
+}
+
+fn short(s: &str) -> String {
+    s.chars().take(8).collect()
+}
@@ -0,0 +1,94 @@
+use crate::output::DevlogOutput;
+use anyhow::{Context, Result};
+use devlog_core::store::{project_name, storage_filename};
+use reqwest::blocking::Client;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long to wait for a freshly started server to answer
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Show a session's page in the default browser, served by `devlog serve` on
+/// `port`. A server already listening there is reused; otherwise one is started
+/// in the background with `storage` as its storage directory.
+pub fn open_session(output: &DevlogOutput, port: u16, storage: &Path) -> Result<()> {
+    let base = format!("http://localhost:{}", port);
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    if !is_running(&client, &base) {
+        start_server(port, storage)?;
+        let started = Instant::now();
+        while !is_running(&client, &base) {
+            if started.elapsed() > STARTUP_TIMEOUT {
+                anyhow::bail!("devlog serve did not start on port {}", port);
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    // The server only shows what it has stored, so hand it this devlog first
+    let response = client
+        .post(format!("{}/ingest", base))
+        .json(output)
+        .send()
+        .with_context(|| format!("Failed to reach {}", base))?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Server refused the session with status {}: {}",
+            response.status(),
+            response.text().unwrap_or_else(|_| "unknown error".to_string())
+        );
+    }
+
+    let mut url = reqwest::Url::parse(&base).context("Invalid server URL")?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid server URL"))?
+        .extend([
+            "session",
+            &output.machine_id,
+            &project_name(output.project_path()),
+            &storage_filename(&output.session_id, &output.timestamp),
+        ]);
+
+    eprintln!("Opening {}", url);
+    open::that(url.as_str()).with_context(|| format!("Failed to open a browser for {}", url))?;
+    Ok(())
+}
+
+fn is_running(client: &Client, base: &str) -> bool {
+    client
+        .get(format!("{}/health", base))
+        .send()
+        .map(|r| r.status().is_success())
+        .unwrap_or(false)
+}
+
+/// Run `devlog serve` detached from this process, so it outlives the command
+fn start_server(port: u16, storage: &Path) -> Result<()> {
+    std::fs::create_dir_all(storage)
+        .with_context(|| format!("Failed to create storage directory: {}", storage.display()))?;
+    let exe = std::env::current_exe().context("Failed to locate the devlog executable")?;
+    let child = Command::new(exe)
+        .arg("serve")
+        .arg("--port")
+        .arg(port.to_string())
+        .arg("--storage")
+        .arg(storage)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start devlog serve")?;
+
+    eprintln!(
+        "Started devlog serve on port {} (pid {}), storing in {}",
+        port,
+        child.id(),
+        storage.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use crate::stats::SessionEntry;
+use chrono::{Datelike, Duration, NaiveDate};
+use devlog_core::{ProjectGrouping, SessionStats};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Weeks a heatmap covers, as on a GitHub profile
+pub const WEEKS: i64 = 53;
+
+/// Side of a day's square and the space between squares, in pixels
+const CELL: i64 = 11;
+const STEP: i64 = 14;
+/// Room for the weekday labels on the left and the months above
+const LEFT: i64 = 28;
+const TOP: i64 = 16;
+
+/// Days a stats-table sparkline covers, up to today
+pub const SPARKLINE_DAYS: i64 = 30;
+
+/// Cell colours from no activity up to the busiest day, matching the dark pages
+const LEVELS: [&str; 5] = ["#16213e", "#0b4f6c", "#0e7490", "#06b6d4", "#00d9ff"];
+
+/// Totals per day
+pub type Days = BTreeMap<NaiveDate, u64>;
+
+/// What a heatmap's days count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Prompts,
+    Tokens,
+}
+
+impl Metric {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "tokens" => Metric::Tokens,
+            _ => Metric::Prompts,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Prompts => "prompts",
+            Metric::Tokens => "tokens",
+        }
+    }
+
+    /// What one session adds to its day
+    fn of(self, stats: &SessionStats) -> u64 {
+        match self {
+            Metric::Prompts => stats.prompts as u64,
+            Metric::Tokens => stats.input_tokens + stats.output_tokens,
+        }
+    }
+}
+
+/// Daily totals over all projects and for each, busiest project first
+pub struct Activity {
+    pub overall: Days,
+    pub projects: Vec<(Arc<str>, Days)>,
+}
+
+impl Activity {
+    /// Tally sessions by the day they started, from `first` on
+    pub fn of(sessions: &[SessionEntry], grouping: ProjectGrouping, metric: Metric, first: NaiveDate) -> Self {
+        let mut overall = Days::new();
+        let mut projects: HashMap<Arc<str>, Days> = HashMap::new();
+        for session in sessions {
+            let Ok(started) = chrono::DateTime::parse_from_rfc3339(&session.entry.header.timestamp) else {
+                continue;
+            };
+            let day = started.date_naive();
+            if day < first {
+                continue;
+            }
+            let value = metric.of(&session.entry.stats);
+            *overall.entry(day).or_default() += value;
+            *projects
+                .entry(session.project(grouping))
+                .or_default()
+                .entry(day)
+                .or_default() += value;
+        }
+
+        let mut projects: Vec<(Arc<str>, Days)> = projects.into_iter().collect();
+        projects.sort_by(|a, b| total(&b.1).cmp(&total(&a.1)).then_with(|| a.0.cmp(&b.0)));
+        Activity { overall, projects }
+    }
+}
+
+pub fn total(days: &Days) -> u64 {
+    days.values().sum()
+}
+
+/// The first day a heatmap ending on `last` shows: the Sunday that starts
+/// the first of its weeks
+pub fn first_day(last: NaiveDate) -> NaiveDate {
+    let week_start = last - Duration::days(last.weekday().num_days_from_sunday() as i64);
+    week_start - Duration::weeks(WEEKS - 1)
+}
+
+/// A GitHub-style heatmap of `days` up to `last` as inline SVG: a column per
+/// week, a row per weekday, each day shaded by its share of the busiest day
+/// and titled with its total
+pub fn heatmap_svg(days: &Days, last: NaiveDate, metric: Metric) -> String {
+    let first = first_day(last);
+    let busiest = days.values().copied().max().unwrap_or(0);
+    let width = LEFT + WEEKS * STEP;
+    let height = TOP + 7 * STEP;
+
+    let mut svg = format!(
+        "<svg class=\"heatmap\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        width, height, width, height
+    );
+    for (row, label) in [(1, "Mon"), (3, "Wed"), (5, "Fri")] {
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{}</text>",
+            TOP + row * STEP + CELL - 1,
+            label
+        ));
+    }
+
+    for week in 0..WEEKS {
+        let week_start = first + Duration::weeks(week);
+        let x = LEFT + week * STEP;
+        // Months are labelled above the week holding their first day
+        if week_start.day() <= 7 {
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\">{}</text>",
+                x,
+                TOP - 5,
+                week_start.format("%b")
+            ));
+        }
+        for weekday in 0..7 {
+            let day = week_start + Duration::days(weekday);
+            if day > last {
+                break;
+            }
+            let value = days.get(&day).copied().unwrap_or(0);
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"2\" fill=\"{}\"><title>{}: {} {}</title></rect>",
+                x,
+                TOP + weekday * STEP,
+                CELL,
+                CELL,
+                LEVELS[level(value, busiest)],
+                day.format("%Y-%m-%d"),
+                value,
+                metric.as_str()
+            ));
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Which of [`LEVELS`] a day's total is shaded with
+fn level(value: u64, busiest: u64) -> usize {
+    if value == 0 || busiest == 0 {
+        return 0;
+    }
+    let top = (LEVELS.len() - 1) as u64;
+    (value * top).div_ceil(busiest).clamp(1, top) as usize
+}
+
+/// The last [`SPARKLINE_DAYS`] of `days` up to `last` as a small inline SVG bar
+/// chart, one bar per day scaled to the busiest, for a row of the stats table
+pub fn sparkline_svg(days: &Days, last: NaiveDate, metric: Metric) -> String {
+    const BAR: i64 = 3;
+    const HEIGHT: i64 = 18;
+    let first = last - Duration::days(SPARKLINE_DAYS - 1);
+    let values: Vec<u64> = (0..SPARKLINE_DAYS)
+        .map(|offset| days.get(&(first + Duration::days(offset))).copied().unwrap_or(0))
+        .collect();
+    let busiest = values.iter().copied().max().unwrap_or(0);
+    let width = SPARKLINE_DAYS * (BAR + 1);
+
+    let mut svg = format!(
+        "<svg class=\"sparkline\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" xmlns=\"http://www.w3.org/2000/svg\"><title>{} {} in the last {} days</title>",
+        width,
+        HEIGHT,
+        width,
+        HEIGHT,
+        values.iter().sum::<u64>(),
+        metric.as_str(),
+        SPARKLINE_DAYS
+    );
+    for (offset, value) in values.into_iter().enumerate() {
+        // Quiet days keep a stub on the baseline, so the axis stays visible
+        let (height, fill) = match (value * (HEIGHT as u64)).checked_div(busiest) {
+            Some(height) if value > 0 => ((height as i64).max(2), LEVELS[LEVELS.len() - 1]),
+            _ => (1, LEVELS[1]),
+        };
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            offset as i64 * (BAR + 1),
+            HEIGHT - height,
+            BAR,
+            height,
+            fill
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
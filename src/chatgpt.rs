@@ -0,0 +1,173 @@
+use crate::parser::ConversationEntry;
+use crate::source::{self, ConversationBuilder, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+/// The file in a ChatGPT data export holding every conversation
+pub const CONVERSATIONS_FILE: &str = "conversations.json";
+
+/// ChatGPT's data exports, whose chats belong to no project
+pub struct ChatGpt;
+
+impl SessionSource for ChatGpt {
+    fn name(&self) -> &'static str {
+        "chatgpt"
+    }
+
+    fn description(&self) -> &'static str {
+        "A ChatGPT data export's zip, or its conversations.json"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        if path.is_dir() {
+            return path.join(CONVERSATIONS_FILE).is_file();
+        }
+        source::has_extension(path, "zip")
+            || (source::has_extension(path, "json")
+                && source::peek(path).map(|head| head.contains("\"mapping\"")).unwrap_or(false))
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        Ok(read_export(path)?
+            .into_iter()
+            .map(|conversation| Session {
+                id: conversation.id,
+                started: Some(conversation.started),
+                standalone: true,
+                title: conversation.title,
+                conversation: conversation.conversation,
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+/// One conversation from a ChatGPT export
+pub struct ChatGptConversation {
+    pub id: String,
+    pub title: Option<String>,
+    pub started: DateTime<Utc>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+/// Read the conversations of a ChatGPT data export, given as the export's zip,
+/// the directory it was unpacked to, or its `conversations.json`
+pub fn read_export(path: &Path) -> Result<Vec<ChatGptConversation>> {
+    let text = if path.is_dir() {
+        let file = path.join(CONVERSATIONS_FILE);
+        std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file.display()))?
+    } else if path.extension().map(|e| e.eq_ignore_ascii_case("zip")).unwrap_or(false) {
+        read_from_zip(path)?
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    };
+
+    let conversations: Vec<Value> =
+        serde_json::from_str(&text).context("conversations.json is not a JSON array of conversations")?;
+    Ok(conversations.iter().filter_map(read_conversation).collect())
+}
+
+fn read_from_zip(path: &Path) -> Result<String> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive =
+        zip::ZipArchive::new(file).with_context(|| format!("Not a zip archive: {}", path.display()))?;
+    // Exports keep it at the top level, but a re-zipped folder nests it
+    let name = archive
+        .file_names()
+        .filter(|name| name.rsplit('/').next() == Some(CONVERSATIONS_FILE))
+        .min_by_key(|name| name.len())
+        .map(String::from)
+        .with_context(|| format!("No {} in {}", CONVERSATIONS_FILE, path.display()))?;
+
+    let mut text = String::new();
+    archive
+        .by_name(&name)
+        .with_context(|| format!("Failed to read {} from {}", name, path.display()))?
+        .read_to_string(&mut text)
+        .with_context(|| format!("Failed to read {} from {}", name, path.display()))?;
+    Ok(text)
+}
+
+/// A conversation's messages are a tree of edits and regenerations in `mapping`;
+/// the branch ending at `current_node` is the one last shown, and the one kept
+fn read_conversation(conversation: &Value) -> Option<ChatGptConversation> {
+    let id = conversation
+        .get("conversation_id")
+        .or_else(|| conversation.get("id"))
+        .and_then(Value::as_str)?
+        .to_string();
+    let mapping = conversation.get("mapping")?.as_object()?;
+
+    let mut branch = Vec::new();
+    let mut node_id = conversation.get("current_node").and_then(Value::as_str);
+    while let Some(node) = node_id.and_then(|id| mapping.get(id)) {
+        branch.push(node);
+        node_id = node.get("parent").and_then(Value::as_str);
+        // A malformed export could loop back on itself
+        if branch.len() > mapping.len() {
+            break;
+        }
+    }
+    branch.reverse();
+
+    let mut entries = ConversationBuilder::new();
+    for message in branch.iter().filter_map(|node| node.get("message")) {
+        if message.pointer("/metadata/is_visually_hidden_from_conversation") == Some(&Value::Bool(true)) {
+            continue;
+        }
+        let role = message.pointer("/author/role").and_then(Value::as_str).unwrap_or("");
+        let recipient = message.get("recipient").and_then(Value::as_str).unwrap_or("all");
+        let timestamp = message.get("create_time").and_then(parse_time).map(|t| t.to_rfc3339());
+        let uuid = message.get("id").and_then(Value::as_str).map(String::from);
+
+        match role {
+            // Assistant messages addressed to a tool are calls to it
+            "assistant" if recipient != "all" => entries.tool(format!("used {}", recipient)),
+            "user" => entries.user(timestamp, message_text(message), uuid),
+            "assistant" => entries.assistant(timestamp, message_text(message), None, uuid),
+            // System prompts and tool output
+            _ => {}
+        }
+    }
+    let entries = entries.finish();
+
+    let started = conversation
+        .get("create_time")
+        .and_then(parse_time)
+        .or_else(|| crate::parser::conversation_span(&entries).map(|(start, _)| start))?;
+    Some(ChatGptConversation {
+        id,
+        title: conversation.get("title").and_then(Value::as_str).map(String::from),
+        started,
+        conversation: entries,
+    })
+}
+
+/// The text of a message; images, files and reasoning are left out
+fn message_text(message: &Value) -> String {
+    let Some(content) = message.get("content") else {
+        return String::new();
+    };
+    match content.get("content_type").and_then(Value::as_str) {
+        Some("text") | Some("multimodal_text") => content
+            .get("parts")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// ChatGPT records times as fractional seconds since the epoch
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    let seconds = value.as_f64()?;
+    Utc.timestamp_millis_opt((seconds * 1000.0) as i64).single()
+}
@@ -0,0 +1,290 @@
+use crate::parser::{truncate, ConversationEntry, TokenUsage};
+use crate::source::{self, ConversationBuilder, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Codex CLI's rollout files
+pub struct Codex;
+
+impl SessionSource for Codex {
+    fn name(&self) -> &'static str {
+        "codex"
+    }
+
+    fn description(&self) -> &'static str {
+        "Codex CLI rollout-*.jsonl files, or a directory such as ~/.codex/sessions"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        if path.is_dir() {
+            // Codex files rollouts by date: sessions/YYYY/MM/DD
+            return has_rollouts(path, 4);
+        }
+        is_rollout(path)
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        let mut files = Vec::new();
+        if path.is_dir() {
+            rollout_files(path, &mut files)?;
+        } else {
+            files.push(path.to_path_buf());
+        }
+
+        let mut sessions = Vec::new();
+        for file in files {
+            eprintln!("Reading Codex session from: {}", file.display());
+            let rollout = read_rollout(&file)?;
+            let started = rollout
+                .timestamp
+                .as_deref()
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|dt| dt.with_timezone(&Utc));
+            sessions.push(Session {
+                id: rollout.id.unwrap_or_else(|| source::id_from_path(&file)),
+                started,
+                dir: rollout.cwd.map(PathBuf::from),
+                conversation: rollout.conversation,
+                ..Default::default()
+            });
+        }
+        Ok(sessions)
+    }
+}
+
+/// One Codex CLI session, from a `rollout-*.jsonl` file
+pub struct CodexSession {
+    pub id: Option<String>,
+    /// When the session started
+    pub timestamp: Option<String>,
+    /// Where Codex ran
+    pub cwd: Option<String>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+/// Read a rollout file. Each line is either `{"type": ..., "payload": ...}`, or in
+/// rollouts from before Codex wrapped them, a bare response item after a first
+/// line holding the session's `id` and `timestamp`.
+pub fn read_rollout(path: &Path) -> Result<CodexSession> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read Codex rollout: {}", path.display()))?;
+
+    let mut session = CodexSession {
+        id: None,
+        timestamp: None,
+        cwd: None,
+        conversation: Vec::new(),
+    };
+    let mut conversation = ConversationBuilder::new();
+
+    for (line_num, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Value = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("Warning: Failed to parse line {}: {} (skipping)", line_num + 1, e);
+                continue;
+            }
+        };
+        let line_time = record.get("timestamp").and_then(Value::as_str).map(String::from);
+
+        let (kind, item) = match record.get("payload") {
+            Some(payload) => (record.get("type").and_then(Value::as_str).unwrap_or(""), payload),
+            None if line_num == 0 && record.get("type").is_none() => ("session_meta", &record),
+            None => ("response_item", &record),
+        };
+
+        match kind {
+            "session_meta" => {
+                session.id = str_field(item, "id").or(session.id);
+                session.timestamp = str_field(item, "timestamp").or(session.timestamp).or(line_time);
+                session.cwd = str_field(item, "cwd").or(session.cwd);
+            }
+            "turn_context" if session.cwd.is_none() => {
+                session.cwd = str_field(item, "cwd");
+            }
+            "event_msg" if item.get("type").and_then(Value::as_str) == Some("token_count") => {
+                if let Some(usage) = item.pointer("/info/last_token_usage").map(token_usage) {
+                    conversation.usage(usage);
+                }
+            }
+            "response_item" => match item.get("type").and_then(Value::as_str) {
+                Some("message") => {
+                    let role = item.get("role").and_then(Value::as_str).unwrap_or("");
+                    let content = message_text(item);
+                    if is_injected_context(&content) {
+                        continue;
+                    }
+                    match role {
+                        "user" => conversation.user(line_time, content, None),
+                        "assistant" => conversation.assistant(line_time, content, None, None),
+                        // System and developer instructions
+                        _ => {}
+                    }
+                }
+                Some("function_call") => {
+                    let name = item.get("name").and_then(Value::as_str).unwrap_or("tool");
+                    let arguments: Value = item
+                        .get("arguments")
+                        .and_then(Value::as_str)
+                        .and_then(|a| serde_json::from_str(a).ok())
+                        .unwrap_or(Value::Null);
+                    conversation.tools(summarize_call(name, &arguments));
+                }
+                Some("custom_tool_call") => {
+                    let name = item.get("name").and_then(Value::as_str).unwrap_or("tool");
+                    let input = item.get("input").cloned().unwrap_or(Value::Null);
+                    conversation.tools(summarize_call(name, &input));
+                }
+                Some("local_shell_call") => {
+                    let command = item.pointer("/action/command").map(shell_command).unwrap_or_default();
+                    conversation.tool(format!("ran {}", truncate(&command, 50)));
+                }
+                Some("web_search_call") => conversation.tool("used WebSearch".to_string()),
+                // Reasoning, tool output
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    session.conversation = conversation.finish();
+    Ok(session)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(String::from)
+}
+
+/// The text parts of a message's `input_text`/`output_text` content
+fn message_text(item: &Value) -> String {
+    let parts = item.get("content").and_then(Value::as_array);
+    parts
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Codex sends the environment and AGENTS.md to the model as user messages
+fn is_injected_context(text: &str) -> bool {
+    ["<environment_context>", "<user_instructions>", "# AGENTS.md instructions"]
+        .iter()
+        .any(|prefix| text.starts_with(prefix))
+}
+
+/// Short actions in the Claude Code parser's wording
+fn summarize_call(name: &str, arguments: &Value) -> Vec<String> {
+    match name {
+        "shell" | "container.exec" | "exec_command" => {
+            let command = arguments
+                .get("command")
+                .or_else(|| arguments.get("cmd"))
+                .map(shell_command)
+                .unwrap_or_default();
+            match command.strip_prefix("apply_patch") {
+                Some(patch) => summarize_patch(patch),
+                None => vec![format!("ran {}", truncate(&command, 50))],
+            }
+        }
+        "apply_patch" => {
+            let patch = arguments
+                .as_str()
+                .or_else(|| arguments.get("input").and_then(Value::as_str))
+                .unwrap_or("");
+            summarize_patch(patch)
+        }
+        "update_plan" => vec!["updated todo list".to_string()],
+        "view_image" => vec![format!("read {}", str_field(arguments, "path").unwrap_or_default())],
+        _ => vec![format!("used {}", name)],
+    }
+}
+
+/// A command as Codex records it: an argv array, whose `bash -lc <script>` form
+/// is shown as the script alone, or a plain string
+fn shell_command(command: &Value) -> String {
+    let Some(argv) = command.as_array() else {
+        return command.as_str().unwrap_or("<command>").to_string();
+    };
+    let argv: Vec<&str> = argv.iter().filter_map(Value::as_str).collect();
+    match argv.as_slice() {
+        [shell, flag, script] if shell.ends_with("sh") && flag.starts_with('-') => script.to_string(),
+        _ => argv.join(" "),
+    }
+}
+
+/// One action per file an `apply_patch` envelope touches
+fn summarize_patch(patch: &str) -> Vec<String> {
+    let actions: Vec<String> = patch
+        .lines()
+        .filter_map(|line| {
+            if let Some(path) = line.strip_prefix("*** Update File: ") {
+                Some(format!("edited {}", path.trim()))
+            } else if let Some(path) = line.strip_prefix("*** Add File: ") {
+                Some(format!("created {}", path.trim()))
+            } else {
+                line.strip_prefix("*** Delete File: ")
+                    .map(|path| format!("deleted {}", path.trim()))
+            }
+        })
+        .collect();
+    if actions.is_empty() {
+        vec!["used apply_patch".to_string()]
+    } else {
+        actions
+    }
+}
+
+fn token_usage(usage: &Value) -> TokenUsage {
+    let count = |key: &str| usage.get(key).and_then(Value::as_u64);
+    TokenUsage {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: count("cached_input_tokens"),
+    }
+}
+
+fn is_rollout(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with("rollout-") && name.ends_with(".jsonl")
+}
+
+/// Whether there are rollouts in `dir`, or in directories up to `depth` below it
+fn has_rollouts(dir: &Path, depth: usize) -> bool {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().map(|entry| entry.path()).any(|path| {
+        if path.is_dir() {
+            depth > 0 && has_rollouts(&path, depth - 1)
+        } else {
+            is_rollout(&path)
+        }
+    })
+}
+
+/// The `rollout-*.jsonl` files under `dir`, which Codex files by date
+fn rollout_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            rollout_files(&path, files)?;
+        } else if is_rollout(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
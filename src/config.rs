@@ -1,17 +1,353 @@
 use anyhow::{Context, Result};
+use devlog_core::{BundlePolicy, RetentionPolicy};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub push: Option<PushConfig>,
+    pub embeddings: Option<EmbeddingsConfig>,
+    /// Named queries, run with `devlog search --saved <name>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub searches: BTreeMap<String, SavedSearch>,
+    /// Pruning applied by `devlog serve` to its storage directory
+    pub retention: Option<RetentionPolicy>,
+    /// Rolling older days of `devlog serve`'s storage into day bundles
+    pub bundles: Option<BundlePolicy>,
+    pub git: Option<GitConfig>,
+    pub obsidian: Option<ObsidianConfig>,
+    pub redact: Option<RedactConfig>,
+    /// Sessions never to be kept
+    pub skip: Option<SkipConfig>,
+    /// Size limits for oversized prompts, responses and tool summaries
+    pub truncate: Option<TruncateConfig>,
+    /// Projects captured as metadata and counts only, without their text
+    pub stats_only: Option<StatsOnlyConfig>,
+    /// What `devlog pii-report` looks for besides emails, phone numbers and names
+    pub pii: Option<PiiConfig>,
+    /// How ticket references in prompts are recognised and linked
+    pub tickets: Option<TicketsConfig>,
+    /// Webhooks `devlog serve` posts to when sessions are ingested
+    pub notifications: Option<NotificationsConfig>,
+    pub telemetry: Option<TelemetryConfig>,
+    /// Accounts allowed into `devlog serve`; with none it is open to all
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub users: BTreeMap<String, UserConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct PushConfig {
     pub endpoint: String,
     pub enabled: bool,
+    /// Server search API; derived from `endpoint` when not set
+    pub search_endpoint: Option<String>,
+    /// Sent as `Authorization: Bearer` to receivers that require it
+    pub api_token: Option<String>,
+    /// Also upload the raw JSONL transcript, so the server can re-parse history later
+    #[serde(default)]
+    pub include_transcript: bool,
+    /// age recipients (`age1...`) to encrypt sessions to before they leave the
+    /// machine; the server then stores only ciphertext
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub encrypt_to: Vec<String>,
+    /// The server's receipt public key (hex); receipts signed with any other
+    /// key are rejected. Without it, any validly signed receipt is kept.
+    pub receipt_key: Option<String>,
+    /// PEM certificate and PKCS#8 key presented to servers that require client
+    /// certificates (mutual TLS)
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    /// PEM CA certificate to trust for the server's own certificate, e.g. a private CA
+    pub ca_cert: Option<PathBuf>,
+}
+
+impl PushConfig {
+    /// URL of the server's `/api/search`, alongside its `/ingest` endpoint
+    pub fn search_url(&self) -> String {
+        if let Some(ref url) = self.search_endpoint {
+            return url.clone();
+        }
+        let base = self.endpoint.trim_end_matches('/');
+        let base = base.strip_suffix("/ingest").unwrap_or(base);
+        format!("{}/api/search", base)
+    }
+
+    /// URL of the server's `/ingest/batch`, next to `/ingest`
+    pub fn batch_url(&self) -> String {
+        format!("{}/batch", self.endpoint.trim_end_matches('/'))
+    }
+
+    /// URL of the server's `/ingest/transcript`, next to `/ingest`
+    pub fn transcript_url(&self) -> String {
+        format!("{}/transcript", self.endpoint.trim_end_matches('/'))
+    }
+
+    /// URL of the server's `/ingest/encrypted`, next to `/ingest`
+    pub fn encrypted_url(&self) -> String {
+        format!("{}/encrypted", self.endpoint.trim_end_matches('/'))
+    }
+
+    /// URL of the server's session list, next to `/ingest`
+    pub fn sessions_url(&self) -> String {
+        let base = self.endpoint.trim_end_matches('/');
+        let base = base.strip_suffix("/ingest").unwrap_or(base);
+        format!("{}/sessions", base)
+    }
+
+    /// URL of a session on the server, which `GET` fetches and `DELETE` removes
+    pub fn session_url(&self, machine_id: &str, session_id: &str) -> String {
+        let base = self.endpoint.trim_end_matches('/');
+        let base = base.strip_suffix("/ingest").unwrap_or(base);
+        format!("{}/sessions/{}/{}", base, machine_id, session_id)
+    }
+}
+
+/// What `ingest` records about the session's repository
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct GitConfig {
+    /// Record `user.name`/`user.email`, so shared receivers can attribute sessions to people
+    #[serde(default)]
+    pub include_author: bool,
+}
+
+/// Obsidian vault that `devlog export --format obsidian` writes session notes into
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ObsidianConfig {
+    pub vault: PathBuf,
+    /// Folder inside the vault; each project gets a subfolder
+    #[serde(default = "default_obsidian_folder")]
+    pub folder: String,
+    /// Also write a note on every ingest
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+impl ObsidianConfig {
+    pub fn new(vault: PathBuf) -> Self {
+        ObsidianConfig {
+            vault,
+            folder: default_obsidian_folder(),
+            enabled: false,
+        }
+    }
+}
+
+fn default_obsidian_folder() -> String {
+    "Devlog".to_string()
+}
+
+/// Issue-tracker tickets mentioned in prompts, such as `PROJ-123` or `#456`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TicketsConfig {
+    /// Page of a ticket such as `PROJ-123`, with `{id}` standing for it, e.g.
+    /// `https://example.atlassian.net/browse/{id}`; `#456` links to the
+    /// GitHub or GitLab repo's issue instead
+    pub url: Option<String>,
+    /// Only these project keys, e.g. `["PROJ", "OPS"]`; when empty any
+    /// uppercase key is, apart from names like `UTF-8` and `SHA-256`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+}
+
+/// Rewrites applied to session text on ingest, and to existing devlogs by `devlog anonymize`
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RedactConfig {
+    /// Regexes whose matches are replaced with `replacement`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_redaction")]
+    pub replacement: String,
+    /// Literal text and what to write instead, e.g. a client's name and its codename
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub replace: BTreeMap<String, String>,
+    /// Write paths in the project as `./src/...` and home directories as `~`,
+    /// so usernames and where projects live stay private
+    #[serde(default)]
+    pub relative_paths: bool,
+}
+
+/// Rules under `[skip]` for sessions `ingest` and `import` hold back entirely:
+/// nothing of them is written to `.devlog`, pushed or exported
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SkipConfig {
+    /// Regexes; a session whose prompts, responses, tool summaries, notes or
+    /// commit subjects match any of them is held back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+    /// Project directory names, where `*` matches any run of characters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    /// Tags that mark a session as not to be kept, e.g. added with `devlog tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Write held-back sessions here, for review, instead of dropping them
+    pub quarantine_dir: Option<PathBuf>,
+}
+
+/// Per-entry size limits applied on ingest and import; content over a limit
+/// is cut short and ends with a marker giving its original size
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TruncateConfig {
+    pub max_prompt_bytes: Option<usize>,
+    pub max_response_bytes: Option<usize>,
+    /// Each line of a tool summary
+    pub max_tool_summary_bytes: Option<usize>,
+}
+
+/// Projects whose sessions keep only metadata and counts (prompts, words,
+/// tools, files, tokens); their text is hashed or dropped before it is written
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct StatsOnlyConfig {
+    /// Project directory names, where `*` matches any run of characters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub content: StatsOnlyContent,
+}
+
+/// What is left of each piece of text in a stats-only session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsOnlyContent {
+    /// Nothing: the text is emptied
+    #[default]
+    Drop,
+    /// `sha256:` and the hex digest of the text, so repeats can still be spotted
+    Hash,
+}
+
+/// Personal data of shapes `devlog pii-report` can't guess
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PiiConfig {
+    /// More kinds to report, each a regex, e.g. `employee-id = "\\bE\\d{6}\\b"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub patterns: BTreeMap<String, String>,
+    /// People's names to report wherever they appear, ignoring case
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+}
+
+fn default_redaction() -> String {
+    "[REDACTED]".to_string()
+}
+
+/// Messages about newly ingested sessions, sent by `devlog serve`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NotificationsConfig {
+    /// Where the server is reached, e.g. `https://devlog.example.com`, for
+    /// links to session pages; messages carry no link without it
+    pub base_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slack: Vec<Webhook>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub discord: Vec<Webhook>,
+    /// Any other endpoint, POSTed a JSON summary of each session
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+    pub github: Option<GitHubConfig>,
+    pub email: Option<EmailConfig>,
+}
+
+/// A webhook URL, and which projects it hears about
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Webhook {
+    pub webhook_url: String,
+    /// Only sessions of these projects (by directory name); every project when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>,
+}
+
+impl Webhook {
+    pub fn wants(&self, project: &str) -> bool {
+        self.projects.is_empty() || self.projects.iter().any(|p| p == project)
+    }
+}
+
+/// Comments on the open pull request of each ingested session's branch
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GitHubConfig {
+    /// Environment variable holding a token allowed to comment on pull
+    /// requests; `GITHUB_TOKEN` when not set
+    pub token_env: Option<String>,
+    /// API root for GitHub Enterprise; `https://api.github.com` when not set
+    pub api_url: Option<String>,
+}
+
+/// A digest of recent activity and notable sessions, emailed on a schedule
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// 587, or 465 with `tls = "tls"`, when not set
+    pub smtp_port: Option<u16>,
+    /// `starttls` (default), `tls` for a connection encrypted from the start,
+    /// or `none` for a relay on a trusted network
+    pub tls: Option<String>,
+    pub username: Option<String>,
+    /// Environment variable holding the SMTP password
+    pub password_env: Option<String>,
+    /// e.g. `devlog <devlog@example.com>`
+    pub from: String,
+    pub to: Vec<String>,
+    /// `daily` (default), or `weekly` to send on Mondays
+    pub schedule: Option<String>,
+    /// Hour of the day, in UTC, to send at; 8 when not set
+    pub hour: Option<u32>,
+    /// How many of the busiest sessions to describe; 5 when not set
+    pub notable_sessions: Option<usize>,
+}
+
+/// Traces of ingests, pushes and server requests, exported over OTLP/HTTP
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// The collector, e.g. `http://localhost:4318`; traces go to its `/v1/traces`.
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` when not set, then localhost
+    pub otlp_endpoint: Option<String>,
+    /// `devlog` when not set
+    pub service_name: Option<String>,
+}
+
+/// OpenAI-compatible embeddings endpoint used for semantic search
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EmbeddingsConfig {
+    pub endpoint: String,
+    pub model: String,
+    pub enabled: bool,
+    /// Environment variable holding the API key, if the endpoint needs one
+    pub api_key_env: Option<String>,
+}
+
+/// A recurring search defined under `[searches.<name>]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SavedSearch {
+    pub query: String,
+    /// prompts, conversations, or all
+    pub scope: Option<String>,
+    pub days: Option<u32>,
+}
+
+/// A `devlog serve` account defined under `[users.<name>]`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserConfig {
+    /// Environment variable holding the user's password
+    pub password_env: String,
+    /// `viewer` (the default) or `admin`, who sees every project and may purge
+    #[serde(default)]
+    pub role: Role,
+    /// Project directory names the user may see; `*` matches any run of
+    /// characters, e.g. `acme-*`
+    #[serde(default)]
+    pub projects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    Viewer,
+    Admin,
 }
 
 impl Default for Config {
@@ -20,7 +356,35 @@ impl Default for Config {
             push: Some(PushConfig {
                 endpoint: "http://localhost:8090/ingest".to_string(),
                 enabled: false,
+                search_endpoint: None,
+                api_token: None,
+                include_transcript: false,
+                encrypt_to: Vec::new(),
+                receipt_key: None,
+                client_cert: None,
+                client_key: None,
+                ca_cert: None,
             }),
+            embeddings: Some(EmbeddingsConfig {
+                endpoint: "http://localhost:11434/v1/embeddings".to_string(),
+                model: "nomic-embed-text".to_string(),
+                enabled: false,
+                api_key_env: None,
+            }),
+            searches: BTreeMap::new(),
+            retention: None,
+            bundles: None,
+            git: None,
+            obsidian: None,
+            redact: None,
+            skip: None,
+            truncate: None,
+            stats_only: None,
+            pii: None,
+            tickets: None,
+            notifications: None,
+            telemetry: None,
+            users: BTreeMap::new(),
         }
     }
 }
@@ -119,10 +483,15 @@ impl Config {
     }
 
     fn config_path() -> Result<PathBuf> {
+        Ok(Self::home_dir()?.join("config.toml"))
+    }
+
+    /// `~/.devlog`, which holds the config and other per-user state
+    pub fn home_dir() -> Result<PathBuf> {
         let home = std::env::var("USERPROFILE")
             .or_else(|_| std::env::var("HOME"))
             .context("Neither USERPROFILE nor HOME environment variable is set")?;
 
-        Ok(PathBuf::from(home).join(".devlog").join("config.toml"))
+        Ok(PathBuf::from(home).join(".devlog"))
     }
 }
@@ -0,0 +1,354 @@
+use crate::parser::{truncate, ConversationEntry, TokenUsage};
+use crate::source::{self, ConversationBuilder, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Where Cursor lists a workspace's composer chats, in its `ItemTable`
+const COMPOSER_KEY: &str = "composer.composerData";
+/// Chats from the panel that preceded the composer
+const LEGACY_CHAT_KEY: &str = "workbench.panel.aichat.view.aichat.chatdata";
+
+/// Cursor's chats, kept in its state databases
+pub struct Cursor;
+
+impl SessionSource for Cursor {
+    fn name(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn description(&self) -> &'static str {
+        "Cursor's User directory, a folder under its workspaceStorage, or a JSON export"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        if path.is_file() {
+            return source::has_extension(path, "json")
+                && source::peek(path).map(|head| head.contains("\"composerId\"")).unwrap_or(false);
+        }
+        path.join("workspace.json").is_file()
+            || path.join("workspaceStorage").is_dir()
+            // workspaceStorage itself
+            || std::fs::read_dir(path)
+                .map(|entries| entries.flatten().any(|entry| entry.path().join("workspace.json").is_file()))
+                .unwrap_or(false)
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        Ok(read_chats(path)?
+            .into_iter()
+            .map(|chat| Session {
+                id: chat.id,
+                started: Some(chat.started),
+                // Exported chats don't say which project they belong to
+                dir: chat.dir,
+                conversation: chat.conversation,
+                ..Default::default()
+            })
+            .collect())
+    }
+}
+
+/// One Cursor chat
+pub struct CursorChat {
+    pub id: String,
+    pub started: DateTime<Utc>,
+    /// The workspace folder, when the chat came from one
+    pub dir: Option<PathBuf>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+/// Read Cursor chats from any of:
+///
+/// - a workspace folder under `workspaceStorage` (holding `workspace.json` and
+///   `state.vscdb`), whose composer chats are kept in `globalStorage/state.vscdb`
+/// - a directory above those, such as Cursor's `User` directory
+/// - a JSON export of one composer chat, or an array of them
+pub fn read_chats(path: &Path) -> Result<Vec<CursorChat>> {
+    if path.is_file() {
+        return read_export(path);
+    }
+    if path.join("workspace.json").is_file() {
+        return read_workspace(path);
+    }
+
+    let storage = if path.join("workspaceStorage").is_dir() {
+        path.join("workspaceStorage")
+    } else {
+        path.to_path_buf()
+    };
+    let mut workspaces: Vec<PathBuf> = std::fs::read_dir(&storage)
+        .with_context(|| format!("Failed to read directory: {}", storage.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    workspaces.retain(|dir| dir.join("workspace.json").is_file());
+    workspaces.sort();
+
+    let mut chats = Vec::new();
+    for workspace in workspaces {
+        match read_workspace(&workspace) {
+            Ok(found) => chats.extend(found),
+            Err(e) => eprintln!("Warning: Skipping {}: {:#}", workspace.display(), e),
+        }
+    }
+    Ok(chats)
+}
+
+fn read_export(path: &Path) -> Result<Vec<CursorChat>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read Cursor export: {}", path.display()))?;
+    let value: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Cursor export is not valid JSON: {}", path.display()))?;
+    let composers = match value {
+        Value::Array(composers) => composers,
+        composer => vec![composer],
+    };
+    Ok(composers
+        .iter()
+        .filter_map(|composer| composer_chat(composer, None, |_| None))
+        .collect())
+}
+
+fn read_workspace(workspace: &Path) -> Result<Vec<CursorChat>> {
+    let dir = workspace_folder(workspace)?;
+    let db = open(&workspace.join("state.vscdb"))?;
+    // Composer chats live in the global database, next to workspaceStorage
+    let global = workspace
+        .parent()
+        .and_then(Path::parent)
+        .map(|user| user.join("globalStorage").join("state.vscdb"))
+        .filter(|path| path.is_file())
+        .map(|path| open(&path))
+        .transpose()?;
+    let lookup = |key: &str| global.as_ref().and_then(|db| disk_value(db, key));
+
+    let mut chats = Vec::new();
+    if let Some(data) = item_value(&db, COMPOSER_KEY) {
+        for header in data.get("allComposers").and_then(Value::as_array).into_iter().flatten() {
+            // Older versions kept the whole chat here; newer ones only its ID
+            let composer = match header.get("conversation") {
+                Some(_) => Some(header.clone()),
+                None => header
+                    .get("composerId")
+                    .and_then(Value::as_str)
+                    .and_then(|id| lookup(&format!("composerData:{}", id))),
+            };
+            if let Some(chat) = composer.and_then(|c| composer_chat(&c, dir.clone(), lookup)) {
+                chats.push(chat);
+            }
+        }
+    }
+    if let Some(data) = item_value(&db, LEGACY_CHAT_KEY) {
+        for tab in data.get("tabs").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(chat) = legacy_chat(tab, dir.clone()) {
+                chats.push(chat);
+            }
+        }
+    }
+    Ok(chats)
+}
+
+fn open(path: &Path) -> Result<Connection> {
+    // Read-only, so a running Cursor is not disturbed
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .with_context(|| format!("Failed to open Cursor database: {}", path.display()))
+}
+
+/// A JSON value from a workspace's `ItemTable`
+fn item_value(db: &Connection, key: &str) -> Option<Value> {
+    json_value(db, "SELECT value FROM ItemTable WHERE key = ?", key)
+}
+
+/// A JSON value from the global `cursorDiskKV` table
+fn disk_value(db: &Connection, key: &str) -> Option<Value> {
+    json_value(db, "SELECT value FROM cursorDiskKV WHERE key = ?", key)
+}
+
+fn json_value(db: &Connection, sql: &str, key: &str) -> Option<Value> {
+    // Values are stored as text or as blobs, depending on the version
+    let bytes: Option<Vec<u8>> = db
+        .query_row(sql, [key], |row| Ok(row.get_ref(0)?.as_bytes().ok().map(<[u8]>::to_vec)))
+        .optional()
+        .ok()
+        .flatten()
+        .flatten();
+    serde_json::from_slice(&bytes?).ok()
+}
+
+/// The folder a workspace was opened on, from its `workspace.json`
+fn workspace_folder(workspace: &Path) -> Result<Option<PathBuf>> {
+    let path = workspace.join("workspace.json");
+    let text = std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let value: Value = serde_json::from_str(&text).with_context(|| format!("Invalid JSON in {}", path.display()))?;
+    // Multi-root and remote workspaces have no single local folder
+    Ok(value
+        .get("folder")
+        .and_then(Value::as_str)
+        .and_then(|uri| uri.strip_prefix("file://"))
+        .map(|path| PathBuf::from(percent_decode(path))))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A composer chat, whose messages ("bubbles") are either inline in
+/// `conversation` or listed in `fullConversationHeadersOnly` and stored apart
+fn composer_chat(
+    composer: &Value,
+    dir: Option<PathBuf>,
+    lookup: impl Fn(&str) -> Option<Value>,
+) -> Option<CursorChat> {
+    let id = composer.get("composerId").and_then(Value::as_str)?.to_string();
+
+    let bubbles: Vec<Value> = match composer.get("conversation").and_then(Value::as_array) {
+        Some(bubbles) if !bubbles.is_empty() => bubbles.clone(),
+        _ => composer
+            .get("fullConversationHeadersOnly")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|header| header.get("bubbleId").and_then(Value::as_str))
+            .filter_map(|bubble| lookup(&format!("bubbleId:{}:{}", id, bubble)))
+            .collect(),
+    };
+    let conversation = to_conversation(&bubbles);
+
+    let started = composer
+        .get("createdAt")
+        .and_then(parse_time)
+        .or_else(|| crate::parser::conversation_span(&conversation).map(|(start, _)| start))?;
+    Some(CursorChat {
+        id,
+        started,
+        dir,
+        conversation,
+    })
+}
+
+fn legacy_chat(tab: &Value, dir: Option<PathBuf>) -> Option<CursorChat> {
+    let id = tab.get("tabId").and_then(Value::as_str)?.to_string();
+    let bubbles = tab.get("bubbles").and_then(Value::as_array)?;
+    let conversation = to_conversation(bubbles);
+    let started = bubbles
+        .iter()
+        .find_map(|b| b.get("createdAt").and_then(parse_time))
+        .or_else(|| tab.get("lastSendTime").and_then(parse_time))?;
+    Some(CursorChat {
+        id,
+        started,
+        dir,
+        conversation,
+    })
+}
+
+/// Bubbles of type 1 (or `"user"`) are the user's; the rest are the model's
+/// replies, whose tool calls become tool summaries
+fn to_conversation(bubbles: &[Value]) -> Vec<ConversationEntry> {
+    let mut conversation = ConversationBuilder::new();
+
+    for bubble in bubbles {
+        let is_user = matches!(bubble.get("type"), Some(Value::Number(n)) if n.as_u64() == Some(1))
+            || bubble.get("type").and_then(Value::as_str) == Some("user");
+        let text = bubble
+            .get("text")
+            .or_else(|| bubble.get("rawText"))
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let timestamp = bubble
+            .get("createdAt")
+            .and_then(parse_time)
+            .map(|time| time.to_rfc3339());
+
+        if is_user {
+            conversation.user(timestamp, text, None);
+        } else {
+            conversation.assistant(timestamp, text, bubble.get("tokenCount").and_then(token_usage), None);
+        }
+        if let Some(tool) = bubble.get("toolFormerData") {
+            conversation.tools(summarize_tool(tool));
+        }
+    }
+
+    conversation.finish()
+}
+
+/// Short actions in the Claude Code parser's wording, for Cursor's agent tools
+fn summarize_tool(tool: &Value) -> Option<String> {
+    let name = tool.get("name").and_then(Value::as_str)?;
+    // Arguments are a JSON string, under `rawArgs` or `params` depending on the version
+    let args: Value = ["rawArgs", "params"]
+        .iter()
+        .filter_map(|key| tool.get(*key))
+        .find_map(|v| match v {
+            Value::String(s) => serde_json::from_str(s).ok(),
+            Value::Object(_) => Some(v.clone()),
+            _ => None,
+        })
+        .unwrap_or(Value::Null);
+    let arg = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| args.get(*key).and_then(Value::as_str))
+            .unwrap_or("<unknown>")
+            .to_string()
+    };
+
+    Some(match name {
+        "edit_file" | "search_replace" | "MultiEdit" | "edit_file_v2" => {
+            format!("edited {}", arg(&["target_file", "file_path", "relativeWorkspacePath"]))
+        }
+        "write" | "create_file" => format!("created {}", arg(&["file_path", "target_file"])),
+        "delete_file" => format!("deleted {}", arg(&["target_file", "file_path"])),
+        "read_file" | "read_file_v2" => format!("read {}", arg(&["target_file", "file_path"])),
+        "run_terminal_cmd" | "run_terminal_command_v2" => format!("ran {}", truncate(&arg(&["command"]), 50)),
+        "codebase_search" | "grep_search" | "file_search" | "grep" => {
+            format!("searched for {}", truncate(&arg(&["query", "pattern"]), 40))
+        }
+        "web_search" => "used WebSearch".to_string(),
+        "todo_write" => "updated todo list".to_string(),
+        other => format!("used {}", other),
+    })
+}
+
+fn token_usage(count: &Value) -> Option<TokenUsage> {
+    let tokens = |key: &str| count.get(key).and_then(Value::as_u64).filter(|&n| n > 0);
+    let usage = TokenUsage {
+        input_tokens: tokens("inputTokens"),
+        output_tokens: tokens("outputTokens"),
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: None,
+    };
+    (usage.input_tokens.is_some() || usage.output_tokens.is_some()).then_some(usage)
+}
+
+/// Cursor records times as milliseconds since the epoch, or as ISO 8601 strings
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(ms) => Utc.timestamp_millis_opt(ms.as_i64()?).single(),
+        Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc)),
+        _ => None,
+    }
+}
@@ -0,0 +1,174 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use crate::redact::Redactor;
+use crate::{brief, search, server};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use devlog_core::store::project_name;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+
+/// Message-list layouts `devlog export --format dataset` writes
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DatasetStyle {
+    /// `{"messages": [...]}`, with the system prompt as the first message
+    OpenAi,
+    /// `{"system": ..., "messages": [...]}`
+    Anthropic,
+}
+
+impl DatasetStyle {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "openai" => Ok(Self::OpenAi),
+            "anthropic" => Ok(Self::Anthropic),
+            _ => anyhow::bail!("Unknown dataset style: {} (expected openai or anthropic)", s),
+        }
+    }
+}
+
+/// Which sessions go into a dataset, and what each line carries
+pub struct DatasetOptions {
+    pub style: DatasetStyle,
+    /// Directory name of the project, as in stats
+    pub project: Option<String>,
+    /// Sessions carrying every one of these tags
+    pub tags: Vec<String>,
+    pub since: Option<DateTime<Utc>>,
+    /// Sessions before this time
+    pub until: Option<DateTime<Utc>>,
+    pub system: Option<String>,
+    /// Add the session's ID, project, machine, date and tags to each line
+    pub metadata: bool,
+}
+
+impl DatasetOptions {
+    fn wants(&self, output: &DevlogOutput) -> bool {
+        if let Some(ref project) = self.project {
+            if project_name(output.project_path()) != *project {
+                return false;
+            }
+        }
+        if !self.tags.iter().all(|tag| output.has_tag(tag)) {
+            return false;
+        }
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let Ok(timestamp) = DateTime::parse_from_rfc3339(&output.timestamp) else {
+            return false;
+        };
+        let timestamp = timestamp.with_timezone(&Utc);
+        self.since.map(|since| timestamp >= since).unwrap_or(true)
+            && self.until.map(|until| timestamp < until).unwrap_or(true)
+    }
+}
+
+/// Midnight UTC starting `date` (`YYYY-MM-DD`)
+pub fn parse_date(date: &str) -> Result<DateTime<Utc>> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+    Ok(day.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc())
+}
+
+/// The latest ingest of every session in a server's storage directory, or in
+/// `devlog_dir` without one, oldest first
+pub fn sessions(devlog_dir: &Path, storage: Option<&Path>) -> Result<Vec<DevlogOutput>> {
+    match storage {
+        Some(storage) => {
+            let mut sessions = server::latest_sessions(storage)?;
+            sessions.reverse();
+            Ok(sessions)
+        }
+        None => brief::latest_sessions(devlog_dir),
+    }
+}
+
+/// One session, read from its devlog file
+pub fn session(path: &Path) -> Result<Vec<DevlogOutput>> {
+    let output = search::read_devlog(path)
+        .with_context(|| format!("Failed to read devlog file: {}", path.display()))?;
+    Ok(vec![output])
+}
+
+/// Write a JSON line for each session the options select, after applying the
+/// `[redact]` rules. Sessions without a prompt and a reply are left out.
+/// Returns the number of lines written.
+pub fn write_dataset(sessions: Vec<DevlogOutput>, options: &DatasetOptions, out: &mut dyn Write) -> Result<usize> {
+    let redactor = Redactor::load()?;
+    let mut written = 0;
+    for mut output in sessions {
+        if !options.wants(&output) {
+            continue;
+        }
+        redactor.redact_output(&mut output);
+        let Some(line) = render_line(&output, options) else {
+            continue;
+        };
+        writeln!(out, "{}", line).context("Failed to write dataset")?;
+        written += 1;
+    }
+    out.flush().context("Failed to write dataset")?;
+    Ok(written)
+}
+
+fn render_line(output: &DevlogOutput, options: &DatasetOptions) -> Option<Value> {
+    let turns = turns(output);
+    if turns.is_empty() {
+        return None;
+    }
+    let mut messages: Vec<Value> = turns
+        .into_iter()
+        .map(|(role, content)| json!({ "role": role, "content": content }))
+        .collect();
+
+    let mut line = match (options.style, options.system.as_deref()) {
+        (DatasetStyle::OpenAi, Some(system)) => {
+            messages.insert(0, json!({ "role": "system", "content": system }));
+            json!({ "messages": messages })
+        }
+        (DatasetStyle::Anthropic, Some(system)) => json!({ "system": system, "messages": messages }),
+        (_, None) => json!({ "messages": messages }),
+    };
+    if options.metadata {
+        line["metadata"] = json!({
+            "session_id": output.session_id,
+            "project": project_name(output.project_path()),
+            "machine_id": output.machine_id,
+            "timestamp": output.timestamp,
+            "tags": output.tags,
+        });
+    }
+    Some(line)
+}
+
+/// The prompts and replies as turns that alternate between user and assistant,
+/// starting with the user and ending with the assistant, as training and eval
+/// formats expect. Tool summaries are left out; back-to-back messages from one
+/// side are joined.
+fn turns(output: &DevlogOutput) -> Vec<(&'static str, String)> {
+    let mut turns: Vec<(&'static str, String)> = Vec::new();
+    for entry in &output.conversation {
+        let (role, content) = match entry {
+            ConversationEntry::User { content, .. } => ("user", content),
+            ConversationEntry::Assistant { content, .. } => ("assistant", content),
+            ConversationEntry::ToolSummary { .. } => continue,
+        };
+        let content = content.trim();
+        if content.is_empty() || (turns.is_empty() && role == "assistant") {
+            continue;
+        }
+        match turns.last_mut() {
+            Some((last, text)) if *last == role => {
+                text.push_str("\n\n");
+                text.push_str(content);
+            }
+            _ => turns.push((role, content.to_string())),
+        }
+    }
+    if turns.last().map(|(role, _)| *role == "user").unwrap_or(false) {
+        turns.pop();
+    }
+    turns
+}
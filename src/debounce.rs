@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long after one ingest of a session a second of the same transcript is
+/// taken for a duplicate hook invocation
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Claim a session for ingesting, by a marker in `.devlog/ingesting/` naming
+/// the transcript as it is now. Claude Code sometimes fires SessionEnd twice
+/// in quick succession; the second invocation finds the first's marker and
+/// gets false, so it skips writing, embedding and pushing the same session
+/// again. A transcript that has changed since, or a marker older than
+/// [`WINDOW`], is claimed afresh.
+pub fn claim(devlog_dir: &Path, session_id: &str, transcript: &Path) -> Result<bool> {
+    let dir = devlog_dir.join("ingesting");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    clear_stale(&dir);
+
+    let marker = dir.join(marker_name(session_id));
+    let fingerprint = fingerprint(transcript);
+    match OpenOptions::new().write(true).create_new(true).open(&marker) {
+        Ok(mut file) => {
+            file.write_all(fingerprint.as_bytes())
+                .with_context(|| format!("Failed to write {}", marker.display()))?;
+            return Ok(true);
+        }
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+            return Err(e).with_context(|| format!("Failed to create {}", marker.display()));
+        }
+        Err(_) => {}
+    }
+
+    // An empty marker is one another invocation has only just created
+    let claimed = fs::read_to_string(&marker).unwrap_or_default();
+    if fresh(&marker) && (claimed.is_empty() || claimed == fingerprint) {
+        return Ok(false);
+    }
+    fs::write(&marker, fingerprint).with_context(|| format!("Failed to write {}", marker.display()))?;
+    Ok(true)
+}
+
+/// Give up a claim, e.g. when the ingest it covered failed
+pub fn release(devlog_dir: &Path, session_id: &str) {
+    let _ = fs::remove_file(devlog_dir.join("ingesting").join(marker_name(session_id)));
+}
+
+/// The transcript's size and modification time, which change as it grows
+fn fingerprint(transcript: &Path) -> String {
+    let Ok(metadata) = fs::metadata(transcript) else {
+        return String::from("-");
+    };
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{} {}", metadata.len(), modified)
+}
+
+/// Session IDs come from transcripts, so keep only what is safe in a file name
+fn marker_name(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn fresh(marker: &Path) -> bool {
+    fs::metadata(marker)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map(|age| age < WINDOW)
+        .unwrap_or(false)
+}
+
+/// Markers outlive their window only until the next ingest in the project
+fn clear_stale(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let stale: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| !fresh(path))
+        .collect();
+    for path in stale {
+        let _ = fs::remove_file(path);
+    }
+}
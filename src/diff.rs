@@ -0,0 +1,235 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+
+/// Above this many old×new entry pairs the differing middle of two sessions is
+/// reported as wholly removed and added instead of aligned entry by entry
+const MAX_ALIGN_CELLS: usize = 4_000_000;
+
+/// How an entry of the newer devlog relates to the older one. Indexes are
+/// positions in the respective conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Unchanged { old: usize, new: usize },
+    /// Same kind and timestamp, different content: typically a response that was
+    /// still being written when the older devlog was ingested
+    Changed { old: usize, new: usize },
+    Added { new: usize },
+    Removed { old: usize },
+}
+
+/// Align the conversations of two devlogs
+pub fn diff_sessions(old: &DevlogOutput, new: &DevlogOutput) -> Vec<Change> {
+    let a = &old.conversation;
+    let b = &new.conversation;
+
+    // Re-ingests mostly share a long prefix, so only the rest needs aligning
+    let prefix = a.iter().zip(b).take_while(|(x, y)| same_entry(x, y)).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| same_entry(x, y))
+        .count();
+
+    let mut changes: Vec<Change> = (0..prefix).map(|i| Change::Unchanged { old: i, new: i }).collect();
+    changes.extend(align(a, b, prefix, a.len() - suffix, prefix, b.len() - suffix));
+    changes.extend((0..suffix).map(|i| Change::Unchanged {
+        old: a.len() - suffix + i,
+        new: b.len() - suffix + i,
+    }));
+    changes
+}
+
+/// Longest common subsequence of `a[a_start..a_end]` and `b[b_start..b_end]`
+fn align(
+    a: &[ConversationEntry],
+    b: &[ConversationEntry],
+    a_start: usize,
+    a_end: usize,
+    b_start: usize,
+    b_end: usize,
+) -> Vec<Change> {
+    let n = a_end - a_start;
+    let m = b_end - b_start;
+    if n * m > MAX_ALIGN_CELLS {
+        let removed = (a_start..a_end).map(|old| Change::Removed { old });
+        let added = (b_start..b_end).map(|new| Change::Added { new });
+        return pair_changed(a, b, removed.chain(added).collect());
+    }
+
+    // lcs[i][j]: length of the LCS of the suffixes starting at i and j
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if same_entry(&a[a_start + i], &b[b_start + j]) {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let mut gap = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && same_entry(&a[a_start + i], &b[b_start + j]) {
+            changes.extend(pair_changed(a, b, std::mem::take(&mut gap)));
+            changes.push(Change::Unchanged { old: a_start + i, new: b_start + j });
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i * (m + 1) + j + 1] >= lcs[(i + 1) * (m + 1) + j]) {
+            gap.push(Change::Added { new: b_start + j });
+            j += 1;
+        } else {
+            gap.push(Change::Removed { old: a_start + i });
+            i += 1;
+        }
+    }
+    changes.extend(pair_changed(a, b, gap));
+    changes
+}
+
+/// Within a run of removals and additions, report an entry that was removed and
+/// re-added with the same kind and timestamp as changed
+fn pair_changed(a: &[ConversationEntry], b: &[ConversationEntry], gap: Vec<Change>) -> Vec<Change> {
+    let mut removed: Vec<usize> = gap
+        .iter()
+        .filter_map(|c| match c {
+            Change::Removed { old } => Some(*old),
+            _ => None,
+        })
+        .collect();
+
+    let mut changes = Vec::with_capacity(gap.len());
+    for change in gap {
+        match change {
+            Change::Added { new } => {
+                match removed.iter().position(|&old| same_slot(&a[old], &b[new])) {
+                    Some(pos) => {
+                        let old = removed.remove(pos);
+                        changes.push(Change::Changed { old, new });
+                    }
+                    None => changes.push(change),
+                }
+            }
+            Change::Removed { .. } => {}
+            other => changes.push(other),
+        }
+    }
+    changes.extend(removed.into_iter().map(|old| Change::Removed { old }));
+    changes
+}
+
+fn same_entry(x: &ConversationEntry, y: &ConversationEntry) -> bool {
+    match (x, y) {
+        (ConversationEntry::User { timestamp: t1, content: c1, .. }, ConversationEntry::User { timestamp: t2, content: c2, .. })
+        | (
+            ConversationEntry::Assistant { timestamp: t1, content: c1, .. },
+            ConversationEntry::Assistant { timestamp: t2, content: c2, .. },
+        ) => t1 == t2 && c1 == c2,
+        (ConversationEntry::ToolSummary { actions: a1 }, ConversationEntry::ToolSummary { actions: a2 }) => a1 == a2,
+        _ => false,
+    }
+}
+
+/// Same kind of entry at the same moment
+fn same_slot(x: &ConversationEntry, y: &ConversationEntry) -> bool {
+    match (x, y) {
+        (ConversationEntry::User { timestamp: t1, .. }, ConversationEntry::User { timestamp: t2, .. })
+        | (ConversationEntry::Assistant { timestamp: t1, .. }, ConversationEntry::Assistant { timestamp: t2, .. }) => t1 == t2,
+        (ConversationEntry::ToolSummary { .. }, ConversationEntry::ToolSummary { .. }) => true,
+        _ => false,
+    }
+}
+
+fn text(entry: &ConversationEntry) -> String {
+    match entry {
+        ConversationEntry::User { content, .. } | ConversationEntry::Assistant { content, .. } => content.clone(),
+        ConversationEntry::ToolSummary { actions } => actions.join(", "),
+    }
+}
+
+fn label(entry: &ConversationEntry) -> String {
+    let (who, timestamp) = match entry {
+        ConversationEntry::User { timestamp, .. } => ("You", timestamp),
+        ConversationEntry::Assistant { timestamp, .. } => ("Claude", timestamp),
+        ConversationEntry::ToolSummary { .. } => return "Tools".to_string(),
+    };
+    match timestamp.as_deref().and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok()) {
+        Some(dt) => format!("{} · {}", who, dt.format("%Y-%m-%d %H:%M")),
+        None => who.to_string(),
+    }
+}
+
+fn preview(entry: &ConversationEntry) -> String {
+    preview_from(entry, 0)
+}
+
+/// Up to 100 characters of an entry, starting near character `from`
+fn preview_from(entry: &ConversationEntry, from: usize) -> String {
+    let text = text(entry).replace('\n', " ");
+    let start = from.saturating_sub(20);
+    let mut preview = if start > 0 { "...".to_string() } else { String::new() };
+    preview.extend(text.chars().skip(start).take(100));
+    if text.chars().count() > start + 100 {
+        preview.push_str("...");
+    }
+    preview
+}
+
+/// Index of the first character where two entries' texts differ
+fn first_difference(x: &ConversationEntry, y: &ConversationEntry) -> usize {
+    text(x).chars().zip(text(y).chars()).take_while(|(a, b)| a == b).count()
+}
+
+pub fn print_diff(old_name: &str, old: &DevlogOutput, new_name: &str, new: &DevlogOutput, changes: &[Change]) {
+    println!("--- {} (session {}, {} entries)", old_name, old.session_id, old.conversation.len());
+    println!("+++ {} (session {}, {} entries)", new_name, new.session_id, new.conversation.len());
+    if old.session_id != new.session_id {
+        println!("Note: these are different sessions");
+    }
+    if let (Some(og), Some(ng)) = (&old.git, &new.git) {
+        if og.commit != ng.commit || og.branch != ng.branch {
+            println!("Git: {} at {} -> {} at {}", og.branch, short(&og.commit), ng.branch, short(&ng.commit));
+        }
+    }
+    println!();
+
+    let (mut unchanged, mut changed, mut added, mut removed) = (0, 0, 0, 0);
+    for change in changes {
+        match *change {
+            Change::Unchanged { .. } => unchanged += 1,
+            Change::Changed { old: o, new: n } => {
+                changed += 1;
+                let (before, after) = (&old.conversation[o], &new.conversation[n]);
+                println!("~ [{}] {}", n, label(after));
+                // Start where they diverge; a grown response shares its beginning
+                let from = first_difference(before, after);
+                println!("    - {}", preview_from(before, from));
+                println!("    + {}", preview_from(after, from));
+            }
+            Change::Added { new: n } => {
+                added += 1;
+                let entry = &new.conversation[n];
+                println!("+ [{}] {}", n, label(entry));
+                println!("    {}", preview(entry));
+            }
+            Change::Removed { old: o } => {
+                removed += 1;
+                let entry = &old.conversation[o];
+                println!("- [{}] {}", o, label(entry));
+                println!("    {}", preview(entry));
+            }
+        }
+    }
+
+    if changed + added + removed > 0 {
+        println!();
+    }
+    println!("{} unchanged, {} added, {} changed, {} removed", unchanged, added, changed, removed);
+}
+
+fn short(commit: &str) -> String {
+    commit.chars().take(8).collect()
+}
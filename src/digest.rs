@@ -0,0 +1,229 @@
+use crate::config::EmailConfig;
+use crate::notify::Summary;
+use crate::{server, stats};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Utc, Weekday};
+use devlog_core::ProjectGrouping;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Schedule {
+    Daily,
+    /// On Mondays
+    Weekly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encryption {
+    StartTls,
+    /// Encrypted from the first byte, usually on port 465
+    Tls,
+    None,
+}
+
+/// The activity digest `devlog serve` emails, as `[notifications.email]` describes it
+pub struct Digest {
+    config: EmailConfig,
+    schedule: Schedule,
+    encryption: Encryption,
+    hour: u32,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+    base_url: Option<String>,
+}
+
+impl Digest {
+    /// Check the settings up front, so a typo is reported when the server starts
+    /// rather than when the first digest is due
+    pub fn new(config: &EmailConfig, base_url: Option<String>) -> Result<Self> {
+        let schedule = match config.schedule.as_deref().unwrap_or("daily") {
+            "daily" => Schedule::Daily,
+            "weekly" => Schedule::Weekly,
+            other => anyhow::bail!("Unknown digest schedule '{}'; use daily or weekly", other),
+        };
+        let encryption = match config.tls.as_deref().unwrap_or("starttls") {
+            "starttls" => Encryption::StartTls,
+            "tls" => Encryption::Tls,
+            "none" => Encryption::None,
+            other => anyhow::bail!("Unknown SMTP tls '{}'; use starttls, tls or none", other),
+        };
+        let hour = config.hour.unwrap_or(8);
+        if hour > 23 {
+            anyhow::bail!("Digest hour must be 0-23, not {}", hour);
+        }
+        if config.to.is_empty() {
+            anyhow::bail!("No recipients for the digest");
+        }
+        let from = config
+            .from
+            .parse()
+            .with_context(|| format!("Invalid from address: {}", config.from))?;
+        let to = config
+            .to
+            .iter()
+            .map(|to| to.parse().with_context(|| format!("Invalid recipient: {}", to)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            config: config.clone(),
+            schedule,
+            encryption,
+            hour,
+            from,
+            to,
+            base_url,
+        })
+    }
+
+    /// When the first digest after `now` is due
+    pub fn next_send(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let mut next = now
+            .date_naive()
+            .and_hms_opt(self.hour, 0, 0)
+            .expect("hour checked in new")
+            .and_utc();
+        while next <= now || (self.schedule == Schedule::Weekly && next.weekday() != Weekday::Mon) {
+            next += Duration::days(1);
+        }
+        next
+    }
+
+    fn days(&self) -> u32 {
+        match self.schedule {
+            Schedule::Daily => 1,
+            Schedule::Weekly => 7,
+        }
+    }
+
+    /// Subject and text of the digest covering the period up to `now`, or None
+    /// when nothing was stored in it
+    pub fn compose(&self, storage_dir: &Path, now: DateTime<Utc>) -> Result<Option<(String, String)>> {
+        let days = self.days();
+        let projects =
+            stats::get_project_stats_grouped(storage_dir, days, ProjectGrouping::Directory, None, None, None)?;
+        if projects.is_empty() {
+            return Ok(None);
+        }
+
+        let cutoff = (now - Duration::days(days as i64)).to_rfc3339();
+        let mut sessions: Vec<Summary> = server::latest_sessions(storage_dir)?
+            .iter()
+            .filter(|output| output.timestamp >= cutoff)
+            .map(|output| Summary::new(output, self.base_url.as_deref()))
+            .collect();
+        // The busiest first: most prompts and tool actions, then most tokens
+        sessions.sort_by_key(|s| std::cmp::Reverse((s.prompts + s.actions, s.tokens)));
+        sessions.truncate(self.config.notable_sessions.unwrap_or(5));
+
+        let period = match self.schedule {
+            Schedule::Daily => "Daily",
+            Schedule::Weekly => "Weekly",
+        };
+        let subject = format!("Devlog {} digest for {}", period.to_lowercase(), now.format("%Y-%m-%d"));
+
+        let session_count: usize = projects.iter().map(|p| p.session_count).sum();
+        let prompts: usize = projects.iter().map(|p| p.prompt_count).sum();
+        let tool_calls: usize = projects.iter().map(|p| p.tool_calls).sum();
+        let tokens: u64 = projects.iter().map(|p| p.input_tokens + p.output_tokens).sum();
+        let mut body = format!(
+            "{} activity up to {}: {} sessions in {} projects, {} prompts, {} tool actions, {} tokens.\n",
+            period,
+            now.format("%Y-%m-%d %H:%M UTC"),
+            session_count,
+            projects.len(),
+            prompts,
+            tool_calls,
+            stats::format_tokens(tokens)
+        );
+
+        body.push_str("\nProjects\n\n");
+        for project in &projects {
+            body.push_str(&format!(
+                "  {:<25} {:>4} sessions {:>5} prompts {:>6} tool actions  last {}\n",
+                project.project,
+                project.session_count,
+                project.prompt_count,
+                project.tool_calls,
+                project.last_activity.get(..16).unwrap_or(&project.last_activity).replace('T', " ")
+            ));
+        }
+
+        if !sessions.is_empty() {
+            body.push_str("\nNotable sessions\n");
+            for session in &sessions {
+                body.push_str(&format!(
+                    "\n  {} · {} · {} · session {}\n  {} prompts, {} tool actions, {} tokens\n",
+                    session.project,
+                    session.machine,
+                    session.timestamp.get(..10).unwrap_or(&session.timestamp),
+                    session.session,
+                    session.prompts,
+                    session.actions,
+                    stats::format_tokens(session.tokens)
+                ));
+                if let Some(ref prompt) = session.first_prompt {
+                    body.push_str(&format!("  > {}\n", prompt));
+                }
+                if let Some(ref link) = session.link {
+                    body.push_str(&format!("  {}\n", link));
+                }
+            }
+        }
+        Ok(Some((subject, body)))
+    }
+
+    pub fn send(&self, subject: &str, body: String) -> Result<()> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message.body(body).context("Failed to build the digest email")?;
+
+        let host = &self.config.smtp_host;
+        let transport = match self.encryption {
+            Encryption::StartTls => SmtpTransport::starttls_relay(host)?,
+            Encryption::Tls => SmtpTransport::relay(host)?,
+            Encryption::None => SmtpTransport::builder_dangerous(host),
+        };
+        let default_port = if self.encryption == Encryption::Tls { 465 } else { 587 };
+        let mut transport = transport
+            .port(self.config.smtp_port.unwrap_or(default_port))
+            .timeout(Some(std::time::Duration::from_secs(30)));
+        if let Some(ref username) = self.config.username {
+            let password = match self.config.password_env {
+                Some(ref var) => std::env::var(var).with_context(|| format!("{} is not set", var))?,
+                None => String::new(),
+            };
+            transport = transport.credentials(Credentials::new(username.clone(), password));
+        }
+
+        transport
+            .build()
+            .send(&message)
+            .with_context(|| format!("Failed to send the digest through {}", host))?;
+        Ok(())
+    }
+
+    /// Compose the digest due at `now` and send it, unless nothing happened;
+    /// returns whether it was sent
+    pub fn send_due(&self, storage_dir: &Path, now: DateTime<Utc>) -> Result<bool> {
+        match self.compose(storage_dir, now)? {
+            Some((subject, body)) => {
+                self.send(&subject, body)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn recipients(&self) -> usize {
+        self.to.len()
+    }
+}
@@ -0,0 +1,311 @@
+use crate::config::{Config, EmbeddingsConfig};
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Longest text (in characters) sent to the embedding model per entry
+const MAX_TEXT_CHARS: usize = 2000;
+
+/// Number of texts sent in a single embedding request
+const BATCH_SIZE: usize = 32;
+
+/// Embedding vectors for the prompts and responses of one session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionEmbeddings {
+    pub model: String,
+    pub session_id: String,
+    pub session_file: String,
+    pub timestamp: String,
+    pub entries: Vec<EmbeddedEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmbeddedEntry {
+    pub entry_type: String,
+    pub snippet: String,
+    pub vector: Vec<f32>,
+}
+
+/// A session ranked by similarity to a query
+pub struct SemanticMatch {
+    pub session_file: String,
+    pub timestamp: String,
+    pub entry_type: String,
+    pub snippet: String,
+    pub score: f32,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// Compute and store embeddings for a freshly written devlog, if enabled in config
+pub fn index_session(output: &DevlogOutput, devlog_path: &Path) -> Result<()> {
+    // A stats-only session has no text to embed
+    if output.stats_only.is_some() {
+        return Ok(());
+    }
+    let config = Config::load()?;
+    let embeddings_config = match config.embeddings {
+        Some(ref ec) if ec.enabled => ec,
+        _ => return Ok(()),
+    };
+
+    let mut entry_types = Vec::new();
+    let mut texts = Vec::new();
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, .. } => {
+                entry_types.push("user");
+                texts.push(truncate_chars(content, MAX_TEXT_CHARS));
+            }
+            ConversationEntry::Assistant { content, .. } => {
+                entry_types.push("assistant");
+                texts.push(truncate_chars(content, MAX_TEXT_CHARS));
+            }
+            ConversationEntry::ToolSummary { .. } => {}
+        }
+    }
+
+    if texts.is_empty() {
+        return Ok(());
+    }
+
+    let vectors = embed(embeddings_config, &texts)?;
+
+    let entries = entry_types
+        .into_iter()
+        .zip(texts)
+        .zip(vectors)
+        .map(|((entry_type, text), vector)| EmbeddedEntry {
+            entry_type: entry_type.to_string(),
+            snippet: truncate_chars(&text, 200),
+            vector,
+        })
+        .collect();
+
+    let session_file = devlog_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    let index = SessionEmbeddings {
+        model: embeddings_config.model.clone(),
+        session_id: output.session_id.clone(),
+        session_file,
+        timestamp: output.timestamp.clone(),
+        entries,
+    };
+
+    let index_path = embeddings_path(devlog_path);
+    if let Some(parent) = index_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let json = serde_json::to_string(&index).context("Failed to serialize embeddings")?;
+    fs::write(&index_path, json)
+        .with_context(|| format!("Failed to write embeddings: {}", index_path.display()))?;
+
+    eprintln!("Wrote embeddings to: {}", index_path.display());
+    Ok(())
+}
+
+/// Bring a devlog's embeddings up to date after its text was rewritten, so
+/// semantic search never ranks or shows text the file no longer has. Devlogs
+/// without embeddings, such as those in a server's storage, are left alone.
+pub fn reindex_session(output: &DevlogOutput, devlog_path: &Path) -> Result<()> {
+    let index_path = embeddings_path(devlog_path);
+    if !index_path.exists() {
+        return Ok(());
+    }
+    // Removed first, so a failed embedding request cannot leave the old text behind
+    fs::remove_file(&index_path)
+        .with_context(|| format!("Failed to remove embeddings: {}", index_path.display()))?;
+    index_session(output, devlog_path)
+}
+
+/// Rank the sessions in a `.devlog` directory by similarity to a natural-language query
+pub fn semantic_search(devlog_dir: &Path, query: &str, limit: usize) -> Result<Vec<SemanticMatch>> {
+    let config = Config::load()?;
+    let embeddings_config = match config.embeddings {
+        Some(ref ec) if ec.enabled => ec,
+        _ => anyhow::bail!("Semantic search requires [embeddings] to be enabled in config"),
+    };
+
+    let query_vector = embed(embeddings_config, &[query.to_string()])?
+        .pop()
+        .context("Embedding endpoint returned no vector for the query")?;
+
+    let index_dir = devlog_dir.join("embeddings");
+    if !index_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Keep the best-scoring entry per session
+    let mut best: HashMap<String, SemanticMatch> = HashMap::new();
+
+    for file_entry in fs::read_dir(&index_dir)? {
+        let path = file_entry?.path();
+        if path.extension().map(|e| e != "json").unwrap_or(true) {
+            continue;
+        }
+
+        let index: SessionEmbeddings = match fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(index) => index,
+            None => {
+                eprintln!("Warning: Failed to read embeddings: {} (skipping)", path.display());
+                continue;
+            }
+        };
+
+        // Vectors from a different model are not comparable
+        if index.model != embeddings_config.model {
+            continue;
+        }
+
+        for entry in &index.entries {
+            let score = cosine_similarity(&query_vector, &entry.vector);
+            let is_better = best
+                .get(&index.session_file)
+                .map(|m| score > m.score)
+                .unwrap_or(true);
+
+            if is_better {
+                best.insert(
+                    index.session_file.clone(),
+                    SemanticMatch {
+                        session_file: index.session_file.clone(),
+                        timestamp: index.timestamp.clone(),
+                        entry_type: entry.entry_type.clone(),
+                        snippet: entry.snippet.clone(),
+                        score,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut matches: Vec<SemanticMatch> = best.into_values().collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit);
+
+    Ok(matches)
+}
+
+pub fn print_semantic_matches(matches: &[SemanticMatch]) {
+    if matches.is_empty() {
+        println!("No indexed sessions found");
+        return;
+    }
+
+    for m in matches {
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&m.timestamp)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| m.timestamp.clone());
+
+        println!("{:.3}  {}  {} [{}]", m.score, timestamp, m.session_file, m.entry_type);
+        println!("    {}\n", m.snippet.replace('\n', " "));
+    }
+}
+
+/// Embeddings are kept in `.devlog/embeddings/` under the devlog's file name
+fn embeddings_path(devlog_path: &Path) -> PathBuf {
+    let dir = devlog_path.parent().unwrap_or_else(|| Path::new("."));
+    dir.join("embeddings")
+        .join(devlog_path.file_name().unwrap_or_default())
+}
+
+fn embed(config: &EmbeddingsConfig, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    let api_key = config
+        .api_key_env
+        .as_ref()
+        .and_then(|var| std::env::var(var).ok());
+
+    let mut vectors = Vec::with_capacity(texts.len());
+
+    for batch in texts.chunks(BATCH_SIZE) {
+        let mut request = client.post(&config.endpoint).json(&EmbeddingRequest {
+            model: &config.model,
+            input: batch,
+        });
+        if let Some(ref key) = api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let response = request
+            .send()
+            .with_context(|| format!("Failed to reach embeddings endpoint {}", config.endpoint))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Embedding request failed with status {}: {}",
+                response.status(),
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            );
+        }
+
+        let parsed: EmbeddingResponse = response
+            .json()
+            .context("Failed to parse embeddings response")?;
+
+        if parsed.data.len() != batch.len() {
+            anyhow::bail!(
+                "Embedding endpoint returned {} vectors for {} inputs",
+                parsed.data.len(),
+                batch.len()
+            );
+        }
+
+        vectors.extend(parsed.data.into_iter().map(|d| d.embedding));
+    }
+
+    Ok(vectors)
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    s.chars().take(max_chars).collect()
+}
@@ -0,0 +1,140 @@
+use crate::output::DevlogOutput;
+use age::armor::{ArmoredReader, ArmoredWriter, Format};
+use age::secrecy::ExposeSecret;
+use age::x25519;
+use anyhow::{Context, Result};
+use devlog_core::store::project_name;
+use devlog_core::{EncryptedSession, FileStore};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The `encrypt_to` recipients of the push config
+pub fn recipients(encrypt_to: &[String]) -> Result<Vec<x25519::Recipient>> {
+    encrypt_to
+        .iter()
+        .map(|recipient| {
+            x25519::Recipient::from_str(recipient.trim())
+                .map_err(|e| anyhow::anyhow!("Invalid age recipient {}: {}", recipient, e))
+        })
+        .collect()
+}
+
+/// Encrypt a session to every recipient, keeping out only what the server
+/// needs to file it
+pub fn encrypt_session(output: &DevlogOutput, recipients: &[x25519::Recipient]) -> Result<EncryptedSession> {
+    let json = serde_json::to_vec(output).context("Failed to serialize session")?;
+    let encryptor = age::Encryptor::with_recipients(recipients.iter().map(|r| r as &dyn age::Recipient))
+        .context("Failed to encrypt session")?;
+
+    let mut ciphertext = Vec::with_capacity(json.len());
+    let mut writer = encryptor.wrap_output(ArmoredWriter::wrap_output(&mut ciphertext, Format::AsciiArmor)?)?;
+    writer.write_all(&json)?;
+    writer.finish()?.finish()?;
+
+    Ok(EncryptedSession {
+        session_id: output.session_id.clone(),
+        machine_id: output.machine_id.clone(),
+        project: project_name(output.project_path()),
+        timestamp: output.timestamp.clone(),
+        ciphertext: String::from_utf8(ciphertext).context("Armored session is not text")?,
+    })
+}
+
+/// Write a new identity file, readable only by its owner, in the format of
+/// `age-keygen`. Returns the recipient to put in `encrypt_to`.
+pub fn generate_identity(path: &Path) -> Result<String> {
+    if path.exists() {
+        anyhow::bail!("{} already exists", path.display());
+    }
+    let identity = x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+    let contents = format!(
+        "# created: {}\n# public key: {}\n{}\n",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        recipient,
+        identity.to_string().expose_secret()
+    );
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(recipient)
+}
+
+/// The private keys (`AGE-SECRET-KEY-1...`) in an identity file, as written by `age-keygen`
+fn load_identities(path: &Path) -> Result<Vec<Box<dyn age::Identity>>> {
+    let file = age::IdentityFile::from_file(path.to_string_lossy().into_owned())
+        .with_context(|| format!("Failed to read identity file {}", path.display()))?;
+    file.into_identities()
+        .with_context(|| format!("Failed to read identity file {}", path.display()))
+}
+
+fn decrypt_file(path: &Path, identities: &[Box<dyn age::Identity>]) -> Result<DevlogOutput> {
+    let ciphertext = fs::read(path)?;
+    let decryptor = age::Decryptor::new_buffered(ArmoredReader::new(&ciphertext[..]))?;
+    let mut reader = decryptor.decrypt(identities.iter().map(|i| i.as_ref()))?;
+    let mut json = Vec::new();
+    reader.read_to_end(&mut json)?;
+    serde_json::from_slice(&json).context("Decrypted file is not a devlog")
+}
+
+/// Decrypt `.age` files, and those anywhere under directories such as a server's
+/// storage directory. With `output_dir` the devlogs are written there in the
+/// server's layout, for `search --storage` and `stats`; otherwise they go to
+/// stdout, one per line.
+pub fn decrypt_files(paths: &[PathBuf], identity: &Path, output_dir: Option<&Path>) -> Result<()> {
+    let identities = load_identities(identity)?;
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            encrypted_files(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+
+    let store = output_dir.map(FileStore::new);
+    let mut decrypted = 0;
+    for file in &files {
+        let output = match decrypt_file(file, &identities) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {:#}", file.display(), e);
+                continue;
+            }
+        };
+        match store {
+            Some(ref store) => {
+                store.store(&output)?;
+            }
+            None => println!("{}", serde_json::to_string(&output)?),
+        }
+        decrypted += 1;
+    }
+
+    if let Some(dir) = output_dir {
+        eprintln!("Decrypted {} of {} sessions into {}", decrypted, files.len(), dir.display());
+    }
+    Ok(())
+}
+
+fn encrypted_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            encrypted_files(&path, files)?;
+        } else if path.extension().map(|e| e == "age").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,507 @@
+use crate::markdown;
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use anyhow::{Context, Result};
+use devlog_core::forge;
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Rgb};
+
+/// Output formats of `devlog export`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Html,
+    Pdf,
+    Org,
+    /// A note in the configured Obsidian vault, see `obsidian::write_note`
+    Obsidian,
+    /// Message-list JSONL of many sessions, see `dataset::write_dataset`
+    Dataset,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "html" => Ok(Self::Html),
+            "pdf" => Ok(Self::Pdf),
+            "org" => Ok(Self::Org),
+            "obsidian" => Ok(Self::Obsidian),
+            "dataset" => Ok(Self::Dataset),
+            _ => anyhow::bail!("Unknown export format: {} (expected html, pdf, org, obsidian or dataset)", s),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Html => "html",
+            Self::Pdf => "pdf",
+            Self::Org => "org",
+            Self::Obsidian => "md",
+            Self::Dataset => "jsonl",
+        }
+    }
+}
+
+/// Render one session in the given format
+pub fn export_session(output: &DevlogOutput, format: ExportFormat) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Html => Ok(render_html(output).into_bytes()),
+        ExportFormat::Pdf => render_pdf(output),
+        ExportFormat::Org => Ok(render_org(output).into_bytes()),
+        ExportFormat::Obsidian => anyhow::bail!("Obsidian notes are written into the vault, not to a single file"),
+        ExportFormat::Dataset => anyhow::bail!("Datasets are written for many sessions at once, not per session"),
+    }
+}
+
+/// A standalone page: styles inline, no scripts or external assets, so it can be
+/// mailed or attached as a single file
+fn render_html(output: &DevlogOutput) -> String {
+    let project = devlog_core::store::project_name(output.project_path());
+    let started = format_timestamp(&output.timestamp);
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{project} · {started}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem auto; padding: 0 1rem; max-width: 900px; background: #1a1a2e; color: #eee; line-height: 1.5; }}
+h1 {{ color: #00d9ff; margin-bottom: 0.3rem; }}
+a {{ color: #00d9ff; }}
+.meta {{ color: #888; margin-bottom: 1.5rem; }}
+.meta div {{ margin: 0.15rem 0; }}
+code {{ font-family: ui-monospace, monospace; font-size: 0.9em; }}
+pre {{ padding: 0.8rem; border-radius: 6px; overflow-x: auto; }}
+.entry {{ margin-bottom: 1rem; padding: 0.8rem 1rem; background: #16213e; border-radius: 8px; }}
+.entry-user {{ border-left: 3px solid #8f8; }}
+.entry-assistant {{ border-left: 3px solid #f8f; }}
+.entry-tool {{ border-left: 3px solid #ff8; color: #aaa; font-size: 0.9rem; }}
+.entry-header {{ font-size: 0.8rem; color: #888; margin-bottom: 0.3rem; }}
+.prompt {{ white-space: pre-wrap; word-break: break-word; }}
+.note {{ margin-bottom: 0.6rem; padding: 0.6rem 1rem; background: #2a2a1e; border-left: 3px solid #fc6; border-radius: 8px; }}
+table {{ border-collapse: collapse; }}
+th, td {{ padding: 0.3rem 0.6rem; border: 1px solid #333; }}
+</style>
+</head>
+<body>
+<h1>{project}</h1>
+<div class="meta">
+<div>{started} · {machine} · session <code>{session}</code></div>
+"#,
+        project = escape(&project),
+        started = started,
+        machine = escape(&output.machine_id),
+        session = escape(&output.session_id),
+    );
+
+    if let Some(ref git) = output.git {
+        let remote = git.remote.as_deref();
+        let link = |url: Option<String>, text: &str| match url {
+            Some(url) => format!("<a href=\"{}\">{}</a>", escape(&url), escape(text)),
+            None => escape(text),
+        };
+        let short: String = git.commit.chars().take(8).collect();
+
+        html.push_str(&format!(
+            "<div>Branch {} at <code>{}</code>{}</div>\n",
+            link(remote.and_then(|r| forge::branch_url(r, &git.branch)), &git.branch),
+            link(remote.and_then(|r| forge::commit_url(r, &git.commit)), &short),
+            if git.dirty { " (uncommitted changes)" } else { "" },
+        ));
+        if let Some(remote) = remote {
+            html.push_str(&format!("<div>{}</div>\n", link(forge::web_url(remote), remote)));
+        }
+    }
+    html.push_str("</div>\n");
+
+    if !output.notes.is_empty() {
+        html.push_str("<h2>Notes</h2>\n");
+        for note in &output.notes {
+            html.push_str(&format!(
+                "<div class=\"note\"><div class=\"entry-header\">{}</div><div class=\"prompt\">{}</div></div>\n",
+                format_timestamp(&note.timestamp),
+                escape(&note.text)
+            ));
+        }
+        html.push_str("<h2>Conversation</h2>\n");
+    }
+
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => html.push_str(&format!(
+                "<div class=\"entry entry-user\"><div class=\"entry-header\">You{}</div><div class=\"prompt\">{}</div></div>\n",
+                entry_time(timestamp.as_deref()),
+                escape(content)
+            )),
+            ConversationEntry::Assistant { content, timestamp, .. } => html.push_str(&format!(
+                "<div class=\"entry entry-assistant\"><div class=\"entry-header\">Claude{}</div>{}</div>\n",
+                entry_time(timestamp.as_deref()),
+                markdown::to_html(content)
+            )),
+            ConversationEntry::ToolSummary { actions } => html.push_str(&format!(
+                "<div class=\"entry entry-tool\">{}</div>\n",
+                escape(&actions.join(", "))
+            )),
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const BODY_SIZE: f32 = 9.0;
+const LINE_HEIGHT: f32 = 4.2;
+/// Courier glyphs are 0.6 em wide, which makes wrapping exact
+const BODY_COLUMNS: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (BODY_SIZE * 0.6 * 25.4 / 72.0)) as usize;
+
+/// Paginated A4 document with a metadata header. Uses the PDF base fonts, so
+/// nothing is embedded and the file renders the same in any viewer.
+fn render_pdf(output: &DevlogOutput) -> Result<Vec<u8>> {
+    let project = devlog_core::store::project_name(output.project_path());
+    let title = format!("{} - {}", project, format_timestamp(&output.timestamp));
+    let mut pdf = PdfWriter::new(&title)?;
+
+    pdf.heading(&project);
+    pdf.meta(&format!("Started:  {}", format_timestamp(&output.timestamp)));
+    pdf.meta(&format!("Machine:  {}", output.machine_id));
+    pdf.meta(&format!("Session:  {}", output.session_id));
+    pdf.meta(&format!("Project:  {}", output.project_dir));
+    if let Some(ref git) = output.git {
+        if let Some(ref remote) = git.remote {
+            pdf.meta(&format!("Remote:   {}", remote));
+        }
+        pdf.meta(&format!(
+            "Branch:   {} at {}{}",
+            git.branch,
+            git.commit,
+            if git.dirty { " (uncommitted changes)" } else { "" }
+        ));
+    }
+    if !output.tags.is_empty() {
+        pdf.meta(&format!("Tags:     {}", output.tags.join(", ")));
+    }
+    pdf.gap(4.0);
+
+    for note in &output.notes {
+        pdf.label(&format!("Note · {}", format_timestamp(&note.timestamp)), (0.7, 0.5, 0.0));
+        pdf.body(&note.text);
+        pdf.gap(2.5);
+    }
+
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => {
+                pdf.label(&format!("You{}", entry_time(timestamp.as_deref())), (0.2, 0.5, 0.2));
+                pdf.body(content);
+            }
+            ConversationEntry::Assistant { content, timestamp, .. } => {
+                pdf.label(&format!("Claude{}", entry_time(timestamp.as_deref())), (0.5, 0.2, 0.5));
+                pdf.body(content);
+            }
+            ConversationEntry::ToolSummary { actions } => {
+                pdf.label("Tools", (0.5, 0.5, 0.5));
+                pdf.body(&actions.join(", "));
+            }
+        }
+        pdf.gap(2.5);
+    }
+
+    pdf.finish()
+}
+
+/// Lays text out top to bottom, starting a new page whenever the current one fills
+struct PdfWriter {
+    doc: PdfDocumentReference,
+    pages: Vec<PdfLayerReference>,
+    y: f32,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    mono: IndirectFontRef,
+}
+
+impl PdfWriter {
+    fn new(title: &str) -> Result<Self> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+        let font = |font| doc.add_builtin_font(font).context("Failed to add PDF font");
+        let regular = font(BuiltinFont::Helvetica)?;
+        let bold = font(BuiltinFont::HelveticaBold)?;
+        let mono = font(BuiltinFont::Courier)?;
+        let first = doc.get_page(page).get_layer(layer);
+
+        Ok(Self {
+            doc,
+            pages: vec![first],
+            y: PAGE_HEIGHT - MARGIN,
+            regular,
+            bold,
+            mono,
+        })
+    }
+
+    fn layer(&self) -> &PdfLayerReference {
+        self.pages.last().expect("a PDF always has a first page")
+    }
+
+    /// Move down by `height`, breaking to a new page if it would not fit
+    fn advance(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.pages.push(self.doc.get_page(page).get_layer(layer));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.advance(8.0);
+        self.layer().use_text(pdf_text(text), 18.0, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.gap(3.0);
+    }
+
+    fn meta(&mut self, text: &str) {
+        self.advance(LINE_HEIGHT + 0.6);
+        let layer = self.layer();
+        layer.set_fill_color(gray(0.35));
+        layer.use_text(pdf_text(text), 9.5, Mm(MARGIN), Mm(self.y), &self.regular);
+        layer.set_fill_color(gray(0.0));
+    }
+
+    fn label(&mut self, text: &str, (r, g, b): (f32, f32, f32)) {
+        // Keep a label on the same page as at least the first line of its entry
+        self.advance(LINE_HEIGHT * 2.0 + 1.0);
+        self.y += LINE_HEIGHT;
+        let layer = self.layer();
+        layer.set_fill_color(Color::Rgb(Rgb::new(r, g, b, None)));
+        layer.use_text(pdf_text(text), 9.5, Mm(MARGIN), Mm(self.y), &self.bold);
+        layer.set_fill_color(gray(0.0));
+        self.gap(1.0);
+    }
+
+    fn body(&mut self, text: &str) {
+        for line in wrap(&pdf_text(text), BODY_COLUMNS) {
+            self.advance(LINE_HEIGHT);
+            self.layer().use_text(line, BODY_SIZE, Mm(MARGIN), Mm(self.y), &self.mono);
+        }
+    }
+
+    /// Number the pages and serialize the document
+    fn finish(self) -> Result<Vec<u8>> {
+        let total = self.pages.len();
+        for (i, layer) in self.pages.iter().enumerate() {
+            layer.set_fill_color(gray(0.5));
+            layer.use_text(
+                format!("Page {} of {}", i + 1, total),
+                8.0,
+                Mm(PAGE_WIDTH - MARGIN - 20.0),
+                Mm(MARGIN / 2.0),
+                &self.regular,
+            );
+        }
+        self.doc.save_to_bytes().context("Failed to write PDF")
+    }
+}
+
+fn gray(level: f32) -> Color {
+    Color::Rgb(Rgb::new(level, level, level, None))
+}
+
+/// Hard-wrap to `columns`, preferring to break at spaces
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for source in text.lines() {
+        let mut rest: Vec<char> = source.trim_end().chars().collect();
+        if rest.is_empty() {
+            lines.push(String::new());
+        }
+        while !rest.is_empty() {
+            if rest.len() <= columns {
+                lines.push(rest.drain(..).collect());
+                break;
+            }
+            let cut = rest[..columns]
+                .iter()
+                .rposition(|&c| c == ' ')
+                .filter(|&i| i > 0)
+                .unwrap_or(columns);
+            lines.push(rest.drain(..cut).collect());
+            while rest.first() == Some(&' ') {
+                rest.remove(0);
+            }
+        }
+    }
+    lines
+}
+
+/// The base fonts only cover WinAnsi (roughly Latin-1), and characters outside
+/// it would silently vanish; show them as `?` instead so gaps stay visible
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '\t' => vec![' '; 4],
+            '\r' => vec![],
+            '\n' | ' '..='~' | '\u{a0}'..='\u{ff}' => vec![c],
+            '‘' | '’' | '“' | '”' | '–' | '—' | '…' | '•' | '€' => vec![c],
+            _ => vec!['?'],
+        })
+        .collect()
+}
+
+/// An org outline: the session as the top heading with its metadata in a
+/// properties drawer, and one subheading per prompt and response
+fn render_org(output: &DevlogOutput) -> String {
+    let project = devlog_core::store::project_name(output.project_path());
+
+    let mut org = format!("#+TITLE: {} {}\n", project, format_timestamp(&output.timestamp));
+    org.push_str(&format!("#+DATE: {}\n\n", org_timestamp(&output.timestamp)));
+    org.push_str(&format!("* Session {}\n:PROPERTIES:\n", output.session_id));
+    org.push_str(&format!(":SESSION_ID: {}\n", output.session_id));
+    org.push_str(&format!(":MACHINE: {}\n", output.machine_id));
+    org.push_str(&format!(":PROJECT_DIR: {}\n", output.project_dir));
+    org.push_str(&format!(":STARTED: {}\n", org_timestamp(&output.timestamp)));
+    if let Some(ref git) = output.git {
+        if let Some(ref remote) = git.remote {
+            org.push_str(&format!(":GIT_REMOTE: {}\n", remote));
+        }
+        org.push_str(&format!(":GIT_BRANCH: {}\n", git.branch));
+        org.push_str(&format!(":GIT_COMMIT: {}\n", git.commit));
+        if git.dirty {
+            org.push_str(":GIT_DIRTY: t\n");
+        }
+    }
+    org.push_str(":END:\n");
+
+    if !output.notes.is_empty() {
+        org.push_str("** Notes\n");
+        for note in &output.notes {
+            org.push_str(&format!("- {} {}\n", org_timestamp(&note.timestamp), note.text.trim().replace('\n', "\n  ")));
+        }
+    }
+
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => {
+                org.push_str(&format!("** You: {}\n", org_title(content)));
+                org.push_str(&org_properties(timestamp.as_deref()));
+                org.push_str("#+begin_example\n");
+                for line in content.trim().lines() {
+                    org.push_str(&org_escape_block_line(line));
+                    org.push('\n');
+                }
+                org.push_str("#+end_example\n");
+            }
+            ConversationEntry::Assistant { content, timestamp, .. } => {
+                org.push_str("** Claude\n");
+                org.push_str(&org_properties(timestamp.as_deref()));
+                org.push_str(&markdown_to_org(content.trim()));
+            }
+            ConversationEntry::ToolSummary { actions } => {
+                org.push_str(&format!("- Tools: {}\n", actions.join(", ")));
+            }
+        }
+    }
+
+    org
+}
+
+fn org_properties(timestamp: Option<&str>) -> String {
+    match timestamp {
+        Some(ts) => format!(":PROPERTIES:\n:TIMESTAMP: {}\n:END:\n", org_timestamp(ts)),
+        None => String::new(),
+    }
+}
+
+/// Inactive org timestamp, `[2026-01-02 Fri 12:00]`
+fn org_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("[%Y-%m-%d %a %H:%M]").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// First line of a prompt, shortened to fit a heading
+fn org_title(content: &str) -> String {
+    let first = content.trim().lines().next().unwrap_or("");
+    let mut title: String = first.chars().take(60).collect();
+    if first.chars().count() > 60 {
+        title.push_str("...");
+    }
+    title
+}
+
+/// Inside blocks org reads lines starting with `*` or `#+` as structure unless
+/// they are escaped with a comma
+fn org_escape_block_line(line: &str) -> String {
+    if line.starts_with('*') || line.starts_with("#+") || line.starts_with(",*") || line.starts_with(",#+") {
+        format!(",{}", line)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Just enough markdown-to-org conversion that responses keep their structure:
+/// fenced code becomes source blocks, headings become bold lines, `*` bullets
+/// become `-` (a `* ` at the start of a line would be an org heading) and
+/// inline code and bold use org markup
+fn markdown_to_org(markdown: &str) -> String {
+    let mut org = String::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        if let Some(info) = line.trim_start().strip_prefix("```") {
+            if in_code {
+                org.push_str("#+end_src\n");
+            } else {
+                match info.split_whitespace().next() {
+                    Some(lang) => org.push_str(&format!("#+begin_src {}\n", lang)),
+                    None => org.push_str("#+begin_src\n"),
+                }
+            }
+            in_code = !in_code;
+            continue;
+        }
+
+        if in_code {
+            org.push_str(&org_escape_block_line(line));
+        } else if let Some(heading) = line.strip_prefix('#').map(|h| h.trim_start_matches('#')).filter(|h| h.starts_with(' ')) {
+            org.push_str(&format!("*{}*", heading.trim()));
+        } else if let Some(item) = line.strip_prefix("* ") {
+            org.push_str(&format!("- {}", org_inline(item)));
+        } else {
+            org.push_str(&org_inline(line));
+        }
+        org.push('\n');
+    }
+
+    if in_code {
+        org.push_str("#+end_src\n");
+    }
+    org
+}
+
+fn org_inline(line: &str) -> String {
+    line.replace("**", "*").replace('`', "~")
+}
+
+fn entry_time(timestamp: Option<&str>) -> String {
+    timestamp
+        .map(|ts| format!(" · {}", format_timestamp(ts)))
+        .unwrap_or_default()
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,199 @@
+use crate::parser::{truncate, ConversationEntry, TokenUsage};
+use crate::source::{self, ConversationBuilder, Session, SessionSource};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// One Gemini CLI session
+pub struct GeminiSession {
+    pub id: Option<String>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+/// Gemini CLI's chat recordings
+pub struct Gemini;
+
+impl SessionSource for Gemini {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn description(&self) -> &'static str {
+        "Gemini CLI chat recordings (~/.gemini/tmp/*/chats/*.json) or logs.json"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        // A recording has `messages`; logs.json entries each have a `messageId`
+        let Some(head) = source::has_extension(path, "json").then(|| source::peek(path)).flatten() else {
+            return false;
+        };
+        head.contains("\"sessionId\"") && (head.contains("\"messages\"") || head.contains("\"messageId\""))
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        let session = read_session(path)?;
+        Ok(vec![Session {
+            id: session.id.unwrap_or_else(|| source::id_from_path(path)),
+            conversation: session.conversation,
+            ..Default::default()
+        }])
+    }
+
+    fn project_dir_var(&self) -> Option<&'static str> {
+        Some("GEMINI_PROJECT_DIR")
+    }
+
+    fn most_recent(&self) -> Result<PathBuf> {
+        find_most_recent_session()
+    }
+}
+
+/// Read a Gemini CLI session: either a chat recording from
+/// `~/.gemini/tmp/<project hash>/chats/session-*.json`, or the `logs.json` next
+/// to it, which holds only the prompts of every session and is read for the
+/// last of them.
+pub fn read_session(path: &Path) -> Result<GeminiSession> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read Gemini session: {}", path.display()))?;
+    let value: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Gemini session is not valid JSON: {}", path.display()))?;
+
+    match value {
+        Value::Array(prompts) => Ok(read_prompt_log(&prompts)),
+        recording => Ok(read_recording(&recording)),
+    }
+}
+
+/// The most recently written chat recording under `~/.gemini/tmp`
+fn find_most_recent_session() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME not set")?;
+    let gemini_dir = PathBuf::from(home).join(".gemini").join("tmp");
+    if !gemini_dir.exists() {
+        anyhow::bail!("No Gemini directory found at {}", gemini_dir.display());
+    }
+
+    let mut most_recent: Option<(PathBuf, std::time::SystemTime)> = None;
+    for project in std::fs::read_dir(&gemini_dir)?.flatten() {
+        let Ok(chats) = std::fs::read_dir(project.path().join("chats")) else {
+            continue;
+        };
+        for chat in chats.flatten() {
+            let path = chat.path();
+            if path.extension().map(|e| e != "json").unwrap_or(true) {
+                continue;
+            }
+            if let Ok(modified) = chat.metadata().and_then(|m| m.modified()) {
+                if most_recent.as_ref().map(|(_, time)| modified > *time).unwrap_or(true) {
+                    most_recent = Some((path, modified));
+                }
+            }
+        }
+    }
+
+    most_recent
+        .map(|(path, _)| path)
+        .context("No Gemini chat recordings found")
+}
+
+fn read_recording(recording: &Value) -> GeminiSession {
+    let mut conversation = ConversationBuilder::new();
+    for message in recording.get("messages").and_then(Value::as_array).into_iter().flatten() {
+        let timestamp = message.get("timestamp").and_then(Value::as_str).map(String::from);
+        let content = message_text(message.get("content"));
+        let uuid = message.get("id").and_then(Value::as_str).map(String::from);
+
+        match message.get("type").and_then(Value::as_str) {
+            Some("user") => conversation.user(timestamp, content, uuid),
+            Some("gemini") => {
+                conversation.assistant(timestamp, content, message.get("tokens").map(token_usage), uuid);
+                conversation.tools(
+                    message
+                        .get("toolCalls")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(summarize_tool_call),
+                );
+            }
+            // Info, warning and error messages from the CLI itself
+            _ => {}
+        }
+    }
+
+    GeminiSession {
+        id: recording.get("sessionId").and_then(Value::as_str).map(String::from),
+        conversation: conversation.finish(),
+    }
+}
+
+fn read_prompt_log(prompts: &[Value]) -> GeminiSession {
+    let id = prompts
+        .iter()
+        .rev()
+        .find_map(|p| p.get("sessionId").and_then(Value::as_str))
+        .map(String::from);
+    let conversation = prompts
+        .iter()
+        .filter(|p| p.get("sessionId").and_then(Value::as_str) == id.as_deref())
+        .filter(|p| p.get("type").and_then(Value::as_str) == Some("user"))
+        .filter_map(|p| {
+            let content = p.get("message").and_then(Value::as_str)?.trim().to_string();
+            (!content.is_empty()).then(|| ConversationEntry::User {
+                timestamp: p.get("timestamp").and_then(Value::as_str).map(String::from),
+                content,
+                uuid: None,
+            })
+        })
+        .collect();
+    GeminiSession { id, conversation }
+}
+
+/// Content is a string, or a list of parts of which the text ones are kept
+fn message_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.trim().to_string(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Short actions in the Claude Code parser's wording, for Gemini CLI's built-in tools
+fn summarize_tool_call(call: &Value) -> Option<String> {
+    let name = call.get("name").and_then(Value::as_str)?;
+    let args = call.get("args").cloned().unwrap_or(Value::Null);
+    let arg = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| args.get(*key).and_then(Value::as_str))
+            .unwrap_or("<unknown>")
+            .to_string()
+    };
+
+    Some(match name {
+        "replace" | "edit" => format!("edited {}", arg(&["file_path", "absolute_path"])),
+        "write_file" => format!("created {}", arg(&["file_path", "absolute_path"])),
+        "read_file" => format!("read {}", arg(&["absolute_path", "file_path", "path"])),
+        "read_many_files" => "read several files".to_string(),
+        "run_shell_command" => format!("ran {}", truncate(&arg(&["command"]), 50)),
+        "glob" | "search_file_content" | "grep" => format!("searched for {}", truncate(&arg(&["pattern"]), 40)),
+        "google_web_search" => "used WebSearch".to_string(),
+        "web_fetch" => format!("fetched {}", truncate(&arg(&["url", "prompt"]), 40)),
+        "write_todos" => "updated todo list".to_string(),
+        other => format!("used {}", other),
+    })
+}
+
+fn token_usage(tokens: &Value) -> TokenUsage {
+    let count = |key: &str| tokens.get(key).and_then(Value::as_u64);
+    TokenUsage {
+        input_tokens: count("input"),
+        output_tokens: count("output"),
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: count("cached"),
+    }
+}
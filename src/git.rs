@@ -1,73 +1,246 @@
-use serde::{Deserialize, Serialize};
-use std::process::Command;
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GitInfo {
-    pub remote: Option<String>,
-    pub branch: String,
-    pub commit: String,
-}
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use git2::{ErrorCode, Repository, Sort, Status, StatusOptions};
+use std::path::{Path, PathBuf};
 
-/// Get git metadata for the current directory
-pub fn get_git_metadata() -> Option<GitInfo> {
-    // Check if we're in a git repo
-    let in_git = Command::new("git")
-        .args(["rev-parse", "--is-inside-work-tree"])
-        .output()
-        .ok()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+pub use devlog_core::{CommitInfo, GitAuthor, GitInfo, GitRemote, ParentRepo, RepoKind};
 
-    if !in_git {
-        return None;
-    }
+/// Most paths listed per kind of change; `dirty` still reflects the full status
+const MAX_LISTED_FILES: usize = 200;
+
+/// How far back through history to look for commits made during a session
+const MAX_COMMITS_WALKED: usize = 1000;
+
+/// Get git metadata for the repository containing `dir`, read with libgit2 so no
+/// `git` binary is needed. `Ok(None)` when `dir` is not in a repository or the
+/// repository has no commits yet. With the session's first and last timestamps,
+/// commits made in between are recorded too.
+pub fn get_git_metadata(
+    dir: &Path,
+    session_span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Result<Option<GitInfo>> {
+    let repo = match Repository::discover(dir) {
+        Ok(repo) => repo,
+        Err(e) if e.code() == ErrorCode::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to open git repository at {}", dir.display()))
+        }
+    };
+
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(None),
+        Err(e) => return Err(e).context("Failed to read HEAD"),
+    };
+
+    // Detached HEADs report "HEAD", matching `git rev-parse --abbrev-ref HEAD`
+    let branch = head.shorthand().unwrap_or("HEAD").to_string();
+    let commit = head
+        .peel_to_commit()
+        .context("HEAD does not point at a commit")?
+        .id()
+        .to_string();
+
+    let remotes = list_remotes(&repo)?;
+    let remote = remotes
+        .iter()
+        .find(|r| r.name == "origin")
+        .map(|r| r.url.clone());
+
+    let (modified_files, untracked_files, dirty) = if repo.is_bare() {
+        (Vec::new(), Vec::new(), false)
+    } else {
+        working_tree_status(&repo)?
+    };
 
-    let remote = get_remote();
-    let branch = get_branch()?;
-    let commit = get_commit()?;
+    let commits = match session_span {
+        Some((start, end)) => commits_between(&repo, start, end)?,
+        None => Vec::new(),
+    };
 
-    Some(GitInfo {
+    let parent_repo = find_parent_repo(&repo, dir);
+
+    Ok(Some(GitInfo {
         remote,
+        remotes,
         branch,
         commit,
-    })
+        dirty,
+        modified_files,
+        untracked_files,
+        commits,
+        parent_repo,
+        author: None,
+    }))
 }
 
-fn get_remote() -> Option<String> {
-    let output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .output()
-        .ok()?;
+/// `user.name` and `user.email` as git resolves them for `dir` (repository, then
+/// global and system config). `None` when neither is set.
+pub fn get_author(dir: &Path) -> Result<Option<GitAuthor>> {
+    let config = match Repository::discover(dir) {
+        Ok(repo) => repo.config(),
+        Err(_) => git2::Config::open_default(),
+    }
+    .context("Failed to read git config")?;
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
+    let name = config.get_string("user.name").ok();
+    let email = config.get_string("user.email").ok();
+    if name.is_none() && email.is_none() {
+        return Ok(None);
     }
+
+    Ok(Some(GitAuthor { name, email }))
 }
 
-fn get_branch() -> Option<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .output()
-        .ok()?;
+/// Top of the working tree containing `dir`, found by walking up to the nearest
+/// `.git`. `None` outside a repository or in a bare one.
+pub fn find_repo_root(dir: &Path) -> Option<PathBuf> {
+    let repo = Repository::discover(dir).ok()?;
+    let root: PathBuf = repo.workdir()?.components().collect();
+    Some(root)
+}
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
+/// The main repository of a linked worktree, or the superproject of a submodule
+fn find_parent_repo(repo: &Repository, dir: &Path) -> Option<ParentRepo> {
+    let workdir = repo.workdir()?.canonicalize().ok()?;
+    let sub_path = dir
+        .canonicalize()
+        .ok()
+        .and_then(|d| d.strip_prefix(&workdir).ok().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    if repo.is_worktree() {
+        // The common dir is the main repository's `.git`
+        let main = Repository::open(repo.commondir()).ok()?;
+        let path = main.workdir().unwrap_or_else(|| main.path());
+        return Some(ParentRepo {
+            kind: RepoKind::Worktree,
+            path: display_path(path),
+            sub_path: display_path(&sub_path),
+        });
     }
+
+    // A submodule is listed by the repository enclosing its working directory
+    let superproject = Repository::discover(workdir.parent()?).ok()?;
+    let super_workdir = superproject.workdir()?.canonicalize().ok()?;
+    let submodule_path = workdir.strip_prefix(&super_workdir).ok()?;
+    let is_submodule = superproject
+        .submodules()
+        .ok()?
+        .iter()
+        .any(|sm| sm.path() == submodule_path);
+    if !is_submodule {
+        return None;
+    }
+
+    Some(ParentRepo {
+        kind: RepoKind::Submodule,
+        path: display_path(&super_workdir),
+        sub_path: display_path(&submodule_path.join(sub_path)),
+    })
+}
+
+/// Paths as recorded in devlogs: no trailing separator, so `project_name` sees the last component
+fn display_path(path: &Path) -> String {
+    let path: PathBuf = path.components().collect();
+    path.to_string_lossy().to_string()
 }
 
-fn get_commit() -> Option<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "HEAD"])
-        .output()
-        .ok()?;
+/// Every configured remote with a readable URL, in config order
+fn list_remotes(repo: &Repository) -> Result<Vec<GitRemote>> {
+    let names = repo.remotes().context("Failed to list remotes")?;
+    Ok(names
+        .iter()
+        .filter_map(|name| name.ok().flatten())
+        .filter_map(|name| {
+            let remote = repo.find_remote(name).ok()?;
+            let url = remote.url().ok()?.to_string();
+            Some(GitRemote {
+                name: name.to_string(),
+                url,
+            })
+        })
+        .collect())
+}
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        None
+/// Commits reachable from HEAD whose commit time falls within `start..=end`
+fn commits_between(
+    repo: &Repository,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<CommitInfo>> {
+    let mut revwalk = repo.revwalk().context("Failed to walk history")?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(MAX_COMMITS_WALKED) {
+        let commit = repo.find_commit(oid?)?;
+        let Some(time) = DateTime::from_timestamp(commit.time().seconds(), 0) else {
+            continue;
+        };
+
+        // Newest first, so everything from here on predates the session
+        if time < start {
+            break;
+        }
+        if time > end {
+            continue;
+        }
+
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let files = diff
+            .deltas()
+            .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        commits.push(CommitInfo {
+            hash: commit.id().to_string(),
+            subject: commit.summary().ok().flatten().unwrap_or_default().to_string(),
+            timestamp: time.to_rfc3339(),
+            files,
+        });
+    }
+
+    commits.reverse();
+    Ok(commits)
+}
+
+/// Changed and untracked paths, and whether there were any changes at all
+fn working_tree_status(repo: &Repository) -> Result<(Vec<String>, Vec<String>, bool)> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(false)
+        .exclude_submodules(true);
+
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .context("Failed to read working tree status")?;
+
+    let mut modified = Vec::new();
+    let mut untracked = Vec::new();
+
+    for entry in statuses.iter() {
+        let Ok(path) = entry.path() else {
+            continue;
+        };
+        let list = if entry.status() == Status::WT_NEW {
+            &mut untracked
+        } else {
+            &mut modified
+        };
+        if list.len() < MAX_LISTED_FILES {
+            list.push(path.to_string());
+        }
     }
+
+    Ok((modified, untracked, !statuses.is_empty()))
 }
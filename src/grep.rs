@@ -0,0 +1,145 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use crate::search;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// ripgrep's default colors
+const PATH_COLOR: &str = "\x1b[35m";
+const NUMBER_COLOR: &str = "\x1b[32m";
+const MATCH_COLOR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+pub struct GrepOptions {
+    pub ignore_case: bool,
+    /// Treat the pattern as a literal string rather than a regex
+    pub fixed_strings: bool,
+    pub word: bool,
+    /// Only print the files with a match
+    pub files_with_matches: bool,
+    /// Only print the number of matching lines per file
+    pub count: bool,
+    pub color: bool,
+}
+
+/// Print every line of every conversation entry that matches `pattern`, as
+/// `file:entry:line` in the manner of `rg -H`, where `entry` is the index of the
+/// entry in the conversation. Directories are searched recursively for devlogs.
+/// Returns whether anything matched.
+pub fn grep(pattern: &str, paths: &[PathBuf], options: &GrepOptions) -> Result<bool> {
+    let pattern = if options.fixed_strings {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    let pattern = if options.word {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern
+    };
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .context("Invalid pattern")?;
+
+    let files = search::files_under(paths)?;
+    match grep_files(&files, paths, &regex, options) {
+        // Output piped into `head` and the like
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(true),
+        result => Ok(result?),
+    }
+}
+
+fn grep_files(files: &[PathBuf], paths: &[PathBuf], regex: &Regex, options: &GrepOptions) -> io::Result<bool> {
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut matched = false;
+    for file in files {
+        let output = match search::read_devlog(file) {
+            Ok(output) => output,
+            // Only complain about files named on the command line
+            Err(_) if !paths.contains(file) => continue,
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let count = print_matches(&mut out, file, &output, regex, options)?;
+        matched |= count > 0;
+        if options.count && count > 0 {
+            writeln!(out, "{}:{}", path_label(file, options), count)?;
+        } else if options.files_with_matches && count > 0 {
+            writeln!(out, "{}", path_label(file, options))?;
+        }
+    }
+    out.flush()?;
+
+    Ok(matched)
+}
+
+/// Print the matching lines of one devlog, unless only counts or file names are
+/// wanted. Returns the number of matching lines.
+fn print_matches(
+    out: &mut impl Write,
+    file: &Path,
+    output: &DevlogOutput,
+    regex: &Regex,
+    options: &GrepOptions,
+) -> io::Result<usize> {
+    let quiet = options.count || options.files_with_matches;
+    let mut count = 0;
+    for (index, entry) in output.conversation.iter().enumerate() {
+        let text = match entry {
+            ConversationEntry::User { content, .. } | ConversationEntry::Assistant { content, .. } => content.clone(),
+            ConversationEntry::ToolSummary { actions } => actions.join(", "),
+        };
+        for line in text.lines().filter(|line| regex.is_match(line)) {
+            count += 1;
+            if options.files_with_matches {
+                return Ok(count);
+            }
+            if quiet {
+                continue;
+            }
+            if options.color {
+                writeln!(
+                    out,
+                    "{}:{}{}{}:{}",
+                    path_label(file, options),
+                    NUMBER_COLOR,
+                    index,
+                    RESET,
+                    highlight(line, regex)
+                )?;
+            } else {
+                writeln!(out, "{}:{}:{}", file.display(), index, line)?;
+            }
+        }
+    }
+    Ok(count)
+}
+
+fn path_label(file: &Path, options: &GrepOptions) -> String {
+    if options.color {
+        format!("{}{}{}", PATH_COLOR, file.display(), RESET)
+    } else {
+        file.display().to_string()
+    }
+}
+
+fn highlight(line: &str, regex: &Regex) -> String {
+    let mut highlighted = String::with_capacity(line.len());
+    let mut last = 0;
+    for m in regex.find_iter(line) {
+        highlighted.push_str(&line[last..m.start()]);
+        highlighted.push_str(MATCH_COLOR);
+        highlighted.push_str(m.as_str());
+        highlighted.push_str(RESET);
+        last = m.end();
+    }
+    highlighted.push_str(&line[last..]);
+    highlighted
+}
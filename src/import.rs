@@ -0,0 +1,81 @@
+use crate::output;
+use crate::source;
+use crate::{config, embeddings, obsidian, push, redact, skip, stats_only, tickets, truncate};
+use anyhow::Result;
+use std::path::Path;
+
+/// Turn another assistant's history into devlogs in the `.devlog` of the
+/// project each session ran in, tagged with the source's name, then push them
+/// if pushing is enabled. Sessions that belong to no project (ChatGPT's) are
+/// filed under `project`, or the source's name, in the current directory's
+/// `.devlog`. Importing the same history again rewrites the same files.
+#[tracing::instrument(skip_all, err, fields(source = source))]
+pub fn import(source: &str, path: &Path, project: Option<&str>) -> Result<()> {
+    let source = source::find(source, Some(path))?;
+    eprintln!("Importing {} sessions from: {}", source.name(), path.display());
+    let sessions: Vec<source::Session> = source
+        .parse(path)?
+        .into_iter()
+        .filter(|s| !s.conversation.is_empty())
+        .collect();
+    if sessions.is_empty() {
+        eprintln!("No sessions found in {}", path.display());
+        return Ok(());
+    }
+
+    let include_author = config::Config::load()
+        .map(|c| c.git.unwrap_or_default().include_author)
+        .unwrap_or(false);
+    let project = project.unwrap_or(source.name());
+    let skip_rules = skip::SkipRules::load()?;
+    let redactor = redact::Redactor::load()?;
+    let truncator = truncate::Truncator::load()?;
+    let stats_only = stats_only::StatsOnly::load()?;
+    let linker = tickets::TicketLinker::load()?;
+    let mut written = Vec::new();
+    for session in sessions {
+        // Dated by when the session happened, so the file is the same on every import
+        let Some(started) = session.start() else {
+            eprintln!("Warning: Skipping session {}: no timestamps", session.id);
+            continue;
+        };
+        let (mut output, output_dir) = source::normalize(session, source.as_ref(), started, project, include_author)?;
+        if let Some(marker) = skip::opted_out(Path::new(&output.project_dir))? {
+            eprintln!("Not importing session {} into {}: {}", output.session_id, output.project_dir, marker);
+            continue;
+        }
+
+        // Keep tags and notes added to earlier imports of this session
+        source::carry_over(&mut output, &output_dir);
+
+        if skip_rules.hold_back(&output)? {
+            continue;
+        }
+        let redactions = redactor.redact_output(&mut output);
+        if redactions > 0 {
+            eprintln!("Redacted {} matches", redactions);
+        }
+        // Tickets are linked from the whole prompt, before it is cut short
+        linker.link(&mut output);
+        stats_only.apply(&mut output)?;
+        let truncated = truncator.truncate_output(&mut output);
+        if truncated > 0 {
+            eprintln!("Truncated {} oversized entries", truncated);
+        }
+
+        let output_path = output::write_output_to(&output_dir, &output)?;
+        if let Err(e) = embeddings::index_session(&output, &output_path) {
+            eprintln!("Warning: Failed to compute embeddings: {}", e);
+        }
+        if let Err(e) = obsidian::export_session(&output) {
+            eprintln!("Warning: Failed to write Obsidian note: {}", e);
+        }
+        written.push(output);
+    }
+    eprintln!("Imported {} sessions", written.len());
+
+    if let Err(e) = push::push_batch(&written) {
+        eprintln!("Warning: Failed to push sessions: {}", e);
+    }
+    Ok(())
+}
@@ -6,10 +6,49 @@ mod push;
 mod server;
 mod stats;
 mod search;
+mod embeddings;
+mod export;
+mod dataset;
+mod markdown;
+mod diff;
+mod brief;
+mod show;
+mod obsidian;
+mod prune;
+mod merge;
+mod browse;
+mod tui;
+mod grep;
+mod redact;
+mod skip;
+mod truncate;
+mod stats_only;
+mod secrets;
+mod pii;
+mod receipts;
+mod encrypt;
+mod aider;
+mod chatgpt;
+mod codex;
+mod cursor;
+mod gemini;
+mod import;
+mod mcp;
+mod notify;
+mod digest;
+mod source;
+mod sync;
+mod tickets;
+mod telemetry;
+mod access;
+mod audit;
+mod session_cache;
+mod debounce;
+mod calendar;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "devlog")]
@@ -21,15 +60,320 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Ingest a Claude Code session JSONL file
+    /// Ingest a Claude Code session JSONL file, or another assistant's session with --source
     Ingest {
-        /// Path to the session JSONL file (optional - will try stdin or find most recent)
+        /// Path to the session file (optional - will try stdin or find most recent)
         path: Option<PathBuf>,
+        /// Which assistant wrote the session
+        #[arg(long, default_value = "claude", value_parser = source::value_parser())]
+        source: String,
+    },
+    /// Import sessions from another AI coding assistant's history
+    Import {
+        /// Where the sessions come from
+        #[arg(long, default_value = source::AUTO, value_parser = source::value_parser())]
+        source: String,
+        /// The history file, or the directory containing it
+        path: PathBuf,
+        /// Project to file sessions under when they have none, as with ChatGPT's
+        /// (default: the source's name)
+        #[arg(long)]
+        project: Option<String>,
     },
     /// Push the most recent session to the central endpoint
     Push {
         /// Path to the devlog JSON file to push (optional - will find most recent)
         path: Option<PathBuf>,
+        /// Push every session in .devlog via the batch endpoint
+        #[arg(long, conflicts_with = "path")]
+        all: bool,
+    },
+    /// Search stored sessions
+    Search {
+        /// Text to search for (or a natural-language question with --semantic)
+        #[arg(required_unless_present_any = ["saved", "ticket"])]
+        query: Option<String>,
+        /// Run a named query from the [searches] section of the config
+        #[arg(long, conflicts_with = "query")]
+        saved: Option<String>,
+        /// What to search: prompts, conversations, or all
+        #[arg(long)]
+        scope: Option<String>,
+        /// Only search sessions from the last N days
+        #[arg(short, long)]
+        days: Option<u32>,
+        /// Maximum number of results
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Match upper/lower case exactly
+        #[arg(long)]
+        case_sensitive: bool,
+        /// Only search sessions with this tag
+        #[arg(short, long)]
+        tag: Option<String>,
+        /// Only search sessions whose prompts mention this ticket, e.g. PROJ-123 or #456
+        #[arg(long)]
+        ticket: Option<String>,
+        /// Only match the query as a whole word
+        #[arg(long)]
+        word: bool,
+        /// Search a server storage directory instead of the local .devlog
+        #[arg(short, long)]
+        storage: Option<PathBuf>,
+        /// Continue from the cursor printed after a previous page
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Also query the server's search API and merge the results with the local ones
+        #[arg(long, conflicts_with = "storage")]
+        everywhere: bool,
+        /// Rank sessions by embedding similarity instead of matching text
+        #[arg(long)]
+        semantic: bool,
+        /// Write the results as a markdown digest to this file instead of printing them
+        #[arg(long, value_name = "FILE", conflicts_with = "semantic")]
+        export: Option<PathBuf>,
+    },
+    /// Print conversation lines matching a regex as file:entry:line, like ripgrep
+    Grep {
+        pattern: String,
+        /// Devlog files or directories to search (default: .devlog)
+        paths: Vec<PathBuf>,
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Treat the pattern as a literal string
+        #[arg(short = 'F', long)]
+        fixed_strings: bool,
+        /// Only match whole words
+        #[arg(short, long)]
+        word_regexp: bool,
+        /// Only print the paths of files with matches
+        #[arg(short = 'l', long, conflicts_with = "count")]
+        files_with_matches: bool,
+        /// Only print the number of matching lines per file
+        #[arg(short, long)]
+        count: bool,
+        /// When to color the output: auto, always or never
+        #[arg(long, default_value = "auto")]
+        color: String,
+    },
+    /// Check devlog files against the schema, e.g. before a bulk import or after hand edits
+    Validate {
+        /// Devlog files or directories to check (default: .devlog)
+        paths: Vec<PathBuf>,
+        /// Also list files without problems
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Export a session for reading outside devlog
+    Export {
+        /// Path to the devlog JSON file to export (optional - will find most recent)
+        path: Option<PathBuf>,
+        /// Output format: html, pdf, org, obsidian (a note in the vault from [obsidian] config),
+        /// or dataset (message-list JSONL of every session, for fine-tuning and evals)
+        #[arg(short, long, default_value = "html")]
+        format: String,
+        /// File to write (default: the devlog's name with the format's extension);
+        /// a directory with --all, the vault with obsidian, stdout with dataset
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Export every session in .devlog
+        #[arg(long, conflicts_with = "path")]
+        all: bool,
+        /// Dataset layout: openai or anthropic
+        #[arg(long, default_value = "openai")]
+        style: String,
+        /// Draw the dataset from a server storage directory instead of the local .devlog
+        #[arg(long, conflicts_with = "path")]
+        storage: Option<PathBuf>,
+        /// Only sessions of this project (directory name) in the dataset
+        #[arg(long)]
+        project: Option<String>,
+        /// Only sessions carrying this tag in the dataset (repeat to require several)
+        #[arg(long)]
+        tag: Vec<String>,
+        /// Only sessions from the last N days in the dataset
+        #[arg(long, conflicts_with = "since")]
+        days: Option<u32>,
+        /// Only sessions from this date (YYYY-MM-DD) on in the dataset
+        #[arg(long)]
+        since: Option<String>,
+        /// Only sessions before this date (YYYY-MM-DD) in the dataset
+        #[arg(long)]
+        until: Option<String>,
+        /// System prompt for every conversation in the dataset
+        #[arg(long)]
+        system: Option<String>,
+        /// Add each session's ID, project, machine, date and tags to its dataset line
+        #[arg(long)]
+        metadata: bool,
+    },
+    /// Add tags to a session, e.g. `bug-hunt` or `refactor`
+    Tag {
+        /// Session ID (or a prefix of one) in .devlog, or a path to a devlog JSON file
+        session: String,
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Attach a note to a session, e.g. how it turned out
+    Note {
+        /// Session ID (or a prefix of one) in .devlog, or a path to a devlog JSON file
+        session: String,
+        /// The note's text
+        text: String,
+    },
+    /// Print a session: metadata, notes and conversation
+    Show {
+        /// Session ID (or a prefix of one) in .devlog, or a path to a devlog JSON file (optional - will find most recent)
+        session: Option<String>,
+    },
+    /// Compare two devlogs, e.g. two ingests of the same session
+    Diff {
+        /// The older devlog JSON file
+        old: PathBuf,
+        /// The newer devlog JSON file
+        new: PathBuf,
+    },
+    /// Summarize recent sessions of this project as a brief for a new Claude session
+    ResumeContext {
+        /// Number of sessions to include
+        #[arg(short = 'n', long, default_value = "3")]
+        sessions: usize,
+        /// Write the brief to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve this project's sessions to an assistant as an MCP server on stdin/stdout
+    Mcp,
+    /// Browse sessions in an interactive terminal UI
+    Tui {
+        /// Browse a server storage directory instead of the local .devlog
+        #[arg(short, long)]
+        storage: Option<PathBuf>,
+    },
+    /// View a session in the browser, starting a local server if none is running
+    Open {
+        /// Session ID (or a prefix of one) or path to a devlog file; defaults to the most recent
+        session: Option<String>,
+        /// Port of the local server
+        #[arg(short, long, default_value = "8090")]
+        port: u16,
+        /// Storage directory for a server started by this command (default: ~/.devlog/store)
+        #[arg(short, long)]
+        storage: Option<PathBuf>,
+    },
+    /// Combine sessions Claude Code split across several transcripts into one devlog
+    Merge {
+        /// Session IDs (or prefixes), or paths to devlog files
+        #[arg(required = true, num_args = 2..)]
+        sessions: Vec<String>,
+        /// Write the merged devlog here instead of to .devlog (and don't push it)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Apply the [redact] rules to devlogs captured before the rules existed
+    Anonymize {
+        /// Devlog files to rewrite (default: every devlog in .devlog)
+        paths: Vec<PathBuf>,
+        /// Rewrite a server storage directory instead
+        #[arg(short, long, conflicts_with = "paths")]
+        storage: Option<PathBuf>,
+        /// Only report what would change
+        #[arg(long)]
+        dry_run: bool,
+        /// Push the corrected sessions to the server in the push config
+        #[arg(long, conflicts_with_all = ["storage", "dry_run"])]
+        push: bool,
+    },
+    /// Report devlogs that contain likely secrets: AWS keys, GitHub tokens, private keys and random-looking tokens
+    ScanSecrets {
+        /// Devlog files to scan (default: every devlog in .devlog)
+        paths: Vec<PathBuf>,
+        /// Scan a server storage directory instead
+        #[arg(short, long, conflicts_with = "paths")]
+        storage: Option<PathBuf>,
+        /// Replace the secrets found with [REDACTED:<kind>], rewriting the devlogs
+        #[arg(long)]
+        redact: bool,
+        /// Push the redacted sessions to the server in the push config
+        #[arg(long, requires = "redact", conflicts_with = "storage")]
+        push: bool,
+    },
+    /// Report the personal data in devlogs, per project: emails, phone numbers, names and [pii] patterns
+    PiiReport {
+        /// Devlog files to scan (default: every devlog in .devlog)
+        paths: Vec<PathBuf>,
+        /// Scan a server storage directory instead
+        #[arg(short, long, conflicts_with = "paths")]
+        storage: Option<PathBuf>,
+        /// List each distinct value found, not just how many
+        #[arg(long)]
+        values: bool,
+    },
+    /// Check devlogs against the receipts the server signed when it stored them
+    VerifyReceipts {
+        /// Devlog files to check (default: every devlog in .devlog)
+        paths: Vec<PathBuf>,
+    },
+    /// Create an age key pair for encrypt_to, printing the public key
+    Keygen {
+        /// Identity file to write the private key to
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Decrypt sessions pushed with encrypt_to, as the server stored them
+    Decrypt {
+        /// .age files, or directories such as a server's storage directory to find them in
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// age identity file holding the private key, as written by age-keygen
+        #[arg(short, long)]
+        identity: PathBuf,
+        /// Write the devlogs into this directory in the server's layout, instead of to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Upload sessions the server lacks and download this repository's sessions missing from .devlog
+    Sync {
+        /// List what would be transferred without transferring it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Delete old devlogs from .devlog, and optionally from the server
+    #[command(group(clap::ArgGroup::new("limit").args(["days", "max_mb"]).required(true).multiple(true)))]
+    Prune {
+        /// Delete devlogs older than this many days
+        #[arg(short, long)]
+        days: Option<u32>,
+        /// Delete the oldest devlogs until .devlog fits in this many megabytes
+        #[arg(long)]
+        max_mb: Option<u64>,
+        /// Only list what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+        /// Also delete pruned sessions from the server in the push config
+        #[arg(long)]
+        remote: bool,
+    },
+    /// Move sessions stored before the YYYY/MM layout into their month's directory
+    ShardStorage {
+        /// Server storage directory to rearrange
+        #[arg(short, long, default_value = "/store/devolver")]
+        storage: PathBuf,
+    },
+    /// Roll the sessions of each day in a server storage directory into one
+    /// bundle file per project and day
+    BundleStorage {
+        /// Server storage directory to bundle
+        #[arg(short, long, default_value = "/store/devolver")]
+        storage: PathBuf,
+        /// Leave the days of the last N days as they are
+        #[arg(short, long, default_value = "2")]
+        days: u32,
     },
     /// Run the devlog receiver server
     Serve {
@@ -39,23 +383,316 @@ enum Commands {
         /// Directory to store received devlogs
         #[arg(short, long, default_value = "/store/devolver")]
         storage: PathBuf,
+        /// Append-only log of session reads and admin actions (default: audit.jsonl in the storage directory)
+        #[arg(long)]
+        audit_log: Option<PathBuf>,
+        /// Megabytes of recently read sessions to keep parsed in memory
+        #[arg(long, default_value_t = session_cache::DEFAULT_CACHE_MB)]
+        cache_mb: u64,
+        /// Requests whose file IO may run at once; more wait for a turn
+        #[arg(long, default_value_t = server::DEFAULT_IO_LIMIT)]
+        io_limit: usize,
+        /// Ingest requests in progress or waiting; more are answered with 429
+        #[arg(long, default_value_t = server::DEFAULT_INGEST_QUEUE)]
+        ingest_queue: usize,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let _telemetry = telemetry::init();
 
     match cli.command {
-        Commands::Ingest { path } => {
-            ingest_session(path)?;
+        Commands::Ingest { path, source } => {
+            ingest_session(path, &source)?;
+        }
+        Commands::Import { source, path, project } => {
+            import::import(&source, &path, project.as_deref())?;
+        }
+        Commands::Push { path, all } => {
+            if all {
+                push_all()?;
+            } else {
+                push_session(path)?;
+            }
+        }
+        Commands::Search {
+            query,
+            saved,
+            scope,
+            days,
+            limit,
+            case_sensitive,
+            tag,
+            ticket,
+            word,
+            storage,
+            cursor,
+            everywhere,
+            semantic,
+            export,
+        } => {
+            // Saved searches supply defaults that explicit flags override
+            let (query, scope, days) = match saved {
+                Some(name) => {
+                    let saved = find_saved_search(&name)?;
+                    (saved.query, scope.or(saved.scope), days.or(saved.days))
+                }
+                None => (query.unwrap_or_default(), scope, days),
+            };
+
+            let options = search::SearchOptions {
+                scope: scope
+                    .as_deref()
+                    .map(search::SearchScope::from_str)
+                    .unwrap_or_default(),
+                days,
+                limit,
+                cursor,
+                case_sensitive,
+                whole_word: word,
+                tag,
+                ticket,
+            };
+            if everywhere {
+                search_everywhere(&query, &options, export)?;
+            } else {
+                search_sessions(&query, &options, storage, semantic, export)?;
+            }
+        }
+        Commands::Grep { pattern, paths, ignore_case, fixed_strings, word_regexp, files_with_matches, count, color } => {
+            let paths = if paths.is_empty() { vec![PathBuf::from(".devlog")] } else { paths };
+            let color = match color.as_str() {
+                "always" => true,
+                "never" => false,
+                "auto" => atty::is(atty::Stream::Stdout),
+                other => anyhow::bail!("Unknown color mode '{}': expected auto, always or never", other),
+            };
+            let options = grep::GrepOptions {
+                ignore_case,
+                fixed_strings,
+                word: word_regexp,
+                files_with_matches,
+                count,
+                color,
+            };
+            // Like grep, exit with 1 when nothing matched
+            if !grep::grep(&pattern, &paths, &options)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate { paths, verbose } => {
+            let paths = if paths.is_empty() { vec![PathBuf::from(".devlog")] } else { paths };
+            if !validate_devlogs(&paths, verbose)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Export {
+            path,
+            format,
+            output,
+            all,
+            style,
+            storage,
+            project,
+            tag,
+            days,
+            since,
+            until,
+            system,
+            metadata,
+        } => {
+            let format = export::ExportFormat::parse(&format)?;
+            if format == export::ExportFormat::Dataset {
+                let since = match (days, since) {
+                    (Some(days), _) => Some(chrono::Utc::now() - chrono::Duration::days(days as i64)),
+                    (None, Some(since)) => Some(dataset::parse_date(&since)?),
+                    (None, None) => None,
+                };
+                let options = dataset::DatasetOptions {
+                    style: dataset::DatasetStyle::parse(&style)?,
+                    project,
+                    tags: tag,
+                    since,
+                    until: until.as_deref().map(dataset::parse_date).transpose()?,
+                    system,
+                    metadata,
+                };
+                export_dataset(path, storage, output, &options)?;
+            } else if all {
+                export_all(format, output)?;
+            } else {
+                export_session(path, format, output)?;
+            }
+        }
+        Commands::Tag { session, tags, remove } => {
+            tag_session(&session, &tags, remove)?;
+        }
+        Commands::Note { session, text } => {
+            note_session(&session, &text)?;
+        }
+        Commands::Show { session } => {
+            show_session(session.as_deref())?;
+        }
+        Commands::Diff { old, new } => {
+            let old_output = search::read_devlog(&old)
+                .with_context(|| format!("Failed to read devlog file: {}", old.display()))?;
+            let new_output = search::read_devlog(&new)
+                .with_context(|| format!("Failed to read devlog file: {}", new.display()))?;
+            let changes = diff::diff_sessions(&old_output, &new_output);
+            diff::print_diff(
+                &old.display().to_string(),
+                &old_output,
+                &new.display().to_string(),
+                &new_output,
+                &changes,
+            );
+        }
+        Commands::ResumeContext { sessions, output } => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            let brief = brief::build_brief(&devlog_dir, sessions)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, brief)
+                        .with_context(|| format!("Failed to write brief: {}", path.display()))?;
+                    eprintln!("Wrote brief to: {}", path.display());
+                }
+                None => print!("{}", brief),
+            }
+        }
+        Commands::Mcp => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            mcp::serve(&devlog_dir)?;
+        }
+        Commands::Tui { storage } => {
+            let files = match storage {
+                Some(storage) => search::storage_files(&storage)?,
+                None => {
+                    let devlog_dir = std::env::current_dir()
+                        .context("Failed to get current directory")?
+                        .join(".devlog");
+                    search::local_files(&devlog_dir)?
+                }
+            };
+            tui::run(files)?;
+        }
+        Commands::Open { session, port, storage } => {
+            let storage = match storage {
+                Some(storage) => storage,
+                None => config::Config::home_dir()?.join("store"),
+            };
+            let devlog_path = session_devlog(session.as_deref())?;
+            let output = search::read_devlog(&devlog_path)
+                .with_context(|| format!("Failed to read devlog file: {}", devlog_path.display()))?;
+            browse::open_session(&output, port, &storage)?;
         }
-        Commands::Push { path } => {
-            push_session(path)?;
+        Commands::Merge { sessions, output } => {
+            merge_sessions(&sessions, output.as_deref())?;
         }
-        Commands::Serve { port, storage } => {
+        Commands::Anonymize { paths, storage, dry_run, push } => {
+            let redactor = redact::Redactor::load()?;
+            if redactor.is_empty() {
+                anyhow::bail!("No [redact] rules in the config");
+            }
+            let files = devlog_files(paths, storage)?;
+            let changed = redact::anonymize_files(&redactor, &files, dry_run)?;
+            if push && !changed.is_empty() {
+                push::push_batch(&changed)?;
+            }
+        }
+        Commands::ScanSecrets { paths, storage, redact, push } => {
+            let files = devlog_files(paths, storage)?;
+            let flagged = secrets::scan_files(&secrets::Scanner::new()?, &files, redact)?;
+            if push && !flagged.is_empty() {
+                push::push_batch(&flagged)?;
+            }
+            // Fail, as validate does, so hooks and CI can stop on a leak
+            if !redact && !flagged.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::PiiReport { paths, storage, values } => {
+            let files = devlog_files(paths, storage)?;
+            pii::report_files(&pii::Detector::load()?, &files, values)?;
+        }
+        Commands::VerifyReceipts { paths } => {
+            let files = devlog_files(paths, None)?;
+            let pinned = config::Config::load()?.push.and_then(|p| p.receipt_key);
+            if !receipts::verify_files(&files, pinned.as_deref())? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Keygen { output } => {
+            let recipient = encrypt::generate_identity(&output)?;
+            eprintln!("Wrote the private key to {}; keep it off the server", output.display());
+            println!("{}", recipient);
+        }
+        Commands::Decrypt { paths, identity, output } => {
+            encrypt::decrypt_files(&paths, &identity, output.as_deref())?;
+        }
+        Commands::Sync { dry_run } => {
+            let project_dir = std::env::current_dir().context("Failed to get current directory")?;
+            sync::sync(&project_dir.join(".devlog"), &project_dir, dry_run)?;
+        }
+        Commands::Prune { days, max_mb, dry_run, remote } => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            let policy = devlog_core::RetentionPolicy {
+                max_age_days: days,
+                max_total_mb: max_mb,
+                ..Default::default()
+            };
+            prune::prune(&devlog_dir, &policy, dry_run, remote)?;
+        }
+        Commands::ShardStorage { storage } => {
+            if !storage.is_dir() {
+                anyhow::bail!("Storage directory does not exist: {}", storage.display());
+            }
+            let moved = devlog_core::FileStore::new(&storage).shard_existing()?;
+            println!("Moved {} sessions into YYYY/MM directories under {}", moved, storage.display());
+        }
+        Commands::BundleStorage { storage, days } => {
+            if !storage.is_dir() {
+                anyhow::bail!("Storage directory does not exist: {}", storage.display());
+            }
+            let policy = devlog_core::BundlePolicy {
+                after_days: days,
+                ..Default::default()
+            };
+            let report = devlog_core::FileStore::new(&storage).bundle_days(policy.last_day())?;
+            println!("{} under {}", report, storage.display());
+        }
+        Commands::Serve {
+            port,
+            storage,
+            audit_log,
+            cache_mb,
+            io_limit,
+            ingest_queue,
+        } => {
+            let devlog_config = config::Config::load()?;
+            let audit_log = audit_log.unwrap_or_else(|| storage.join("audit.jsonl"));
             let config = server::ServerConfig {
                 storage_dir: storage,
                 port,
+                saved_searches: devlog_config.searches,
+                retention: devlog_config.retention,
+                bundles: devlog_config.bundles,
+                notifications: devlog_config.notifications,
+                admin_token: std::env::var("DEVLOG_ADMIN_TOKEN").ok().filter(|t| !t.trim().is_empty()),
+                users: access::load_users(&devlog_config.users)?,
+                web_login: access::WebLogin::from_env(),
+                ingest_token: std::env::var("DEVLOG_INGEST_TOKEN").ok().filter(|t| !t.trim().is_empty()),
+                audit: std::sync::Arc::new(audit::AuditLog::new(audit_log)),
+                metrics: Default::default(),
+                sessions: std::sync::Arc::new(session_cache::SessionCache::new(cache_mb * 1024 * 1024)),
+                io_limit: std::sync::Arc::new(tokio::sync::Semaphore::new(io_limit.max(1))),
+                ingest_queue: std::sync::Arc::new(tokio::sync::Semaphore::new(ingest_queue.max(1))),
             };
             tokio::runtime::Runtime::new()
                 .context("Failed to create async runtime")?
@@ -66,60 +703,100 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn ingest_session(path: Option<PathBuf>) -> Result<()> {
+#[tracing::instrument(skip_all, err, fields(source = source))]
+fn ingest_session(path: Option<PathBuf>, source: &str) -> Result<()> {
+    let source = source::find(source, path.as_deref())?;
+
     // Determine the session file path
     let session_path = match path {
         Some(p) => p,
-        None => find_session_from_stdin_or_recent()?,
+        None => find_session_from_stdin_or_recent(source.as_ref())?,
     };
 
     eprintln!("Ingesting session from: {}", session_path.display());
 
-    // Parse the JSONL file
-    let entries = parser::parse_session_file(&session_path)
-        .with_context(|| format!("Failed to parse session file: {}", session_path.display()))?;
+    // Attribute the session to a person, if enabled under [git]
+    let include_author = config::Config::load()
+        .map(|c| c.git.unwrap_or_default().include_author)
+        .unwrap_or(false);
 
-    // Filter and transform to conversation
-    let conversation = parser::filter_to_conversation(entries);
+    for session in source.parse(&session_path)? {
+        let (mut output, output_dir) =
+            source::normalize(session, source.as_ref(), chrono::Utc::now(), source.name(), include_author)?;
 
-    // Get git metadata
-    let git_info = git::get_git_metadata();
+        // Projects marked with .devlogignore are never captured, nor written to
+        if let Some(marker) = skip::opted_out(Path::new(&output.project_dir))? {
+            eprintln!("Not capturing session in {}: {}", output.project_dir, marker);
+            continue;
+        }
 
-    // Get project directory
-    let project_dir = std::env::var("CLAUDE_PROJECT_DIR")
-        .unwrap_or_else(|_| std::env::current_dir()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|_| ".".to_string()));
+        // A second SessionEnd for the same transcript moments later is a no-op
+        if !debounce::claim(&output_dir, &output.session_id, &session_path)? {
+            eprintln!("Session {} was ingested moments ago, skipping", output.session_id);
+            continue;
+        }
 
-    // Extract session ID from filename or generate one
-    let session_id = extract_session_id(&session_path);
+        // Keep tags and notes added to earlier ingests of this session
+        source::carry_over(&mut output, &output_dir);
 
-    // Build output
-    let output = output::DevlogOutput {
-        schema_version: "1.0".to_string(),
-        session_id,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-        machine_id: output::get_machine_id(),
-        project_dir,
-        git: git_info,
-        conversation,
-    };
+        // A failed ingest gives up its claim, so a retry is not taken for a duplicate
+        let session_id = output.session_id.clone();
+        if let Err(e) = store_ingested(output, &output_dir) {
+            debounce::release(&output_dir, &session_id);
+            return Err(e);
+        }
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, err, fields(session_id = %output.session_id))]
+fn store_ingested(mut output: output::DevlogOutput, output_dir: &Path) -> Result<()> {
+    // Sessions matching [skip] rules are never written to .devlog or pushed
+    if skip::SkipRules::load()?.hold_back(&output)? {
+        return Ok(());
+    }
+
+    // Apply [redact] rules before anything is written or pushed
+    let redactions = redact::Redactor::load()?.redact_output(&mut output);
+    if redactions > 0 {
+        eprintln!("Redacted {} matches", redactions);
+    }
+    // Tickets are linked from the whole prompt, before it is cut short
+    tickets::TicketLinker::load()?.link(&mut output);
+    if stats_only::StatsOnly::load()?.apply(&mut output)? {
+        eprintln!("Keeping only stats for this project");
+    }
+    let truncated = truncate::Truncator::load()?.truncate_output(&mut output);
+    if truncated > 0 {
+        eprintln!("Truncated {} oversized entries", truncated);
+    }
 
     // Write output
-    let _output_path = output::write_output(&output)?;
+    let output_path = output::write_output_to(output_dir, &output)?;
 
     eprintln!("Session ingested successfully");
 
+    // Build the semantic search index if enabled
+    if let Err(e) = embeddings::index_session(&output, &output_path) {
+        eprintln!("Warning: Failed to compute embeddings: {}", e);
+    }
+
     // Auto-push if enabled
-    if let Err(e) = push::push_session(&output) {
+    if let Err(e) = push::push_session(&output, Some(&output_path)) {
         eprintln!("Warning: Failed to push session: {}", e);
         // Don't fail the whole ingest if push fails
     }
 
+    // Add a note to the Obsidian vault if enabled
+    if let Err(e) = obsidian::export_session(&output) {
+        eprintln!("Warning: Failed to write Obsidian note: {}", e);
+    }
+
     Ok(())
 }
 
-fn find_session_from_stdin_or_recent() -> Result<PathBuf> {
+fn find_session_from_stdin_or_recent(source: &dyn source::SessionSource) -> Result<PathBuf> {
     // First, try to read from stdin (hook input)
     use std::io::{self, BufRead};
 
@@ -130,10 +807,8 @@ fn find_session_from_stdin_or_recent() -> Result<PathBuf> {
     if atty::is(atty::Stream::Stdin) {
         // No stdin piped, look for recent session
     } else {
-        for line in stdin.lock().lines() {
-            if let Ok(line) = line {
-                stdin_content.push_str(&line);
-            }
+        for line in stdin.lock().lines().map_while(Result::ok) {
+            stdin_content.push_str(&line);
         }
     }
 
@@ -149,57 +824,134 @@ fn find_session_from_stdin_or_recent() -> Result<PathBuf> {
         }
     }
 
-    // Fallback: find most recent session in ~/.claude/
-    find_most_recent_session()
+    // Fallback: the source's most recent session, e.g. in ~/.claude/
+    source.most_recent()
 }
 
-fn find_most_recent_session() -> Result<PathBuf> {
-    let home = std::env::var("HOME").context("HOME not set")?;
-    let claude_dir = PathBuf::from(home).join(".claude").join("projects");
+fn find_saved_search(name: &str) -> Result<config::SavedSearch> {
+    let mut searches = config::Config::load()?.searches;
+    match searches.remove(name) {
+        Some(saved) => Ok(saved),
+        None if searches.is_empty() => {
+            anyhow::bail!("No saved search named '{}' (none are configured)", name)
+        }
+        None => anyhow::bail!(
+            "No saved search named '{}' (available: {})",
+            name,
+            searches.keys().cloned().collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
 
-    if !claude_dir.exists() {
-        anyhow::bail!("No Claude directory found at {}", claude_dir.display());
+fn search_sessions(
+    query: &str,
+    options: &search::SearchOptions,
+    storage: Option<PathBuf>,
+    semantic: bool,
+    export: Option<PathBuf>,
+) -> Result<()> {
+    if semantic {
+        if storage.is_some() {
+            anyhow::bail!("Semantic search only covers the local .devlog embedding index");
+        }
+        let devlog_dir = std::env::current_dir()
+            .context("Failed to get current directory")?
+            .join(".devlog");
+        let matches = embeddings::semantic_search(&devlog_dir, query, options.limit)?;
+        embeddings::print_semantic_matches(&matches);
+        return Ok(());
     }
 
-    let mut most_recent: Option<(PathBuf, std::time::SystemTime)> = None;
+    let (page, link_root) = match storage {
+        Some(storage_dir) => {
+            let page = search::search_devlogs(&storage_dir, query, options)?;
+            (page, storage_dir)
+        }
+        None => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            let page = search::search_local(&devlog_dir, query, options)?;
+            (page, devlog_dir)
+        }
+    };
 
-    fn find_jsonl_files(dir: &PathBuf, most_recent: &mut Option<(PathBuf, std::time::SystemTime)>) {
-        if let Ok(entries) = std::fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_dir() {
-                    find_jsonl_files(&path, most_recent);
-                } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-                    if let Ok(meta) = path.metadata() {
-                        if let Ok(modified) = meta.modified() {
-                            match most_recent {
-                                Some((_, ref time)) if modified > *time => {
-                                    *most_recent = Some((path, modified));
-                                }
-                                None => {
-                                    *most_recent = Some((path, modified));
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            }
+    match export {
+        Some(path) => {
+            let local = link_root.ends_with(".devlog");
+            let link = |r: &search::SearchResult| {
+                let file = if local {
+                    link_root.join(&r.session_file)
+                } else {
+                    link_root.join(&r.machine).join(&r.project).join(&r.session_file)
+                };
+                Some(file.to_string_lossy().to_string())
+            };
+            write_digest(&path, query, &page, &link)
+        }
+        None => {
+            search::print_results(&page);
+            Ok(())
         }
     }
+}
 
-    find_jsonl_files(&claude_dir, &mut most_recent);
-
-    most_recent
-        .map(|(path, _)| path)
-        .context("No session files found")
+fn write_digest(
+    path: &Path,
+    query: &str,
+    page: &search::SearchPage,
+    link: &dyn Fn(&search::SearchResult) -> Option<String>,
+) -> Result<()> {
+    let markdown = search::render_markdown(query, page, link);
+    std::fs::write(path, markdown)
+        .with_context(|| format!("Failed to write digest: {}", path.display()))?;
+    eprintln!("Wrote {} results to: {}", page.results.len(), path.display());
+    Ok(())
 }
 
-fn extract_session_id(path: &PathBuf) -> String {
-    path.file_stem()
-        .and_then(|s| s.to_str())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| format!("session_{}", chrono::Utc::now().timestamp()))
+/// Search the local .devlog and the push server concurrently
+fn search_everywhere(
+    query: &str,
+    options: &search::SearchOptions,
+    export: Option<PathBuf>,
+) -> Result<()> {
+    let config = config::Config::load()?;
+    let push_config = config
+        .push
+        .as_ref()
+        .context("No push config found; the server's search API is unknown")?;
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+
+    let (local, remote) = std::thread::scope(|s| {
+        let remote = s.spawn(|| search::search_remote(push_config, query, options));
+        let local = search::search_local(&devlog_dir, query, options);
+        (local, remote.join())
+    });
+
+    let mut pages = vec![local?];
+    match remote {
+        Ok(Ok(page)) => pages.push(page),
+        Ok(Err(e)) => eprintln!("Warning: Remote search failed: {}", e),
+        Err(_) => eprintln!("Warning: Remote search failed"),
+    }
+
+    let page = search::merge_pages(pages, options.limit);
+    match export {
+        Some(path) => {
+            // Only sessions present locally can be linked
+            let link = |r: &search::SearchResult| {
+                let file = devlog_dir.join(&r.session_file);
+                file.exists().then(|| file.to_string_lossy().to_string())
+            };
+            write_digest(&path, query, &page, &link)
+        }
+        None => {
+            search::print_results(&page);
+            Ok(())
+        }
+    }
 }
 
 fn push_session(path: Option<PathBuf>) -> Result<()> {
@@ -219,11 +971,338 @@ fn push_session(path: Option<PathBuf>) -> Result<()> {
         .with_context(|| format!("Failed to parse devlog file: {}", devlog_path.display()))?;
 
     // Push it
-    push::push_session(&output)?;
+    push::push_session(&output, Some(&devlog_path))?;
+
+    Ok(())
+}
+
+fn export_session(path: Option<PathBuf>, format: export::ExportFormat, output_path: Option<PathBuf>) -> Result<()> {
+    let devlog_path = match path {
+        Some(p) => p,
+        None => find_most_recent_devlog()?,
+    };
+
+    let content = std::fs::read_to_string(&devlog_path)
+        .with_context(|| format!("Failed to read devlog file: {}", devlog_path.display()))?;
+    let output: output::DevlogOutput = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse devlog file: {}", devlog_path.display()))?;
+
+    if format == export::ExportFormat::Obsidian {
+        let obsidian = obsidian_config(output_path)?;
+        let note = obsidian::write_note(&obsidian, &output)?;
+        eprintln!("Exported {} to: {}", devlog_path.display(), note.display());
+        return Ok(());
+    }
+
+    let output_path = output_path.unwrap_or_else(|| {
+        let stem = devlog_path.file_stem().unwrap_or_default();
+        PathBuf::from(stem).with_extension(format.extension())
+    });
+
+    let rendered = export::export_session(&output, format)?;
+    std::fs::write(&output_path, rendered)
+        .with_context(|| format!("Failed to write export: {}", output_path.display()))?;
+
+    eprintln!("Exported {} to: {}", devlog_path.display(), output_path.display());
+    Ok(())
+}
+
+/// Write the sessions the options select as JSONL, to a file or stdout
+fn export_dataset(
+    path: Option<PathBuf>,
+    storage: Option<PathBuf>,
+    output_path: Option<PathBuf>,
+    options: &dataset::DatasetOptions,
+) -> Result<()> {
+    let sessions = match path {
+        Some(path) => dataset::session(&path)?,
+        None => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            dataset::sessions(&devlog_dir, storage.as_deref())?
+        }
+    };
+
+    let written = match output_path {
+        Some(ref output_path) => {
+            let file = std::fs::File::create(output_path)
+                .with_context(|| format!("Failed to write export: {}", output_path.display()))?;
+            dataset::write_dataset(sessions, options, &mut std::io::BufWriter::new(file))?
+        }
+        None => dataset::write_dataset(sessions, options, &mut std::io::stdout().lock())?,
+    };
+
+    match output_path {
+        Some(output_path) => eprintln!("Exported {} conversations to: {}", written, output_path.display()),
+        None => eprintln!("Exported {} conversations", written),
+    }
+    Ok(())
+}
+
+/// Export every session in .devlog, into a directory or the Obsidian vault
+fn export_all(format: export::ExportFormat, output_dir: Option<PathBuf>) -> Result<()> {
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+
+    let mut sessions = Vec::new();
+    for file in search::local_files(&devlog_dir)? {
+        match search::read_devlog(&file.path) {
+            Ok(output) => sessions.push((file.path, output)),
+            Err(e) => eprintln!("Warning: Skipping {}: {}", file.path.display(), e),
+        }
+    }
+    if sessions.is_empty() {
+        anyhow::bail!("No devlog JSON files found in .devlog directory");
+    }
+    // Oldest first, so each Obsidian note can link to the one before it
+    sessions.sort_by(|a, b| a.1.timestamp.cmp(&b.1.timestamp));
+
+    if format == export::ExportFormat::Obsidian {
+        let obsidian = obsidian_config(output_dir)?;
+        for (_, output) in &sessions {
+            obsidian::write_note(&obsidian, output)?;
+        }
+        eprintln!("Exported {} sessions to: {}", sessions.len(), obsidian.vault.join(&obsidian.folder).display());
+        return Ok(());
+    }
+
+    let output_dir = output_dir.unwrap_or_else(|| PathBuf::from("."));
+    std::fs::create_dir_all(&output_dir)
+        .with_context(|| format!("Failed to create directory: {}", output_dir.display()))?;
+    for (path, output) in &sessions {
+        let stem = path.file_stem().unwrap_or_default();
+        let output_path = output_dir.join(stem).with_extension(format.extension());
+        std::fs::write(&output_path, export::export_session(output, format)?)
+            .with_context(|| format!("Failed to write export: {}", output_path.display()))?;
+    }
+    eprintln!("Exported {} sessions to: {}", sessions.len(), output_dir.display());
+    Ok(())
+}
+
+/// The [obsidian] config, with its vault overridden from the command line
+fn obsidian_config(vault: Option<PathBuf>) -> Result<config::ObsidianConfig> {
+    match (config::Config::load()?.obsidian, vault) {
+        (Some(oc), Some(vault)) => Ok(config::ObsidianConfig { vault, ..oc }),
+        (Some(oc), None) => Ok(oc),
+        (None, Some(vault)) => Ok(config::ObsidianConfig::new(vault)),
+        (None, None) => anyhow::bail!("No [obsidian] config found; set its vault or pass --output <vault>"),
+    }
+}
+
+fn tag_session(session: &str, tags: &[String], remove: bool) -> Result<()> {
+    let tags: Vec<&str> = tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()).collect();
+    if tags.is_empty() {
+        anyhow::bail!("No tags given");
+    }
+
+    let output = update_session(session, |output| {
+        if remove {
+            output.tags.retain(|t| !tags.contains(&t.as_str()));
+        } else {
+            for tag in &tags {
+                if !output.has_tag(tag) {
+                    output.tags.push(tag.to_string());
+                }
+            }
+        }
+    })?;
+
+    if output.tags.is_empty() {
+        eprintln!("Session {} has no tags", output.session_id);
+    } else {
+        eprintln!("Session {} tags: {}", output.session_id, output.tags.join(", "));
+    }
+    Ok(())
+}
+
+fn note_session(session: &str, text: &str) -> Result<()> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("The note is empty");
+    }
+
+    let note = devlog_core::Note {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        text: text.to_string(),
+    };
+    let output = update_session(session, |output| output.notes.push(note.clone()))?;
+
+    eprintln!("Session {} has {} note(s)", output.session_id, output.notes.len());
+    Ok(())
+}
+
+/// Apply `change` to every devlog file of a session and push the result, since
+/// the server only learns of the change when the session is pushed again.
+/// Returns the most recent ingest, as changed.
+fn update_session(session: &str, mut change: impl FnMut(&mut output::DevlogOutput)) -> Result<output::DevlogOutput> {
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+    let files = output::find_session_files(&devlog_dir, session)?;
+
+    let mut latest: Option<(output::DevlogOutput, &PathBuf)> = None;
+    for file in &files {
+        let mut output = search::read_devlog(file)
+            .with_context(|| format!("Failed to read devlog file: {}", file.display()))?;
+        change(&mut output);
+        output::rewrite_output(file, &output)?;
+
+        if latest.as_ref().map(|(l, _)| output.timestamp > l.timestamp).unwrap_or(true) {
+            latest = Some((output, file));
+        }
+    }
+    let (output, file) = latest.context("No devlog files for session")?;
+
+    if let Err(e) = push::push_session(&output, Some(file)) {
+        eprintln!("Warning: Failed to push session: {}", e);
+    }
+
+    Ok(output)
+}
+
+fn merge_sessions(sessions: &[String], output_path: Option<&Path>) -> Result<()> {
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+
+    let mut parts = Vec::new();
+    for session in sessions {
+        // The last file of a session is its most recent ingest
+        let path = output::find_session_files(&devlog_dir, session)?
+            .pop()
+            .context("No devlog files for session")?;
+        let part = search::read_devlog(&path)
+            .with_context(|| format!("Failed to read devlog file: {}", path.display()))?;
+        parts.push(part);
+    }
+
+    let merged = merge::merge_sessions(parts)?;
+    eprintln!(
+        "Merged {} sessions into {} ({} entries)",
+        sessions.len(),
+        merged.session_id,
+        merged.conversation.len()
+    );
+
+    match output_path {
+        Some(path) => {
+            output::rewrite_output(path, &merged)?;
+            eprintln!("Wrote devlog to: {}", path.display());
+        }
+        None => {
+            let path = output::write_output(&merged)?;
+            // Found by semantic search like any ingested session
+            if let Err(e) = embeddings::index_session(&merged, &path) {
+                eprintln!("Warning: Failed to compute embeddings: {}", e);
+            }
+            if let Err(e) = push::push_session(&merged, Some(&path)) {
+                eprintln!("Warning: Failed to push session: {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The devlogs a rewriting command works on: a server storage directory, the
+/// given files, or else every devlog in .devlog
+fn devlog_files(paths: Vec<PathBuf>, storage: Option<PathBuf>) -> Result<Vec<PathBuf>> {
+    if let Some(storage) = storage {
+        return Ok(search::storage_files(&storage)?.into_iter().map(|f| f.path).collect());
+    }
+    if !paths.is_empty() {
+        return Ok(paths);
+    }
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+    Ok(search::local_files(&devlog_dir)?.into_iter().map(|f| f.path).collect())
+}
+
+/// Print the schema problems of each devlog; returns false if any has errors
+fn validate_devlogs(paths: &[PathBuf], verbose: bool) -> Result<bool> {
+    use devlog_core::validate::{validate_str, Severity};
+
+    let files = search::files_under(paths)?;
+    let (mut invalid, mut warned) = (0, 0);
+    for file in &files {
+        let issues = match std::fs::read_to_string(file) {
+            Ok(json) => validate_str(&json),
+            Err(e) => {
+                println!("{}: error: unreadable: {}", file.display(), e);
+                invalid += 1;
+                continue;
+            }
+        };
+
+        if issues.iter().any(|i| i.severity == Severity::Error) {
+            invalid += 1;
+        } else if !issues.is_empty() {
+            warned += 1;
+        } else if verbose {
+            println!("{}: ok", file.display());
+        }
+        for issue in &issues {
+            println!("{}: {}", file.display(), issue);
+        }
+    }
+
+    println!(
+        "Checked {} devlogs: {} valid, {} with warnings only, {} invalid",
+        files.len(),
+        files.len() - invalid - warned,
+        warned,
+        invalid
+    );
+    Ok(invalid == 0)
+}
+
+/// The latest devlog of a session in `.devlog`, or the most recent devlog of all
+fn session_devlog(session: Option<&str>) -> Result<PathBuf> {
+    match session {
+        Some(session) => {
+            let devlog_dir = std::env::current_dir()
+                .context("Failed to get current directory")?
+                .join(".devlog");
+            // The last file of a session is its most recent ingest
+            output::find_session_files(&devlog_dir, session)?
+                .pop()
+                .context("No devlog files for session")
+        }
+        None => find_most_recent_devlog(),
+    }
+}
+
+fn show_session(session: Option<&str>) -> Result<()> {
+    let devlog_path = session_devlog(session)?;
 
+    let output = search::read_devlog(&devlog_path)
+        .with_context(|| format!("Failed to read devlog file: {}", devlog_path.display()))?;
+    show::print_session(&output);
     Ok(())
 }
 
+fn push_all() -> Result<()> {
+    let devlog_dir = std::env::current_dir()
+        .context("Failed to get current directory")?
+        .join(".devlog");
+
+    let mut outputs = Vec::new();
+    for file in search::local_files(&devlog_dir)? {
+        match search::read_devlog(&file.path) {
+            Ok(output) => outputs.push(output),
+            Err(e) => eprintln!("Warning: Skipping {}: {}", file.path.display(), e),
+        }
+    }
+
+    if outputs.is_empty() {
+        anyhow::bail!("No devlog JSON files found in .devlog directory");
+    }
+
+    push::push_batch(&outputs)
+}
+
 fn find_most_recent_devlog() -> Result<PathBuf> {
     // Look in current directory's .devlog folder
     let current_dir = std::env::current_dir().context("Failed to get current directory")?;
@@ -0,0 +1,125 @@
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::sync::OnceLock;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+/// Theme for fenced code; its background suits the dark pages devlog renders
+const CODE_THEME: &str = "base16-ocean.dark";
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        ThemeSet::load_defaults()
+            .themes
+            .remove(CODE_THEME)
+            .unwrap_or_default()
+    })
+}
+
+/// Render conversation markdown to HTML, highlighting fenced code blocks by their
+/// language tag. Raw HTML in the input is shown as text, never passed through,
+/// and so are links and images to anything but the web, email or a relative path.
+pub fn to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+
+    let mut events = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+    // Links and images cannot nest, so one flag covers the tag being unwrapped
+    let mut unwrapped = false;
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((lang, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((lang, code)) = code_block.take() {
+                    events.push(Event::Html(highlight(&lang, &code).into()));
+                }
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, ref mut code)) = code_block {
+                    code.push_str(&text);
+                }
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) => events.push(Event::Text(raw)),
+            // Only the text of the link, or the alt text of the image, is kept
+            Event::Start(Tag::Link { ref dest_url, .. } | Tag::Image { ref dest_url, .. })
+                if !safe_url(dest_url) =>
+            {
+                unwrapped = true;
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) if unwrapped => unwrapped = false,
+            event => events.push(event),
+        }
+    }
+
+    let mut out = String::with_capacity(markdown.len() * 2);
+    html::push_html(&mut out, events.into_iter());
+    out
+}
+
+/// Whether a link may point at `url`: http, https, mailto or a relative path.
+/// Anything else, `javascript:` above all, would run or open something in the
+/// reader's browser.
+fn safe_url(url: &str) -> bool {
+    let scheme_end = url.find([':', '/', '?', '#']);
+    match scheme_end {
+        Some(end) if url[end..].starts_with(':') => {
+            matches!(url[..end].to_ascii_lowercase().as_str(), "http" | "https" | "mailto")
+        }
+        _ => true,
+    }
+}
+
+/// A `<pre>` block with inline styles, so the output needs no stylesheet
+fn highlight(lang: &str, code: &str) -> String {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    highlighted_html_for_string(code, syntaxes, syntax, theme()).unwrap_or_else(|_| {
+        let escaped = code.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        format!("<pre><code>{}</code></pre>", escaped)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn javascript_links_are_text() {
+        let html = to_html("[click](javascript:alert(1)) and <JavaScript:alert(2)>");
+        assert!(!html.contains("href"), "{}", html);
+        assert!(!html.to_lowercase().contains("javascript:alert(1)"), "{}", html);
+        assert!(html.contains("click"), "{}", html);
+    }
+
+    #[test]
+    fn unsafe_images_keep_their_alt_text() {
+        let html = to_html("![diagram](data:image/svg+xml;base64,PHN2Zz4=)");
+        assert!(!html.contains("<img"), "{}", html);
+        assert!(!html.contains("data:"), "{}", html);
+        assert!(html.contains("diagram"), "{}", html);
+    }
+
+    #[test]
+    fn web_mail_and_relative_links_are_kept() {
+        let html = to_html("[a](https://example.com) [b](http://x.org) [c](mailto:me@x.org) [d](docs/a.md) [e](#top)");
+        for href in ["https://example.com", "http://x.org", "mailto:me@x.org", "docs/a.md", "#top"] {
+            assert!(html.contains(&format!("href=\"{}\"", href)), "{}", html);
+        }
+    }
+}
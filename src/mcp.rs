@@ -0,0 +1,276 @@
+use crate::parser::ConversationEntry;
+use crate::search::{self, SearchOptions, SearchScope};
+use crate::{brief, embeddings, output, show};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// MCP revisions this server speaks, newest first; a client asking for another
+/// is offered the newest
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve the Model Context Protocol over stdin/stdout, one JSON-RPC message per
+/// line, so an assistant can search and read the sessions in `devlog_dir`.
+/// Nothing but protocol messages may go to stdout; logging goes to stderr.
+pub fn serve(devlog_dir: &Path) -> Result<()> {
+    eprintln!("Serving MCP for: {}", devlog_dir.display());
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+
+    for line in stdin.lock().lines() {
+        let line = line.context("Failed to read from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(devlog_dir, &message),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &format!("Invalid JSON: {}", e))),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response).context("Failed to write to stdout")?;
+            stdout.flush().context("Failed to write to stdout")?;
+        }
+    }
+    Ok(())
+}
+
+fn handle(devlog_dir: &Path, message: &Value) -> Option<Value> {
+    // Notifications (such as notifications/initialized) carry no ID and get no reply
+    let id = message.get("id").cloned()?;
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(error(id, INVALID_REQUEST, "Missing method"));
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tools() })),
+        "tools/call" => call_tool(devlog_dir, &params),
+        _ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error(id, code, &message),
+    })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "devlog", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Past coding sessions of this project, captured by devlog. Search them for earlier \
+            decisions and discussions before asking the user to repeat themselves.",
+    })
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "search_devlogs",
+            "description": "Search this project's past sessions for text, newest first. Returns the matching \
+                prompts and responses grouped by session, with each session's file name for get_session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Text to look for, or a question with semantic" },
+                    "scope": {
+                        "type": "string",
+                        "enum": ["prompts", "conversations", "all"],
+                        "description": "prompts: the user's prompts only; conversations (default): prompts and \
+                            responses; all: tool summaries too"
+                    },
+                    "days": { "type": "integer", "description": "Only sessions from the last this many days" },
+                    "tag": { "type": "string", "description": "Only sessions carrying this tag" },
+                    "ticket": {
+                        "type": "string",
+                        "description": "Only sessions whose prompts mention this ticket, e.g. PROJ-123 or #456"
+                    },
+                    "limit": { "type": "integer", "description": "Most results to return (default 20)" },
+                    "cursor": { "type": "string", "description": "Continue from a previous page" },
+                    "semantic": {
+                        "type": "boolean",
+                        "description": "Rank sessions by similarity to the query instead of matching text; \
+                            needs [embeddings] enabled"
+                    }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_session",
+            "description": "Read one past session in full: its git branch, notes, and every prompt, response \
+                and tool summary.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "session": {
+                        "type": "string",
+                        "description": "A session ID, or a prefix of one, or a devlog file name from search_devlogs"
+                    }
+                },
+                "required": ["session"]
+            }
+        },
+        {
+            "name": "project_summary",
+            "description": "Totals for every session recorded for this project, and a summary of the most \
+                recent ones: their prompts, decisions, files touched and outcome.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sessions": { "type": "integer", "description": "How many recent sessions to summarize (default 5)" }
+                }
+            }
+        }
+    ])
+}
+
+fn call_tool(devlog_dir: &Path, params: &Value) -> std::result::Result<Value, (i64, String)> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+    let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+    let outcome = match name {
+        "search_devlogs" => search_devlogs(devlog_dir, &args),
+        "get_session" => get_session(devlog_dir, &args),
+        "project_summary" => project_summary(devlog_dir, &args),
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+    // A failing tool is reported to the model, which can try another way
+    Ok(match outcome {
+        Ok(text) => json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        Err(e) => json!({ "content": [{ "type": "text", "text": format!("{:#}", e) }], "isError": true }),
+    })
+}
+
+fn str_arg<'a>(args: &'a Value, key: &str) -> Option<&'a str> {
+    args.get(key).and_then(Value::as_str).filter(|s| !s.is_empty())
+}
+
+fn search_devlogs(devlog_dir: &Path, args: &Value) -> Result<String> {
+    let query = str_arg(args, "query").context("query is required")?;
+    let limit = args.get("limit").and_then(Value::as_u64).unwrap_or(20) as usize;
+
+    if args.get("semantic").and_then(Value::as_bool) == Some(true) {
+        let matches = embeddings::semantic_search(devlog_dir, query, limit)?;
+        if matches.is_empty() {
+            return Ok("No indexed sessions found".to_string());
+        }
+        let mut text = format!("Sessions most similar to \"{}\":\n", query);
+        for m in matches {
+            text.push_str(&format!(
+                "\n- {} ({}, similarity {:.3}) [{}]: {}",
+                m.session_file,
+                m.timestamp,
+                m.score,
+                m.entry_type,
+                m.snippet.replace('\n', " ")
+            ));
+        }
+        return Ok(text);
+    }
+
+    let options = SearchOptions {
+        scope: str_arg(args, "scope").map(SearchScope::from_str).unwrap_or_default(),
+        days: args.get("days").and_then(Value::as_u64).map(|d| d as u32),
+        limit,
+        cursor: str_arg(args, "cursor").map(String::from),
+        case_sensitive: false,
+        whole_word: false,
+        tag: str_arg(args, "tag").map(String::from),
+        ticket: str_arg(args, "ticket").map(String::from),
+    };
+    let page = search::search_local(devlog_dir, query, &options)?;
+    if page.results.is_empty() {
+        return Ok(format!("No sessions mention \"{}\"", query));
+    }
+
+    let mut text = search::render_markdown(query, &page, &|_| None);
+    if let Some(cursor) = page.next_cursor {
+        text.push_str(&format!("\nMore results: call again with cursor \"{}\"\n", cursor));
+    }
+    Ok(text)
+}
+
+fn get_session(devlog_dir: &Path, args: &Value) -> Result<String> {
+    let session = str_arg(args, "session").context("session is required")?;
+    // Only sessions of this project; not any file the caller names
+    if session.contains(['/', '\\']) {
+        anyhow::bail!("Pass a session ID or a devlog file name, not a path");
+    }
+
+    let by_name = devlog_dir.join(session);
+    let path: PathBuf = if by_name.is_file() {
+        by_name
+    } else {
+        // The last file of a session is its most recent ingest
+        output::find_session_files(devlog_dir, session)?
+            .pop()
+            .context("No devlog files for session")?
+    };
+    let output = search::read_devlog(&path)
+        .with_context(|| format!("Failed to read devlog file: {}", path.display()))?;
+    Ok(show::render_session(&output))
+}
+
+fn project_summary(devlog_dir: &Path, args: &Value) -> Result<String> {
+    let count = args.get("sessions").and_then(Value::as_u64).unwrap_or(5) as usize;
+    let sessions = brief::latest_sessions(devlog_dir)?;
+    let (Some(first), Some(last)) = (sessions.first(), sessions.last()) else {
+        anyhow::bail!("No sessions recorded in {}", devlog_dir.display());
+    };
+
+    let mut prompts = 0;
+    let mut actions = 0;
+    let mut input_tokens = 0;
+    let mut output_tokens = 0;
+    for entry in sessions.iter().flat_map(|s| &s.conversation) {
+        match entry {
+            ConversationEntry::User { .. } => prompts += 1,
+            ConversationEntry::Assistant { usage: Some(usage), .. } => {
+                input_tokens += usage.input_tokens.unwrap_or(0);
+                output_tokens += usage.output_tokens.unwrap_or(0);
+            }
+            ConversationEntry::Assistant { .. } => {}
+            ConversationEntry::ToolSummary { actions: a } => actions += a.len(),
+        }
+    }
+
+    let mut text = format!(
+        "{} sessions recorded from {} to {}: {} prompts, {} tool actions, {} input and {} output tokens.\n\n",
+        sessions.len(),
+        date(&first.timestamp),
+        date(&last.timestamp),
+        prompts,
+        actions,
+        input_tokens,
+        output_tokens
+    );
+    text.push_str(&brief::build_brief(devlog_dir, count.max(1))?);
+    Ok(text)
+}
+
+fn date(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
@@ -0,0 +1,169 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use anyhow::Result;
+use devlog_core::{Note, TicketLink};
+use std::collections::HashSet;
+
+/// An entry with the tool summaries that followed it, kept together when the
+/// conversations of several devlogs are interleaved
+struct Turn {
+    timestamp: Option<chrono::DateTime<chrono::FixedOffset>>,
+    entries: Vec<ConversationEntry>,
+}
+
+/// Combine devlogs of one logical session that Claude Code split across several
+/// transcripts, e.g. after a crash or a resume. Entries are put in timestamp order,
+/// and entries present in more than one part (a resumed transcript repeats the
+/// history it continues) are kept once.
+///
+/// The result takes the session ID of the earliest part and the git state of the latest.
+pub fn merge_sessions(mut parts: Vec<DevlogOutput>) -> Result<DevlogOutput> {
+    if parts.len() < 2 {
+        anyhow::bail!("Merging needs at least two sessions");
+    }
+    if parts.iter().any(|p| p.stats_only.is_some()) {
+        anyhow::bail!("Sessions captured stats-only have no entries to merge");
+    }
+    parts.sort_by_key(start_time);
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut duplicates = 0;
+    for part in &parts {
+        for turn in turns_of(part) {
+            let keys: Vec<String> = turn.entries.first().map(entry_keys).unwrap_or_default();
+            if keys.iter().any(|k| seen.contains(k)) {
+                duplicates += 1;
+                continue;
+            }
+            seen.extend(keys);
+            turns.push(turn);
+        }
+    }
+    // Stable, so turns without a timestamp stay after the one they followed
+    turns.sort_by_key(|t| t.timestamp);
+    if duplicates > 0 {
+        eprintln!("Dropped {} entries present in more than one session", duplicates);
+    }
+
+    let session_ids: Vec<&str> = parts.iter().map(|p| p.session_id.as_str()).collect();
+    let mut tags: Vec<String> = Vec::new();
+    let mut notes: Vec<Note> = Vec::new();
+    let mut tickets: Vec<TicketLink> = Vec::new();
+    for part in &parts {
+        for tag in &part.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+        for note in &part.notes {
+            if !notes.iter().any(|n| n.timestamp == note.timestamp && n.text == note.text) {
+                notes.push(note.clone());
+            }
+        }
+        for ticket in &part.tickets {
+            if !tickets.iter().any(|t| t.id == ticket.id) {
+                tickets.push(ticket.clone());
+            }
+        }
+    }
+    notes.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let now = chrono::Utc::now().to_rfc3339();
+    notes.push(Note {
+        timestamp: now.clone(),
+        text: format!("Merged from sessions {}", session_ids.join(", ")),
+    });
+
+    let session_id = parts[0].session_id.clone();
+    let mut latest = parts.pop().expect("at least two parts");
+    // Commits made during any part belong to the merged session
+    if let Some(ref mut git) = latest.git {
+        for part in &parts {
+            for commit in part.git.iter().flat_map(|g| &g.commits) {
+                if !git.commits.iter().any(|c| c.hash == commit.hash) {
+                    git.commits.push(commit.clone());
+                }
+            }
+        }
+        git.commits.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    }
+
+    Ok(DevlogOutput {
+        schema_version: latest.schema_version,
+        session_id,
+        timestamp: now,
+        machine_id: latest.machine_id,
+        project_dir: latest.project_dir,
+        git: latest.git,
+        tags,
+        notes,
+        tickets,
+        conversation: turns.into_iter().flat_map(|t| t.entries).collect(),
+        // No single transcript holds the merged session
+        transcript_path: None,
+        stats_only: None,
+    })
+}
+
+/// When the part's conversation began, or else when it was ingested
+fn start_time(part: &DevlogOutput) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    part.conversation
+        .iter()
+        .find_map(|entry| parse_time(timestamp(entry)))
+        .or_else(|| parse_time(Some(&part.timestamp)))
+}
+
+fn turns_of(part: &DevlogOutput) -> Vec<Turn> {
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut last_time = start_time(part);
+    for entry in &part.conversation {
+        match entry {
+            ConversationEntry::ToolSummary { .. } if !turns.is_empty() => {
+                let turn = turns.last_mut().expect("checked non-empty");
+                turn.entries.push(entry.clone());
+            }
+            _ => {
+                if let Some(time) = parse_time(timestamp(entry)) {
+                    last_time = Some(time);
+                }
+                turns.push(Turn {
+                    timestamp: last_time,
+                    entries: vec![entry.clone()],
+                });
+            }
+        }
+    }
+    turns
+}
+
+/// What identifies an entry across devlogs: its transcript `uuid`, and for
+/// devlogs written before uuids were kept, its kind, time and text
+fn entry_keys(entry: &ConversationEntry) -> Vec<String> {
+    let (kind, timestamp, content, uuid) = match entry {
+        ConversationEntry::User { timestamp, content, uuid } => ("user", timestamp, content, uuid),
+        ConversationEntry::Assistant { timestamp, content, uuid, .. } => ("assistant", timestamp, content, uuid),
+        ConversationEntry::ToolSummary { .. } => return Vec::new(),
+    };
+    let mut keys = Vec::new();
+    if let Some(uuid) = uuid {
+        keys.push(format!("uuid:{}", uuid));
+    }
+    // Without a timestamp, equal text ("yes", "continue") is no evidence of a repeat
+    if let Some(timestamp) = timestamp {
+        keys.push(format!("{}:{}:{}", kind, timestamp, content));
+    }
+    keys
+}
+
+fn timestamp(entry: &ConversationEntry) -> Option<&str> {
+    match entry {
+        ConversationEntry::User { timestamp, .. } | ConversationEntry::Assistant { timestamp, .. } => {
+            timestamp.as_deref()
+        }
+        ConversationEntry::ToolSummary { .. } => None,
+    }
+}
+
+fn parse_time(timestamp: Option<&str>) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    timestamp.and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+}
@@ -0,0 +1,572 @@
+use crate::config::{GitHubConfig, NotificationsConfig, Webhook};
+use crate::output::DevlogOutput;
+use crate::parser::{truncate, ConversationEntry};
+use crate::{server, telemetry};
+use anyhow::{Context, Result};
+use devlog_core::webhook::{self, SessionEvent};
+use devlog_core::forge;
+use devlog_core::store::{project_name, storage_filename};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Batches larger than this get one roll-up message instead of one per session,
+/// so a backfill doesn't flood the channel
+const MAX_MESSAGES_PER_BATCH: usize = 5;
+
+/// Somewhere to tell about stored sessions. Each kind of target formats the
+/// same [`Summary`] its own way; adding one means implementing this and
+/// listing it in [`notifiers`].
+trait Notifier {
+    /// For log messages
+    fn name(&self) -> &'static str;
+
+    /// Whether it wants to hear about sessions of `project`
+    fn wants(&self, project: &str) -> bool;
+
+    /// Deliver the message for one session
+    fn session(&self, client: &Client, summary: &Summary) -> Result<()>;
+
+    /// Deliver one message covering a large batch
+    fn batch(&self, client: &Client, batch: &[Summary]) -> Result<()>;
+}
+
+/// Every target in the config
+fn notifiers(config: &NotificationsConfig, storage_dir: &Path) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    for webhook in &config.slack {
+        notifiers.push(Box::new(Slack(webhook.clone())));
+    }
+    for webhook in &config.discord {
+        notifiers.push(Box::new(Discord(webhook.clone())));
+    }
+    for webhook in &config.webhooks {
+        notifiers.push(Box::new(Generic(webhook.clone())));
+    }
+    if let Some(ref github) = config.github {
+        match GitHub::new(github, storage_dir, config.base_url.clone()) {
+            Ok(github) => notifiers.push(Box::new(github)),
+            Err(e) => eprintln!("Not commenting on pull requests: {:#}", e),
+        }
+    }
+    notifiers
+}
+
+/// Tell the configured targets about sessions just stored. Blocks on the
+/// requests, so the server calls it off the async runtime; failures are logged
+/// and never reach the client that pushed.
+pub fn sessions_ingested(config: &NotificationsConfig, storage_dir: &Path, outputs: &[DevlogOutput]) {
+    let notifiers = notifiers(config, storage_dir);
+    if notifiers.is_empty() {
+        return;
+    }
+    let client = match Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to create HTTP client for notifications: {}", e);
+            return;
+        }
+    };
+
+    let summaries: Vec<Summary> = outputs
+        .iter()
+        .map(|output| Summary::new(output, config.base_url.as_deref()))
+        .collect();
+    for notifier in notifiers {
+        let wanted: Vec<Summary> = summaries
+            .iter()
+            .filter(|summary| notifier.wants(&summary.project))
+            .cloned()
+            .collect();
+        let result = if wanted.len() > MAX_MESSAGES_PER_BATCH {
+            notifier.batch(&client, &wanted)
+        } else {
+            wanted.iter().try_for_each(|summary| notifier.session(&client, summary))
+        };
+        if let Err(e) = result {
+            telemetry::record_error(&e);
+            eprintln!("Failed to notify {}: {:#}", notifier.name(), e);
+        }
+    }
+}
+
+/// What a message says about a session
+#[derive(Clone)]
+pub struct Summary {
+    pub project: String,
+    pub machine: String,
+    /// The start of the session ID
+    pub session: String,
+    pub timestamp: String,
+    /// Its page on the server, when the server's address is configured
+    pub link: Option<String>,
+    /// The first line of the first prompt, shortened
+    pub first_prompt: Option<String>,
+    pub prompts: usize,
+    pub actions: usize,
+    pub tokens: u64,
+    pub branch: Option<String>,
+    /// The repository's remote URLs, `origin` first
+    pub remotes: Vec<String>,
+    /// What generic webhooks are sent
+    pub event: SessionEvent,
+}
+
+impl Summary {
+    pub fn new(output: &DevlogOutput, base_url: Option<&str>) -> Self {
+        let project = project_name(output.project_path());
+        let link = base_url.map(|base| {
+            format!(
+                "{}/session/{}/{}/{}",
+                base.trim_end_matches('/'),
+                output.machine_id,
+                project,
+                storage_filename(&output.session_id, &output.timestamp)
+            )
+        });
+        let first_prompt = output.conversation.iter().find_map(|entry| match entry {
+            ConversationEntry::User { content, .. } => {
+                Some(truncate(content.trim().lines().next().unwrap_or(""), 200))
+            }
+            _ => None,
+        });
+
+        let mut remotes: Vec<String> = Vec::new();
+        if let Some(ref git) = output.git {
+            for url in git.remote.iter().chain(git.remotes.iter().map(|r| &r.url)) {
+                if !remotes.contains(url) {
+                    remotes.push(url.clone());
+                }
+            }
+        }
+
+        let mut summary = Self {
+            project,
+            machine: output.machine_id.clone(),
+            session: output.session_id.chars().take(8).collect(),
+            timestamp: output.timestamp.clone(),
+            link: link.clone(),
+            first_prompt,
+            prompts: 0,
+            actions: 0,
+            tokens: 0,
+            branch: output.git.as_ref().map(|git| git.branch.clone()),
+            remotes,
+            event: SessionEvent {
+                url: link,
+                ..SessionEvent::new(output)
+            },
+        };
+        for entry in &output.conversation {
+            match entry {
+                ConversationEntry::User { .. } => summary.prompts += 1,
+                ConversationEntry::Assistant { usage, .. } => {
+                    summary.tokens += usage
+                        .as_ref()
+                        .map(|u| u.input_tokens.unwrap_or(0) + u.output_tokens.unwrap_or(0))
+                        .unwrap_or(0);
+                }
+                ConversationEntry::ToolSummary { actions } => summary.actions += actions.len(),
+            }
+        }
+        summary
+    }
+}
+
+/// Sessions per project and machine, for batch messages
+fn count_by_project(batch: &[Summary]) -> Vec<(&str, &str, usize)> {
+    let mut counts: Vec<(&str, &str, usize)> = Vec::new();
+    for summary in batch {
+        match counts
+            .iter_mut()
+            .find(|(project, machine, _)| *project == summary.project && *machine == summary.machine)
+        {
+            Some((_, _, count)) => *count += 1,
+            None => counts.push((&summary.project, &summary.machine, 1)),
+        }
+    }
+    counts
+}
+
+fn post(client: &Client, url: &str, body: &Value) -> Result<()> {
+    let response = client.post(url).json(body).send().context("Failed to reach the webhook")?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Webhook returned {}: {}",
+            response.status(),
+            response.text().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// A Slack incoming webhook, sent mrkdwn text
+struct Slack(Webhook);
+
+impl Notifier for Slack {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn wants(&self, project: &str) -> bool {
+        self.0.wants(project)
+    }
+
+    fn session(&self, client: &Client, summary: &Summary) -> Result<()> {
+        let session = match summary.link {
+            Some(ref link) => format!("<{}|session {}>", link, summary.session),
+            None => format!("session {}", summary.session),
+        };
+        let mut text = format!(
+            "*{}* · {} · {}",
+            slack_escape(&summary.project),
+            slack_escape(&summary.machine),
+            session
+        );
+        if let Some(ref prompt) = summary.first_prompt {
+            text.push_str(&format!("\n> {}", slack_escape(prompt)));
+        }
+        text.push_str(&format!("\n{} prompts · {} tool actions", summary.prompts, summary.actions));
+        if summary.tokens > 0 {
+            text.push_str(&format!(" · {} tokens", summary.tokens));
+        }
+        if let Some(ref branch) = summary.branch {
+            text.push_str(&format!(" · `{}`", slack_escape(branch)));
+        }
+        post(client, &self.0.webhook_url, &json!({ "text": text }))
+    }
+
+    fn batch(&self, client: &Client, batch: &[Summary]) -> Result<()> {
+        let mut text = format!("Stored a batch of {} sessions", batch.len());
+        for (project, machine, count) in count_by_project(batch) {
+            text.push_str(&format!(
+                "\n• *{}* · {} · {} sessions",
+                slack_escape(project),
+                slack_escape(machine),
+                count
+            ));
+        }
+        post(client, &self.0.webhook_url, &json!({ "text": text }))
+    }
+}
+
+/// Slack reads `&`, `<` and `>` as markup
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// A Discord webhook, sent an embed per session
+struct Discord(Webhook);
+
+impl Notifier for Discord {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn wants(&self, project: &str) -> bool {
+        self.0.wants(project)
+    }
+
+    fn session(&self, client: &Client, summary: &Summary) -> Result<()> {
+        let mut fields = vec![
+            json!({ "name": "Prompts", "value": summary.prompts.to_string(), "inline": true }),
+            json!({ "name": "Tool actions", "value": summary.actions.to_string(), "inline": true }),
+        ];
+        if summary.tokens > 0 {
+            fields.push(json!({ "name": "Tokens", "value": summary.tokens.to_string(), "inline": true }));
+        }
+        if let Some(ref branch) = summary.branch {
+            fields.push(json!({ "name": "Branch", "value": branch, "inline": true }));
+        }
+
+        let mut embed = json!({
+            "title": format!("{} · {}", summary.project, summary.machine),
+            "fields": fields,
+            "footer": { "text": format!("session {}", summary.session) },
+            "timestamp": summary.timestamp,
+        });
+        if let Some(ref prompt) = summary.first_prompt {
+            embed["description"] = json!(prompt);
+        }
+        if let Some(ref link) = summary.link {
+            embed["url"] = json!(link);
+        }
+        post(client, &self.0.webhook_url, &json!({ "embeds": [embed] }))
+    }
+
+    fn batch(&self, client: &Client, batch: &[Summary]) -> Result<()> {
+        // An embed takes at most 25 fields
+        let counts = count_by_project(batch);
+        let mut fields: Vec<Value> = counts
+            .iter()
+            .take(25)
+            .map(|(project, machine, count)| {
+                json!({ "name": project, "value": format!("{} · {} sessions", machine, count), "inline": true })
+            })
+            .collect();
+        if counts.len() > 25 {
+            fields.truncate(24);
+            fields.push(json!({ "name": "…", "value": format!("{} more projects", counts.len() - 24) }));
+        }
+        let embed = json!({
+            "title": format!("Stored a batch of {} sessions", batch.len()),
+            "fields": fields,
+        });
+        post(client, &self.0.webhook_url, &json!({ "embeds": [embed] }))
+    }
+}
+
+/// Any other endpoint, POSTed a [`SessionEvent`] for every session, even in
+/// large batches, as automation wants each one rather than a roll-up
+struct Generic(Webhook);
+
+impl Notifier for Generic {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn wants(&self, project: &str) -> bool {
+        self.0.wants(project)
+    }
+
+    fn session(&self, client: &Client, summary: &Summary) -> Result<()> {
+        deliver(client, &self.0.webhook_url, &summary.event)
+    }
+
+    fn batch(&self, client: &Client, batch: &[Summary]) -> Result<()> {
+        batch.iter().try_for_each(|summary| self.session(client, summary))
+    }
+}
+
+/// POST `event` to `url`, retrying while the endpoint is down or busy
+fn deliver(client: &Client, url: &str, event: &SessionEvent) -> Result<()> {
+    let mut delays = webhook::RETRY_DELAYS.iter();
+    loop {
+        let error = match client.post(url).json(event).send() {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) if !webhook::is_retryable(response.status().as_u16()) => anyhow::bail!(
+                "{} returned {}: {}",
+                url,
+                response.status(),
+                response.text().unwrap_or_default()
+            ),
+            Ok(response) => anyhow::anyhow!("{} returned {}", url, response.status()),
+            Err(e) => anyhow::Error::new(e).context(format!("Failed to reach {}", url)),
+        };
+        match delays.next() {
+            Some(delay) => std::thread::sleep(*delay),
+            None => {
+                return Err(error.context(format!(
+                    "Gave up on session {} after {} retries",
+                    event.session_id,
+                    webhook::RETRY_DELAYS.len()
+                )))
+            }
+        }
+    }
+}
+
+/// Marks the comment on a pull request that devlog keeps up to date
+const COMMENT_MARKER: &str = "<!-- devlog:sessions -->";
+
+/// Most sessions listed in a pull request comment; the oldest are left out
+const MAX_COMMENT_SESSIONS: usize = 50;
+
+/// Keeps one comment on the open pull request of each session's branch up to
+/// date, listing every session stored for that branch
+struct GitHub {
+    token: String,
+    api_url: String,
+    storage_dir: PathBuf,
+    base_url: Option<String>,
+}
+
+impl GitHub {
+    fn new(config: &GitHubConfig, storage_dir: &Path, base_url: Option<String>) -> Result<Self> {
+        let token_env = config.token_env.as_deref().unwrap_or("GITHUB_TOKEN");
+        let token = std::env::var(token_env).with_context(|| format!("{} is not set", token_env))?;
+        Ok(Self {
+            token,
+            api_url: config
+                .api_url
+                .as_deref()
+                .unwrap_or("https://api.github.com")
+                .trim_end_matches('/')
+                .to_string(),
+            storage_dir: storage_dir.to_path_buf(),
+            base_url,
+        })
+    }
+
+    /// Update the pull request of every branch in `summaries`, once per branch
+    fn update(&self, client: &Client, summaries: &[Summary]) -> Result<()> {
+        let mut seen: Vec<(String, &str)> = Vec::new();
+        for summary in summaries {
+            let (Some(branch), Some(origin)) = (summary.branch.as_deref(), summary.remotes.first()) else {
+                continue;
+            };
+            let key = (forge::normalize_remote(origin), branch);
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            if let Err(e) = self.update_branch(client, &summary.remotes, branch) {
+                eprintln!("Failed to comment on the pull request for {}: {:#}", branch, e);
+            }
+        }
+        Ok(())
+    }
+
+    fn update_branch(&self, client: &Client, remotes: &[String], branch: &str) -> Result<()> {
+        let Some((head_owner, _)) = forge::github_repo(&remotes[0]) else {
+            return Ok(());
+        };
+        // A pull request from a fork is opened against its upstream, so look in every GitHub remote
+        for remote in remotes {
+            let Some((owner, repo)) = forge::github_repo(remote) else {
+                continue;
+            };
+            let pulls = self.send(
+                self.request(client, Method::GET, &format!("/repos/{}/{}/pulls", owner, repo))
+                    .query(&[("head", format!("{}:{}", head_owner, branch)), ("state", "open".to_string())]),
+            )?;
+            let Some(number) = pulls.get(0).and_then(|pull| pull.get("number")).and_then(Value::as_u64) else {
+                continue;
+            };
+
+            let body = json!({ "body": self.render_comment(&remotes[0], branch)? });
+            match self.find_comment(client, &owner, &repo, number)? {
+                Some(id) => {
+                    let path = format!("/repos/{}/{}/issues/comments/{}", owner, repo, id);
+                    self.send(self.request(client, Method::PATCH, &path).json(&body))?;
+                    eprintln!("Updated devlog comment on {}/{}#{}", owner, repo, number);
+                }
+                None => {
+                    let path = format!("/repos/{}/{}/issues/{}/comments", owner, repo, number);
+                    self.send(self.request(client, Method::POST, &path).json(&body))?;
+                    eprintln!("Commented on {}/{}#{}", owner, repo, number);
+                }
+            }
+            return Ok(());
+        }
+        Ok(())
+    }
+
+    /// The ID of the comment devlog left on a pull request before, if any
+    fn find_comment(&self, client: &Client, owner: &str, repo: &str, number: u64) -> Result<Option<u64>> {
+        for page in 1.. {
+            let comments = self.send(
+                self.request(client, Method::GET, &format!("/repos/{}/{}/issues/{}/comments", owner, repo, number))
+                    .query(&[("per_page", 100), ("page", page)]),
+            )?;
+            let Some(comments) = comments.as_array().filter(|c| !c.is_empty()) else {
+                break;
+            };
+            let found = comments.iter().find(|comment| {
+                comment.get("body").and_then(Value::as_str).map(|b| b.contains(COMMENT_MARKER)).unwrap_or(false)
+            });
+            if let Some(comment) = found {
+                return Ok(comment.get("id").and_then(Value::as_u64));
+            }
+        }
+        Ok(None)
+    }
+
+    /// A table of the stored sessions on `branch` of the repository at `origin`
+    fn render_comment(&self, origin: &str, branch: &str) -> Result<String> {
+        let origin = forge::normalize_remote(origin);
+        let mut sessions: Vec<Summary> = server::latest_sessions(&self.storage_dir)?
+            .iter()
+            .map(|output| Summary::new(output, self.base_url.as_deref()))
+            .filter(|summary| {
+                summary.branch.as_deref() == Some(branch)
+                    && summary.remotes.first().map(|r| forge::normalize_remote(r)) == Some(origin.clone())
+            })
+            .collect();
+        // Oldest first, as the branch's history reads
+        sessions.reverse();
+
+        let prompts: usize = sessions.iter().map(|s| s.prompts).sum();
+        let actions: usize = sessions.iter().map(|s| s.actions).sum();
+        let tokens: u64 = sessions.iter().map(|s| s.tokens).sum();
+        let mut body = format!("{}\n### Assistant sessions on `{}`\n\n", COMMENT_MARKER, branch);
+        body.push_str(&format!(
+            "{} sessions: {} prompts, {} tool actions, {} tokens.\n\n",
+            sessions.len(),
+            prompts,
+            actions,
+            tokens
+        ));
+
+        let skipped = sessions.len().saturating_sub(MAX_COMMENT_SESSIONS);
+        if skipped > 0 {
+            body.push_str(&format!("The {} earliest are not listed.\n\n", skipped));
+        }
+        body.push_str("| Session | Machine | Date | Prompts | Tool actions | First prompt |\n");
+        body.push_str("| --- | --- | --- | ---: | ---: | --- |\n");
+        for summary in &sessions[skipped..] {
+            let session = match summary.link {
+                Some(ref link) => format!("[{}]({})", summary.session, link),
+                None => format!("`{}`", summary.session),
+            };
+            body.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                session,
+                table_escape(&summary.machine),
+                summary.timestamp.get(..10).unwrap_or(&summary.timestamp),
+                summary.prompts,
+                summary.actions,
+                table_escape(summary.first_prompt.as_deref().unwrap_or(""))
+            ));
+        }
+        body.push_str("\n<sub>Kept up to date by devlog</sub>\n");
+        Ok(body)
+    }
+
+    fn request(&self, client: &Client, method: Method, path: &str) -> RequestBuilder {
+        client
+            .request(method, format!("{}{}", self.api_url, path))
+            .bearer_auth(&self.token)
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "devlog")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+
+    fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let response = request.send().context("Failed to reach the GitHub API")?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "GitHub returned {}: {}",
+                response.status(),
+                response.text().unwrap_or_default()
+            );
+        }
+        response.json().context("Invalid response from the GitHub API")
+    }
+}
+
+impl Notifier for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn wants(&self, _project: &str) -> bool {
+        true
+    }
+
+    fn session(&self, client: &Client, summary: &Summary) -> Result<()> {
+        self.update(client, std::slice::from_ref(summary))
+    }
+
+    fn batch(&self, client: &Client, batch: &[Summary]) -> Result<()> {
+        self.update(client, batch)
+    }
+}
+
+/// Text for a Markdown table cell
+fn table_escape(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,174 @@
+use crate::config::ObsidianConfig;
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use anyhow::{Context, Result};
+use devlog_core::store::project_name;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Write a session as a note under `<vault>/<folder>/<project>/`, and refresh the
+/// project's hub note that links all of its sessions. Returns the note's path.
+///
+/// Notes are named by start time, so a project's folder sorts chronologically and
+/// each note can link to the one before it.
+pub fn write_note(config: &ObsidianConfig, output: &DevlogOutput) -> Result<PathBuf> {
+    let project = project_name(output.project_path());
+    let project_dir = config.vault.join(&config.folder).join(&project);
+    fs::create_dir_all(&project_dir)
+        .with_context(|| format!("Failed to create vault folder: {}", project_dir.display()))?;
+
+    let name = note_name(output);
+    let previous = session_notes(&project_dir, &project)?
+        .into_iter()
+        .rfind(|n| *n < name);
+
+    let note = render_note(output, &link_base(config, &project), &project, &name, previous.as_deref());
+    let path = project_dir.join(format!("{}.md", name));
+    fs::write(&path, note).with_context(|| format!("Failed to write note: {}", path.display()))?;
+
+    let sessions = session_notes(&project_dir, &project)?;
+    let hub = render_hub(&link_base(config, &project), &project, &sessions);
+    let hub_path = project_dir.join(format!("{}.md", project));
+    fs::write(&hub_path, hub).with_context(|| format!("Failed to write note: {}", hub_path.display()))?;
+
+    Ok(path)
+}
+
+/// Write the note on ingest, if enabled in config
+pub fn export_session(output: &DevlogOutput) -> Result<()> {
+    let config = crate::config::Config::load()?;
+    let obsidian = match config.obsidian {
+        Some(ref oc) if oc.enabled => oc,
+        _ => return Ok(()),
+    };
+
+    let path = write_note(obsidian, output)?;
+    eprintln!("Wrote Obsidian note: {}", path.display());
+    Ok(())
+}
+
+/// `YYYY-MM-DD HHMM <session_id_short>`
+fn note_name(output: &DevlogOutput) -> String {
+    let started = chrono::DateTime::parse_from_rfc3339(&output.timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H%M").to_string())
+        .unwrap_or_else(|_| output.timestamp.clone());
+    let short_id: String = output.session_id.chars().take(8).collect();
+    format!("{} {}", started, short_id)
+}
+
+/// Vault-relative folder of a project's notes. Links use it in full, since note
+/// names are only unique within a project.
+fn link_base(config: &ObsidianConfig, project: &str) -> String {
+    let folder = config.folder.trim_matches('/');
+    if folder.is_empty() {
+        project.to_string()
+    } else {
+        format!("{}/{}", folder, project)
+    }
+}
+
+/// Names of the session notes in a project folder, oldest first
+fn session_notes(project_dir: &Path, project: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(project_dir)
+        .with_context(|| format!("Failed to read directory: {}", project_dir.display()))?
+    {
+        let path = entry?.path();
+        if path.extension().map(|e| e == "md").unwrap_or(false) {
+            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                if stem != project {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn render_note(output: &DevlogOutput, base: &str, project: &str, name: &str, previous: Option<&str>) -> String {
+    let mut note = String::from("---\n");
+    note.push_str(&format!("project: {}\n", yaml_string(project)));
+    // A bare date, so Obsidian treats the property as a date
+    match chrono::DateTime::parse_from_rfc3339(&output.timestamp) {
+        Ok(dt) => note.push_str(&format!("date: {}\n", dt.format("%Y-%m-%d"))),
+        Err(_) => note.push_str(&format!("date: {}\n", yaml_string(&output.timestamp))),
+    }
+    note.push_str(&format!("session: {}\n", yaml_string(&output.session_id)));
+    note.push_str(&format!("machine: {}\n", yaml_string(&output.machine_id)));
+    if let Some(ref git) = output.git {
+        note.push_str(&format!("branch: {}\n", yaml_string(&git.branch)));
+        note.push_str(&format!("commit: {}\n", yaml_string(&git.commit)));
+        if let Some(ref remote) = git.remote {
+            note.push_str(&format!("remote: {}\n", yaml_string(remote)));
+        }
+    }
+    note.push_str("tags:\n  - devlog\n");
+    note.push_str(&format!("  - {}\n", yaml_string(&format!("devlog/{}", tag(project)))));
+    for session_tag in &output.tags {
+        note.push_str(&format!("  - {}\n", yaml_string(&tag(session_tag))));
+    }
+    note.push_str("---\n\n");
+
+    note.push_str(&format!("# {}\n\n", name));
+    note.push_str(&format!("Project: [[{}/{}|{}]]", base, project, project));
+    if let Some(previous) = previous {
+        note.push_str(&format!(" · Previous: [[{}/{}|{}]]", base, previous, previous));
+    }
+    note.push_str("\n\n");
+
+    for annotation in &output.notes {
+        let added = chrono::DateTime::parse_from_rfc3339(&annotation.timestamp)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| annotation.timestamp.clone());
+        note.push_str(&format!("> [!note] {}\n", added));
+        for line in annotation.text.trim().lines() {
+            note.push_str(&format!("> {}\n", line));
+        }
+        note.push('\n');
+    }
+
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => {
+                note.push_str(&format!("## You{}\n\n{}\n\n", entry_time(timestamp.as_deref()), content.trim()));
+            }
+            ConversationEntry::Assistant { content, timestamp, .. } => {
+                note.push_str(&format!("## Claude{}\n\n{}\n\n", entry_time(timestamp.as_deref()), content.trim()));
+            }
+            ConversationEntry::ToolSummary { actions } => {
+                note.push_str(&format!("> Tools: {}\n\n", actions.join(", ")));
+            }
+        }
+    }
+
+    note
+}
+
+/// The project's note: every session, newest first
+fn render_hub(base: &str, project: &str, sessions: &[String]) -> String {
+    let mut hub = format!("---\ntags:\n  - devlog\n  - {}\n---\n\n# {}\n\n", yaml_string(&format!("devlog/{}", tag(project))), project);
+    for session in sessions.iter().rev() {
+        hub.push_str(&format!("- [[{}/{}|{}]]\n", base, session, session));
+    }
+    hub
+}
+
+fn entry_time(timestamp: Option<&str>) -> String {
+    timestamp
+        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| format!(" · {}", dt.format("%H:%M")))
+        .unwrap_or_default()
+}
+
+/// Obsidian tags cannot contain spaces or most punctuation
+fn tag(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
+
+/// A double-quoted YAML scalar; JSON string syntax is valid YAML
+fn yaml_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| format!("\"{}\"", s))
+}
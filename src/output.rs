@@ -1,33 +1,25 @@
-use crate::git::GitInfo;
-use crate::parser::ConversationEntry;
 use anyhow::{Context, Result};
-use chrono::Utc;
-use serde::{Deserialize, Serialize};
+use devlog_core::store::storage_filename;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DevlogOutput {
-    pub schema_version: String,
-    pub session_id: String,
-    pub timestamp: String,
-    pub machine_id: String,
-    pub project_dir: String,
-    pub git: Option<GitInfo>,
-    pub conversation: Vec<ConversationEntry>,
-}
+pub use devlog_core::DevlogOutput;
 
 /// Write the devlog output to the .devlog directory
 pub fn write_output(output: &DevlogOutput) -> Result<PathBuf> {
-    // Determine output directory
-    let output_dir = get_output_dir(&output.project_dir)?;
+    write_output_to(&get_output_dir(&output.project_dir)?, output)
+}
 
+/// Write the devlog output to a given `.devlog` directory, e.g. for a session
+/// fetched from the server whose project_dir is on another machine
+pub fn write_output_to(output_dir: &Path, output: &DevlogOutput) -> Result<PathBuf> {
     // Ensure directory exists
-    fs::create_dir_all(&output_dir)
+    fs::create_dir_all(output_dir)
         .with_context(|| format!("Failed to create output directory: {}", output_dir.display()))?;
 
-    // Generate filename: YYYY-MM-DD-HHMMSS-<session_id_short>.json
-    let filename = generate_filename(&output.session_id);
+    // Generate filename: YYYY-MM-DD-HHMMSS-<session_id_short>.json, dated by the
+    // output's timestamp so imported sessions are filed under when they happened
+    let filename = storage_filename(&output.session_id, &output.timestamp);
     let output_path = output_dir.join(&filename);
 
     // Serialize to JSON
@@ -41,20 +33,68 @@ pub fn write_output(output: &DevlogOutput) -> Result<PathBuf> {
     Ok(output_path)
 }
 
-fn get_output_dir(project_dir: &str) -> Result<PathBuf> {
-    let mut path = PathBuf::from(project_dir);
-    path.push(".devlog");
-    Ok(path)
+/// Overwrite an existing devlog file, e.g. after adding tags
+pub fn rewrite_output(path: &Path, output: &DevlogOutput) -> Result<()> {
+    devlog_core::store::rewrite_session(path, output)
+}
+
+/// The most recent devlog already written for a session in a `.devlog`
+/// directory, so that what was added to it by hand (such as tags) carries over
+/// when the session is ingested again
+pub fn find_previous_in(output_dir: &Path, session_id: &str) -> Option<DevlogOutput> {
+    fs::read_dir(output_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "json").unwrap_or(false))
+        .filter_map(|path| crate::search::read_devlog(&path).ok())
+        .filter(|output| output.session_id == session_id)
+        .max_by(|a, b| a.timestamp.cmp(&b.timestamp))
 }
 
-fn generate_filename(session_id: &str) -> String {
-    let now = Utc::now();
-    let date_part = now.format("%Y-%m-%d-%H%M%S");
+/// Devlog files of a session, given either a path to one of them or a session ID
+/// (or a prefix of one) in the `.devlog` directory. A session ingested several
+/// times has several files, and all of them are returned.
+pub fn find_session_files(devlog_dir: &Path, session: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(session);
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut matches: Vec<(PathBuf, String)> = Vec::new();
+    if devlog_dir.exists() {
+        for entry in fs::read_dir(devlog_dir)
+            .with_context(|| format!("Failed to read directory: {}", devlog_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                if let Ok(output) = crate::search::read_devlog(&path) {
+                    if output.session_id.starts_with(session) {
+                        matches.push((path, output.session_id));
+                    }
+                }
+            }
+        }
+    }
 
-    // Shorten session_id for filename
-    let short_id: String = session_id.chars().take(8).collect();
+    let mut ids: Vec<&str> = matches.iter().map(|(_, id)| id.as_str()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    match ids.len() {
+        0 => anyhow::bail!("No devlog found for session '{}'", session),
+        1 => {
+            let mut files: Vec<PathBuf> = matches.into_iter().map(|(path, _)| path).collect();
+            files.sort();
+            Ok(files)
+        }
+        _ => anyhow::bail!("Session '{}' is ambiguous (matches {})", session, ids.join(", ")),
+    }
+}
 
-    format!("{}-{}.json", date_part, short_id)
+fn get_output_dir(project_dir: &str) -> Result<PathBuf> {
+    let mut path = PathBuf::from(project_dir);
+    path.push(".devlog");
+    Ok(path)
 }
 
 /// Get a stable machine identifier (hostname)
@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use crate::source::{self, ConversationBuilder, Session, SessionSource};
 
 /// Raw entry from Claude Code JSONL file
 #[derive(Debug, Deserialize)]
@@ -28,7 +30,6 @@ pub enum MessageContent {
 #[derive(Debug, Deserialize)]
 pub struct MessageObject {
     pub content: Option<ContentType>,
-    pub tool_use: Option<Vec<ToolUse>>,
     #[serde(flatten)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
@@ -47,46 +48,9 @@ pub struct ContentBlock {
     pub text: Option<String>,
     pub name: Option<String>,
     pub input: Option<serde_json::Value>,
-    #[serde(flatten)]
-    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ToolUse {
-    pub name: Option<String>,
-    #[serde(rename = "type")]
-    pub tool_type: Option<String>,
-    pub input: Option<serde_json::Value>,
-}
-
-/// Token usage information from Claude API
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct TokenUsage {
-    pub input_tokens: Option<u64>,
-    pub output_tokens: Option<u64>,
-    pub cache_creation_input_tokens: Option<u64>,
-    pub cache_read_input_tokens: Option<u64>,
-}
-
-/// Output conversation entry
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(tag = "type")]
-pub enum ConversationEntry {
-    #[serde(rename = "user")]
-    User {
-        timestamp: Option<String>,
-        content: String,
-    },
-    #[serde(rename = "assistant")]
-    Assistant {
-        timestamp: Option<String>,
-        content: String,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        usage: Option<TokenUsage>,
-    },
-    #[serde(rename = "tool_summary")]
-    ToolSummary { actions: Vec<String> },
-}
+pub use devlog_core::{ConversationEntry, TokenUsage};
 
 /// Parse a JSONL session file into raw entries
 pub fn parse_session_file(path: &Path) -> Result<Vec<RawEntry>> {
@@ -100,7 +64,7 @@ pub fn parse_session_file(path: &Path) -> Result<Vec<RawEntry>> {
             continue;
         }
 
-        match serde_json::from_str::<RawEntry>(&line) {
+        match parse_line(&line) {
             Ok(entry) => entries.push(entry),
             Err(e) => {
                 eprintln!(
@@ -115,58 +79,162 @@ pub fn parse_session_file(path: &Path) -> Result<Vec<RawEntry>> {
     Ok(entries)
 }
 
+/// One transcript line. simd-json is tried first; a line it rejects is
+/// parsed again by serde_json, which has the final say and the clearer error.
+#[cfg(all(feature = "simd-json", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn parse_line(line: &str) -> serde_json::Result<RawEntry> {
+    // simd-json parses in place, so it gets a copy
+    let mut bytes = line.as_bytes().to_vec();
+    simd_json::serde::from_slice::<RawEntry>(&mut bytes).or_else(|_| serde_json::from_str(line))
+}
+
+/// One transcript line
+#[cfg(not(all(feature = "simd-json", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn parse_line(line: &str) -> serde_json::Result<RawEntry> {
+    serde_json::from_str(line)
+}
+
+/// Working directory Claude Code recorded for the session (the first entry's `cwd`)
+pub fn session_cwd(entries: &[RawEntry]) -> Option<String> {
+    entries
+        .iter()
+        .find_map(|e| e.extra.get("cwd").and_then(|v| v.as_str()))
+        .filter(|cwd| !cwd.is_empty())
+        .map(String::from)
+}
+
+/// Claude Code's JSONL transcripts, the first source devlog read
+pub struct Claude;
+
+impl SessionSource for Claude {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn description(&self) -> &'static str {
+        "Claude Code JSONL transcripts (~/.claude/projects)"
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        // Entries carry the session ID, or in older transcripts at least a message object
+        source::has_extension(path, "jsonl")
+            && source::peek(path)
+                .map(|head| head.contains("\"sessionId\"") || head.contains("\"message\":{"))
+                .unwrap_or(false)
+    }
+
+    fn parse(&self, path: &Path) -> Result<Vec<Session>> {
+        let entries = parse_session_file(path)
+            .with_context(|| format!("Failed to parse session file: {}", path.display()))?;
+        Ok(vec![Session {
+            id: source::id_from_path(path),
+            dir: session_cwd(&entries).map(PathBuf::from),
+            transcript_path: Some(path.to_path_buf()),
+            conversation: filter_to_conversation(entries),
+            ..Default::default()
+        }])
+    }
+
+    /// Sessions are the default, so they go untagged
+    fn tag(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn project_dir_var(&self) -> Option<&'static str> {
+        Some("CLAUDE_PROJECT_DIR")
+    }
+
+    fn most_recent(&self) -> Result<PathBuf> {
+        let home = std::env::var("HOME").context("HOME not set")?;
+        let claude_dir = PathBuf::from(home).join(".claude").join("projects");
+
+        if !claude_dir.exists() {
+            anyhow::bail!("No Claude directory found at {}", claude_dir.display());
+        }
+
+        let mut most_recent: Option<(PathBuf, std::time::SystemTime)> = None;
+
+        fn find_jsonl_files(dir: &PathBuf, most_recent: &mut Option<(PathBuf, std::time::SystemTime)>) {
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.is_dir() {
+                        find_jsonl_files(&path, most_recent);
+                    } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
+                        if let Ok(meta) = path.metadata() {
+                            if let Ok(modified) = meta.modified() {
+                                match most_recent {
+                                    Some((_, ref time)) if modified > *time => {
+                                        *most_recent = Some((path, modified));
+                                    }
+                                    None => {
+                                        *most_recent = Some((path, modified));
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        find_jsonl_files(&claude_dir, &mut most_recent);
+
+        most_recent
+            .map(|(path, _)| path)
+            .context("No session files found")
+    }
+}
+
+/// Timestamps of the first and last timestamped entries
+pub fn conversation_span(conversation: &[ConversationEntry]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut times = conversation.iter().filter_map(|entry| match entry {
+        ConversationEntry::User { timestamp, .. } | ConversationEntry::Assistant { timestamp, .. } => timestamp
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+        ConversationEntry::ToolSummary { .. } => None,
+    });
+
+    let first = times.next()?;
+    let (start, end) = times.fold((first, first), |(lo, hi), t| (lo.min(t), hi.max(t)));
+    Some((start, end))
+}
+
 /// Filter and transform raw entries into conversation entries
 pub fn filter_to_conversation(entries: Vec<RawEntry>) -> Vec<ConversationEntry> {
-    let mut conversation = Vec::new();
-    let mut pending_tools: Vec<String> = Vec::new();
+    let mut conversation = ConversationBuilder::new();
 
     for entry in entries {
         match entry.entry_type.as_str() {
             "human" | "user" => {
-                // Flush pending tools
-                flush_tool_summary(&mut conversation, &mut pending_tools);
-
+                // Every entry closes the tool calls before it, even one without text
+                conversation.end_tools();
                 let content = extract_content(&entry);
-                if !content.is_empty() {
-                    conversation.push(ConversationEntry::User {
-                        timestamp: entry.timestamp,
-                        content,
-                    });
-                }
+                conversation.user(entry.timestamp.clone(), content, entry_uuid(&entry));
             }
             "assistant" => {
-                // Flush pending tools before new assistant message
-                flush_tool_summary(&mut conversation, &mut pending_tools);
-
+                conversation.end_tools();
                 let content = extract_content(&entry);
-                if !content.is_empty() {
-                    let usage = extract_usage(&entry);
-                    conversation.push(ConversationEntry::Assistant {
-                        timestamp: entry.timestamp,
-                        content,
-                        usage,
-                    });
-                }
+                let usage = extract_usage(&entry);
+                conversation.assistant(entry.timestamp.clone(), content, usage, entry_uuid(&entry));
 
                 // Check for tool_use blocks in message content
                 if let Some(MessageContent::Object(ref msg)) = entry.message {
-                    if let Some(ref content) = msg.content {
-                        if let ContentType::Blocks(blocks) = content {
-                            for block in blocks {
-                                if block.block_type == "tool_use" {
-                                    if let Some(action) = summarize_tool_use_from_block(block) {
-                                        pending_tools.push(action);
-                                    }
-                                }
-                            }
-                        }
+                    if let Some(ContentType::Blocks(ref blocks)) = msg.content {
+                        conversation.tools(
+                            blocks
+                                .iter()
+                                .filter(|block| block.block_type == "tool_use")
+                                .filter_map(summarize_tool_use_from_block),
+                        );
                     }
                 }
             }
             "tool_use" => {
-                let action = summarize_tool_use(&entry);
-                if let Some(action) = action {
-                    pending_tools.push(action);
+                if let Some(action) = summarize_tool_use(&entry) {
+                    conversation.tool(action);
                 }
             }
             "tool_result" => {
@@ -178,18 +246,7 @@ pub fn filter_to_conversation(entries: Vec<RawEntry>) -> Vec<ConversationEntry>
         }
     }
 
-    // Flush any remaining tools
-    flush_tool_summary(&mut conversation, &mut pending_tools);
-
-    conversation
-}
-
-fn flush_tool_summary(conversation: &mut Vec<ConversationEntry>, pending_tools: &mut Vec<String>) {
-    if !pending_tools.is_empty() {
-        conversation.push(ConversationEntry::ToolSummary {
-            actions: pending_tools.drain(..).collect(),
-        });
-    }
+    conversation.finish()
 }
 
 fn extract_content(entry: &RawEntry) -> String {
@@ -224,6 +281,12 @@ fn extract_content(entry: &RawEntry) -> String {
     String::new()
 }
 
+/// Claude Code's ID of a transcript entry; the same entry keeps it when a
+/// resumed session copies earlier history into a new transcript
+fn entry_uuid(entry: &RawEntry) -> Option<String> {
+    entry.extra.get("uuid").and_then(|v| v.as_str()).map(String::from)
+}
+
 fn extract_usage(entry: &RawEntry) -> Option<TokenUsage> {
     // Look for usage in message.usage or extra.message.usage
     if let Some(MessageContent::Object(ref msg)) = entry.message {
@@ -245,11 +308,6 @@ fn summarize_tool_use(entry: &RawEntry) -> Option<String> {
     Some(format_tool_action(tool_name, input))
 }
 
-fn summarize_tool_use_from_tool(tool: &ToolUse) -> Option<String> {
-    let tool_name = tool.name.as_ref().or(tool.tool_type.as_ref())?;
-    Some(format_tool_action(tool_name, tool.input.as_ref()))
-}
-
 fn summarize_tool_use_from_block(block: &ContentBlock) -> Option<String> {
     let tool_name = block.name.as_ref()?;
     Some(format_tool_action(tool_name, block.input.as_ref()))
@@ -320,10 +378,11 @@ fn extract_url(input: Option<&serde_json::Value>) -> String {
         .to_string()
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
+/// At most `max_len` characters of `s`, ending in `...` when cut short
+pub fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len - 3])
+        format!("{}...", s.chars().take(max_len - 3).collect::<String>())
     }
 }
@@ -0,0 +1,152 @@
+use crate::config::{Config, PiiConfig};
+use crate::redact;
+use crate::search;
+use anyhow::{Context, Result};
+use devlog_core::store::project_name;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Personal data recognised by its shape. Where a pattern has a group, only the
+/// group is reported.
+const PATTERNS: &[(&str, &str)] = &[
+    ("email", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b"),
+    // `(555) 123-4567`, `555.123.4567`, `+44 20 7946 0958`; dates and versions
+    // lack the separators, and the digit count is checked after matching
+    (
+        "phone",
+        r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?|\b\d{2,4}[\s.-])\d{3,4}[\s.-]\d{3,4}\b",
+    ),
+    // Names are guessed from how they are introduced: a title, or "my name is"
+    (
+        "name",
+        r"\b(?:Mr|Mrs|Ms|Miss|Dr|Prof)\.?\s+([A-Z][a-z]+(?:[ -][A-Z][a-z]+)?)",
+    ),
+    ("name", r"(?i:\bmy name is)\s+([A-Z][a-z]+(?: [A-Z][a-z]+)?)"),
+];
+
+/// Phone numbers have at least this many digits, which keeps out most IDs and times
+const PHONE_MIN_DIGITS: usize = 10;
+const PHONE_MAX_DIGITS: usize = 15;
+
+/// The built-in patterns, plus those and the names under `[pii]`
+pub struct Detector {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl Detector {
+    pub fn new(config: &PiiConfig) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for (kind, pattern) in PATTERNS {
+            let regex = Regex::new(pattern).with_context(|| format!("Invalid {} pattern", kind))?;
+            patterns.push((kind.to_string(), regex));
+        }
+        for (kind, pattern) in &config.patterns {
+            let regex = Regex::new(pattern).with_context(|| format!("Invalid pii pattern {}: {}", kind, pattern))?;
+            patterns.push((kind.clone(), regex));
+        }
+        if !config.names.is_empty() {
+            let names: Vec<String> = config.names.iter().map(|n| regex::escape(n.trim())).collect();
+            let regex = Regex::new(&format!(r"(?i)\b(?:{})\b", names.join("|"))).context("Invalid pii names")?;
+            patterns.push(("name".to_string(), regex));
+        }
+        Ok(Self { patterns })
+    }
+
+    /// The patterns in the user's config; only the built-in ones when it has no `[pii]` section
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load pii patterns")?;
+        Self::new(&config.pii.unwrap_or_default())
+    }
+
+    /// Each piece of personal data in `text`, with its kind
+    fn find<'t>(&self, text: &'t str) -> Vec<(&str, &'t str)> {
+        let mut found = Vec::new();
+        for (kind, regex) in &self.patterns {
+            for captures in regex.captures_iter(text) {
+                let value = captures.get(1).or_else(|| captures.get(0)).expect("match has a group 0").as_str();
+                if kind == "phone" && !(PHONE_MIN_DIGITS..=PHONE_MAX_DIGITS).contains(&digits(value)) {
+                    continue;
+                }
+                found.push((kind.as_str(), value));
+            }
+        }
+        found
+    }
+}
+
+fn digits(text: &str) -> usize {
+    text.bytes().filter(u8::is_ascii_digit).count()
+}
+
+/// What was found in one project's sessions
+#[derive(Default)]
+struct ProjectReport {
+    sessions: usize,
+    flagged: usize,
+    /// Each kind's distinct values, and how often each occurred
+    kinds: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// Report the personal data in devlog files project by project: how many
+/// sessions hold any, and how many matches and distinct values of each kind.
+/// With `values`, each distinct value is listed with its count.
+pub fn report_files(detector: &Detector, files: &[PathBuf], values: bool) -> Result<()> {
+    let mut projects: BTreeMap<String, ProjectReport> = BTreeMap::new();
+    for file in files {
+        let mut output = match search::read_devlog(file) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let report = projects.entry(project_name(&output.project_dir)).or_default();
+        report.sessions += 1;
+        let mut flagged = false;
+        redact::for_each_text(&mut output, |_, text| {
+            for (kind, value) in detector.find(text) {
+                flagged = true;
+                *report
+                    .kinds
+                    .entry(kind.to_string())
+                    .or_default()
+                    .entry(value.to_string())
+                    .or_default() += 1;
+            }
+        });
+        if flagged {
+            report.flagged += 1;
+        }
+    }
+
+    for (project, report) in &projects {
+        if report.kinds.is_empty() {
+            println!("{}: nothing found in {} sessions", project, report.sessions);
+            continue;
+        }
+        let kinds: Vec<String> = report
+            .kinds
+            .iter()
+            .map(|(kind, found)| format!("{} {} ({} distinct)", kind, found.values().sum::<usize>(), found.len()))
+            .collect();
+        println!("{}: {} of {} sessions; {}", project, report.flagged, report.sessions, kinds.join(", "));
+        if values {
+            for (kind, found) in &report.kinds {
+                for (value, count) in found {
+                    println!("  {} {}: {}", kind, value, count);
+                }
+            }
+        }
+    }
+
+    let flagged: usize = projects.values().map(|r| r.flagged).sum();
+    println!(
+        "Found personal data in {} of {} devlogs across {} projects",
+        flagged,
+        files.len(),
+        projects.values().filter(|r| r.flagged > 0).count()
+    );
+    Ok(())
+}
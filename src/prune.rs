@@ -0,0 +1,149 @@
+use crate::config::Config;
+use crate::output::DevlogOutput;
+use crate::{push, search};
+use anyhow::{Context, Result};
+use devlog_core::{RetentionPolicy, SessionFile};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// A devlog file outside the retention policy
+struct Pruned {
+    file: SessionFile,
+    /// `None` when the file could not be read as a devlog
+    session: Option<(String, String)>,
+}
+
+/// Delete the devlogs in `devlog_dir` outside `policy`, or only list them with
+/// `dry_run`. With `remote`, sessions left with no devlog here are deleted from
+/// the push endpoint's server as well.
+pub fn prune(devlog_dir: &Path, policy: &RetentionPolicy, dry_run: bool, remote: bool) -> Result<()> {
+    let mut files = Vec::new();
+    for file in search::local_files(devlog_dir)? {
+        files.push(SessionFile::read(file.path)?);
+    }
+    let total = files.len();
+    let selected = policy.select(files.clone());
+    if selected.is_empty() {
+        println!("Nothing to prune ({} devlogs kept)", total);
+        return Ok(());
+    }
+
+    let pruned: Vec<Pruned> = selected
+        .into_iter()
+        .map(|file| {
+            let session = search::read_devlog(&file.path)
+                .ok()
+                .map(|output: DevlogOutput| (output.machine_id, output.session_id));
+            Pruned { file, session }
+        })
+        .collect();
+
+    // A session is ingested again on each compaction; an older copy can go while
+    // the latest stays, and then the session must stay on the server too
+    let kept: BTreeSet<(String, String)> = files
+        .iter()
+        .filter(|f| !pruned.iter().any(|p| p.file.path == f.path))
+        .filter_map(|f| search::read_devlog(&f.path).ok())
+        .map(|output| (output.machine_id, output.session_id))
+        .collect();
+    let remote_sessions: BTreeSet<(String, String)> = pruned
+        .iter()
+        .filter_map(|p| p.session.clone())
+        .filter(|session| !kept.contains(session))
+        .collect();
+
+    let bytes: u64 = pruned.iter().map(|p| p.file.size).sum();
+    println!(
+        "{} {} of {} devlogs ({}):",
+        if dry_run { "Would delete" } else { "Deleting" },
+        pruned.len(),
+        total,
+        format_size(bytes)
+    );
+    for p in &pruned {
+        let name = p.file.path.file_name().unwrap_or_default().to_string_lossy();
+        let session = p
+            .session
+            .as_ref()
+            .map(|(_, id)| format!("session {}", id))
+            .unwrap_or_else(|| "unreadable".to_string());
+        println!(
+            "  {}  {:>9}  {}  {}",
+            p.file.timestamp.format("%Y-%m-%d %H:%M"),
+            format_size(p.file.size),
+            name,
+            session
+        );
+    }
+    if remote {
+        println!(
+            "{} {} sessions from the server",
+            if dry_run { "Would delete" } else { "Deleting" },
+            remote_sessions.len()
+        );
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    for p in &pruned {
+        fs::remove_file(&p.file.path)
+            .with_context(|| format!("Failed to delete {}", p.file.path.display()))?;
+    }
+    eprintln!("Deleted {} devlogs", pruned.len());
+
+    if remote && !remote_sessions.is_empty() {
+        delete_remote(&remote_sessions)?;
+    }
+
+    Ok(())
+}
+
+/// Send `DELETE /sessions/{machine}/{id}` for each session to the push endpoint's server
+fn delete_remote(sessions: &BTreeSet<(String, String)>) -> Result<()> {
+    let config = Config::load()?;
+    let push_config = match config.push {
+        Some(ref pc) if pc.enabled => pc,
+        _ => anyhow::bail!("--remote needs an enabled [push] section in the config"),
+    };
+
+    let client = push::client(push_config, Duration::from_secs(30))?;
+
+    let mut deleted = 0;
+    for (machine_id, session_id) in sessions {
+        let url = push_config.session_url(machine_id, session_id);
+        let mut request = client.delete(&url);
+        if let Some(ref token) = push_config.api_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .with_context(|| format!("Failed to reach {}", url))?;
+        match response.status() {
+            status if status.is_success() => deleted += 1,
+            // Never pushed, or already pruned by the server's own retention
+            reqwest::StatusCode::NOT_FOUND => {}
+            status => anyhow::bail!(
+                "Deleting session {} failed with status {}: {}",
+                session_id,
+                status,
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            ),
+        }
+    }
+
+    eprintln!("Deleted {} sessions from the server", deleted);
+    Ok(())
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1024 * 1024 => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{} B", b),
+    }
+}
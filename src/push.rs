@@ -1,40 +1,108 @@
-use crate::config::Config;
+use crate::config::{Config, PushConfig};
 use crate::output::DevlogOutput;
+use crate::{encrypt, receipts, telemetry};
 use anyhow::{Context, Result};
-use reqwest::blocking::Client;
+use devlog_core::{Receipt, TranscriptParams};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use std::path::Path;
 use std::time::Duration;
 
-/// Push a devlog session to the central endpoint
-pub fn push_session(output: &DevlogOutput) -> Result<()> {
-    let config = Config::load()?;
+/// Sessions sent per `/ingest/batch` request
+const BATCH_SIZE: usize = 100;
 
-    let push_config = match config.push {
-        Some(ref pc) if pc.enabled => pc,
-        Some(_) => {
-            eprintln!("Push is disabled in config");
-            return Ok(());
-        }
-        None => {
-            eprintln!("No push config found");
-            return Ok(());
+/// Times a request is tried again while the server answers 429 Too Many Requests
+const BUSY_RETRIES: u32 = 5;
+
+/// Longest a Retry-After header can make a push wait
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// An HTTP client for the push server: it trusts `ca_cert` and, for mutual TLS,
+/// presents `client_cert` when they are set
+pub fn client(push_config: &PushConfig, timeout: Duration) -> Result<Client> {
+    let mut builder = Client::builder().timeout(timeout);
+    if let Some(ref ca_cert) = push_config.ca_cert {
+        let pem = std::fs::read(ca_cert).with_context(|| format!("Failed to read {}", ca_cert.display()))?;
+        let ca = reqwest::Certificate::from_pem(&pem).with_context(|| format!("Invalid CA certificate {}", ca_cert.display()))?;
+        builder = builder.add_root_certificate(ca);
+    }
+    match (&push_config.client_cert, &push_config.client_key) {
+        (Some(cert), Some(key)) => {
+            let cert_pem = std::fs::read(cert).with_context(|| format!("Failed to read {}", cert.display()))?;
+            let key_pem = std::fs::read(key).with_context(|| format!("Failed to read {}", key.display()))?;
+            let identity = reqwest::Identity::from_pkcs8_pem(&cert_pem, &key_pem)
+                .with_context(|| format!("Invalid client certificate {} or key {}", cert.display(), key.display()))?;
+            builder = builder.identity(identity);
         }
+        (None, None) => {}
+        _ => anyhow::bail!("client_cert and client_key must be set together"),
+    }
+    builder.build().context("Failed to create HTTP client")
+}
+
+/// Push a devlog session to the central endpoint. When the server signs a
+/// receipt for it, the receipt is checked and kept beside `devlog_path`.
+#[tracing::instrument(skip_all, err, fields(session_id = %output.session_id))]
+pub fn push_session(output: &DevlogOutput, devlog_path: Option<&Path>) -> Result<()> {
+    let config = Config::load()?;
+
+    let Some(push_config) = enabled_push_config(&config) else {
+        return Ok(());
     };
+    if !push_config.encrypt_to.is_empty() {
+        return push_encrypted(push_config, std::slice::from_ref(output));
+    }
 
     eprintln!("Pushing session to: {}", push_config.endpoint);
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()
-        .context("Failed to create HTTP client")?;
+    let client = client(push_config, Duration::from_secs(30))?;
 
-    let response = client
+    let mut request = client
         .post(&push_config.endpoint)
-        .json(output)
-        .send()
+        .headers(telemetry::trace_headers())
+        .json(output);
+    if let Some(ref token) = push_config.api_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = send(request)
         .with_context(|| format!("Failed to push to {}", push_config.endpoint))?;
 
     if response.status().is_success() {
-        eprintln!("Session pushed successfully");
+        // devlog-receiver reports re-pushes of unchanged sessions as duplicates
+        let body: Option<serde_json::Value> = response.json().ok();
+        let duplicate = body
+            .as_ref()
+            .and_then(|b| b.get("status"))
+            .and_then(|s| s.as_str())
+            == Some("duplicate");
+
+        if duplicate {
+            eprintln!("Session unchanged on server, skipped");
+        } else {
+            eprintln!("Session pushed successfully");
+            if push_config.include_transcript {
+                upload_transcripts(push_config, std::slice::from_ref(output))?;
+            }
+        }
+
+        let receipt = body
+            .as_ref()
+            .and_then(|b| b.get("receipt"))
+            .filter(|r| !r.is_null())
+            .map(|r| serde_json::from_value::<Receipt>(r.clone()))
+            .transpose()
+            .context("Server returned an unreadable receipt")?;
+        match (receipt, devlog_path) {
+            (Some(receipt), Some(path)) => {
+                receipts::keep(&receipt, output, path, push_config.receipt_key.as_deref())?;
+                eprintln!("Kept the server's receipt in {}", receipts::receipt_path(path).display());
+            }
+            (None, _) if push_config.receipt_key.is_some() => {
+                eprintln!("Warning: The server returned no receipt");
+            }
+            _ => {}
+        }
         Ok(())
     } else {
         anyhow::bail!(
@@ -44,3 +112,192 @@ pub fn push_session(output: &DevlogOutput) -> Result<()> {
         )
     }
 }
+
+/// Push many sessions through the batch endpoint, e.g. to backfill old sessions
+#[tracing::instrument(skip_all, err, fields(sessions = outputs.len()))]
+pub fn push_batch(outputs: &[DevlogOutput]) -> Result<()> {
+    let config = Config::load()?;
+
+    let Some(push_config) = enabled_push_config(&config) else {
+        return Ok(());
+    };
+    if !push_config.encrypt_to.is_empty() {
+        return push_encrypted(push_config, outputs);
+    }
+
+    let batch_url = push_config.batch_url();
+    eprintln!("Pushing {} sessions to: {}", outputs.len(), batch_url);
+
+    // Batches can be large; give the server time to store them
+    let client = client(push_config, Duration::from_secs(300))?;
+
+    for (i, chunk) in outputs.chunks(BATCH_SIZE).enumerate() {
+        let mut request = client
+            .post(&batch_url)
+            .headers(telemetry::trace_headers())
+            .json(chunk);
+        if let Some(ref token) = push_config.api_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = send(request)
+            .with_context(|| format!("Failed to push to {}", batch_url))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Batch {} failed with status {}: {}",
+                i + 1,
+                response.status(),
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            );
+        }
+
+        eprintln!("Pushed {} of {} sessions", i * BATCH_SIZE + chunk.len(), outputs.len());
+    }
+
+    if push_config.include_transcript {
+        upload_transcripts(push_config, outputs)?;
+        eprintln!("Uploaded transcripts");
+    }
+
+    Ok(())
+}
+
+/// Encrypt sessions to the `encrypt_to` recipients and send them to
+/// `/ingest/encrypted`. Transcripts are never uploaded this way: the server
+/// would get them in the clear.
+#[tracing::instrument(skip_all, err, fields(sessions = outputs.len()))]
+fn push_encrypted(push_config: &PushConfig, outputs: &[DevlogOutput]) -> Result<()> {
+    let recipients = encrypt::recipients(&push_config.encrypt_to)?;
+    let encrypted_url = push_config.encrypted_url();
+    eprintln!("Pushing {} encrypted sessions to: {}", outputs.len(), encrypted_url);
+
+    let client = client(push_config, Duration::from_secs(300))?;
+
+    for (i, chunk) in outputs.chunks(BATCH_SIZE).enumerate() {
+        let sessions = chunk
+            .iter()
+            .map(|output| encrypt::encrypt_session(output, &recipients))
+            .collect::<Result<Vec<_>>>()?;
+        let mut request = client
+            .post(&encrypted_url)
+            .headers(telemetry::trace_headers())
+            .json(&sessions);
+        if let Some(ref token) = push_config.api_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = send(request)
+            .with_context(|| format!("Failed to push to {}", encrypted_url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Batch {} failed with status {}: {}",
+                i + 1,
+                response.status(),
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            );
+        }
+    }
+
+    eprintln!("Pushed {} encrypted sessions", outputs.len());
+    if push_config.include_transcript {
+        eprintln!("Warning: Transcripts are not uploaded when sessions are encrypted");
+    }
+    Ok(())
+}
+
+/// Upload the raw JSONL of each session that still has its transcript on this machine
+#[tracing::instrument(skip_all, err, fields(sessions = outputs.len()))]
+fn upload_transcripts(push_config: &PushConfig, outputs: &[DevlogOutput]) -> Result<()> {
+    let transcript_url = push_config.transcript_url();
+
+    // Transcripts of long sessions run to many megabytes
+    let client = client(push_config, Duration::from_secs(300))?;
+
+    for output in outputs {
+        let Some(ref path) = output.transcript_path else {
+            continue;
+        };
+        let transcript = match std::fs::read(path) {
+            Ok(transcript) => transcript,
+            Err(e) => {
+                eprintln!("Warning: Transcript {} not uploaded: {}", path, e);
+                continue;
+            }
+        };
+
+        let params = TranscriptParams {
+            machine_id: output.machine_id.clone(),
+            project_dir: output.project_path().to_string(),
+            session_id: output.session_id.clone(),
+        };
+        let mut request = client
+            .post(&transcript_url)
+            .headers(telemetry::trace_headers())
+            .query(&params)
+            .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+            .body(transcript);
+        if let Some(ref token) = push_config.api_token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = send(request)
+            .with_context(|| format!("Failed to upload transcript to {}", transcript_url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Transcript upload failed with status {}: {}",
+                response.status(),
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Send a request, waiting and trying again while the server is too busy for
+/// it: after the delay its 429 response's Retry-After asks for, or else a
+/// growing one, up to `BUSY_RETRIES` times
+fn send(request: RequestBuilder) -> reqwest::Result<Response> {
+    let mut tries = 0;
+    loop {
+        // Streamed bodies can't be sent twice; none of ours are
+        let Some(attempt) = request.try_clone() else {
+            return request.send();
+        };
+        let response = attempt.send()?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || tries == BUSY_RETRIES {
+            return Ok(response);
+        }
+        tries += 1;
+        let wait = retry_after(&response)
+            .unwrap_or(Duration::from_secs(1 << tries))
+            .min(MAX_RETRY_AFTER);
+        eprintln!("Server busy, retrying in {}s", wait.as_secs());
+        std::thread::sleep(wait);
+    }
+}
+
+/// The wait a Retry-After header asks for: a number of seconds, or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
+
+fn enabled_push_config(config: &Config) -> Option<&PushConfig> {
+    match config.push {
+        Some(ref pc) if pc.enabled => Some(pc),
+        Some(_) => {
+            eprintln!("Push is disabled in config");
+            None
+        }
+        None => {
+            eprintln!("No push config found");
+            None
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::output::DevlogOutput;
+use crate::search;
+use anyhow::{Context, Result};
+use devlog_core::Receipt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the server's receipt for a devlog is kept: beside it, as `<name>.receipt`
+pub fn receipt_path(devlog_path: &Path) -> PathBuf {
+    devlog_path.with_extension("receipt")
+}
+
+/// Check a receipt the server returned for `output` and keep it beside the
+/// devlog. With `pinned`, the receipt must be signed with that public key.
+pub fn keep(receipt: &Receipt, output: &DevlogOutput, devlog_path: &Path, pinned: Option<&str>) -> Result<()> {
+    if let Some(pinned) = pinned {
+        if !receipt.public_key.eq_ignore_ascii_case(pinned.trim()) {
+            anyhow::bail!("Receipt is signed with {}, not the pinned receipt_key", receipt.public_key);
+        }
+    }
+    receipt.verify_for(output).context("Server returned an invalid receipt")?;
+    let path = receipt_path(devlog_path);
+    let json = serde_json::to_string_pretty(receipt)?;
+    fs::write(&path, json).with_context(|| format!("Failed to write receipt {}", path.display()))
+}
+
+/// Check devlogs against their receipts: each must be validly signed, by
+/// `pinned` if given, and acknowledge the devlog as it is now. Returns false
+/// if any fails or is missing.
+pub fn verify_files(files: &[PathBuf], pinned: Option<&str>) -> Result<bool> {
+    let (mut verified, mut failed) = (0, 0);
+    for file in files {
+        let path = receipt_path(file);
+        let result = fs::read_to_string(&path)
+            .with_context(|| format!("no receipt at {}", path.display()))
+            .and_then(|json| serde_json::from_str::<Receipt>(&json).context("unreadable receipt"))
+            .and_then(|receipt| {
+                if let Some(pinned) = pinned {
+                    if !receipt.public_key.eq_ignore_ascii_case(pinned.trim()) {
+                        anyhow::bail!("signed with {}, not the pinned receipt_key", receipt.public_key);
+                    }
+                }
+                let output = search::read_devlog(file)?;
+                receipt.verify_for(&output)?;
+                Ok(receipt)
+            });
+        match result {
+            Ok(receipt) => {
+                println!("{}: archived unmodified at {}", file.display(), receipt.received_at);
+                verified += 1;
+            }
+            Err(e) => {
+                println!("{}: {:#}", file.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    println!("{} of {} devlogs verified", verified, verified + failed);
+    Ok(failed == 0)
+}
@@ -0,0 +1,237 @@
+use crate::config::{Config, RedactConfig};
+use crate::output::{self, DevlogOutput};
+use crate::parser::ConversationEntry;
+use crate::{embeddings, search};
+use anyhow::{Context, Result};
+use regex::{NoExpand, Regex};
+use std::path::PathBuf;
+
+/// The `[redact]` rules, compiled
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+    /// Home directories, when `relative_paths` is on
+    homes: Option<Regex>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid redact pattern: {}", pattern))?;
+            rules.push((regex, config.replacement.clone()));
+        }
+        for (text, replacement) in &config.replace {
+            let regex = Regex::new(&regex::escape(text)).context("Invalid redact replacement")?;
+            rules.push((regex, replacement.clone()));
+        }
+        let homes = if config.relative_paths { Some(home_dirs()?) } else { None };
+        Ok(Self { rules, homes })
+    }
+
+    /// The rules in the user's config; none when it has no `[redact]` section
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load redaction rules")?;
+        match config.redact {
+            Some(ref redact) => Self::new(redact),
+            None => Ok(Self {
+                rules: Vec::new(),
+                homes: None,
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.homes.is_none()
+    }
+
+    /// Apply every rule to `text`; returns the number of replacements
+    pub fn redact(&self, text: &mut String) -> usize {
+        let mut count = 0;
+        for (regex, replacement) in &self.rules {
+            let found = regex.find_iter(text).count();
+            if found > 0 {
+                *text = regex.replace_all(text, NoExpand(replacement)).into_owned();
+                count += found;
+            }
+        }
+        count
+    }
+
+    /// Apply the rules to everything people wrote or read in a session: the
+    /// conversation, tool summaries, notes and commit subjects. Returns the number
+    /// of replacements.
+    pub fn redact_output(&self, output: &mut DevlogOutput) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let project = self.homes.as_ref().and_then(|_| path_pattern(&output.project_dir));
+        let mut count = 0;
+        for_each_text(output, |_, text| {
+            count += self.redact(text);
+            if let Some(ref homes) = self.homes {
+                if let Some(ref project) = project {
+                    count += replace_paths(text, project, ".");
+                }
+                count += replace_paths(text, homes, "~");
+            }
+        });
+        count
+    }
+}
+
+/// Path separators as the text may write them, whichever the OS
+const SEPARATOR: &str = r"[/\\]";
+
+/// A directory as it appears in paths in session text, with either separator
+fn path_pattern(dir: &str) -> Option<Regex> {
+    let components: Vec<String> = dir
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty())
+        .map(regex::escape)
+        .collect();
+    if components.is_empty() {
+        return None;
+    }
+    let root = if dir.starts_with(['/', '\\']) { SEPARATOR } else { "" };
+    Regex::new(&format!("{}{}", root, components.join(SEPARATOR))).ok()
+}
+
+/// Home directories, whose names give away usernames: `/home/<user>`,
+/// `/Users/<user>`, `C:\Users\<user>`, and this user's own wherever it is
+fn home_dirs() -> Result<Regex> {
+    let user = r#"[^/\\\s"'`]+"#;
+    let mut homes = vec![
+        format!("/home/{}", user),
+        format!("/Users/{}", user),
+        format!(r"\b[A-Za-z]:{sep}(?i:Users){sep}{}", user, sep = SEPARATOR),
+    ];
+    let own = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")).ok();
+    if let Some(own) = own.as_deref().and_then(path_pattern) {
+        homes.push(own.as_str().to_string());
+    }
+    Regex::new(&homes.join("|")).context("Invalid home directory pattern")
+}
+
+/// Replace the matches of `prefix` that begin a path with `replacement`, so
+/// `/home/jane/app/src` becomes `./src`, leaving alone ones inside a longer
+/// path or URL and ones followed by more of a name (`/home/jane/app2`).
+/// Returns the number replaced.
+fn replace_paths(text: &mut String, prefix: &Regex, replacement: &str) -> usize {
+    let is_name = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-');
+    let mut rewritten = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for found in prefix.find_iter(text) {
+        let before = text[..found.start()].chars().next_back();
+        let mut after = text[found.end()..].chars();
+        let starts_path = before.map(|c| !is_name(c) && !matches!(c, '.' | '/' | '\\')).unwrap_or(true);
+        let next = after.next();
+        let ends_name = match next {
+            None => true,
+            Some('.') => !after.next().map(is_name).unwrap_or(false),
+            Some(c) => !is_name(c),
+        };
+        if !starts_path || !ends_name {
+            continue;
+        }
+        rewritten.push_str(&text[last..found.start()]);
+        rewritten.push_str(replacement);
+        // `cd /home/jane/app.` would otherwise read `cd ..`
+        if replacement.ends_with('.') && next == Some('.') {
+            rewritten.push('/');
+        }
+        last = found.end();
+        count += 1;
+    }
+    if count > 0 {
+        rewritten.push_str(&text[last..]);
+        *text = rewritten;
+    }
+    count
+}
+
+/// Where in a session a piece of text sits, for reports
+#[derive(Debug, Clone, Copy)]
+pub enum TextKind {
+    Prompt,
+    Response,
+    ToolSummary,
+    Note,
+    CommitSubject,
+}
+
+impl TextKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Prompt => "prompt",
+            Self::Response => "response",
+            Self::ToolSummary => "tool summary",
+            Self::Note => "note",
+            Self::CommitSubject => "commit subject",
+        }
+    }
+}
+
+/// Call `f` on everything people wrote or read in a session: the conversation,
+/// tool summaries, notes and commit subjects
+pub fn for_each_text(output: &mut DevlogOutput, mut f: impl FnMut(TextKind, &mut String)) {
+    for entry in &mut output.conversation {
+        match entry {
+            ConversationEntry::User { content, .. } => f(TextKind::Prompt, content),
+            ConversationEntry::Assistant { content, .. } => f(TextKind::Response, content),
+            ConversationEntry::ToolSummary { actions } => {
+                for action in actions {
+                    f(TextKind::ToolSummary, action);
+                }
+            }
+        }
+    }
+    for note in &mut output.notes {
+        f(TextKind::Note, &mut note.text);
+    }
+    if let Some(ref mut git) = output.git {
+        for commit in &mut git.commits {
+            f(TextKind::CommitSubject, &mut commit.subject);
+        }
+    }
+}
+
+/// Apply the rules to devlog files written before them, rewriting those that
+/// change unless `dry_run`. Returns the changed sessions.
+pub fn anonymize_files(redactor: &Redactor, files: &[PathBuf], dry_run: bool) -> Result<Vec<DevlogOutput>> {
+    let mut changed = Vec::new();
+    let mut replacements = 0;
+    for file in files {
+        let mut output = match search::read_devlog(file) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let count = redactor.redact_output(&mut output);
+        if count == 0 {
+            continue;
+        }
+        println!("{}: {} replacements", file.display(), count);
+        if !dry_run {
+            output::rewrite_output(file, &output)?;
+            if let Err(e) = embeddings::reindex_session(&output, file) {
+                eprintln!("Warning: Failed to update embeddings: {}", e);
+            }
+        }
+        replacements += count;
+        changed.push(output);
+    }
+
+    println!(
+        "{} {} replacements in {} of {} devlogs",
+        if dry_run { "Would make" } else { "Made" },
+        replacements,
+        changed.len(),
+        files.len()
+    );
+    Ok(changed)
+}
@@ -1,21 +1,127 @@
+use crate::config::PushConfig;
 use crate::output::DevlogOutput;
 use crate::parser::ConversationEntry;
-use anyhow::Result;
+use crate::session_cache::SessionCache;
+use anyhow::{Context, Result};
+use devlog_core::compression;
+use devlog_core::manifest::Manifests;
+use devlog_core::store::{read_session_header, session_dirs, session_files};
+use devlog_core::SessionHeader;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::fs;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// A single search result with context
+#[derive(Serialize, Deserialize)]
 pub struct SearchResult {
     pub machine: String,
     pub project: String,
     pub session_id: String,
     pub session_file: String,
     pub timestamp: String,
+    /// Position of the matching entry within the session's conversation
+    pub entry_index: usize,
     pub entry_type: String,
     pub snippet: String,
     pub query: String,
 }
 
+/// One page of search results
+#[derive(Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<SearchResult>,
+    /// Pass back as the cursor to fetch the following page
+    pub next_cursor: Option<String>,
+}
+
+/// Filters and paging for a search
+#[derive(Clone)]
+pub struct SearchOptions {
+    pub scope: SearchScope,
+    pub days: Option<u32>,
+    pub limit: usize,
+    /// Resume after the last result of a previous page
+    pub cursor: Option<String>,
+    pub case_sensitive: bool,
+    /// Only match the query as a whole word (e.g. `id` but not `uuid`)
+    pub whole_word: bool,
+    /// Only sessions carrying this tag
+    pub tag: Option<String>,
+    /// Only sessions whose prompts refer to this ticket, e.g. `PROJ-123` or `#456`
+    pub ticket: Option<String>,
+}
+
+impl SearchOptions {
+    /// Sessions before this are left out
+    pub fn cutoff(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.days.map(|d| chrono::Utc::now() - chrono::Duration::days(d as i64))
+    }
+}
+
+/// Position of the last result on a page, encoded opaquely for clients
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    timestamp: String,
+    machine: String,
+    project: String,
+    session_file: String,
+    entry_index: usize,
+}
+
+impl Cursor {
+    fn from_result(result: &SearchResult) -> Self {
+        Cursor {
+            timestamp: result.timestamp.clone(),
+            machine: result.machine.clone(),
+            project: result.project.clone(),
+            session_file: result.session_file.clone(),
+            entry_index: result.entry_index,
+        }
+    }
+
+    fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        json.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn decode(s: &str) -> Result<Self> {
+        let bytes = (0..s.len())
+            .step_by(2)
+            .map(|i| s.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .context("Invalid search cursor")?;
+        serde_json::from_slice(&bytes).context("Invalid search cursor")
+    }
+
+    fn key(&self) -> SortKey<'_> {
+        (
+            Reverse(self.timestamp.as_str()),
+            self.machine.as_str(),
+            self.project.as_str(),
+            self.session_file.as_str(),
+            self.entry_index,
+        )
+    }
+}
+
+/// Results are ordered newest session first, then by location within the store
+type SortKey<'a> = (Reverse<&'a str>, &'a str, &'a str, &'a str, usize);
+
+impl SearchResult {
+    fn key(&self) -> SortKey<'_> {
+        (
+            Reverse(self.timestamp.as_str()),
+            self.machine.as_str(),
+            self.project.as_str(),
+            self.session_file.as_str(),
+            self.entry_index,
+        )
+    }
+}
+
 /// What to search through
 #[derive(Clone, Copy, Default)]
 pub enum SearchScope {
@@ -33,26 +139,60 @@ impl SearchScope {
             _ => Self::Conversations,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PromptsOnly => "prompts",
+            Self::Conversations => "conversations",
+            Self::Everything => "all",
+        }
+    }
+}
+
+/// A devlog file on disk and the machine/project it belongs to
+pub struct DevlogFile {
+    pub machine: String,
+    pub project: String,
+    pub path: PathBuf,
+}
+
+/// Where a matched entry came from
+struct SessionContext<'a> {
+    machine: &'a str,
+    project: &'a str,
+    session_id: &'a str,
+    session_file: &'a str,
+    timestamp: &'a str,
 }
 
 /// Search through devlog files for matching content
-pub fn search_devlogs(
-    storage_dir: &Path,
-    query: &str,
-    scope: SearchScope,
-    days: Option<u32>,
-    limit: usize,
-) -> Result<Vec<SearchResult>> {
-    let cutoff = days.map(|d| chrono::Utc::now() - chrono::Duration::days(d as i64));
-    let query_lower = query.to_lowercase();
-    let mut results = Vec::new();
+pub fn search_devlogs(storage_dir: &Path, query: &str, options: &SearchOptions) -> Result<SearchPage> {
+    let files = storage_files_since(storage_dir, options.cutoff())?;
+    search_files(&files, query, options, None)
+}
+
+/// Search the devlogs in a local `.devlog` directory
+pub fn search_local(devlog_dir: &Path, query: &str, options: &SearchOptions) -> Result<SearchPage> {
+    let files = local_files(devlog_dir)?;
+    search_files(&files, query, options, None)
+}
+
+/// List devlog files in a server storage directory: storage_dir/machine/project/YYYY/MM/*.json[.zst],
+/// and those rolled into day bundles there
+pub fn storage_files(storage_dir: &Path) -> Result<Vec<DevlogFile>> {
+    storage_files_since(storage_dir, None)
+}
+
+/// The devlog files in a server storage directory, leaving out the months that
+/// ended before `since`
+pub fn storage_files_since(storage_dir: &Path, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<Vec<DevlogFile>> {
+    let mut files = Vec::new();
 
     if !storage_dir.exists() {
-        return Ok(results);
+        return Ok(files);
     }
 
-    // Walk storage directory: storage_dir/machine/project/*.json
-    'outer: for machine_entry in fs::read_dir(storage_dir)? {
+    for machine_entry in fs::read_dir(storage_dir)? {
         let machine_entry = machine_entry?;
         let machine_path = machine_entry.path();
         if !machine_path.is_dir() {
@@ -68,133 +208,367 @@ pub fn search_devlogs(
             }
             let project = project_entry.file_name().to_string_lossy().to_string();
 
-            for file_entry in fs::read_dir(&project_path)? {
-                let file_entry = file_entry?;
-                let file_path = file_entry.path();
-
-                if file_path.extension().map(|e| e == "json").unwrap_or(false) {
-                    if let Ok(devlog) = read_devlog(&file_path) {
-                        // Check date filter
-                        if let Some(ref cutoff) = cutoff {
-                            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&devlog.timestamp)
-                            {
-                                if dt < *cutoff {
-                                    continue;
-                                }
-                            }
-                        }
-
-                        let session_file =
-                            file_path.file_name().unwrap_or_default().to_string_lossy().to_string();
-
-                        // Search conversation entries
-                        for entry in &devlog.conversation {
-                            if let Some(result) = search_entry(
-                                entry,
-                                &query_lower,
-                                query,
-                                scope,
-                                &machine,
-                                &project,
-                                &devlog.session_id,
-                                &session_file,
-                                &devlog.timestamp,
-                            ) {
-                                results.push(result);
-                                if results.len() >= limit {
-                                    break 'outer;
-                                }
-                            }
-                        }
-                    }
+            for dir in session_dirs(&project_path, since)? {
+                for file_path in session_files(&dir)? {
+                    files.push(DevlogFile {
+                        machine: machine.clone(),
+                        project: project.clone(),
+                        path: file_path,
+                    });
                 }
             }
         }
     }
 
-    // Sort by timestamp descending (most recent first)
-    results.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(files)
+}
+
+/// The given devlog files, and the `.json` files under the given directories in
+/// path order. The embeddings index kept in `.devlog/embeddings` is left out.
+pub fn files_under(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        entries.sort();
+
+        for path in entries {
+            if path.is_dir() {
+                if path.file_name().map(|n| n != "embeddings").unwrap_or(true) {
+                    walk(&path, files)?;
+                }
+            } else if compression::is_session_file(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
 
-    Ok(results)
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            walk(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
 }
 
-fn read_devlog(path: &Path) -> Result<DevlogOutput> {
-    let content = fs::read_to_string(path)?;
-    let devlog: DevlogOutput = serde_json::from_str(&content)?;
-    Ok(devlog)
+/// List devlog files in a project's `.devlog` directory, attributed to this machine
+pub fn local_files(devlog_dir: &Path) -> Result<Vec<DevlogFile>> {
+    let mut files = Vec::new();
+
+    if !devlog_dir.exists() {
+        return Ok(files);
+    }
+
+    let machine = crate::output::get_machine_id();
+    let project = devlog_dir
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    for file_entry in fs::read_dir(devlog_dir)? {
+        let file_path = file_entry?.path();
+        if file_path.extension().map(|e| e == "json").unwrap_or(false) {
+            files.push(DevlogFile {
+                machine: machine.clone(),
+                project: project.clone(),
+                path: file_path,
+            });
+        }
+    }
+
+    Ok(files)
+}
+
+/// Search the given devlog files. The server passes its cache of parsed sessions.
+pub fn search_files(
+    files: &[DevlogFile],
+    query: &str,
+    options: &SearchOptions,
+    cache: Option<&SessionCache>,
+) -> Result<SearchPage> {
+    let cursor = options.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let cutoff = options.cutoff();
+    let matcher = Matcher::new(query, options);
+    let mut results = Vec::new();
+    let wanted = |header: &SessionHeader| {
+        // Check date filter
+        if let Some(ref cutoff) = cutoff {
+            if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&header.timestamp) {
+                if dt < *cutoff {
+                    return false;
+                }
+            }
+        }
+        let tag = options.tag.as_ref().map(|tag| header.has_tag(tag)).unwrap_or(true);
+        let ticket = options.ticket.as_ref().map(|ticket| header.has_ticket(ticket)).unwrap_or(true);
+        tag && ticket
+    };
+    // Stored sessions listed in a manifest can be filtered out without opening them
+    let filtered = cutoff.is_some() || options.tag.is_some() || options.ticket.is_some();
+    let mut manifests = Manifests::default();
+
+    for file in files {
+        if filtered {
+            if let Some(entry) = manifests.entry(&file.path) {
+                if !wanted(&entry.header) {
+                    continue;
+                }
+            }
+        }
+        // The server's cache holds whole sessions; otherwise only sessions
+        // that pass the filters have their conversation parsed
+        let devlog = match cache {
+            Some(cache) => match cache.read(&file.path) {
+                Ok(devlog) if wanted(&devlog.header()) => devlog,
+                _ => continue,
+            },
+            None => match read_session_header(&file.path) {
+                Ok(session) if wanted(&session.header) => match session.parse() {
+                    Ok(devlog) => Arc::new(devlog),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            },
+        };
+
+        let session_file = file
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let ctx = SessionContext {
+            machine: &file.machine,
+            project: &file.project,
+            session_id: &devlog.session_id,
+            session_file: &session_file,
+            timestamp: &devlog.timestamp,
+        };
+
+        // Search conversation entries
+        for (idx, entry) in devlog.conversation.iter().enumerate() {
+            if let Some(mut result) =
+                search_entry(entry, &matcher, query, options.scope, &ctx)
+            {
+                result.entry_index = idx;
+                results.push(result);
+            }
+        }
+    }
+
+    // Sort by timestamp descending (most recent first) with a stable tie-break,
+    // so that pages are deterministic
+    results.sort_by(|a, b| a.key().cmp(&b.key()));
+
+    if let Some(ref cursor) = cursor {
+        results.retain(|r| r.key() > cursor.key());
+    }
+
+    let next_cursor = if results.len() > options.limit {
+        results.truncate(options.limit);
+        results.last().map(|r| Cursor::from_result(r).encode())
+    } else {
+        None
+    };
+
+    Ok(SearchPage {
+        results,
+        next_cursor,
+    })
+}
+
+/// Query a devlog server's `/api/search` endpoint
+pub fn search_remote(push_config: &PushConfig, query: &str, options: &SearchOptions) -> Result<SearchPage> {
+    let client = crate::push::client(push_config, std::time::Duration::from_secs(30))?;
+    let search_url = push_config.search_url();
+    let api_token = push_config.api_token.as_deref();
+
+    let mut params = vec![
+        ("q", query.to_string()),
+        ("scope", options.scope.as_str().to_string()),
+        ("limit", options.limit.to_string()),
+    ];
+    if let Some(days) = options.days {
+        params.push(("days", days.to_string()));
+    }
+    if let Some(ref cursor) = options.cursor {
+        params.push(("cursor", cursor.clone()));
+    }
+    if options.case_sensitive {
+        params.push(("case", "true".to_string()));
+    }
+    if options.whole_word {
+        params.push(("word", "true".to_string()));
+    }
+    if let Some(ref tag) = options.tag {
+        params.push(("tag", tag.clone()));
+    }
+    if let Some(ref ticket) = options.ticket {
+        params.push(("ticket", ticket.clone()));
+    }
+
+    let mut request = client.get(&search_url).query(&params);
+    if let Some(token) = api_token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to reach {}", search_url))?;
+
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Remote search failed with status {}: {}",
+            response.status(),
+            response.text().unwrap_or_else(|_| "unknown error".to_string())
+        );
+    }
+
+    response.json().context("Failed to parse remote search results")
+}
+
+/// Combine pages from several sources, dropping entries that appear in more than one
+/// (a pushed session is found both locally and on the server). Every source
+/// orders results by the same key and resumes after a cursor's key, so the
+/// cursor of the last merged result continues each of them where it left off.
+pub fn merge_pages(pages: Vec<SearchPage>, limit: usize) -> SearchPage {
+    let more = pages.iter().any(|p| p.next_cursor.is_some());
+    let mut seen = std::collections::HashSet::new();
+    let mut results: Vec<SearchResult> = pages
+        .into_iter()
+        .flat_map(|p| p.results)
+        .filter(|r| seen.insert((r.session_id.clone(), r.timestamp.clone(), r.entry_index)))
+        .collect();
+
+    results.sort_by(|a, b| a.key().cmp(&b.key()));
+    let more = more || results.len() > limit;
+    results.truncate(limit);
+
+    let next_cursor = results
+        .last()
+        .filter(|_| more)
+        .map(|r| Cursor::from_result(r).encode());
+    SearchPage {
+        results,
+        next_cursor,
+    }
+}
+
+/// Read a devlog, from .devlog or a server's (possibly encrypted) storage
+pub fn read_devlog(path: &Path) -> Result<DevlogOutput> {
+    devlog_core::store::read_session(path)
 }
 
 fn search_entry(
     entry: &ConversationEntry,
-    query_lower: &str,
+    matcher: &Matcher,
     query_original: &str,
     scope: SearchScope,
-    machine: &str,
-    project: &str,
-    session_id: &str,
-    session_file: &str,
-    timestamp: &str,
+    ctx: &SessionContext,
 ) -> Option<SearchResult> {
     let (entry_type, content) = match entry {
-        ConversationEntry::User { content, .. } => ("user", content.as_str()),
+        ConversationEntry::User { content, .. } => ("user", content.clone()),
         ConversationEntry::Assistant { content, .. } => {
             if matches!(scope, SearchScope::PromptsOnly) {
                 return None;
             }
-            ("assistant", content.as_str())
+            ("assistant", content.clone())
         }
         ConversationEntry::ToolSummary { actions } => {
             if !matches!(scope, SearchScope::Everything) {
                 return None;
             }
             // Join actions for searching
-            let joined = actions.join(" | ");
-            if joined.to_lowercase().contains(query_lower) {
-                return Some(SearchResult {
-                    machine: machine.to_string(),
-                    project: project.to_string(),
-                    session_id: session_id.to_string(),
-                    session_file: session_file.to_string(),
-                    timestamp: timestamp.to_string(),
-                    entry_type: "tool".to_string(),
-                    snippet: create_snippet(&joined, query_lower),
-                    query: query_original.to_string(),
-                });
-            }
-            return None;
+            ("tool", actions.join(" | "))
         }
     };
 
-    let content_lower = content.to_lowercase();
-    if content_lower.contains(query_lower) {
-        Some(SearchResult {
-            machine: machine.to_string(),
-            project: project.to_string(),
-            session_id: session_id.to_string(),
-            session_file: session_file.to_string(),
-            timestamp: timestamp.to_string(),
-            entry_type: entry_type.to_string(),
-            snippet: create_snippet(content, query_lower),
-            query: query_original.to_string(),
-        })
-    } else {
-        None
+    let found = matcher.find(&content)?;
+
+    Some(SearchResult {
+        machine: ctx.machine.to_string(),
+        project: ctx.project.to_string(),
+        session_id: ctx.session_id.to_string(),
+        session_file: ctx.session_file.to_string(),
+        timestamp: ctx.timestamp.to_string(),
+        entry_index: 0,
+        entry_type: entry_type.to_string(),
+        snippet: create_snippet(&content, found.start, found.len()),
+        query: query_original.to_string(),
+    })
+}
+
+/// Finds a query in text, honouring the case and whole-word options
+pub struct Matcher {
+    needle: String,
+    case_sensitive: bool,
+    whole_word: bool,
+}
+
+impl Matcher {
+    pub fn new(query: &str, options: &SearchOptions) -> Self {
+        let needle = if options.case_sensitive {
+            query.to_string()
+        } else {
+            query.to_lowercase()
+        };
+        Matcher {
+            needle,
+            case_sensitive: options.case_sensitive,
+            whole_word: options.whole_word,
+        }
+    }
+
+    /// Byte range in `content` of the first acceptable match
+    pub fn find(&self, content: &str) -> Option<Range<usize>> {
+        if self.case_sensitive || self.needle.is_empty() {
+            return content
+                .match_indices(&self.needle)
+                .map(|(pos, _)| pos..pos + self.needle.len())
+                .find(|found| self.accepts(content, found));
+        }
+
+        // Lowercasing can change a character's length in bytes, so remember
+        // which character of `content` each byte of the lowered text came from
+        let mut lowered = String::with_capacity(content.len());
+        let mut origin = Vec::with_capacity(content.len());
+        for (pos, c) in content.char_indices() {
+            lowered.extend(c.to_lowercase());
+            origin.resize(lowered.len(), (pos, pos + c.len_utf8()));
+        }
+
+        lowered
+            .match_indices(&self.needle)
+            .map(|(pos, _)| origin[pos].0..origin[pos + self.needle.len() - 1].1)
+            .find(|found| self.accepts(content, found))
+    }
+
+    fn accepts(&self, content: &str, found: &Range<usize>) -> bool {
+        !self.whole_word || is_whole_word(content, found.start, found.len())
     }
 }
 
-/// Create a snippet with context around the match
-fn create_snippet(content: &str, query_lower: &str) -> String {
-    let content_lower = content.to_lowercase();
-    let match_pos = match content_lower.find(query_lower) {
-        Some(pos) => pos,
-        None => return content.chars().take(200).collect(),
-    };
+fn is_whole_word(haystack: &str, pos: usize, len: usize) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let before = haystack[..pos].chars().next_back();
+    let after = haystack[pos + len..].chars().next();
+    !before.map(is_word_char).unwrap_or(false) && !after.map(is_word_char).unwrap_or(false)
+}
 
+/// Create a snippet with context around the match
+fn create_snippet(content: &str, match_pos: usize, match_len: usize) -> String {
     let context_chars = 80;
 
     // Find start position (try to start at word boundary)
     let start = if match_pos > context_chars {
-        let candidate = match_pos - context_chars;
+        let candidate = floor_char_boundary(content, match_pos - context_chars);
         // Find next space after candidate
         content[candidate..]
             .find(' ')
@@ -205,8 +579,9 @@ fn create_snippet(content: &str, query_lower: &str) -> String {
     };
 
     // Find end position
-    let end_candidate = match_pos + query_lower.len() + context_chars;
+    let end_candidate = match_pos + match_len + context_chars;
     let end = if end_candidate < content.len() {
+        let end_candidate = floor_char_boundary(content, end_candidate);
         // Find previous space before end
         content[..end_candidate]
             .rfind(' ')
@@ -219,10 +594,105 @@ fn create_snippet(content: &str, query_lower: &str) -> String {
     if start > 0 {
         snippet.push_str("...");
     }
-    snippet.push_str(content[start..end].trim());
+    snippet.push_str(content[start.min(end)..end].trim());
     if end < content.len() {
         snippet.push_str("...");
     }
 
     snippet
 }
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// A session's file name and the results found in it
+type SessionMatches<'a> = (&'a str, Vec<&'a SearchResult>);
+
+/// Render results as a markdown digest grouped by project and session.
+/// `link` maps a result to where its session can be opened, if anywhere.
+pub fn render_markdown(
+    query: &str,
+    page: &SearchPage,
+    link: &dyn Fn(&SearchResult) -> Option<String>,
+) -> String {
+    // Group by project, then session, keeping the newest-first result order
+    let mut projects: Vec<(&str, Vec<SessionMatches>)> = Vec::new();
+    for result in &page.results {
+        let project_idx = match projects.iter().position(|(p, _)| *p == result.project) {
+            Some(idx) => idx,
+            None => {
+                projects.push((&result.project, Vec::new()));
+                projects.len() - 1
+            }
+        };
+        let sessions = &mut projects[project_idx].1;
+        match sessions.iter_mut().find(|(f, _)| *f == result.session_file) {
+            Some((_, matches)) => matches.push(result),
+            None => sessions.push((&result.session_file, vec![result])),
+        }
+    }
+
+    let session_count: usize = projects.iter().map(|(_, s)| s.len()).sum();
+    let mut md = format!(
+        "# Search digest: \"{}\"\n\n_Generated {} · {} matches across {} sessions in {} projects_\n",
+        query,
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        page.results.len(),
+        session_count,
+        projects.len()
+    );
+
+    for (project, sessions) in &projects {
+        md.push_str(&format!("\n## {}\n", project));
+
+        for (session_file, matches) in sessions {
+            let first = matches[0];
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&first.timestamp)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|_| first.timestamp.clone());
+            let title = match link(first) {
+                Some(href) => format!("[{}]({})", session_file, href.replace(' ', "%20")),
+                None => session_file.to_string(),
+            };
+
+            md.push_str(&format!("\n### {} · {} · {}\n\n", timestamp, first.machine, title));
+            for m in matches {
+                md.push_str(&format!("- **{}**: {}\n", m.entry_type, m.snippet.replace('\n', " ")));
+            }
+        }
+    }
+
+    md
+}
+
+pub fn print_results(page: &SearchPage) {
+    if page.results.is_empty() {
+        println!("No results found");
+        return;
+    }
+
+    for result in &page.results {
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&result.timestamp)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| result.timestamp.clone());
+
+        println!(
+            "{} · {} · {}  {} [{}]",
+            result.project, result.machine, timestamp, result.session_file, result.entry_type
+        );
+        println!("    session {}", result.session_id);
+        println!("    {}\n", result.snippet.replace('\n', " "));
+    }
+
+    println!("{} results", page.results.len());
+    if let Some(ref cursor) = page.next_cursor {
+        println!("More results: --cursor {}", cursor);
+    }
+}
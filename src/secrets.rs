@@ -0,0 +1,186 @@
+use crate::output::{self, DevlogOutput};
+use crate::redact::{self, TextKind};
+use crate::{embeddings, search};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Credentials recognised by their shape, with what they are reported as. Where
+/// a pattern has a group, only the group is the secret.
+const PATTERNS: &[(&str, &str)] = &[
+    ("aws-access-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    (
+        "aws-secret-key",
+        r#"(?i)aws_?secret_?(?:access_?)?key["']?\s*[:=]\s*["']?([A-Za-z0-9/+=]{40})"#,
+    ),
+    ("github-token", r"\b(?:gh[pousr]_[A-Za-z0-9]{36,255}|github_pat_[A-Za-z0-9_]{22,255})\b"),
+    // A key cut off by truncation is still a secret; take the rest of the text
+    (
+        "private-key",
+        r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----[\s\S]*?(?:-----END (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----|\z)",
+    ),
+];
+
+/// Tokens shorter than this are not checked for randomness; commit hashes,
+/// UUIDs and IDs would drown out the secrets. Tokens end at `/`, so paths are
+/// checked a component at a time.
+const ENTROPY_MIN_LEN: usize = 32;
+/// Bits per character above which a token looks random rather than written.
+/// Hex (at most 4) never reaches it, so hashes are left alone.
+const ENTROPY_THRESHOLD: f64 = 4.2;
+
+/// A likely secret in a session
+pub struct Finding {
+    pub kind: &'static str,
+    pub location: TextKind,
+    /// The first few characters, enough to find it without repeating it
+    pub preview: String,
+}
+
+/// Detection patterns for credentials, plus an entropy check for random tokens
+/// none of them know
+pub struct Scanner {
+    patterns: Vec<(&'static str, Regex)>,
+    tokens: Regex,
+}
+
+impl Scanner {
+    pub fn new() -> Result<Self> {
+        let patterns = PATTERNS
+            .iter()
+            .map(|(kind, pattern)| {
+                let regex = Regex::new(pattern).with_context(|| format!("Invalid {} pattern", kind))?;
+                Ok((*kind, regex))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            patterns,
+            tokens: Regex::new(r"[A-Za-z0-9+_-]{32,}={0,2}").context("Invalid token pattern")?,
+        })
+    }
+
+    /// The likely secrets in `text`, in order and not overlapping
+    fn find(&self, text: &str) -> Vec<(Range<usize>, &'static str)> {
+        let mut found: Vec<(Range<usize>, &'static str)> = Vec::new();
+        for (kind, regex) in &self.patterns {
+            for captures in regex.captures_iter(text) {
+                let secret = captures.get(1).or_else(|| captures.get(0)).expect("match has a group 0");
+                let range = secret.range();
+                if !found.iter().any(|(r, _)| overlaps(r, &range)) {
+                    found.push((range, kind));
+                }
+            }
+        }
+        for token in self.tokens.find_iter(text) {
+            let range = token.range();
+            if looks_random(token.as_str()) && !found.iter().any(|(r, _)| overlaps(r, &range)) {
+                found.push((range, "high-entropy"));
+            }
+        }
+        found.sort_by_key(|(range, _)| range.start);
+        found
+    }
+
+    /// Report the likely secrets in a session's conversation, tool summaries,
+    /// notes and commit subjects, replacing them with `[REDACTED:<kind>]` if `redact`
+    pub fn scan_output(&self, output: &mut DevlogOutput, redact: bool) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        redact::for_each_text(output, |location, text| {
+            let found = self.find(text);
+            if found.is_empty() {
+                return;
+            }
+            for (range, kind) in &found {
+                findings.push(Finding {
+                    kind,
+                    location,
+                    preview: preview(&text[range.clone()]),
+                });
+            }
+            if redact {
+                for (range, kind) in found.iter().rev() {
+                    text.replace_range(range.clone(), &format!("[REDACTED:{}]", kind));
+                }
+            }
+        });
+        findings
+    }
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Long, mixing letters and digits, and with the character spread of random data
+fn looks_random(token: &str) -> bool {
+    token.len() >= ENTROPY_MIN_LEN
+        && token.bytes().any(|b| b.is_ascii_digit())
+        && token.bytes().any(|b| b.is_ascii_alphabetic())
+        && entropy(token) >= ENTROPY_THRESHOLD
+}
+
+/// Shannon entropy in bits per character
+fn entropy(token: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for b in token.bytes() {
+        counts[b as usize] += 1;
+    }
+    let len = token.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn preview(secret: &str) -> String {
+    let shown: String = secret.chars().take(6).collect();
+    format!("{}… ({} chars)", shown, secret.chars().count())
+}
+
+/// Scan devlog files, listing each session with likely secrets and, if
+/// `redact`, rewriting it without them. Returns the sessions that had any.
+pub fn scan_files(scanner: &Scanner, files: &[PathBuf], redact: bool) -> Result<Vec<DevlogOutput>> {
+    let mut flagged = Vec::new();
+    let mut total = 0;
+    for file in files {
+        let mut output = match search::read_devlog(file) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {}", file.display(), e);
+                continue;
+            }
+        };
+
+        let findings = scanner.scan_output(&mut output, redact);
+        if findings.is_empty() {
+            continue;
+        }
+        let short_id: String = output.session_id.chars().take(8).collect();
+        println!("{} ({}): {} likely secrets", file.display(), short_id, findings.len());
+        for finding in &findings {
+            println!("  {} in {}: {}", finding.kind, finding.location.as_str(), finding.preview);
+        }
+        if redact {
+            output::rewrite_output(file, &output)?;
+            if let Err(e) = embeddings::reindex_session(&output, file) {
+                eprintln!("Warning: Failed to update embeddings: {}", e);
+            }
+        }
+        total += findings.len();
+        flagged.push(output);
+    }
+
+    println!(
+        "{} {} likely secrets in {} of {} devlogs",
+        if redact { "Redacted" } else { "Found" },
+        total,
+        flagged.len(),
+        files.len()
+    );
+    Ok(flagged)
+}
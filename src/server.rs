@@ -1,21 +1,74 @@
+use crate::access::{self, Viewer};
+use crate::audit::{AuditAction, AuditLog};
+use crate::config::{NotificationsConfig, SavedSearch};
 use crate::output::DevlogOutput;
 use crate::search::{self, SearchScope};
-use crate::stats;
+use crate::session_cache::SessionCache;
+use crate::digest::Digest;
+use crate::{calendar, markdown, notify, stats, telemetry};
+use devlog_core::manifest::{self, Manifests};
+use devlog_core::{
+    forge, BundlePolicy, EncryptedSession, FileStore, GitInfo, ManifestEntry, Metrics, ProjectGrouping, PurgeRequest,
+    RetentionPolicy, TranscriptParams,
+};
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
-    response::{Html, IntoResponse},
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    extract::Request,
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Largest accepted `/ingest/batch` body
+const BATCH_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// Requests doing file IO at once unless `--io-limit` says otherwise
+pub const DEFAULT_IO_LIMIT: usize = 16;
+
+/// Ingest requests in progress or waiting unless `--ingest-queue` says otherwise
+pub const DEFAULT_INGEST_QUEUE: usize = 64;
+
+/// Seconds a client turned away by a full ingest queue is asked to wait
+const INGEST_RETRY_AFTER_SECS: u64 = 5;
 
 #[derive(Clone)]
 pub struct ServerConfig {
     pub storage_dir: PathBuf,
     pub port: u16,
+    /// Named queries listed on the search page
+    pub saved_searches: BTreeMap<String, SavedSearch>,
+    /// Periodically prune the storage directory
+    pub retention: Option<RetentionPolicy>,
+    /// Periodically roll older days into day bundles
+    pub bundles: Option<BundlePolicy>,
+    /// Webhooks told about each session stored
+    pub notifications: Option<NotificationsConfig>,
+    /// Bearer token for the admin endpoints, from `DEVLOG_ADMIN_TOKEN`
+    pub admin_token: Option<String>,
+    /// Accounts that must sign in to see sessions, each limited to its
+    /// projects; with none, and no web login, the pages are open
+    pub users: Vec<access::User>,
+    /// One login for every page and session API, from `DEVLOG_WEB_USER` and
+    /// `DEVLOG_WEB_PASSWORD`; it is not accepted on the ingest routes
+    pub web_login: Option<access::WebLogin>,
+    /// Bearer token the ingest routes require, from `DEVLOG_INGEST_TOKEN`
+    pub ingest_token: Option<String>,
+    /// Where reads of session content and admin actions are recorded
+    pub audit: Arc<AuditLog>,
+    pub metrics: Arc<Metrics>,
+    /// Sessions parsed lately, for pages and searches
+    pub sessions: Arc<SessionCache>,
+    /// Requests whose file IO may run at once; the rest wait their turn
+    pub io_limit: Arc<Semaphore>,
+    /// Ingest requests being stored or waiting to be; more are turned away with 429
+    pub ingest_queue: Arc<Semaphore>,
 }
 
 impl Default for ServerConfig {
@@ -23,6 +76,19 @@ impl Default for ServerConfig {
         Self {
             storage_dir: PathBuf::from("/store/devolver"),
             port: 8090,
+            saved_searches: BTreeMap::new(),
+            retention: None,
+            bundles: None,
+            notifications: None,
+            admin_token: None,
+            users: Vec::new(),
+            web_login: None,
+            ingest_token: None,
+            audit: Arc::new(AuditLog::new("/store/devolver/audit.jsonl")),
+            metrics: Arc::default(),
+            sessions: Arc::default(),
+            io_limit: Arc::new(Semaphore::new(DEFAULT_IO_LIMIT)),
+            ingest_queue: Arc::new(Semaphore::new(DEFAULT_INGEST_QUEUE)),
         }
     }
 }
@@ -31,31 +97,217 @@ pub async fn run_server(config: ServerConfig) -> anyhow::Result<()> {
     // Ensure storage directory exists
     fs::create_dir_all(&config.storage_dir)?;
 
+    // A bad storage key stops the server before it stores anything in the clear
+    if devlog_core::at_rest::key()?.is_some() {
+        let sealed = FileStore::new(&config.storage_dir).seal_existing()?;
+        eprintln!("Encrypting stored sessions at rest ({} existing files encrypted)", sealed);
+    }
+    if devlog_core::compression::enabled()? {
+        eprintln!("Storing sessions compressed with zstd");
+    }
+    if let Some(signer) = devlog_core::receipt::signer()? {
+        eprintln!("Signing ingest receipts with public key {}", signer.public_key());
+    }
+
     let state = Arc::new(config.clone());
 
+    if let Some(policy) = config.retention.clone().filter(RetentionPolicy::is_enabled) {
+        tokio::spawn(prune_periodically(
+            FileStore::new(&config.storage_dir),
+            policy,
+            config.metrics.clone(),
+        ));
+    }
+
+    if let Some(policy) = config.bundles.clone() {
+        tokio::spawn(bundle_periodically(FileStore::new(&config.storage_dir), policy));
+    }
+
+    if let Some(notifications) = config.notifications.as_ref() {
+        if let Some(ref email) = notifications.email {
+            let digest = Digest::new(email, notifications.base_url.clone())?;
+            tokio::spawn(send_digests(digest, config.storage_dir.clone()));
+        }
+    }
+
+    let ingest_routes = Router::new()
+        .route("/ingest", post(ingest))
+        .route(
+            "/ingest/batch",
+            post(ingest_batch).layer(DefaultBodyLimit::max(BATCH_BODY_LIMIT)),
+        )
+        .route(
+            "/ingest/transcript",
+            post(ingest_transcript).layer(DefaultBodyLimit::max(BATCH_BODY_LIMIT)),
+        )
+        .route(
+            "/ingest/encrypted",
+            post(ingest_encrypted).layer(DefaultBodyLimit::max(BATCH_BODY_LIMIT)),
+        )
+        .route_layer(middleware::from_fn_with_state(state.clone(), queue_ingest))
+        .route_layer(middleware::from_fn_with_state(state.clone(), access::require_ingest_token));
+
     let app = Router::new()
         .route("/", get(index))
         .route("/health", get(health))
+        .route("/metrics", get(metrics))
         .route("/stats", get(stats_page))
+        .route("/project/:name", get(project_page))
+        .route("/project/:name/files", get(project_files_page))
+        .route("/calendar", get(calendar_page))
+        .route("/dashboard", get(dashboard_page))
         .route("/search", get(search_page))
-        .route("/ingest", post(ingest))
+        .route("/session/:machine/:project/:file", get(session_page))
+        .route("/api/search", get(search_api))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/:machine/:session", get(get_session).delete(delete_session))
+        .merge(ingest_routes)
+        .route("/admin/purge", post(purge))
+        .route("/admin/compact", post(compact))
+        .route("/admin/audit", get(audit_page))
         .with_state(state);
 
+    // A bad certificate stops the server rather than falling back to plain HTTP
+    let tls = devlog_core::tls::from_env()?;
+
     let addr = format!("0.0.0.0:{}", config.port);
-    eprintln!("Devlog receiver listening on {}", addr);
+    eprintln!("Devlog receiver listening on {}{}", addr, if tls.is_some() { " (TLS)" } else { "" });
+    if let (Some(_), Ok(client_ca)) = (tls.as_ref(), std::env::var("DEVLOG_TLS_CLIENT_CA")) {
+        eprintln!("Requiring client certificates issued by {}", client_ca);
+    }
     eprintln!("Storage directory: {}", config.storage_dir.display());
+    eprintln!("Audit log: {}", config.audit.path().display());
+    if config.users.is_empty() && config.web_login.is_none() {
+        eprintln!("Warning: No [users] or DEVLOG_WEB_PASSWORD set; pages and session APIs are open to anyone who can reach the server");
+    }
+    if config.ingest_token.is_none() {
+        eprintln!("Warning: DEVLOG_INGEST_TOKEN is not set; anyone who can reach the server may ingest sessions");
+    }
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    match tls {
+        Some(tls) => serve_tls(listener, app, tls).await,
+        None => Ok(axum::serve(listener, app).await?),
+    }
+}
+
+/// Serve `app` over TLS, handshaking each connection before any request is
+/// read; a client without an accepted certificate never reaches a handler
+async fn serve_tls(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    tls: tokio_rustls::rustls::ServerConfig,
+) -> anyhow::Result<()> {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::service::TowerToHyperService;
+
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(tls));
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                eprintln!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let (acceptor, app) = (acceptor.clone(), app.clone());
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+            let service = TowerToHyperService::new(app);
+            if let Err(e) = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                eprintln!("Connection from {} failed: {}", peer, e);
+            }
+        });
+    }
+}
 
-    Ok(())
+/// Enforce the retention policy now and then every `interval_hours`
+async fn prune_periodically(store: FileStore, policy: RetentionPolicy, metrics: Arc<Metrics>) {
+    loop {
+        let (store_ref, policy_ref) = (store.clone(), policy.clone());
+        match tokio::task::spawn_blocking(move || store_ref.prune(&policy_ref)).await {
+            Ok(Ok(report)) => {
+                metrics.record_pruned(report.sessions);
+                if report.sessions > 0 {
+                    eprintln!("Retention: {}", report);
+                }
+            }
+            Ok(Err(e)) => eprintln!("Retention pruning failed: {}", e),
+            Err(e) => eprintln!("Retention pruning failed: {}", e),
+        }
+        tokio::time::sleep(policy.interval()).await;
+    }
+}
+
+/// Bundle the days old enough now and then every `interval_hours`
+async fn bundle_periodically(store: FileStore, policy: BundlePolicy) {
+    loop {
+        let (store_ref, last_day) = (store.clone(), policy.last_day());
+        match tokio::task::spawn_blocking(move || store_ref.bundle_days(last_day)).await {
+            Ok(Ok(report)) if report.sessions > 0 => eprintln!("Bundles: {}", report),
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => eprintln!("Bundling failed: {:#}", e),
+            Err(e) => eprintln!("Bundling failed: {}", e),
+        }
+        tokio::time::sleep(policy.interval()).await;
+    }
+}
+
+/// Email the activity digest whenever it is due
+async fn send_digests(digest: Digest, storage_dir: PathBuf) {
+    let digest = Arc::new(digest);
+    loop {
+        let due = digest.next_send(chrono::Utc::now());
+        eprintln!("Next digest due at {}", due.format("%Y-%m-%d %H:%M UTC"));
+        let wait = (due - chrono::Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let (digest_ref, storage_ref) = (digest.clone(), storage_dir.clone());
+        match tokio::task::spawn_blocking(move || digest_ref.send_due(&storage_ref, due)).await {
+            Ok(Ok(true)) => eprintln!("Sent digest to {} recipients", digest.recipients()),
+            Ok(Ok(false)) => eprintln!("No sessions since the last digest; not sending one"),
+            Ok(Err(e)) => eprintln!("Failed to send digest: {:#}", e),
+            Err(e) => eprintln!("Failed to send digest: {}", e),
+        }
+    }
+}
+
+/// Prometheus text exposition, shared with devlog-receiver
+async fn metrics(State(config): State<Arc<ServerConfig>>) -> impl IntoResponse {
+    let store = FileStore::new(&config.storage_dir);
+    let storage_bytes = blocking(&config, move || store.total_bytes()).await.unwrap_or(0);
+
+    (
+        [(axum::http::header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        config.metrics.render(storage_bytes),
+    )
+}
+
+/// Run a request's file IO and parsing on the blocking thread pool, so slow
+/// disks and big searches don't stall the async runtime. At most `io_limit`
+/// run at once; later requests wait for a turn.
+async fn blocking<T: Send + 'static>(
+    config: &ServerConfig,
+    work: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    let _permit = config.io_limit.acquire().await?;
+    tokio::task::spawn_blocking(work).await?
 }
 
 async fn health() -> &'static str {
     "devlog-receiver OK"
 }
 
-async fn index() -> Html<&'static str> {
+async fn index(_viewer: Viewer) -> Html<&'static str> {
     Html(r#"<!DOCTYPE html>
 <html>
 <head><title>Devlog Receiver</title></head>
@@ -64,6 +316,9 @@ async fn index() -> Html<&'static str> {
 <ul>
 <li><a href="search">Search</a></li>
 <li><a href="stats">Project Stats</a></li>
+<li><a href="calendar">Activity Calendar</a></li>
+<li><a href="dashboard">Live Dashboard</a></li>
+<li><a href="admin/audit">Audit Log</a> (admins)</li>
 <li><a href="health">Health Check</a></li>
 </ul>
 </body>
@@ -73,6 +328,12 @@ async fn index() -> Html<&'static str> {
 #[derive(serde::Deserialize)]
 struct StatsQuery {
     days: Option<u32>,
+    /// `remote` to key projects by git remote instead of directory name
+    group: Option<String>,
+    /// Only sessions with this tag
+    tag: Option<String>,
+    /// Only sessions whose prompts mention this ticket
+    ticket: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -80,23 +341,101 @@ struct SearchQuery {
     q: Option<String>,
     scope: Option<String>,
     days: Option<u32>,
+    limit: Option<usize>,
+    cursor: Option<String>,
+    case: Option<String>,
+    word: Option<String>,
+    tag: Option<String>,
+    ticket: Option<String>,
+    /// Only this project's sessions, as stored
+    project: Option<String>,
+    /// Only sessions from this machine
+    machine: Option<String>,
+}
+
+/// Which stored projects and machines a search covers
+#[derive(Clone, Default)]
+struct SearchWithin {
+    project: Option<String>,
+    machine: Option<String>,
+}
+
+impl SearchWithin {
+    fn covers(&self, file: &search::DevlogFile) -> bool {
+        self.project.as_ref().map(|p| &file.project == p).unwrap_or(true)
+            && self.machine.as_ref().map(|m| &file.machine == m).unwrap_or(true)
+    }
+}
+
+impl SearchQuery {
+    fn within(&self) -> SearchWithin {
+        // The search form submits empty fields when no filter is wanted
+        SearchWithin {
+            project: self.project.clone().filter(|p| !p.trim().is_empty()),
+            machine: self.machine.clone().filter(|m| !m.trim().is_empty()),
+        }
+    }
+
+    fn options(&self) -> search::SearchOptions {
+        search::SearchOptions {
+            scope: self
+                .scope
+                .as_deref()
+                .map(SearchScope::from_str)
+                .unwrap_or_default(),
+            days: self.days,
+            limit: self.limit.unwrap_or(50),
+            cursor: self.cursor.clone(),
+            case_sensitive: is_checked(&self.case),
+            whole_word: is_checked(&self.word),
+            // The search form submits an empty field when no tag is wanted
+            tag: self.tag.clone().filter(|t| !t.trim().is_empty()),
+            ticket: self.ticket.clone().filter(|t| !t.trim().is_empty()),
+        }
+    }
+}
+
+/// HTML checkboxes submit "on"; API clients send "true"
+fn is_checked(value: &Option<String>) -> bool {
+    matches!(value.as_deref(), Some("on" | "true" | "1"))
 }
 
 async fn stats_page(
     State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
     Query(query): Query<StatsQuery>,
 ) -> impl IntoResponse {
     let days = query.days.unwrap_or(7);
+    let grouping = ProjectGrouping::parse(query.group.as_deref().unwrap_or_default());
+    let tag = query.tag.as_deref().filter(|t| !t.is_empty());
+    let ticket = query.ticket.as_deref().filter(|t| !t.is_empty());
+
+    let counted = {
+        let (config, viewer) = (config.clone(), viewer.clone());
+        let (tag, ticket) = (tag.map(String::from), ticket.map(String::from));
+        move || {
+            let (tag, ticket, projects) = (tag.as_deref(), ticket.as_deref(), viewer.projects());
+            // One walk of the store serves the table and its sparklines,
+            // which may reach further back than the table
+            let walked = days.max(calendar::SPARKLINE_DAYS as u32);
+            let sessions = stats::session_entries(&config.storage_dir, walked, tag, ticket, projects)?;
+            let first = chrono::Utc::now().date_naive() - chrono::Duration::days(calendar::SPARKLINE_DAYS - 1);
+            let activity = calendar::Activity::of(&sessions, grouping, calendar::Metric::Prompts, first);
 
-    let grouped = stats::get_project_stats_grouped(&config.storage_dir, days);
-    let by_machine = stats::get_project_stats(&config.storage_dir, days);
+            let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+            let sessions = sessions.into_iter().filter(|s| s.since(cutoff)).collect();
+            let by_machine = stats::tally(sessions, grouping);
+            let grouped = stats::group_by_project(by_machine.clone());
+            Ok((grouped, by_machine, activity))
+        }
+    };
 
-    match (grouped, by_machine) {
-        (Ok(grouped_stats), Ok(machine_stats)) => {
-            let html = render_stats_html(&grouped_stats, &machine_stats, days);
+    match blocking(&config, counted).await {
+        Ok((grouped_stats, machine_stats, activity)) => {
+            let html = render_stats_html(&grouped_stats, &machine_stats, &activity, days, grouping, tag, ticket);
             (StatusCode::OK, Html(html))
         }
-        (Err(e), _) | (_, Err(e)) => (
+        Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Html(format!("<h1>Error</h1><p>{}</p>", e)),
         ),
@@ -106,8 +445,16 @@ async fn stats_page(
 fn render_stats_html(
     grouped: &[stats::ProjectStats],
     by_machine: &[stats::ProjectStats],
+    activity: &calendar::Activity,
     days: u32,
+    grouping: ProjectGrouping,
+    tag: Option<&str>,
+    ticket: Option<&str>,
 ) -> String {
+    let mut filter_param = tag.map(|t| format!("&tag={}", url_encode(t))).unwrap_or_default();
+    if let Some(ticket) = ticket {
+        filter_param.push_str(&format!("&ticket={}", url_encode(ticket)));
+    }
     let mut html = format!(
         r#"<!DOCTYPE html>
 <html>
@@ -132,42 +479,96 @@ a {{ color: #00d9ff; }}
 .filter a {{ padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
 .filter a:hover, .filter a.active {{ background: #00d9ff; color: #1a1a2e; }}
 .total {{ margin-top: 1rem; color: #888; }}
+a.forge {{ margin-left: 0.5rem; font-size: 0.8rem; }}
+.sparkline {{ vertical-align: middle; }}
 </style>
 </head>
 <body>
 <h1>Project Activity</h1>
 <div class="filter">
-  <a href="stats?days=1" {}>Today</a>
-  <a href="stats?days=7" {}>7 days</a>
-  <a href="stats?days=30" {}>30 days</a>
-  <a href="stats?days=90" {}>90 days</a>
+  <a href="stats?days=1&group={group}{filter}" {}>Today</a>
+  <a href="stats?days=7&group={group}{filter}" {}>7 days</a>
+  <a href="stats?days=30&group={group}{filter}" {}>30 days</a>
+  <a href="stats?days=90&group={group}{filter}" {}>90 days</a>
+</div>
+<div class="filter">
+  <a href="stats?days={days}&group=directory{filter}" {}>By directory</a>
+  <a href="stats?days={days}&group=remote{filter}" {}>By remote</a>
 </div>
 "#,
         if days == 1 { "class=\"active\"" } else { "" },
         if days == 7 { "class=\"active\"" } else { "" },
         if days == 30 { "class=\"active\"" } else { "" },
         if days == 90 { "class=\"active\"" } else { "" },
+        if grouping == ProjectGrouping::Directory { "class=\"active\"" } else { "" },
+        if grouping == ProjectGrouping::Remote { "class=\"active\"" } else { "" },
+        group = grouping.as_str(),
+        days = days,
+        filter = html_escape(&filter_param),
     );
 
+    if tag.is_some() || ticket.is_some() {
+        let mut filters = Vec::new();
+        if let Some(tag) = tag {
+            filters.push(format!("Tagged <strong>{}</strong>", html_escape(tag)));
+        }
+        if let Some(ticket) = ticket {
+            filters.push(format!("Ticket <strong>{}</strong>", html_escape(ticket)));
+        }
+        html.push_str(&format!(
+            "<div class=\"filter\"><span>{}</span><a href=\"stats?days={}&group={}\">Clear</a></div>\n",
+            filters.join(" · "),
+            days,
+            grouping.as_str()
+        ));
+    }
+
     if grouped.is_empty() {
         html.push_str(&format!("<p>No activity in the last {} days</p>", days));
     } else {
         html.push_str(
             r#"<table>
-<tr><th>Project</th><th class="number">Prompts</th><th class="number">Tools</th><th class="number">Files</th><th class="number">Words In</th><th class="number">Words Out</th><th>Last Activity</th></tr>
+<tr><th>Project</th><th>Last 30 days</th><th class="number">Prompts</th><th class="number">Tools</th><th class="number">Files</th><th class="number">Words In</th><th class="number">Words Out</th><th>Last Activity</th></tr>
 "#,
         );
 
+        let (today, quiet) = (chrono::Utc::now().date_naive(), calendar::Days::new());
         for (idx, stat) in grouped.iter().enumerate() {
             let last = chrono::DateTime::parse_from_rfc3339(&stat.last_activity)
                 .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
                 .unwrap_or_else(|_| stat.last_activity.clone());
 
+            // Link the project to its sessions, and to its repository when the
+            // remote is on a known forge
+            let mut project = format!(
+                "<a href=\"project/{}?days={}&group={}{}\" onclick=\"event.stopPropagation()\">{}</a>",
+                html_escape(&url_encode(&stat.project)),
+                days,
+                grouping.as_str(),
+                html_escape(&filter_param),
+                html_escape(&stat.project)
+            );
+            if let Some(url) = stat.remote.as_deref().and_then(forge::web_url) {
+                project.push_str(&format!(
+                    " <a class=\"forge\" href=\"{}\" onclick=\"event.stopPropagation()\">repo</a>",
+                    html_escape(&url)
+                ));
+            }
+
+            let sparkline = activity
+                .projects
+                .iter()
+                .find(|(name, _)| *name == stat.project)
+                .map(|(_, days)| days)
+                .unwrap_or(&quiet);
+            let sparkline = calendar::sparkline_svg(sparkline, today, calendar::Metric::Prompts);
+
             // Parent row (grouped)
             html.push_str(&format!(
-                "<tr class=\"parent\" data-idx=\"{}\"><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                "<tr class=\"parent\" data-idx=\"{}\"><td>{}</td><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
                 idx,
-                html_escape(&stat.project),
+                project,
+                sparkline,
                 stat.prompt_count,
                 stat.tool_calls,
                 stat.files_touched,
@@ -183,7 +584,7 @@ a {{ color: #00d9ff; }}
                     .unwrap_or_else(|_| machine_stat.last_activity.clone());
 
                 html.push_str(&format!(
-                    "<tr class=\"child\" data-parent=\"{}\"><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
+                    "<tr class=\"child\" data-parent=\"{}\"><td>{}</td><td></td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td>{}</td></tr>\n",
                     idx,
                     html_escape(&machine_stat.machine),
                     machine_stat.prompt_count,
@@ -227,177 +628,175 @@ document.querySelectorAll('tr.parent').forEach(row => {
     html
 }
 
-fn html_escape(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
+/// Sessions listed on a project page, newest first; each is read for its first prompt
+const PROJECT_PAGE_SESSIONS: usize = 200;
 
-fn format_number(n: usize) -> String {
-    if n >= 1000 {
-        format!("{:.1}k", n as f64 / 1000.0)
-    } else {
-        n.to_string()
-    }
+/// A session's row on its project's page
+struct ProjectSession {
+    session: stats::SessionEntry,
+    first_prompt: Option<String>,
 }
 
-async fn search_page(
+/// The sessions of one project, as the stats table counts it, with links to each
+async fn project_page(
     State(config): State<Arc<ServerConfig>>,
-    Query(query): Query<SearchQuery>,
+    viewer: Viewer,
+    Path(name): Path<String>,
+    Query(query): Query<StatsQuery>,
 ) -> impl IntoResponse {
-    let scope = query
-        .scope
-        .as_deref()
-        .map(SearchScope::from_str)
-        .unwrap_or_default();
+    let days = query.days.unwrap_or(7);
+    let grouping = ProjectGrouping::parse(query.group.as_deref().unwrap_or_default());
+    let tag = query.tag.as_deref().filter(|t| !t.is_empty());
+    let ticket = query.ticket.as_deref().filter(|t| !t.is_empty());
 
-    let results = query.q.as_ref().map(|q| {
-        if q.trim().is_empty() {
-            Ok(Vec::new())
-        } else {
-            search::search_devlogs(&config.storage_dir, q, scope, query.days, 50)
+    let listed = {
+        let (config, viewer, name) = (config.clone(), viewer.clone(), name.clone());
+        let (tag, ticket) = (tag.map(String::from), ticket.map(String::from));
+        move || {
+            let (tag, ticket, projects) = (tag.as_deref(), ticket.as_deref(), viewer.projects());
+            let mut sessions = stats::session_entries(&config.storage_dir, days, tag, ticket, projects)?;
+            sessions.retain(|s| *s.project(grouping) == *name);
+            sessions.sort_by(|a, b| b.entry.header.timestamp.cmp(&a.entry.header.timestamp));
+            let total = sessions.len();
+            let listed = sessions
+                .into_iter()
+                .take(PROJECT_PAGE_SESSIONS)
+                .map(|session| {
+                    let first_prompt = config.sessions.read(&session.path).ok().and_then(|output| {
+                        output.conversation.iter().find_map(|entry| match entry {
+                            crate::parser::ConversationEntry::User { content, .. } => Some(content.clone()),
+                            _ => None,
+                        })
+                    });
+                    ProjectSession { session, first_prompt }
+                })
+                .collect::<Vec<_>>();
+            Ok((listed, total))
         }
-    });
-
-    let html = render_search_html(
-        query.q.as_deref().unwrap_or(""),
-        query.scope.as_deref().unwrap_or("conversations"),
-        query.days,
-        results,
-    );
+    };
 
-    (StatusCode::OK, Html(html))
+    match blocking(&config, listed).await {
+        Ok((sessions, total)) => {
+            let html = render_project_html(&name, &sessions, total, days, grouping, tag, ticket);
+            (StatusCode::OK, Html(html))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(format!("<h1>Error</h1><p>{}</p>", html_escape(&e.to_string()))),
+        ),
+    }
 }
 
-fn render_search_html(
-    query: &str,
-    scope: &str,
-    days: Option<u32>,
-    results: Option<anyhow::Result<Vec<search::SearchResult>>>,
+fn render_project_html(
+    name: &str,
+    sessions: &[ProjectSession],
+    total: usize,
+    days: u32,
+    grouping: ProjectGrouping,
+    tag: Option<&str>,
+    ticket: Option<&str>,
 ) -> String {
+    let mut filter_param = format!("&group={}", grouping.as_str());
+    if let Some(tag) = tag {
+        filter_param.push_str(&format!("&tag={}", url_encode(tag)));
+    }
+    if let Some(ticket) = ticket {
+        filter_param.push_str(&format!("&ticket={}", url_encode(ticket)));
+    }
+    let days_link = |d: u32, label: &str| {
+        format!(
+            "<a href=\"?days={}{}\"{}>{}</a>",
+            d,
+            html_escape(&filter_param),
+            if d == days { " class=\"active\"" } else { "" },
+            label
+        )
+    };
+
     let mut html = format!(
         r#"<!DOCTYPE html>
 <html>
 <head>
-<title>Search Devlogs</title>
+<title>{} · Devlog</title>
 <style>
 body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
 h1 {{ color: #00d9ff; }}
-.search-form {{ margin-bottom: 1.5rem; }}
-.search-form input[type="text"] {{
-    width: 400px; padding: 0.5rem; font-size: 1rem;
-    background: #16213e; border: 1px solid #333; color: #eee; border-radius: 4px;
-}}
-.search-form button {{
-    padding: 0.5rem 1rem; font-size: 1rem; cursor: pointer;
-    background: #00d9ff; color: #1a1a2e; border: none; border-radius: 4px;
-}}
-.search-form button:hover {{ background: #00b8d4; }}
-.filters {{ margin-bottom: 1rem; display: flex; gap: 1.5rem; align-items: center; }}
-.filters label {{ color: #888; cursor: pointer; }}
-.filters input[type="radio"] {{ margin-right: 0.3rem; }}
-.filters select {{
-    background: #16213e; color: #eee; border: 1px solid #333;
-    padding: 0.3rem; border-radius: 4px;
-}}
-.result {{
-    margin-bottom: 1.5rem; padding: 1rem;
-    background: #16213e; border-radius: 8px; border-left: 3px solid #00d9ff;
-}}
-.result-header {{
-    display: flex; justify-content: space-between; align-items: center;
-    margin-bottom: 0.5rem; font-size: 0.9rem; color: #888;
-}}
-.result-header .project {{ color: #00d9ff; font-weight: bold; }}
-.result-header .type {{
-    padding: 0.15rem 0.5rem; border-radius: 3px; font-size: 0.8rem;
-}}
-.type-user {{ background: #2d5a2d; color: #8f8; }}
-.type-assistant {{ background: #5a2d5a; color: #f8f; }}
-.type-tool {{ background: #5a5a2d; color: #ff8; }}
-.snippet {{ line-height: 1.5; white-space: pre-wrap; word-break: break-word; }}
-.snippet mark {{ background: #ff0; color: #000; padding: 0 2px; }}
+table {{ border-collapse: collapse; width: 100%; max-width: 1200px; }}
+th, td {{ padding: 0.5rem 1rem; text-align: left; border-bottom: 1px solid #333; vertical-align: top; }}
+th {{ background: #16213e; color: #00d9ff; }}
+tr:hover {{ background: #16213e; }}
+.number {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.when {{ white-space: nowrap; }}
+.prompt {{ color: #ccc; }}
 a {{ color: #00d9ff; }}
-.no-results {{ color: #888; font-style: italic; }}
 .back {{ margin-bottom: 1rem; }}
+.filter {{ margin-bottom: 1rem; display: flex; gap: 0.5rem; }}
+.filter a {{ padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.filter a:hover, .filter a.active {{ background: #00d9ff; color: #1a1a2e; }}
+.total {{ margin-top: 1rem; color: #888; }}
 </style>
 </head>
 <body>
-<div class="back"><a href="/">← Back</a></div>
-<h1>Search Devlogs</h1>
-<form class="search-form" method="get">
-  <input type="text" name="q" placeholder="Search conversations..." value="{}" autofocus>
-  <button type="submit">Search</button>
-  <div class="filters">
-    <span>Scope:</span>
-    <label><input type="radio" name="scope" value="prompts" {}> Prompts only</label>
-    <label><input type="radio" name="scope" value="conversations" {}> Conversations</label>
-    <label><input type="radio" name="scope" value="all" {}> Everything</label>
-    <span style="margin-left: 1rem;">Days:</span>
-    <select name="days">
-      <option value="" {}>All time</option>
-      <option value="1" {}>Today</option>
-      <option value="7" {}>7 days</option>
-      <option value="30" {}>30 days</option>
-      <option value="90" {}>90 days</option>
-    </select>
-  </div>
-</form>
+<div class="back"><a href="../stats?days={}{}">← Stats</a> · <a href="{}/files?days={}&group={}">Files</a> · <a href="../calendar?project={}&group={}">Calendar</a> · <a href="../search">Search</a></div>
+<h1>{}</h1>
+<div class="filter">{} {} {} {}</div>
 "#,
-        html_escape(query),
-        if scope == "prompts" { "checked" } else { "" },
-        if scope == "conversations" { "checked" } else { "" },
-        if scope == "all" { "checked" } else { "" },
-        if days.is_none() { "selected" } else { "" },
-        if days == Some(1) { "selected" } else { "" },
-        if days == Some(7) { "selected" } else { "" },
-        if days == Some(30) { "selected" } else { "" },
-        if days == Some(90) { "selected" } else { "" },
+        html_escape(name),
+        days,
+        html_escape(&filter_param),
+        html_escape(&url_encode(name)),
+        days,
+        grouping.as_str(),
+        html_escape(&url_encode(name)),
+        grouping.as_str(),
+        html_escape(name),
+        days_link(1, "Today"),
+        days_link(7, "7 days"),
+        days_link(30, "30 days"),
+        days_link(90, "90 days"),
     );
 
-    match results {
-        None => {
-            // No search performed yet
-        }
-        Some(Ok(results)) if results.is_empty() => {
-            html.push_str("<p class=\"no-results\">No results found.</p>");
-        }
-        Some(Ok(results)) => {
-            html.push_str(&format!("<p>{} results</p>", results.len()));
-            for result in results {
-                let timestamp = chrono::DateTime::parse_from_rfc3339(&result.timestamp)
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-                    .unwrap_or_else(|_| result.timestamp.clone());
-
-                let type_class = match result.entry_type.as_str() {
-                    "user" => "type-user",
-                    "assistant" => "type-assistant",
-                    _ => "type-tool",
-                };
-
-                let highlighted_snippet = highlight_match(&result.snippet, &result.query);
-
-                html.push_str(&format!(
-                    r#"<div class="result">
-  <div class="result-header">
-    <span><span class="project">{}</span> · {} · {}</span>
-    <span class="type {}">{}</span>
-  </div>
-  <div class="snippet">{}</div>
-</div>
+    if sessions.is_empty() {
+        html.push_str(&format!("<p>No sessions in the last {} days</p>", days));
+    } else {
+        html.push_str(
+            r#"<table>
+<tr><th>Date</th><th>Machine</th><th>First prompt</th><th class="number">Prompts</th><th class="number">Tools</th><th class="number">Files</th><th class="number">In Tokens</th><th class="number">Out Tokens</th></tr>
 "#,
-                    html_escape(&result.project),
-                    html_escape(&result.machine),
-                    timestamp,
-                    type_class,
-                    result.entry_type,
-                    highlighted_snippet,
-                ));
-            }
+        );
+        for row in sessions {
+            let entry = &row.session.entry;
+            let when = chrono::DateTime::parse_from_rfc3339(&entry.header.timestamp)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|_| entry.header.timestamp.clone());
+            let href = format!("../{}", session_link(&row.session));
+            let prompt = row
+                .first_prompt
+                .as_deref()
+                .map(|p| crate::parser::truncate(p.lines().next().unwrap_or_default(), 120))
+                .unwrap_or_else(|| "(no prompt)".to_string());
+            html.push_str(&format!(
+                "<tr><td class=\"when\"><a href=\"{}\">{}</a></td><td>{}</td><td class=\"prompt\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+                html_escape(&href),
+                when,
+                html_escape(&row.session.machine),
+                html_escape(&prompt),
+                entry.stats.prompts,
+                entry.stats.tool_calls,
+                entry.stats.files_touched,
+                stats::format_tokens(entry.stats.input_tokens),
+                stats::format_tokens(entry.stats.output_tokens),
+            ));
         }
-        Some(Err(e)) => {
-            html.push_str(&format!("<p class=\"no-results\">Error: {}</p>", e));
+        html.push_str("</table>");
+        if total > sessions.len() {
+            html.push_str(&format!(
+                "<p class=\"total\">Showing the latest {} of {} sessions</p>",
+                sessions.len(),
+                total
+            ));
+        } else {
+            html.push_str(&format!("<p class=\"total\">{} sessions</p>", total));
         }
     }
 
@@ -405,78 +804,1805 @@ a {{ color: #00d9ff; }}
     html
 }
 
-fn highlight_match(snippet: &str, query: &str) -> String {
-    let escaped = html_escape(snippet);
-    let query_lower = query.to_lowercase();
-    let escaped_lower = escaped.to_lowercase();
-
-    // Find match position in escaped string
-    if let Some(pos) = escaped_lower.find(&query_lower) {
-        let before = &escaped[..pos];
-        let matched = &escaped[pos..pos + query.len()];
-        let after = &escaped[pos + query.len()..];
-        format!("{}<mark>{}</mark>{}", before, matched, after)
-    } else {
-        escaped
-    }
+/// A session page's address, relative to the server's root
+fn session_link(session: &stats::SessionEntry) -> String {
+    let file = session.path.file_name().and_then(|f| f.to_str()).unwrap_or_default();
+    format!(
+        "session/{}/{}/{}",
+        url_encode(&session.machine),
+        url_encode(&session.project_dir),
+        url_encode(file)
+    )
 }
 
-async fn ingest(
-    State(config): State<Arc<ServerConfig>>,
-    Json(payload): Json<DevlogOutput>,
-) -> impl IntoResponse {
-    match store_devlog(&config.storage_dir, &payload) {
-        Ok(path) => {
-            eprintln!("Stored devlog: {}", path.display());
-            (StatusCode::OK, format!("Stored: {}", path.display()))
-        }
-        Err(e) => {
-            eprintln!("Failed to store devlog: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e))
-        }
-    }
+#[derive(serde::Deserialize)]
+struct FilesQuery {
+    days: Option<u32>,
+    /// `remote` to key projects by git remote instead of directory name
+    group: Option<String>,
+    /// Only files whose path contains this
+    path: Option<String>,
 }
 
-fn store_devlog(storage_dir: &PathBuf, output: &DevlogOutput) -> anyhow::Result<PathBuf> {
-    // Organize by machine_id/project
-    let machine_dir = storage_dir.join(&output.machine_id);
-
-    // Extract project name from project_dir (last component)
-    // Handle both Windows and Unix paths
-    let project_name = extract_project_name(&output.project_dir);
+/// Sessions linked from each file on a project's files page; the rest are counted
+const FILE_PAGE_SESSIONS: usize = 10;
 
-    let project_dir = machine_dir.join(&project_name);
-    fs::create_dir_all(&project_dir)?;
+/// A file touched in a project, with the sessions that touched it, newest first
+struct FileActivity {
+    file: String,
+    sessions: Vec<FileTouch>,
+}
 
-    // Generate filename: YYYY-MM-DD-HHMMSS-<session_id_short>.json
-    let filename = generate_filename(&output.session_id, &output.timestamp);
-    let output_path = project_dir.join(&filename);
+struct FileTouch {
+    link: String,
+    timestamp: String,
+    /// How the session touched the file: edited, created, read or committed
+    how: String,
+}
 
-    // Serialize and write
-    let json = serde_json::to_string_pretty(output)?;
-    fs::write(&output_path, json)?;
+/// The files a project's sessions touched, busiest first, from their tool
+/// actions and the commits made during them, with paths relative to the
+/// project where they can be
+fn file_activity(config: &ServerConfig, mut sessions: Vec<stats::SessionEntry>, path: Option<&str>) -> Vec<FileActivity> {
+    use crate::parser::ConversationEntry;
 
-    Ok(output_path)
+    sessions.sort_by(|a, b| b.entry.header.timestamp.cmp(&a.entry.header.timestamp));
+    let mut files: HashMap<String, Vec<FileTouch>> = HashMap::new();
+    for session in &sessions {
+        let Ok(output) = config.sessions.read(&session.path) else {
+            continue;
+        };
+        let root = format!("{}/", output.project_dir.trim_end_matches('/'));
+        let mut touched: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        let mut touch = |file: &str, verb| {
+            let verbs = touched.entry(file.strip_prefix(root.as_str()).unwrap_or(file).to_string()).or_default();
+            if !verbs.contains(&verb) {
+                verbs.push(verb);
+            }
+        };
+        for entry in &output.conversation {
+            if let ConversationEntry::ToolSummary { actions } = entry {
+                for (verb, file) in actions.iter().filter_map(|a| devlog_core::counts::file_action(a)) {
+                    touch(file, verb);
+                }
+            }
+        }
+        for file in output.git.iter().flat_map(|git| &git.commits).flat_map(|c| &c.files) {
+            touch(file, "committed");
+        }
+
+        for (file, verbs) in touched {
+            if path.map(|p| !file.contains(p)).unwrap_or(false) {
+                continue;
+            }
+            files.entry(file).or_default().push(FileTouch {
+                link: session_link(session),
+                timestamp: session.entry.header.timestamp.clone(),
+                how: verbs.join(", "),
+            });
+        }
+    }
+
+    let mut files: Vec<FileActivity> = files
+        .into_iter()
+        .map(|(file, sessions)| FileActivity { file, sessions })
+        .collect();
+    files.sort_by(|a, b| b.sessions.len().cmp(&a.sessions.len()).then_with(|| a.file.cmp(&b.file)));
+    files
+}
+
+/// The files touched in one project, each linked to the sessions that touched it
+async fn project_files_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Path(name): Path<String>,
+    Query(query): Query<FilesQuery>,
+) -> impl IntoResponse {
+    let days = query.days.unwrap_or(30);
+    let grouping = ProjectGrouping::parse(query.group.as_deref().unwrap_or_default());
+    let path = query.path.filter(|p| !p.trim().is_empty());
+
+    let listed = {
+        let (config, viewer, name, path) = (config.clone(), viewer.clone(), name.clone(), path.clone());
+        move || {
+            let mut sessions = stats::session_entries(&config.storage_dir, days, None, None, viewer.projects())?;
+            sessions.retain(|s| *s.project(grouping) == *name);
+            Ok(file_activity(&config, sessions, path.as_deref().map(str::trim)))
+        }
+    };
+
+    match blocking(&config, listed).await {
+        Ok(files) => {
+            let html = render_files_html(&name, &files, days, grouping, path.as_deref());
+            (StatusCode::OK, Html(html))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(format!("<h1>Error</h1><p>{}</p>", html_escape(&e.to_string()))),
+        ),
+    }
+}
+
+fn render_files_html(
+    name: &str,
+    files: &[FileActivity],
+    days: u32,
+    grouping: ProjectGrouping,
+    path: Option<&str>,
+) -> String {
+    let path_param = path.map(|p| format!("&path={}", url_encode(p))).unwrap_or_default();
+    let days_link = |d: u32, label: &str| {
+        format!(
+            "<a href=\"?days={}&group={}{}\"{}>{}</a>",
+            d,
+            grouping.as_str(),
+            html_escape(&path_param),
+            if d == days { " class=\"active\"" } else { "" },
+            label
+        )
+    };
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>{} files · Devlog</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
+h1 {{ color: #00d9ff; }}
+table {{ border-collapse: collapse; width: 100%; max-width: 1200px; }}
+th, td {{ padding: 0.5rem 1rem; text-align: left; border-bottom: 1px solid #333; vertical-align: top; }}
+th {{ background: #16213e; color: #00d9ff; }}
+tr:hover {{ background: #16213e; }}
+.number {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.sessions a {{ margin-right: 0.8rem; white-space: nowrap; }}
+.how {{ color: #888; font-size: 0.85rem; }}
+a {{ color: #00d9ff; }}
+code {{ font-family: ui-monospace, monospace; }}
+.back {{ margin-bottom: 1rem; }}
+.filter {{ margin-bottom: 1rem; display: flex; gap: 0.5rem; }}
+.filter a {{ padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.filter a:hover, .filter a.active {{ background: #00d9ff; color: #1a1a2e; }}
+input[type=text] {{ padding: 0.4rem; width: 300px; background: #16213e; border: 1px solid #333; color: #eee; border-radius: 4px; }}
+button {{ padding: 0.4rem 0.8rem; background: #00d9ff; border: none; border-radius: 4px; cursor: pointer; }}
+.total {{ margin-top: 1rem; color: #888; }}
+</style>
+</head>
+<body>
+<div class="back"><a href="../{}?days={}&group={}">← {}</a> · <a href="../../stats?days={}&group={}">Stats</a></div>
+<h1>Files in {}</h1>
+<div class="filter">{} {} {} {}</div>
+<form class="filter" method="get">
+<input type="hidden" name="days" value="{}"><input type="hidden" name="group" value="{}">
+<input type="text" name="path" value="{}" placeholder="Path contains, e.g. parser.rs">
+<button type="submit">Filter</button>
+</form>
+"#,
+        html_escape(name),
+        html_escape(&url_encode(name)),
+        days,
+        grouping.as_str(),
+        html_escape(name),
+        days,
+        grouping.as_str(),
+        html_escape(name),
+        days_link(7, "7 days"),
+        days_link(30, "30 days"),
+        days_link(90, "90 days"),
+        days_link(365, "Year"),
+        days,
+        grouping.as_str(),
+        html_escape(path.unwrap_or("")),
+    );
+
+    if files.is_empty() {
+        let matching = path.map(|p| format!(" matching <code>{}</code>", html_escape(p))).unwrap_or_default();
+        html.push_str(&format!("<p>No files{} touched in the last {} days</p>", matching, days));
+    } else {
+        html.push_str("<table>\n<tr><th>File</th><th class=\"number\">Sessions</th><th>Touched in</th></tr>\n");
+        for activity in files {
+            let mut sessions: Vec<String> = activity
+                .sessions
+                .iter()
+                .take(FILE_PAGE_SESSIONS)
+                .map(|touch| {
+                    let when = chrono::DateTime::parse_from_rfc3339(&touch.timestamp)
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|_| touch.timestamp.clone());
+                    format!(
+                        "<a href=\"../../{}\">{}</a> <span class=\"how\">{}</span>",
+                        html_escape(&touch.link),
+                        when,
+                        html_escape(&touch.how)
+                    )
+                })
+                .collect();
+            if activity.sessions.len() > FILE_PAGE_SESSIONS {
+                sessions.push(format!(
+                    "<span class=\"how\">and {} more</span>",
+                    activity.sessions.len() - FILE_PAGE_SESSIONS
+                ));
+            }
+            html.push_str(&format!(
+                "<tr><td><code>{}</code></td><td class=\"number\">{}</td><td class=\"sessions\">{}</td></tr>\n",
+                html_escape(&activity.file),
+                activity.sessions.len(),
+                sessions.join(" ")
+            ));
+        }
+        html.push_str("</table>");
+        html.push_str(&format!("<p class=\"total\">{} files</p>", files.len()));
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+#[derive(serde::Deserialize)]
+struct DashboardQuery {
+    /// Seconds between refreshes
+    refresh: Option<u64>,
+}
+
+/// How far back the dashboard looks for its recent sessions
+const DASHBOARD_DAYS: u32 = 7;
+/// Sessions listed on the dashboard, newest first
+const DASHBOARD_SESSIONS: usize = 15;
+/// A project counts as active while its last session is this recent
+const ACTIVE_MINUTES: i64 = 60;
+
+/// What the dashboard shows: today's totals, the projects active in the last
+/// [`ACTIVE_MINUTES`], and the latest sessions
+struct Dashboard {
+    today: stats::ProjectStats,
+    today_projects: usize,
+    active: Vec<stats::ProjectStats>,
+    recent: Vec<ProjectSession>,
+}
+
+fn dashboard(config: &ServerConfig, viewer: &Viewer) -> anyhow::Result<Dashboard> {
+    let now = chrono::Utc::now();
+    let midnight = now.date_naive().and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let mut sessions = stats::session_entries(&config.storage_dir, DASHBOARD_DAYS, None, None, viewer.projects())?;
+    sessions.sort_by(|a, b| b.entry.header.timestamp.cmp(&a.entry.header.timestamp));
+
+    let todays: Vec<stats::SessionEntry> = sessions.iter().filter(|s| s.since(midnight)).cloned().collect();
+    let by_project = stats::group_by_project(stats::tally(todays, ProjectGrouping::Directory));
+    let mut today = stats::ProjectStats::new(Arc::from(""), Arc::from(""));
+    for project in &by_project {
+        today.add(project.clone());
+    }
+
+    let active_since = now - chrono::Duration::minutes(ACTIVE_MINUTES);
+    let active: Vec<stats::SessionEntry> = sessions.iter().filter(|s| s.since(active_since)).cloned().collect();
+    let mut active = stats::group_by_project(stats::tally(active, ProjectGrouping::Directory));
+    active.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+
+    let recent = sessions
+        .into_iter()
+        .take(DASHBOARD_SESSIONS)
+        .map(|session| {
+            let first_prompt = config.sessions.read(&session.path).ok().and_then(|output| {
+                output.conversation.iter().find_map(|entry| match entry {
+                    crate::parser::ConversationEntry::User { content, .. } => Some(content.clone()),
+                    _ => None,
+                })
+            });
+            ProjectSession { session, first_prompt }
+        })
+        .collect();
+
+    Ok(Dashboard {
+        today,
+        today_projects: by_project.len(),
+        active,
+        recent,
+    })
+}
+
+/// Today's activity, the projects being worked on now and the latest
+/// sessions, for leaving open on a wall monitor; the page polls itself and
+/// swaps in the fresh figures
+async fn dashboard_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<DashboardQuery>,
+) -> impl IntoResponse {
+    let refresh = query.refresh.unwrap_or(30).max(5);
+    let (config_ref, viewer) = (config.clone(), viewer.clone());
+    match blocking(&config, move || dashboard(&config_ref, &viewer)).await {
+        Ok(dashboard) => (StatusCode::OK, Html(render_dashboard_html(&dashboard, refresh))),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(format!("<h1>Error</h1><p>{}</p>", html_escape(&e.to_string()))),
+        ),
+    }
+}
+
+fn render_dashboard_html(dashboard: &Dashboard, refresh: u64) -> String {
+    let now = chrono::Utc::now();
+    let ago = |timestamp: &str| match chrono::DateTime::parse_from_rfc3339(timestamp) {
+        Ok(dt) => {
+            let minutes = (now - dt.with_timezone(&chrono::Utc)).num_minutes().max(0);
+            match minutes {
+                0 => "just now".to_string(),
+                m if m < 60 => format!("{} min ago", m),
+                m if m < 24 * 60 => format!("{} h ago", m / 60),
+                _ => dt.format("%Y-%m-%d %H:%M").to_string(),
+            }
+        }
+        Err(_) => timestamp.to_string(),
+    };
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>Devlog Dashboard</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
+h1 {{ color: #00d9ff; margin-bottom: 0.3rem; }}
+h2 {{ color: #00d9ff; font-size: 1.1rem; margin-top: 2rem; }}
+.updated {{ color: #888; font-size: 0.85rem; }}
+.totals {{ display: flex; gap: 1rem; flex-wrap: wrap; margin-top: 1rem; }}
+.total {{ background: #16213e; border-radius: 8px; padding: 1rem 1.5rem; min-width: 8rem; }}
+.total .value {{ font-size: 2.2rem; color: #00d9ff; font-variant-numeric: tabular-nums; }}
+.total .label {{ color: #888; font-size: 0.9rem; }}
+table {{ border-collapse: collapse; width: 100%; max-width: 1400px; }}
+th, td {{ padding: 0.5rem 1rem; text-align: left; border-bottom: 1px solid #333; vertical-align: top; }}
+th {{ background: #16213e; color: #00d9ff; }}
+.number {{ text-align: right; font-variant-numeric: tabular-nums; }}
+.when {{ white-space: nowrap; color: #888; }}
+.quiet {{ color: #888; }}
+a {{ color: #00d9ff; }}
+.back {{ margin-bottom: 1rem; }}
+</style>
+</head>
+<body>
+<div class="back"><a href="stats">Stats</a> · <a href="calendar">Calendar</a> · <a href="search">Search</a></div>
+<main id="dashboard">
+<h1>Today</h1>
+<div class="updated">Updated {} UTC, every {} seconds</div>
+<div class="totals">
+"#,
+        now.format("%H:%M:%S"),
+        refresh
+    );
+
+    let today = &dashboard.today;
+    for (value, label) in [
+        (today.session_count.to_string(), "sessions"),
+        (today.prompt_count.to_string(), "prompts"),
+        (today.tool_calls.to_string(), "tool calls"),
+        (stats::format_tokens(today.input_tokens), "tokens in"),
+        (stats::format_tokens(today.output_tokens), "tokens out"),
+        (dashboard.today_projects.to_string(), "projects"),
+    ] {
+        html.push_str(&format!(
+            "<div class=\"total\"><div class=\"value\">{}</div><div class=\"label\">{}</div></div>\n",
+            value, label
+        ));
+    }
+    html.push_str("</div>\n");
+
+    html.push_str(&format!("<h2>Active in the last {} minutes</h2>\n", ACTIVE_MINUTES));
+    if dashboard.active.is_empty() {
+        html.push_str("<p class=\"quiet\">Nothing right now</p>\n");
+    } else {
+        html.push_str("<table>\n<tr><th>Project</th><th>Machines</th><th class=\"number\">Sessions</th><th class=\"number\">Prompts</th><th>Last activity</th></tr>\n");
+        for project in &dashboard.active {
+            html.push_str(&format!(
+                "<tr><td><a href=\"project/{}?days=1\">{}</a></td><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td><td class=\"when\">{}</td></tr>\n",
+                html_escape(&url_encode(&project.project)),
+                html_escape(&project.project),
+                html_escape(&project.machine),
+                project.session_count,
+                project.prompt_count,
+                ago(&project.last_activity)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("<h2>Recent sessions</h2>\n");
+    if dashboard.recent.is_empty() {
+        html.push_str(&format!("<p class=\"quiet\">No sessions in the last {} days</p>\n", DASHBOARD_DAYS));
+    } else {
+        html.push_str("<table>\n<tr><th>When</th><th>Project</th><th>Machine</th><th>First prompt</th><th class=\"number\">Prompts</th><th class=\"number\">Tools</th></tr>\n");
+        for row in &dashboard.recent {
+            let entry = &row.session.entry;
+            let prompt = row
+                .first_prompt
+                .as_deref()
+                .map(|p| crate::parser::truncate(p.lines().next().unwrap_or_default(), 100))
+                .unwrap_or_else(|| "(no prompt)".to_string());
+            html.push_str(&format!(
+                "<tr><td class=\"when\"><a href=\"{}\">{}</a></td><td><a href=\"project/{}?days=7\">{}</a></td><td>{}</td><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>\n",
+                html_escape(&session_link(&row.session)),
+                ago(&entry.header.timestamp),
+                html_escape(&url_encode(&row.session.project_dir)),
+                html_escape(&row.session.project_dir),
+                html_escape(&row.session.machine),
+                html_escape(&prompt),
+                entry.stats.prompts,
+                entry.stats.tool_calls
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    // Swap in the fresh page without reloading, so a wall monitor doesn't
+    // flash; a failed fetch keeps what is shown and tries again next time
+    html.push_str(&format!(
+        r#"</main>
+<script>
+setInterval(async () => {{
+  try {{
+    const response = await fetch(location.href, {{ cache: 'no-store' }});
+    if (!response.ok) return;
+    const page = new DOMParser().parseFromString(await response.text(), 'text/html');
+    const fresh = page.getElementById('dashboard');
+    if (fresh) document.getElementById('dashboard').replaceWith(fresh);
+  }} catch (e) {{}}
+}}, {});
+</script>
+</body></html>"#,
+        refresh * 1000
+    ));
+    html
+}
+
+#[derive(serde::Deserialize)]
+struct CalendarQuery {
+    /// Only this project's heatmap
+    project: Option<String>,
+    /// `remote` to key projects by git remote instead of directory name
+    group: Option<String>,
+    /// `tokens` to shade days by tokens instead of prompts
+    metric: Option<String>,
+}
+
+/// Heatmaps of the last year's activity per day, overall and per project
+async fn calendar_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<CalendarQuery>,
+) -> impl IntoResponse {
+    let grouping = ProjectGrouping::parse(query.group.as_deref().unwrap_or_default());
+    let metric = calendar::Metric::parse(query.metric.as_deref().unwrap_or_default());
+    let project = query.project.filter(|p| !p.is_empty());
+    let last = chrono::Utc::now().date_naive();
+    let first = calendar::first_day(last);
+    let days = (last - first).num_days() as u32 + 1;
+
+    let tallied = {
+        let (config, viewer, project) = (config.clone(), viewer.clone(), project.clone());
+        move || {
+            let mut sessions = stats::session_entries(&config.storage_dir, days, None, None, viewer.projects())?;
+            if let Some(ref project) = project {
+                sessions.retain(|s| *s.project(grouping) == **project);
+            }
+            Ok(calendar::Activity::of(&sessions, grouping, metric, first))
+        }
+    };
+
+    match blocking(&config, tallied).await {
+        Ok(activity) => {
+            let html = render_calendar_html(&activity, last, metric, grouping, project.as_deref());
+            (StatusCode::OK, Html(html))
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Html(format!("<h1>Error</h1><p>{}</p>", html_escape(&e.to_string()))),
+        ),
+    }
+}
+
+fn render_calendar_html(
+    activity: &calendar::Activity,
+    last: chrono::NaiveDate,
+    metric: calendar::Metric,
+    grouping: ProjectGrouping,
+    project: Option<&str>,
+) -> String {
+    let project_param = project.map(|p| format!("&project={}", url_encode(p))).unwrap_or_default();
+    let link = |metric: calendar::Metric, group: ProjectGrouping, label: &str, active: bool| {
+        format!(
+            "<a href=\"calendar?metric={}&group={}{}\"{}>{}</a>",
+            metric.as_str(),
+            group.as_str(),
+            html_escape(&project_param),
+            if active { " class=\"active\"" } else { "" },
+            label
+        )
+    };
+    let title = project.unwrap_or("All projects");
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>{} · Devlog Calendar</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
+h1 {{ color: #00d9ff; }}
+h2 {{ font-size: 1rem; margin: 1.5rem 0 0.5rem 0; }}
+h2 .total {{ color: #888; font-weight: normal; margin-left: 0.5rem; }}
+a {{ color: #00d9ff; }}
+.back {{ margin-bottom: 1rem; }}
+.filter {{ margin-bottom: 1rem; display: flex; gap: 0.5rem; }}
+.filter a {{ padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.filter a:hover, .filter a.active {{ background: #00d9ff; color: #1a1a2e; }}
+.heatmap text {{ fill: #888; font-size: 9px; }}
+</style>
+</head>
+<body>
+<div class="back"><a href="stats">← Stats</a> · <a href="search">Search</a>{}</div>
+<h1>{}</h1>
+<div class="filter">{} {}</div>
+<div class="filter">{} {}</div>
+"#,
+        html_escape(title),
+        if project.is_some() {
+            format!(" · <a href=\"calendar?metric={}&group={}\">All projects</a>", metric.as_str(), grouping.as_str())
+        } else {
+            String::new()
+        },
+        html_escape(title),
+        link(calendar::Metric::Prompts, grouping, "Prompts", metric == calendar::Metric::Prompts),
+        link(calendar::Metric::Tokens, grouping, "Tokens", metric == calendar::Metric::Tokens),
+        link(metric, ProjectGrouping::Directory, "By directory", grouping == ProjectGrouping::Directory),
+        link(metric, ProjectGrouping::Remote, "By remote", grouping == ProjectGrouping::Remote),
+    );
+
+    let heading = |name: String, days: &calendar::Days| {
+        format!(
+            "<h2>{}<span class=\"total\">{} {} in the last year</span></h2>\n",
+            name,
+            calendar::total(days),
+            metric.as_str()
+        )
+    };
+
+    let overall = if project.is_some() {
+        format!(
+            "<a href=\"project/{}?days=365&group={}\">{}</a>",
+            html_escape(&url_encode(title)),
+            grouping.as_str(),
+            html_escape(title)
+        )
+    } else {
+        "All projects".to_string()
+    };
+    html.push_str(&heading(overall, &activity.overall));
+    html.push_str(&calendar::heatmap_svg(&activity.overall, last, metric));
+
+    // Each project's own, unless the page is already one project's
+    if project.is_none() {
+        for (name, days) in &activity.projects {
+            let name = format!(
+                "<a href=\"calendar?metric={}&group={}&project={}\">{}</a>",
+                metric.as_str(),
+                grouping.as_str(),
+                html_escape(&url_encode(name)),
+                html_escape(name)
+            );
+            html.push_str(&heading(name, days));
+            html.push_str(&calendar::heatmap_svg(days, last, metric));
+        }
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_number(n: usize) -> String {
+    if n >= 1000 {
+        format!("{:.1}k", n as f64 / 1000.0)
+    } else {
+        n.to_string()
+    }
+}
+
+async fn search_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let (options, within) = (query.options(), query.within());
+
+    let results = match query.q.clone() {
+        Some(q) if q.trim().is_empty() => Some(Ok(search::SearchPage {
+            results: Vec::new(),
+            next_cursor: None,
+        })),
+        Some(q) => {
+            let (config_ref, options, within) = (config.clone(), options.clone(), within.clone());
+            Some(blocking(&config, move || search_visible(&config_ref, &viewer, &q, &options, &within)).await)
+        }
+        None => None,
+    };
+
+    let html = render_search_html(
+        query.q.as_deref().unwrap_or(""),
+        query.scope.as_deref().unwrap_or("conversations"),
+        query.days,
+        &options,
+        &within,
+        &config.saved_searches,
+        results,
+    );
+
+    (StatusCode::OK, Html(html))
+}
+
+fn render_search_html(
+    query: &str,
+    scope: &str,
+    days: Option<u32>,
+    options: &search::SearchOptions,
+    within: &SearchWithin,
+    saved_searches: &BTreeMap<String, SavedSearch>,
+    results: Option<anyhow::Result<search::SearchPage>>,
+) -> String {
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>Search Devlogs</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }}
+h1 {{ color: #00d9ff; }}
+.search-form {{ margin-bottom: 1.5rem; }}
+.search-form input[type="text"] {{
+    width: 400px; padding: 0.5rem; font-size: 1rem;
+    background: #16213e; border: 1px solid #333; color: #eee; border-radius: 4px;
+}}
+.search-form button {{
+    padding: 0.5rem 1rem; font-size: 1rem; cursor: pointer;
+    background: #00d9ff; color: #1a1a2e; border: none; border-radius: 4px;
+}}
+.search-form button:hover {{ background: #00b8d4; }}
+.filters {{ margin-bottom: 1rem; display: flex; gap: 1.5rem; align-items: center; }}
+.filters label {{ color: #888; cursor: pointer; }}
+.filters input[type="radio"] {{ margin-right: 0.3rem; }}
+.filters input[type="text"] {{
+    width: 120px; padding: 0.3rem; font-size: 0.9rem;
+    background: #16213e; border: 1px solid #333; color: #eee; border-radius: 4px;
+}}
+.filters select {{
+    background: #16213e; color: #eee; border: 1px solid #333;
+    padding: 0.3rem; border-radius: 4px;
+}}
+.result {{
+    margin-bottom: 1.5rem; padding: 1rem;
+    background: #16213e; border-radius: 8px; border-left: 3px solid #00d9ff;
+}}
+.result-header {{
+    display: flex; justify-content: space-between; align-items: center;
+    margin-bottom: 0.5rem; font-size: 0.9rem; color: #888;
+}}
+.result-header .project {{ color: #00d9ff; font-weight: bold; }}
+.result-header .type {{
+    padding: 0.15rem 0.5rem; border-radius: 3px; font-size: 0.8rem;
+}}
+.type-user {{ background: #2d5a2d; color: #8f8; }}
+.type-assistant {{ background: #5a2d5a; color: #f8f; }}
+.type-tool {{ background: #5a5a2d; color: #ff8; }}
+.snippet {{ line-height: 1.5; white-space: pre-wrap; word-break: break-word; }}
+.snippet mark {{ background: #ff0; color: #000; padding: 0 2px; }}
+a {{ color: #00d9ff; }}
+.no-results {{ color: #888; font-style: italic; }}
+.back {{ margin-bottom: 1rem; }}
+.saved {{ margin-bottom: 1rem; display: flex; gap: 0.5rem; flex-wrap: wrap; align-items: center; color: #888; }}
+.saved a {{ padding: 0.2rem 0.6rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.facets {{ margin-bottom: 0.5rem; display: flex; gap: 0.5rem; flex-wrap: wrap; align-items: center; color: #888; font-size: 0.9rem; }}
+.facets a {{ padding: 0.2rem 0.6rem; background: #16213e; text-decoration: none; border-radius: 4px; }}
+.facets a.active {{ background: #00d9ff; color: #1a1a2e; }}
+.facets .count {{ color: #888; margin-left: 0.3rem; }}
+</style>
+</head>
+<body>
+<div class="back"><a href="/">← Back</a></div>
+<h1>Search Devlogs</h1>
+<form class="search-form" method="get">
+  <input type="text" name="q" placeholder="Search conversations..." value="{}" autofocus>
+  <button type="submit">Search</button>
+  <div class="filters">
+    <span>Scope:</span>
+    <label><input type="radio" name="scope" value="prompts" {}> Prompts only</label>
+    <label><input type="radio" name="scope" value="conversations" {}> Conversations</label>
+    <label><input type="radio" name="scope" value="all" {}> Everything</label>
+    <span style="margin-left: 1rem;">Days:</span>
+    <select name="days">
+      <option value="" {}>All time</option>
+      <option value="1" {}>Today</option>
+      <option value="7" {}>7 days</option>
+      <option value="30" {}>30 days</option>
+      <option value="90" {}>90 days</option>
+    </select>
+    <label><input type="checkbox" name="case" {}> Match case</label>
+    <label><input type="checkbox" name="word" {}> Whole word</label>
+    <span>Tag:</span>
+    <input type="text" name="tag" value="{}">
+    <span>Ticket:</span>
+    <input type="text" name="ticket" value="{}">
+    <span>Project:</span>
+    <input type="text" name="project" value="{}">
+    <span>Machine:</span>
+    <input type="text" name="machine" value="{}">
+  </div>
+</form>
+"#,
+        html_escape(query),
+        if scope == "prompts" { "checked" } else { "" },
+        if scope == "conversations" { "checked" } else { "" },
+        if scope == "all" { "checked" } else { "" },
+        if days.is_none() { "selected" } else { "" },
+        if days == Some(1) { "selected" } else { "" },
+        if days == Some(7) { "selected" } else { "" },
+        if days == Some(30) { "selected" } else { "" },
+        if days == Some(90) { "selected" } else { "" },
+        if options.case_sensitive { "checked" } else { "" },
+        if options.whole_word { "checked" } else { "" },
+        html_escape(options.tag.as_deref().unwrap_or("")),
+        html_escape(options.ticket.as_deref().unwrap_or("")),
+        html_escape(within.project.as_deref().unwrap_or("")),
+        html_escape(within.machine.as_deref().unwrap_or("")),
+    );
+
+    if !saved_searches.is_empty() {
+        html.push_str("<div class=\"saved\"><span>Saved:</span>");
+        for (name, saved) in saved_searches {
+            let mut href = format!("search?q={}", url_encode(&saved.query));
+            if let Some(ref s) = saved.scope {
+                href.push_str(&format!("&scope={}", url_encode(s)));
+            }
+            if let Some(d) = saved.days {
+                href.push_str(&format!("&days={}", d));
+            }
+            html.push_str(&format!(
+                "<a href=\"{}\" title=\"{}\">{}</a>",
+                html_escape(&href),
+                html_escape(&saved.query),
+                html_escape(name)
+            ));
+        }
+        html.push_str("</div>\n");
+    }
+
+    match results {
+        None => {
+            // No search performed yet
+        }
+        Some(Ok(page)) if page.results.is_empty() => {
+            html.push_str(&render_search_facets(query, scope, days, options, within, &[]));
+            html.push_str("<p class=\"no-results\">No results found.</p>");
+        }
+        Some(Ok(page)) => {
+            html.push_str(&render_search_facets(query, scope, days, options, within, &page.results));
+            html.push_str(&format!("<p>{} results</p>", page.results.len()));
+            let matcher = search::Matcher::new(query, options);
+            for result in &page.results {
+                let timestamp = chrono::DateTime::parse_from_rfc3339(&result.timestamp)
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|_| result.timestamp.clone());
+
+                let type_class = match result.entry_type.as_str() {
+                    "user" => "type-user",
+                    "assistant" => "type-assistant",
+                    _ => "type-tool",
+                };
+
+                let highlighted_snippet = highlight_match(&result.snippet, &matcher);
+
+                html.push_str(&format!(
+                    r#"<div class="result">
+  <div class="result-header">
+    <span><span class="project">{}</span> · {} · <a href="{}">{}</a></span>
+    <span class="type {}">{}</span>
+  </div>
+  <div class="snippet">{}</div>
+</div>
+"#,
+                    html_escape(&result.project),
+                    html_escape(&result.machine),
+                    html_escape(&format!(
+                        "session/{}/{}/{}#entry-{}",
+                        url_encode(&result.machine),
+                        url_encode(&result.project),
+                        url_encode(&result.session_file),
+                        result.entry_index
+                    )),
+                    timestamp,
+                    type_class,
+                    result.entry_type,
+                    highlighted_snippet,
+                ));
+            }
+
+            if let Some(ref cursor) = page.next_cursor {
+                let next = format!("{}&cursor={}", search_href(query, scope, days, options, within), cursor);
+                html.push_str(&format!(
+                    "<p class=\"next\"><a href=\"{}\">Next page →</a></p>",
+                    html_escape(&next)
+                ));
+            }
+        }
+        Some(Err(e)) => {
+            html.push_str(&format!("<p class=\"no-results\">Error: {}</p>", e));
+        }
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// The search page for a query and its filters, from the first result
+fn search_href(
+    query: &str,
+    scope: &str,
+    days: Option<u32>,
+    options: &search::SearchOptions,
+    within: &SearchWithin,
+) -> String {
+    let mut href = format!("search?q={}&scope={}", url_encode(query), url_encode(scope));
+    if let Some(d) = days {
+        href.push_str(&format!("&days={}", d));
+    }
+    if options.case_sensitive {
+        href.push_str("&case=on");
+    }
+    if options.whole_word {
+        href.push_str("&word=on");
+    }
+    if let Some(ref tag) = options.tag {
+        href.push_str(&format!("&tag={}", url_encode(tag)));
+    }
+    if let Some(ref ticket) = options.ticket {
+        href.push_str(&format!("&ticket={}", url_encode(ticket)));
+    }
+    if let Some(ref project) = within.project {
+        href.push_str(&format!("&project={}", url_encode(project)));
+    }
+    if let Some(ref machine) = within.machine {
+        href.push_str(&format!("&machine={}", url_encode(machine)));
+    }
+    href
+}
+
+/// The projects and machines of a page of results, with how many results
+/// each has, linked to narrow the search to it; a filter already applied
+/// links to lift it
+fn render_search_facets(
+    query: &str,
+    scope: &str,
+    days: Option<u32>,
+    options: &search::SearchOptions,
+    within: &SearchWithin,
+    results: &[search::SearchResult],
+) -> String {
+    let facet = |label: &str, active: &Option<String>, key: fn(&search::SearchResult) -> &str, narrow: &dyn Fn(&str) -> SearchWithin| {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for result in results {
+            *counts.entry(key(result)).or_default() += 1;
+        }
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        if counts.is_empty() && active.is_none() {
+            return String::new();
+        }
+
+        let mut html = format!("<div class=\"facets\"><span>{}:</span>", label);
+        if let Some(active) = active {
+            let lifted = narrow("");
+            html.push_str(&format!(
+                "<a class=\"active\" href=\"{}\" title=\"Remove this filter\">{} ×</a>",
+                html_escape(&search_href(query, scope, days, options, &lifted)),
+                html_escape(active)
+            ));
+        } else {
+            for (value, count) in counts {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}<span class=\"count\">{}</span></a>",
+                    html_escape(&search_href(query, scope, days, options, &narrow(value))),
+                    html_escape(value),
+                    count
+                ));
+            }
+        }
+        html.push_str("</div>\n");
+        html
+    };
+
+    let with_project = |project: &str| SearchWithin {
+        project: Some(project.to_string()).filter(|p| !p.is_empty()),
+        ..within.clone()
+    };
+    let with_machine = |machine: &str| SearchWithin {
+        machine: Some(machine.to_string()).filter(|m| !m.is_empty()),
+        ..within.clone()
+    };
+    format!(
+        "{}{}",
+        facet("Projects", &within.project, |r| r.project.as_str(), &with_project),
+        facet("Machines", &within.machine, |r| r.machine.as_str(), &with_machine)
+    )
+}
+
+/// One stored session, with links from its git metadata to the forge
+async fn session_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Path((machine, project, file)): Path<(String, String, String)>,
+) -> impl IntoResponse {
+    // Hidden projects look missing, so their names cannot be probed for
+    let session = if viewer.can_see(&project) {
+        let (config_ref, machine, project, file) = (config.clone(), machine.clone(), project.clone(), file.clone());
+        blocking(&config, move || {
+            let path = FileStore::new(&config_ref.storage_dir).session_path(&machine, &project, &file)?;
+            config_ref.sessions.read(&path)
+        })
+        .await
+    } else {
+        Err(anyhow::anyhow!("No such session"))
+    };
+
+    match session {
+        Ok(session) => {
+            let target = format!("{}/{}/{}", machine, project, file);
+            if let Some(response) = audit(&config, &viewer, AuditAction::ReadSession, target) {
+                return response;
+            }
+            (
+                StatusCode::OK,
+                Html(render_session_html(&machine, &project, &session)),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::NOT_FOUND,
+            Html(format!("<h1>Not found</h1><p>{}</p>", html_escape(&e.to_string()))),
+        )
+            .into_response(),
+    }
+}
+
+/// Record an access in the audit log, or the response to send instead when it
+/// cannot be recorded: content is never served unaudited
+fn audit(config: &ServerConfig, viewer: &Viewer, action: AuditAction, target: String) -> Option<Response> {
+    let e = config.audit.record(viewer, action, target).err()?;
+    eprintln!("Failed to write audit log: {:#}", e);
+    Some((StatusCode::INTERNAL_SERVER_ERROR, "Failed to write audit log").into_response())
+}
+
+fn render_session_html(machine: &str, project: &str, session: &DevlogOutput) -> String {
+    use crate::parser::ConversationEntry;
+
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&session.timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| session.timestamp.clone());
+
+    let mut html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>{} · {}</title>
+<style>
+body {{ font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; max-width: 1000px; }}
+h1 {{ color: #00d9ff; margin-bottom: 0.3rem; }}
+.meta {{ color: #888; margin-bottom: 1.5rem; }}
+.git {{ background: #16213e; border-radius: 8px; padding: 1rem; margin-bottom: 1.5rem; }}
+.git ul {{ margin: 0.5rem 0 0 0; padding-left: 1.2rem; }}
+.git .files {{ color: #888; font-size: 0.85rem; }}
+code {{ font-family: ui-monospace, monospace; }}
+.entry {{ margin-bottom: 1rem; padding: 1rem; background: #16213e; border-radius: 8px; }}
+.entry .type {{ padding: 0.15rem 0.5rem; border-radius: 3px; font-size: 0.8rem; }}
+.entry-user {{ border-left: 3px solid #8f8; }}
+.entry-assistant {{ border-left: 3px solid #f8f; }}
+.entry-tool {{ border-left: 3px solid #ff8; color: #aaa; font-size: 0.9rem; }}
+.type-user {{ background: #2d5a2d; color: #8f8; }}
+.type-assistant {{ background: #5a2d5a; color: #f8f; }}
+.content {{ margin-top: 0.5rem; line-height: 1.5; white-space: pre-wrap; word-break: break-word; }}
+.markdown {{ white-space: normal; }}
+.markdown > :first-child {{ margin-top: 0; }}
+.markdown > :last-child {{ margin-bottom: 0; }}
+.markdown pre {{ padding: 0.8rem; border-radius: 6px; overflow-x: auto; white-space: pre; }}
+.markdown :not(pre) > code {{ background: #0f1629; padding: 0.1rem 0.3rem; border-radius: 3px; }}
+.markdown table {{ border-collapse: collapse; }}
+.markdown th, .markdown td {{ border: 1px solid #333; padding: 0.3rem 0.6rem; }}
+.entry:target {{ outline: 2px solid #00d9ff; }}
+a {{ color: #00d9ff; }}
+.back {{ margin-bottom: 1rem; }}
+.tags {{ margin: -1rem 0 1.5rem 0; display: flex; gap: 0.4rem; }}
+.note {{ margin-bottom: 1rem; padding: 0.8rem 1rem; background: #2a2a1e; border-left: 3px solid #fc6; border-radius: 8px; }}
+.note .added {{ color: #888; font-size: 0.8rem; }}
+.tags a {{ padding: 0.1rem 0.5rem; background: #16213e; border-radius: 3px; font-size: 0.85rem; text-decoration: none; }}
+.tags a.ticket {{ background: #2a2a1e; color: #fc6; }}
+</style>
+</head>
+<body>
+<div class="back"><a href="/search">← Search</a> · <a href="/stats">Stats</a></div>
+<h1>{}</h1>
+<div class="meta">{} · {} · session <code>{}</code></div>
+"#,
+        html_escape(project),
+        timestamp,
+        html_escape(project),
+        html_escape(machine),
+        timestamp,
+        html_escape(&session.session_id),
+    );
+
+    if !session.tags.is_empty() || !session.tickets.is_empty() {
+        html.push_str("<div class=\"tags\">");
+        for tag in &session.tags {
+            html.push_str(&format!(
+                "<a href=\"/stats?days=90&tag={}\">{}</a>",
+                html_escape(&url_encode(tag)),
+                html_escape(tag)
+            ));
+        }
+        // To the tracker when its address is known, else to the ticket's other sessions
+        for ticket in &session.tickets {
+            let href = ticket
+                .url
+                .clone()
+                .unwrap_or_else(|| format!("/stats?days=90&ticket={}", url_encode(&ticket.id)));
+            html.push_str(&format!(
+                "<a class=\"ticket\" href=\"{}\">{}</a>",
+                html_escape(&href),
+                html_escape(&ticket.id)
+            ));
+        }
+        html.push_str("</div>\n");
+    }
+
+    if let Some(ref git) = session.git {
+        html.push_str(&render_git_html(git));
+    }
+
+    for note in &session.notes {
+        let added = chrono::DateTime::parse_from_rfc3339(&note.timestamp)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| note.timestamp.clone());
+        html.push_str(&format!(
+            "<div class=\"note\"><span class=\"added\">Note · {}</span><div class=\"content\">{}</div></div>\n",
+            added,
+            html_escape(&note.text)
+        ));
+    }
+
+    for (idx, entry) in session.conversation.iter().enumerate() {
+        match entry {
+            ConversationEntry::User { content, .. } => html.push_str(&format!(
+                "<div class=\"entry entry-user\" id=\"entry-{}\"><span class=\"type type-user\">user</span><div class=\"content\">{}</div></div>\n",
+                idx,
+                html_escape(content)
+            )),
+            // Replies are mostly markdown and code, so they are rendered, with
+            // fenced code highlighted; prompts are shown as typed
+            ConversationEntry::Assistant { content, .. } => html.push_str(&format!(
+                "<div class=\"entry entry-assistant\" id=\"entry-{}\"><span class=\"type type-assistant\">assistant</span><div class=\"content markdown\">{}</div></div>\n",
+                idx,
+                markdown::to_html(content)
+            )),
+            ConversationEntry::ToolSummary { actions } => html.push_str(&format!(
+                "<div class=\"entry entry-tool\" id=\"entry-{}\">{}</div>\n",
+                idx,
+                html_escape(&actions.join(", "))
+            )),
+        }
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// Branch, HEAD and the commits made during the session, linked to the forge when possible
+fn render_git_html(git: &GitInfo) -> String {
+    let remote = git.remote.as_deref();
+    let link = |url: Option<String>, text: &str| match url {
+        Some(url) => format!("<a href=\"{}\">{}</a>", html_escape(&url), html_escape(text)),
+        None => html_escape(text),
+    };
+
+    let short = |hash: &str| hash.chars().take(8).collect::<String>();
+
+    let mut html = String::from("<div class=\"git\">");
+    if let Some(remote) = remote {
+        html.push_str(&format!(
+            "Repository: {}<br>",
+            link(forge::web_url(remote), remote)
+        ));
+    }
+    let others: Vec<String> = git
+        .remotes
+        .iter()
+        .filter(|r| r.name != "origin")
+        .map(|r| format!("{} {}", html_escape(&r.name), link(forge::web_url(&r.url), &r.url)))
+        .collect();
+    if !others.is_empty() {
+        html.push_str(&format!("Other remotes: {}<br>", others.join(", ")));
+    }
+    html.push_str(&format!(
+        "Branch: {} · HEAD: <code>{}</code>{}",
+        link(remote.and_then(|r| forge::branch_url(r, &git.branch)), &git.branch),
+        link(remote.and_then(|r| forge::commit_url(r, &git.commit)), &short(&git.commit)),
+        if git.dirty { " (uncommitted changes)" } else { "" }
+    ));
+
+    if !git.commits.is_empty() {
+        html.push_str("<div>Commits during this session:</div><ul>");
+        for commit in &git.commits {
+            html.push_str(&format!(
+                "<li><code>{}</code> {}<div class=\"files\">{}</div></li>",
+                link(remote.and_then(|r| forge::commit_url(r, &commit.hash)), &short(&commit.hash)),
+                html_escape(&commit.subject),
+                html_escape(&commit.files.join(", "))
+            ));
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("</div>\n");
+    html
+}
+
+async fn search_api(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let (config_ref, q, options, within) =
+        (config.clone(), query.q.clone().unwrap_or_default(), query.options(), query.within());
+    match blocking(&config, move || search_visible(&config_ref, &viewer, &q, &options, &within)).await {
+        Ok(page) => (StatusCode::OK, Json(page)).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, format!("Error: {}", e)).into_response(),
+    }
+}
+
+/// Search the stored devlogs of the projects the viewer may see, within the
+/// given project and machine, recording the search in the audit log
+fn search_visible(
+    config: &ServerConfig,
+    viewer: &Viewer,
+    query: &str,
+    options: &search::SearchOptions,
+    within: &SearchWithin,
+) -> anyhow::Result<search::SearchPage> {
+    let mut files = search::storage_files_since(&config.storage_dir, options.cutoff())?;
+    files.retain(|file| viewer.can_see(&file.project) && within.covers(file));
+    let page = search::search_files(&files, query, options, Some(&config.sessions))?;
+    let target = format!("{:?} ({} results)", query, page.results.len());
+    config.audit.record(viewer, AuditAction::Search, target)?;
+    Ok(page)
+}
+
+/// Percent-encode a query-string value
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The snippet as HTML with its match marked, found the way search found it.
+/// Matching the raw text and escaping each piece after keeps queries with
+/// `&`, `<` or quotes highlighted.
+fn highlight_match(snippet: &str, matcher: &search::Matcher) -> String {
+    match matcher.find(snippet) {
+        Some(found) => format!(
+            "{}<mark>{}</mark>{}",
+            html_escape(&snippet[..found.start]),
+            html_escape(&snippet[found.clone()]),
+            html_escape(&snippet[found.end..])
+        ),
+        None => html_escape(snippet),
+    }
+}
+
+/// Let an ingest request in while the ingest queue has room, or turn it away
+/// with 429 and a Retry-After, so a flood of pushes (say every machine's
+/// outbox after an outage) waits at the clients instead of swamping the server
+async fn queue_ingest(State(config): State<Arc<ServerConfig>>, request: Request, next: Next) -> Response {
+    let Ok(_place) = config.ingest_queue.try_acquire() else {
+        config.metrics.record_ingest_rejected();
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, INGEST_RETRY_AFTER_SECS.to_string())],
+            "Too many ingests in progress, retry later",
+        )
+            .into_response();
+    };
+    next.run(request).await
+}
+
+async fn ingest(
+    State(config): State<Arc<ServerConfig>>,
+    headers: HeaderMap,
+    Json(payload): Json<DevlogOutput>,
+) -> impl IntoResponse {
+    let span = tracing::info_span!(
+        "ingest",
+        session_id = %payload.session_id,
+        machine_id = %payload.machine_id
+    );
+    telemetry::continue_trace(&span, &headers);
+
+    let started = std::time::Instant::now();
+    let (config_ref, span_ref) = (config.clone(), span.clone());
+    let stored = blocking(&config, move || {
+        span_ref.in_scope(|| {
+            let signer = devlog_core::receipt::signer()?;
+            let path = FileStore::new(&config_ref.storage_dir).store(&payload)?;
+            let receipt = signer.map(|s| s.sign(&payload));
+            Ok((path, receipt, payload))
+        })
+    })
+    .await;
+    let _entered = span.enter();
+    match stored {
+        Ok((path, receipt, payload)) => {
+            config.metrics.record_ingest(1, started.elapsed());
+            eprintln!("Stored devlog: {}", path.display());
+            let session_id = payload.session_id.clone();
+            notify_ingested(&config, vec![payload]);
+            // The same shape as devlog-receiver's, with a signed receipt when a key is set
+            let mut body = serde_json::json!({ "status": "success", "session_id": session_id });
+            if let Some(receipt) = receipt {
+                body["receipt"] = serde_json::json!(receipt);
+            }
+            (StatusCode::OK, Json(body))
+        }
+        Err(e) => {
+            config.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            eprintln!("Failed to store devlog: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "status": "error", "error": format!("{}", e) })),
+            )
+        }
+    }
+}
+
+/// Send the configured notifications about newly stored sessions, without
+/// holding up the response
+fn notify_ingested(config: &ServerConfig, outputs: Vec<DevlogOutput>) {
+    if let Some(notifications) = config.notifications.clone() {
+        let storage_dir = config.storage_dir.clone();
+        let span = tracing::info_span!("notify", sessions = outputs.len());
+        tokio::task::spawn_blocking(move || {
+            span.in_scope(|| notify::sessions_ingested(&notifications, &storage_dir, &outputs))
+        });
+    }
+}
+
+/// Parameters accepted by `GET /sessions`, a subset of devlog-receiver's
+#[derive(serde::Deserialize)]
+struct SessionsQuery {
+    machine: Option<String>,
+    /// Matches the last component of the session's project directory
+    project: Option<String>,
+    /// Substring of any of the session's git remotes, in canonical form
+    remote: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+/// The latest devlog stored for each session, newest first
+pub fn latest_sessions(storage_dir: &std::path::Path) -> anyhow::Result<Vec<DevlogOutput>> {
+    let mut latest: BTreeMap<(String, String), DevlogOutput> = BTreeMap::new();
+    for file in search::storage_files(storage_dir)? {
+        let Ok(output) = search::read_devlog(&file.path) else {
+            continue;
+        };
+        let key = (output.machine_id.clone(), output.session_id.clone());
+        if latest.get(&key).map(|l| l.timestamp < output.timestamp).unwrap_or(true) {
+            latest.insert(key, output);
+        }
+    }
+    let mut sessions: Vec<DevlogOutput> = latest.into_values().collect();
+    sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(sessions)
+}
+
+/// A devlog file's manifest line, or one made by reading the file when the
+/// manifest lacks it
+fn manifest_entry(manifests: &mut Manifests, path: &std::path::Path) -> Option<ManifestEntry> {
+    if let Some(entry) = manifests.entry(path) {
+        return Some(entry.clone());
+    }
+    let entry = search::read_devlog(path)
+        .and_then(|output| ManifestEntry::for_file(path, &output))
+        .ok()?;
+    // Listed from the manifest next time
+    let _ = manifest::remember(path, &entry);
+    Some(entry)
+}
+
+/// The latest devlog stored for each session, as its manifest line describes
+/// it, newest first. Files missing from the manifests are read instead.
+pub fn latest_entries(storage_dir: &std::path::Path) -> anyhow::Result<Vec<ManifestEntry>> {
+    let mut manifests = Manifests::default();
+    let mut latest: BTreeMap<(String, String), ManifestEntry> = BTreeMap::new();
+    for file in search::storage_files(storage_dir)? {
+        let Some(entry) = manifest_entry(&mut manifests, &file.path) else {
+            continue;
+        };
+        let key = (entry.header.machine_id.clone(), entry.header.session_id.clone());
+        if latest
+            .get(&key)
+            .map(|l| l.header.timestamp < entry.header.timestamp)
+            .unwrap_or(true)
+        {
+            latest.insert(key, entry);
+        }
+    }
+    let mut entries: Vec<ManifestEntry> = latest.into_values().collect();
+    entries.sort_by(|a, b| b.header.timestamp.cmp(&a.header.timestamp));
+    Ok(entries)
+}
+
+/// The file holding the latest devlog of one session, with its manifest line.
+/// Only that machine's files named for the session are looked at.
+fn latest_copy(
+    storage_dir: &std::path::Path,
+    machine: &str,
+    session: &str,
+) -> anyhow::Result<Option<(PathBuf, ManifestEntry)>> {
+    let mut manifests = Manifests::default();
+    let mut latest: Option<(PathBuf, ManifestEntry)> = None;
+    for path in FileStore::new(storage_dir).session_candidates(machine, session)? {
+        let Some(entry) = manifest_entry(&mut manifests, &path) else {
+            continue;
+        };
+        if entry.header.machine_id != machine || entry.header.session_id != session {
+            continue;
+        }
+        if latest
+            .as_ref()
+            .map(|(_, l)| l.header.timestamp < entry.header.timestamp)
+            .unwrap_or(true)
+        {
+            latest = Some((path, entry));
+        }
+    }
+    Ok(latest)
+}
+
+/// Stored sessions, summarised as devlog-receiver's `GET /sessions` does, e.g. for `devlog sync`.
+/// Only the manifest lines are held; the JSON array is written out a session
+/// at a time as the client reads it.
+async fn list_sessions(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<SessionsQuery>,
+) -> impl IntoResponse {
+    let storage_dir = config.storage_dir.clone();
+    let sessions = match blocking(&config, move || latest_entries(&storage_dir)).await {
+        Ok(sessions) => sessions,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    };
+
+    let remote = query.remote.as_deref().map(forge::normalize_remote);
+    let (skip, take) = (query.offset.unwrap_or(0), query.limit.unwrap_or(100).min(1000));
+    let wanted = move |s: &ManifestEntry| {
+        let project = devlog_core::store::project_name(s.project_path());
+        viewer.can_see(&project)
+            && query.machine.as_ref().map(|m| &s.header.machine_id == m).unwrap_or(true)
+            && query.project.as_ref().map(|p| &project == p).unwrap_or(true)
+            && match (&remote, &s.git) {
+                (None, _) => true,
+                (Some(remote), Some(git)) => git
+                    .remote
+                    .iter()
+                    .chain(git.remotes.iter().map(|r| &r.url))
+                    .any(|url| forge::normalize_remote(url).contains(remote.as_str())),
+                (Some(_), None) => false,
+            }
+    };
+    let summaries = sessions
+        .into_iter()
+        .filter(wanted)
+        .skip(skip)
+        .take(take)
+        .enumerate()
+        .map(|(i, s)| {
+            let summary = serde_json::json!({
+                "session_id": s.header.session_id,
+                "machine_id": s.header.machine_id,
+                "project_dir": s.header.project_dir,
+                "timestamp": s.header.timestamp,
+                "git_remote": s.git.as_ref().and_then(|g| g.remote.clone()),
+                "git_branch": s.git.as_ref().map(|g| g.branch.clone()),
+                "git_commit": s.git.as_ref().map(|g| g.commit.clone()),
+                "entry_count": s.entry_count,
+                "tags": s.header.tags,
+            });
+            format!("{}{}", if i == 0 { "" } else { "," }, summary)
+        });
+    let chunks = std::iter::once(String::from("["))
+        .chain(summaries)
+        .chain(std::iter::once(String::from("]")))
+        .map(Ok::<_, std::convert::Infallible>);
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        axum::body::Body::from_stream(futures_util::stream::iter(chunks)),
+    )
+        .into_response()
+}
+
+/// The latest devlog of a session, e.g. for `devlog sync` to download
+async fn get_session(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Path((machine, session)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let found = {
+        let (storage_dir, machine, session) = (config.storage_dir.clone(), machine.clone(), session.clone());
+        blocking(&config, move || latest_copy(&storage_dir, &machine, &session)).await
+    };
+    let path = match found {
+        Ok(found) => found
+            .filter(|(_, entry)| viewer.can_see(&devlog_core::store::project_name(entry.project_path())))
+            .map(|(path, _)| path),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+    };
+    match path {
+        Some(path) => {
+            let target = format!("{}/{}", machine, session);
+            if let Some(response) = audit(&config, &viewer, AuditAction::ReadSession, target) {
+                return response;
+            }
+            let config_ref = config.clone();
+            match blocking(&config, move || config_ref.sessions.read(&path)).await {
+                Ok(output) => (StatusCode::OK, Json(&*output)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response(),
+            }
+        }
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("No session {} from machine {}", session, machine),
+        )
+            .into_response(),
+    }
+}
+
+/// Remove a session from storage, e.g. for `devlog prune --remote`. Needs the
+/// admin token or an admin user.
+async fn delete_session(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Path((machine, session)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if !viewer.is_admin() {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Deleting sessions needs the admin token or an admin user".to_string(),
+        )
+            .into_response();
+    }
+    let deleted = {
+        let (storage_dir, machine, session) = (config.storage_dir.clone(), machine.clone(), session.clone());
+        move || FileStore::new(&storage_dir).delete_session(&machine, &session)
+    };
+    match blocking(&config, deleted).await {
+        Ok(0) => (StatusCode::NOT_FOUND, format!("No session {} from machine {}", session, machine)).into_response(),
+        Ok(count) => {
+            eprintln!("Deleted session {} from {} ({} files)", session, machine, count);
+            record_admin_action(&config, &viewer, AuditAction::DeleteSession, format!("{}/{}", machine, session));
+            (StatusCode::OK, format!("Deleted {} files", count)).into_response()
+        }
+        Err(e) => {
+            eprintln!("Failed to delete session: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e)).into_response()
+        }
+    }
+}
+
+/// Delete everything stored for a machine, project or date range, e.g. to
+/// honour a data removal request. Needs the admin token or an admin user; with
+/// `dry_run` only lists what would go.
+async fn purge(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Json(request): Json<PurgeRequest>,
+) -> impl IntoResponse {
+    if !viewer.is_admin() {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Purging needs the admin token or an admin user".to_string(),
+        )
+            .into_response();
+    }
+    if let Err(e) = request.validate() {
+        return (StatusCode::BAD_REQUEST, format!("Error: {:#}", e)).into_response();
+    }
+
+    let (storage_dir, request_ref) = (config.storage_dir.clone(), request.clone());
+    match blocking(&config, move || FileStore::new(&storage_dir).purge(&request_ref)).await {
+        Ok(report) => {
+            eprintln!("Purge ({}): {}", request, report);
+            record_admin_action(&config, &viewer, AuditAction::Purge, format!("{}: {}", request, report));
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Err(e) => {
+            eprintln!("Failed to purge: {:#}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {:#}", e)).into_response()
+        }
+    }
+}
+
+/// Compress every stored session that is still plain JSON
+async fn compact(State(config): State<Arc<ServerConfig>>, viewer: Viewer) -> impl IntoResponse {
+    if !viewer.is_admin() {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "Compacting needs the admin token or an admin user".to_string(),
+        )
+            .into_response();
+    }
+
+    let store = FileStore::new(&config.storage_dir);
+    match blocking(&config, move || store.compress_existing()).await {
+        Ok(report) => {
+            eprintln!("Compact: {}", report);
+            record_admin_action(&config, &viewer, AuditAction::Compact, report.to_string());
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Err(e) => {
+            eprintln!("Failed to compact: {:#}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {:#}", e)).into_response()
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct AuditQuery {
+    user: Option<String>,
+    action: Option<String>,
+    limit: Option<usize>,
+}
+
+/// The audit log, newest first, for admins
+async fn audit_page(
+    State(config): State<Arc<ServerConfig>>,
+    viewer: Viewer,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    if !viewer.is_admin() {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Basic realm=\"devlog\"")],
+            "The audit log is for admins",
+        )
+            .into_response();
+    }
+
+    let user = query.user.as_deref().filter(|u| !u.is_empty());
+    let action = query
+        .action
+        .as_deref()
+        .and_then(|a| AuditAction::ALL.into_iter().find(|known| known.as_str() == a));
+    let limit = query.limit.unwrap_or(200).min(5000);
+    let (config_ref, user_ref) = (config.clone(), user.map(String::from));
+    let recent = move || config_ref.audit.recent(user_ref.as_deref(), action, limit);
+    let entries = match blocking(&config, recent).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(format!("<h1>Error</h1><p>{}</p>", html_escape(&format!("{:#}", e)))),
+            )
+                .into_response()
+        }
+    };
+    let target = format!("user={} action={}", user.unwrap_or("*"), action.map(|a| a.as_str()).unwrap_or("*"));
+    if let Some(response) = audit(&config, &viewer, AuditAction::ReadAudit, target) {
+        return response;
+    }
+
+    let mut html = String::from(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<title>Devlog Audit Log</title>
+<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; background: #1a1a2e; color: #eee; }
+h1 { color: #00d9ff; }
+table { border-collapse: collapse; width: 100%; max-width: 1200px; }
+th, td { padding: 0.5rem 1rem; text-align: left; border-bottom: 1px solid #333; }
+th { background: #16213e; color: #00d9ff; }
+tr:hover { background: #16213e; }
+td.target { font-family: ui-monospace, monospace; font-size: 0.9rem; word-break: break-all; }
+a { color: #00d9ff; }
+.filter { margin-bottom: 1rem; display: flex; gap: 0.5rem; flex-wrap: wrap; }
+.filter a { padding: 0.3rem 0.8rem; background: #16213e; text-decoration: none; border-radius: 4px; }
+.filter a:hover, .filter a.active { background: #00d9ff; color: #1a1a2e; }
+.empty { color: #888; font-style: italic; }
+</style>
+</head>
+<body>
+<div class="back"><a href="/">← Back</a></div>
+<h1>Audit Log</h1>
+<div class="filter">
+"#,
+    );
+    let user_param = user.map(|u| format!("&user={}", url_encode(u))).unwrap_or_default();
+    html.push_str(&format!(
+        "  <a href=\"audit?{}\" {}>All actions</a>\n",
+        html_escape(user_param.trim_start_matches('&')),
+        if action.is_none() { "class=\"active\"" } else { "" }
+    ));
+    for known in AuditAction::ALL {
+        html.push_str(&format!(
+            "  <a href=\"audit?action={}{}\" {}>{}</a>\n",
+            known.as_str(),
+            html_escape(&user_param),
+            if action == Some(known) { "class=\"active\"" } else { "" },
+            known.as_str()
+        ));
+    }
+    html.push_str("</div>\n");
+    if let Some(user) = user {
+        html.push_str(&format!(
+            "<div class=\"filter\"><span>User <strong>{}</strong></span><a href=\"audit{}\">Clear</a></div>\n",
+            html_escape(user),
+            action.map(|a| format!("?action={}", a.as_str())).unwrap_or_default()
+        ));
+    }
+
+    if entries.is_empty() {
+        html.push_str("<p class=\"empty\">No entries</p>");
+    } else {
+        html.push_str("<table>\n<tr><th>Time</th><th>User</th><th>Action</th><th>Target</th></tr>\n");
+        for entry in &entries {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td><a href=\"audit?user={}\">{}</a></td><td>{}</td><td class=\"target\">{}</td></tr>\n",
+                html_escape(&entry.time),
+                html_escape(&url_encode(&entry.user)),
+                html_escape(&entry.user),
+                entry.action.as_str(),
+                html_escape(&entry.target)
+            ));
+        }
+        html.push_str("</table>");
+    }
+    html.push_str("</body></html>");
+    (StatusCode::OK, Html(html)).into_response()
+}
+
+/// Record a change already made in the audit log; a failure to is reported
+/// but cannot undo it
+fn record_admin_action(config: &ServerConfig, viewer: &Viewer, action: AuditAction, target: String) {
+    if let Err(e) = config.audit.record(viewer, action, target) {
+        eprintln!("Failed to write audit log: {:#}", e);
+    }
+}
+
+/// Keep a session's raw JSONL transcript next to its devlog
+async fn ingest_transcript(
+    State(config): State<Arc<ServerConfig>>,
+    Query(params): Query<TranscriptParams>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> impl IntoResponse {
+    let span = tracing::info_span!(
+        "ingest_transcript",
+        session_id = %params.session_id,
+        machine_id = %params.machine_id,
+        bytes = body.len()
+    );
+    telemetry::continue_trace(&span, &headers);
+
+    let store = FileStore::new(&config.storage_dir);
+    let span_ref = span.clone();
+    let stored = blocking(&config, move || {
+        span_ref.in_scope(|| store.store_transcript(&params.machine_id, &params.project_dir, &params.session_id, &body))
+    })
+    .await;
+    let _entered = span.enter();
+    match stored {
+        Ok(path) => {
+            eprintln!("Stored transcript: {}", path.display());
+            (StatusCode::OK, format!("Stored: {}", path.display()))
+        }
+        Err(e) => {
+            telemetry::record_error(&e);
+            eprintln!("Failed to store transcript: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e))
+        }
+    }
 }
 
-/// Extract project name from a path, handling both Windows and Unix separators
-fn extract_project_name(path: &str) -> String {
-    // Split by both Windows and Unix separators, take the last non-empty component
-    path.split(|c| c == '/' || c == '\\')
-        .filter(|s| !s.is_empty())
-        .last()
-        .unwrap_or("unknown")
-        .to_string()
+/// Store sessions the client encrypted, which the server cannot read: they are
+/// kept as `.age` files, and neither searched, counted nor notified about
+async fn ingest_encrypted(
+    State(config): State<Arc<ServerConfig>>,
+    headers: HeaderMap,
+    Json(sessions): Json<Vec<EncryptedSession>>,
+) -> impl IntoResponse {
+    let span = tracing::info_span!("ingest_encrypted", sessions = sessions.len());
+    telemetry::continue_trace(&span, &headers);
+
+    let started = std::time::Instant::now();
+    let (store, span_ref) = (FileStore::new(&config.storage_dir), span.clone());
+    let stored = blocking(&config, move || span_ref.in_scope(|| store.store_encrypted(&sessions))).await;
+    let _entered = span.enter();
+    match stored {
+        Ok(count) => {
+            config.metrics.record_ingest(count, started.elapsed());
+            eprintln!("Stored {} encrypted sessions", count);
+            (StatusCode::OK, format!("Stored: {} sessions", count))
+        }
+        Err(e) => {
+            config.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            eprintln!("Failed to store encrypted sessions: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {}", e))
+        }
+    }
 }
 
-fn generate_filename(session_id: &str, timestamp: &str) -> String {
-    // Try to parse the timestamp for the date part
-    let date_part = chrono::DateTime::parse_from_rfc3339(timestamp)
-        .map(|dt| dt.format("%Y-%m-%d-%H%M%S").to_string())
-        .unwrap_or_else(|_| chrono::Utc::now().format("%Y-%m-%d-%H%M%S").to_string());
+/// Store many sessions at once (JSON array or NDJSON), for client backfills
+async fn ingest_batch(
+    State(config): State<Arc<ServerConfig>>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    let span = tracing::info_span!("ingest_batch", bytes = body.len(), sessions = tracing::field::Empty);
+    telemetry::continue_trace(&span, &headers);
+
+    // Parse everything up front so a malformed entry stores nothing
+    let span_ref = span.clone();
+    let parsed = blocking(&config, move || span_ref.in_scope(|| devlog_core::parse_batch(&body))).await;
+    let outputs = match parsed {
+        Ok(outputs) => outputs,
+        Err(e) => {
+            config.metrics.record_ingest_error();
+            span.in_scope(|| telemetry::record_error(&e));
+            return (StatusCode::BAD_REQUEST, format!("Error: {:#}", e));
+        }
+    };
 
-    // Shorten session_id for filename
-    let short_id: String = session_id.chars().take(8).collect();
+    span.record("sessions", outputs.len());
 
-    format!("{}-{}.json", date_part, short_id)
+    let started = std::time::Instant::now();
+    let (store, span_ref) = (FileStore::new(&config.storage_dir), span.clone());
+    let stored = blocking(&config, move || {
+        span_ref.in_scope(|| store.store_batch(&outputs).map(|count| (count, outputs)))
+    })
+    .await;
+    let _entered = span.enter();
+    match stored {
+        Ok((count, outputs)) => {
+            config.metrics.record_ingest(count, started.elapsed());
+            eprintln!("Stored batch of {} devlogs", count);
+            notify_ingested(&config, outputs);
+            (StatusCode::OK, format!("Stored: {} sessions", count))
+        }
+        Err(e) => {
+            config.metrics.record_ingest_error();
+            telemetry::record_error(&e);
+            eprintln!("Failed to store batch: {:#}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Error: {:#}", e))
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use crate::output::DevlogOutput;
+use anyhow::Result;
+use lru::LruCache;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Devlogs the server has parsed lately, shared by its session pages and
+/// searches so the same recent files are not parsed over and over. A file is
+/// parsed again once its modification time or size changes. The cache holds at
+/// most `max_bytes` of session files, dropping the least recently used first.
+pub struct SessionCache {
+    max_bytes: u64,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    sessions: LruCache<PathBuf, Cached>,
+    bytes: u64,
+}
+
+struct Cached {
+    modified: Option<SystemTime>,
+    size: u64,
+    output: Arc<DevlogOutput>,
+}
+
+impl SessionCache {
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            inner: Mutex::new(Inner {
+                sessions: LruCache::unbounded(),
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// The devlog at `path`, parsed now or taken from the cache
+    pub fn read(&self, path: &Path) -> Result<Arc<DevlogOutput>> {
+        let (size, modified) = devlog_core::bundle::stat(path)?;
+
+        if let Some(cached) = self.lock().sessions.get(path) {
+            if cached.modified.is_some() && cached.modified == modified && cached.size == size {
+                return Ok(cached.output.clone());
+            }
+        }
+
+        // Parsed outside the lock, so other requests are not held up
+        let output = Arc::new(devlog_core::store::read_session(path)?);
+        if size > self.max_bytes {
+            return Ok(output);
+        }
+
+        let mut inner = self.lock();
+        let cached = Cached {
+            modified,
+            size,
+            output: output.clone(),
+        };
+        if let Some(old) = inner.sessions.put(path.to_path_buf(), cached) {
+            inner.bytes -= old.size;
+        }
+        inner.bytes += size;
+        while inner.bytes > self.max_bytes {
+            match inner.sessions.pop_lru() {
+                Some((_, evicted)) => inner.bytes -= evicted.size,
+                None => break,
+            }
+        }
+        Ok(output)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // The cache stays consistent even if a holder panicked
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for SessionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_MB * 1024 * 1024)
+    }
+}
+
+/// Size of the session cache of `devlog serve` unless `--cache-mb` says otherwise
+pub const DEFAULT_CACHE_MB: u64 = 256;
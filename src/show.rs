@@ -0,0 +1,84 @@
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+
+/// Print a session for reading in the terminal
+pub fn print_session(output: &DevlogOutput) {
+    print!("{}", render_session(output));
+}
+
+/// A session as plain text: a header, then every entry, numbered
+pub fn render_session(output: &DevlogOutput) -> String {
+    let mut text = String::new();
+    let project = devlog_core::store::project_name(output.project_path());
+    text.push_str(&format!(
+        "{} · {} · {} · session {}\n",
+        project,
+        format_timestamp(&output.timestamp),
+        output.machine_id,
+        output.session_id
+    ));
+    if let Some(ref git) = output.git {
+        let short: String = git.commit.chars().take(8).collect();
+        text.push_str(&format!(
+            "Branch {} at {}{}\n",
+            git.branch,
+            short,
+            if git.dirty { " (uncommitted changes)" } else { "" }
+        ));
+    }
+    if !output.tags.is_empty() {
+        text.push_str(&format!("Tags: {}\n", output.tags.join(", ")));
+    }
+    if !output.tickets.is_empty() {
+        let tickets: Vec<String> = output
+            .tickets
+            .iter()
+            .map(|t| match t.url {
+                Some(ref url) => format!("{} ({})", t.id, url),
+                None => t.id.clone(),
+            })
+            .collect();
+        text.push_str(&format!("Tickets: {}\n", tickets.join(", ")));
+    }
+
+    if !output.notes.is_empty() {
+        text.push_str("\nNotes:\n");
+        for note in &output.notes {
+            text.push_str(&format!("  {}  {}\n", format_timestamp(&note.timestamp), indent(&note.text, "    ")));
+        }
+    }
+
+    for (idx, entry) in output.conversation.iter().enumerate() {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => {
+                text.push_str(&format!("\n[{}] You{}\n", idx, entry_time(timestamp.as_deref())));
+                text.push_str(&format!("    {}\n", indent(content.trim(), "    ")));
+            }
+            ConversationEntry::Assistant { content, timestamp, .. } => {
+                text.push_str(&format!("\n[{}] Claude{}\n", idx, entry_time(timestamp.as_deref())));
+                text.push_str(&format!("    {}\n", indent(content.trim(), "    ")));
+            }
+            ConversationEntry::ToolSummary { actions } => {
+                text.push_str(&format!("    > {}\n", actions.join(", ")));
+            }
+        }
+    }
+    text
+}
+
+/// Continue a multi-line text at the given indentation
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines().collect::<Vec<_>>().join(&format!("\n{}", prefix))
+}
+
+fn entry_time(timestamp: Option<&str>) -> String {
+    timestamp
+        .map(|ts| format!(" · {}", format_timestamp(ts)))
+        .unwrap_or_default()
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
@@ -0,0 +1,141 @@
+use crate::access;
+use crate::config::{Config, SkipConfig};
+use crate::output::{self, DevlogOutput};
+use crate::parser::ConversationEntry;
+use anyhow::{Context, Result};
+use devlog_core::store::project_name;
+use regex::Regex;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The `[skip]` rules, compiled
+pub struct SkipRules {
+    patterns: Vec<Regex>,
+    projects: Vec<String>,
+    tags: Vec<String>,
+    quarantine_dir: Option<PathBuf>,
+}
+
+impl SkipRules {
+    pub fn new(config: &SkipConfig) -> Result<Self> {
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).with_context(|| format!("Invalid skip pattern: {}", pattern)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            patterns,
+            projects: config.projects.clone(),
+            tags: config.tags.clone(),
+            quarantine_dir: config.quarantine_dir.clone(),
+        })
+    }
+
+    /// The rules in the user's config; none when it has no `[skip]` section
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load skip rules")?;
+        Self::new(&config.skip.unwrap_or_default())
+    }
+
+    /// The rule a session breaks, if any, described without quoting its content
+    pub fn check(&self, output: &DevlogOutput) -> Option<String> {
+        let project = project_name(&output.project_dir);
+        if access::grants(&self.projects, &project) {
+            return Some(format!("project {}", project));
+        }
+        if let Some(tag) = self.tags.iter().find(|tag| output.has_tag(tag)) {
+            return Some(format!("tag {}", tag));
+        }
+        let mut texts = Vec::new();
+        for entry in &output.conversation {
+            match entry {
+                ConversationEntry::User { content, .. } | ConversationEntry::Assistant { content, .. } => {
+                    texts.push(content)
+                }
+                ConversationEntry::ToolSummary { actions } => texts.extend(actions),
+            }
+        }
+        texts.extend(output.notes.iter().map(|note| &note.text));
+        if let Some(ref git) = output.git {
+            texts.extend(git.commits.iter().map(|commit| &commit.subject));
+        }
+        self.patterns
+            .iter()
+            .position(|regex| texts.iter().any(|text| regex.is_match(text)))
+            .map(|index| format!("pattern {}", index + 1))
+    }
+
+    /// Hold back a session that breaks a rule: note it in `~/.devlog/skipped.log`
+    /// and, with a quarantine directory, write it there. Returns whether it was
+    /// held back, in which case it must not be stored or pushed.
+    pub fn hold_back(&self, output: &DevlogOutput) -> Result<bool> {
+        let Some(reason) = self.check(output) else {
+            return Ok(false);
+        };
+        let short_id: String = output.session_id.chars().take(8).collect();
+
+        let quarantined = match self.quarantine_dir {
+            Some(ref dir) => {
+                output::write_output_to(&dir.join(project_name(&output.project_dir)), output)?;
+                eprintln!("Quarantined session {} ({})", short_id, reason);
+                true
+            }
+            None => {
+                eprintln!("Skipping session {} ({})", short_id, reason);
+                false
+            }
+        };
+
+        let log = Config::home_dir()?.join("skipped.log");
+        let line = format!(
+            "{} {} {} {} ({})\n",
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            if quarantined { "quarantined" } else { "skipped" },
+            output.session_id,
+            output.project_dir,
+            reason
+        );
+        if let Some(parent) = log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to write {}", log.display()))?;
+        Ok(true)
+    }
+}
+
+/// Why a project refuses capture, if it does: a `.devlogignore` file in its
+/// root, or `devlog = false` in its `.devlog/config.toml`. Checked before any
+/// other rule, so a marked repository is never written to or pushed from.
+pub fn opted_out(project_dir: &Path) -> Result<Option<&'static str>> {
+    if project_dir.join(".devlogignore").exists() {
+        return Ok(Some(".devlogignore"));
+    }
+    Ok(match project_setting(project_dir, "devlog")? {
+        Some(toml::Value::Boolean(false)) => Some("devlog = false in .devlog/config.toml"),
+        _ => None,
+    })
+}
+
+/// Whether the project's `.devlog/config.toml` sets `stats_only = true`
+pub fn marked_stats_only(project_dir: &Path) -> Result<bool> {
+    Ok(matches!(project_setting(project_dir, "stats_only")?, Some(toml::Value::Boolean(true))))
+}
+
+/// A key of the project's own `.devlog/config.toml`, if it has one
+fn project_setting(project_dir: &Path, key: &str) -> Result<Option<toml::Value>> {
+    let path = project_dir.join(".devlog").join("config.toml");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    // Refuse rather than capture when the markers can't be read
+    let mut table: toml::Table = toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(table.remove(key))
+}
@@ -0,0 +1,299 @@
+use crate::output::{self, DevlogOutput};
+use crate::parser::{self, ConversationEntry, TokenUsage};
+use crate::{aider, chatgpt, codex, cursor, gemini, git};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use devlog_core::Note;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The `--source` that picks whichever source recognises the path
+pub const AUTO: &str = "auto";
+
+/// An assistant whose sessions devlog can read. Each source detects its own
+/// files and parses them into [`Session`]s; [`normalize`] then turns those into
+/// devlogs the same way for every source, so supporting another assistant
+/// means implementing this trait and adding it to [`all`].
+pub trait SessionSource {
+    /// What `--source` calls it
+    fn name(&self) -> &'static str;
+
+    /// One line for `--help`
+    fn description(&self) -> &'static str;
+
+    /// Whether `path` looks like something this source wrote. Cheap checks
+    /// only: names, and the first bytes of files.
+    fn detect(&self, path: &Path) -> bool;
+
+    /// Read every session at `path`
+    fn parse(&self, path: &Path) -> Result<Vec<Session>>;
+
+    /// Tag added to its sessions, telling them apart from Claude Code's
+    fn tag(&self) -> Option<&'static str> {
+        Some(self.name())
+    }
+
+    /// The variable the assistant's hooks set to the project directory
+    fn project_dir_var(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The session it wrote last, for `ingest` without a path
+    fn most_recent(&self) -> Result<PathBuf> {
+        anyhow::bail!("Give the path of the {} session to ingest", self.name())
+    }
+}
+
+/// Every source, in the order `--source auto` tries them; Codex goes last as
+/// recognising its directories means walking them
+pub fn all() -> Vec<Box<dyn SessionSource>> {
+    vec![
+        Box::new(parser::Claude),
+        Box::new(gemini::Gemini),
+        Box::new(aider::Aider),
+        Box::new(cursor::Cursor),
+        Box::new(chatgpt::ChatGpt),
+        Box::new(codex::Codex),
+    ]
+}
+
+/// The values `--source` accepts
+pub fn value_parser() -> PossibleValuesParser {
+    PossibleValuesParser::new(
+        std::iter::once(PossibleValue::new(AUTO).help("Whichever source recognises the path"))
+            .chain(all().iter().map(|source| PossibleValue::new(source.name()).help(source.description()))),
+    )
+}
+
+/// The source called `name`, or for [`AUTO`], the first to recognise `path`
+pub fn find(name: &str, path: Option<&Path>) -> Result<Box<dyn SessionSource>> {
+    if name != AUTO {
+        return all()
+            .into_iter()
+            .find(|source| source.name() == name)
+            .with_context(|| format!("Unknown source '{}'", name));
+    }
+    let path = path.context("--source auto needs a path to look at")?;
+    all()
+        .into_iter()
+        .find(|source| source.detect(path))
+        .with_context(|| format!("Can't tell which assistant wrote {}; pass --source", path.display()))
+}
+
+/// A session as a source read it, before git metadata is added
+#[derive(Default)]
+pub struct Session {
+    pub id: String,
+    /// When the session started, if the source records it
+    pub started: Option<DateTime<Utc>>,
+    /// When the session ran, for the commits made meanwhile, if it is known
+    /// better than from the conversation's timestamps
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Where the assistant ran, if the source records it
+    pub dir: Option<PathBuf>,
+    /// The session belongs to no project, like a chat in a web UI
+    pub standalone: bool,
+    pub title: Option<String>,
+    /// The transcript, when it is one the server can parse again
+    pub transcript_path: Option<PathBuf>,
+    pub conversation: Vec<ConversationEntry>,
+}
+
+impl Session {
+    /// When the session started, from the source or failing that the conversation
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.started
+            .or(self.span.map(|(start, _)| start))
+            .or_else(|| parser::conversation_span(&self.conversation).map(|(start, _)| start))
+    }
+}
+
+/// Turn a session into a devlog dated `timestamp`, tagged with its source's tag
+/// and given git metadata from the repository it ran in. Standalone sessions are
+/// filed under `project` instead. Also returns the `.devlog` directory the
+/// devlog belongs in.
+pub fn normalize(
+    session: Session,
+    source: &dyn SessionSource,
+    timestamp: DateTime<Utc>,
+    project: &str,
+    include_author: bool,
+) -> Result<(DevlogOutput, PathBuf)> {
+    let notes = session
+        .title
+        .as_ref()
+        .map(|title| Note {
+            timestamp: session.started.unwrap_or(timestamp).to_rfc3339(),
+            text: format!("Title: {}", title),
+        })
+        .into_iter()
+        .collect();
+    let span = session.span.or_else(|| parser::conversation_span(&session.conversation));
+    let mut output = DevlogOutput {
+        schema_version: "1.0".to_string(),
+        session_id: session.id,
+        timestamp: timestamp.to_rfc3339(),
+        machine_id: output::get_machine_id(),
+        project_dir: project.to_string(),
+        git: None,
+        tags: source.tag().map(String::from).into_iter().collect(),
+        notes,
+        tickets: Vec::new(),
+        conversation: session.conversation,
+        transcript_path: session.transcript_path.map(|p| p.to_string_lossy().to_string()),
+        stats_only: None,
+    };
+
+    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+    if session.standalone {
+        return Ok((output, current_dir.join(".devlog")));
+    }
+
+    // Where the session ran: the source's own record beats the hook's environment,
+    // and the hook's environment beats whatever directory devlog was started in
+    let dir = session
+        .dir
+        .or_else(|| source.project_dir_var().and_then(std::env::var_os).map(PathBuf::from))
+        .unwrap_or(current_dir);
+
+    // The repository root identifies the project, even if the session ran in a subdirectory
+    let project_dir = git::find_repo_root(&dir).unwrap_or_else(|| dir.clone());
+
+    // Get git metadata, including commits made while the session ran
+    let mut git_info = git::get_git_metadata(&dir, span).unwrap_or_else(|e| {
+        eprintln!("Warning: Failed to read git metadata: {:#}", e);
+        None
+    });
+    if include_author {
+        if let Some(info) = git_info.as_mut() {
+            info.author = git::get_author(&project_dir).unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to read git author: {:#}", e);
+                None
+            });
+        }
+    }
+
+    output.project_dir = project_dir.to_string_lossy().to_string();
+    output.git = git_info;
+    let devlog_dir = project_dir.join(".devlog");
+    Ok((output, devlog_dir))
+}
+
+/// Keep the tags and notes added to earlier devlogs of the same session
+pub fn carry_over(output: &mut DevlogOutput, devlog_dir: &Path) {
+    let Some(previous) = output::find_previous_in(devlog_dir, &output.session_id) else {
+        return;
+    };
+    for tag in previous.tags {
+        if !output.has_tag(&tag) {
+            output.tags.push(tag);
+        }
+    }
+    output.notes = previous.notes;
+}
+
+/// A session ID from a transcript's file name, for sources that record none
+pub fn id_from_path(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("session_{}", Utc::now().timestamp()))
+}
+
+/// The start of a file, for [`SessionSource::detect`] to look at
+pub fn peek(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    std::fs::File::open(path).ok()?.take(8192).read_to_end(&mut head).ok()?;
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+pub fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().map(|e| e.eq_ignore_ascii_case(extension)).unwrap_or(false)
+}
+
+/// Builds a conversation the way every source records one: messages in order,
+/// with the tool calls made between two messages gathered into one summary
+#[derive(Default)]
+pub struct ConversationBuilder {
+    conversation: Vec<ConversationEntry>,
+    pending_tools: Vec<String>,
+}
+
+impl ConversationBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A message from the user; empty ones are dropped
+    pub fn user(&mut self, timestamp: Option<String>, content: String, uuid: Option<String>) {
+        if content.is_empty() {
+            return;
+        }
+        self.end_tools();
+        self.conversation.push(ConversationEntry::User {
+            timestamp,
+            content,
+            uuid,
+        });
+    }
+
+    /// A reply from the model; empty ones are dropped
+    pub fn assistant(
+        &mut self,
+        timestamp: Option<String>,
+        content: String,
+        usage: Option<TokenUsage>,
+        uuid: Option<String>,
+    ) {
+        if content.is_empty() {
+            return;
+        }
+        self.end_tools();
+        self.conversation.push(ConversationEntry::Assistant {
+            timestamp,
+            content,
+            usage,
+            uuid,
+        });
+    }
+
+    /// A short action such as "edited src/main.rs"
+    pub fn tool(&mut self, action: String) {
+        self.pending_tools.push(action);
+    }
+
+    pub fn tools(&mut self, actions: impl IntoIterator<Item = String>) {
+        self.pending_tools.extend(actions);
+    }
+
+    /// Close the current tool summary, so later calls start another
+    pub fn end_tools(&mut self) {
+        if !self.pending_tools.is_empty() {
+            self.conversation.push(ConversationEntry::ToolSummary {
+                actions: std::mem::take(&mut self.pending_tools),
+            });
+        }
+    }
+
+    /// Token counts reported after the reply they were spent on
+    pub fn usage(&mut self, usage: TokenUsage) {
+        if let Some(ConversationEntry::Assistant { usage: slot @ None, .. }) = self
+            .conversation
+            .iter_mut()
+            .rev()
+            .find(|entry| !matches!(entry, ConversationEntry::ToolSummary { .. }))
+        {
+            *slot = Some(usage);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conversation.is_empty() && self.pending_tools.is_empty()
+    }
+
+    pub fn finish(mut self) -> Vec<ConversationEntry> {
+        self.end_tools();
+        self.conversation
+    }
+}
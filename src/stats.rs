@@ -1,12 +1,20 @@
-use crate::output::DevlogOutput;
+use crate::access;
 use anyhow::Result;
+use devlog_core::manifest::{self, Manifests};
+use devlog_core::store::{read_session_header, session_dirs, session_files};
+use devlog_core::{ManifestEntry, ProjectGrouping, SessionHeader};
+use rayon::prelude::*;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+/// Machine and project names are shared by every session tallied under them,
+/// rather than copied once per session file
+#[derive(Clone)]
 pub struct ProjectStats {
-    pub machine: String,
-    pub project: String,
+    pub machine: Arc<str>,
+    pub project: Arc<str>,
     pub session_count: usize,
     pub prompt_count: usize,
     pub tool_calls: usize,
@@ -18,102 +26,15 @@ pub struct ProjectStats {
     pub cache_read_tokens: u64,
     pub cache_write_tokens: u64,
     pub last_activity: String,
+    /// Git remote of the most recent session that recorded one
+    pub remote: Option<String>,
 }
 
-pub fn get_project_stats(storage_dir: &Path, days: u32) -> Result<Vec<ProjectStats>> {
-    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
-    let mut stats: HashMap<(String, String), ProjectStats> = HashMap::new();
-
-    if !storage_dir.exists() {
-        anyhow::bail!("Storage directory does not exist: {}", storage_dir.display());
-    }
-
-    // Walk storage directory: storage_dir/machine/project/*.json
-    for machine_entry in fs::read_dir(storage_dir)? {
-        let machine_entry = machine_entry?;
-        let machine_path = machine_entry.path();
-        if !machine_path.is_dir() {
-            continue;
-        }
-        let machine = machine_entry.file_name().to_string_lossy().to_string();
-
-        for project_entry in fs::read_dir(&machine_path)? {
-            let project_entry = project_entry?;
-            let project_path = project_entry.path();
-            if !project_path.is_dir() {
-                continue;
-            }
-            let project = project_entry.file_name().to_string_lossy().to_string();
-
-            for file_entry in fs::read_dir(&project_path)? {
-                let file_entry = file_entry?;
-                let file_path = file_entry.path();
-
-                if file_path.extension().map(|e| e == "json").unwrap_or(false) {
-                    if let Ok(devlog) = read_devlog(&file_path) {
-                        // Check if within date range
-                        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&devlog.timestamp) {
-                            if dt < cutoff {
-                                continue;
-                            }
-                        }
-
-                        let key = (machine.clone(), project.clone());
-                        let entry = stats.entry(key).or_insert(ProjectStats {
-                            machine: machine.clone(),
-                            project: project.clone(),
-                            session_count: 0,
-                            prompt_count: 0,
-                            tool_calls: 0,
-                            files_touched: 0,
-                            prompt_words: 0,
-                            response_words: 0,
-                            input_tokens: 0,
-                            output_tokens: 0,
-                            cache_read_tokens: 0,
-                            cache_write_tokens: 0,
-                            last_activity: String::new(),
-                        });
-
-                        entry.session_count += 1;
-                        let session_stats = analyze_session(&devlog);
-                        entry.prompt_count += session_stats.prompts;
-                        entry.tool_calls += session_stats.tool_calls;
-                        entry.files_touched += session_stats.files_touched;
-                        entry.prompt_words += session_stats.prompt_words;
-                        entry.response_words += session_stats.response_words;
-                        entry.input_tokens += session_stats.input_tokens;
-                        entry.output_tokens += session_stats.output_tokens;
-                        entry.cache_read_tokens += session_stats.cache_read_tokens;
-                        entry.cache_write_tokens += session_stats.cache_write_tokens;
-
-                        if devlog.timestamp > entry.last_activity {
-                            entry.last_activity = devlog.timestamp.clone();
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    let mut result: Vec<ProjectStats> = stats.into_values().collect();
-
-    // Sort by prompt count descending
-    result.sort_by(|a, b| b.prompt_count.cmp(&a.prompt_count));
-
-    Ok(result)
-}
-
-pub fn get_project_stats_grouped(storage_dir: &Path, days: u32) -> Result<Vec<ProjectStats>> {
-    let by_machine = get_project_stats(storage_dir, days)?;
-
-    // Aggregate by project name only
-    let mut grouped: HashMap<String, ProjectStats> = HashMap::new();
-
-    for stat in by_machine {
-        let entry = grouped.entry(stat.project.clone()).or_insert(ProjectStats {
-            machine: String::new(),
-            project: stat.project.clone(),
+impl ProjectStats {
+    pub fn new(machine: Arc<str>, project: Arc<str>) -> Self {
+        ProjectStats {
+            machine,
+            project,
             session_count: 0,
             prompt_count: 0,
             tool_calls: 0,
@@ -125,176 +46,270 @@ pub fn get_project_stats_grouped(storage_dir: &Path, days: u32) -> Result<Vec<Pr
             cache_read_tokens: 0,
             cache_write_tokens: 0,
             last_activity: String::new(),
-        });
-
-        entry.session_count += stat.session_count;
-        entry.prompt_count += stat.prompt_count;
-        entry.tool_calls += stat.tool_calls;
-        entry.files_touched += stat.files_touched;
-        entry.prompt_words += stat.prompt_words;
-        entry.response_words += stat.response_words;
-        entry.input_tokens += stat.input_tokens;
-        entry.output_tokens += stat.output_tokens;
-        entry.cache_read_tokens += stat.cache_read_tokens;
-        entry.cache_write_tokens += stat.cache_write_tokens;
+            remote: None,
+        }
+    }
 
-        if stat.last_activity > entry.last_activity {
-            entry.last_activity = stat.last_activity;
+    /// One session's contribution, as its manifest line describes it
+    fn of_session(machine: Arc<str>, project: Arc<str>, entry: ManifestEntry) -> Self {
+        let session_stats = &entry.stats;
+        ProjectStats {
+            session_count: 1,
+            prompt_count: session_stats.prompts,
+            tool_calls: session_stats.tool_calls,
+            files_touched: session_stats.files_touched,
+            prompt_words: session_stats.prompt_words,
+            response_words: session_stats.response_words,
+            input_tokens: session_stats.input_tokens,
+            output_tokens: session_stats.output_tokens,
+            cache_read_tokens: session_stats.cache_read_tokens,
+            cache_write_tokens: session_stats.cache_write_tokens,
+            last_activity: entry.header.timestamp,
+            remote: entry.git.and_then(|g| g.remote),
+            ..ProjectStats::new(machine, project)
         }
+    }
 
-        // Track machines
-        if entry.machine.is_empty() {
-            entry.machine = stat.machine;
-        } else if !entry.machine.contains(&stat.machine) {
-            entry.machine = format!("{}, {}", entry.machine, stat.machine);
+    /// Add another tally of the same project, in any order
+    pub fn add(&mut self, other: ProjectStats) {
+        self.session_count += other.session_count;
+        self.prompt_count += other.prompt_count;
+        self.tool_calls += other.tool_calls;
+        self.files_touched += other.files_touched;
+        self.prompt_words += other.prompt_words;
+        self.response_words += other.response_words;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+
+        if other.last_activity > self.last_activity {
+            self.last_activity = other.last_activity;
+            if other.remote.is_some() {
+                self.remote = other.remote;
+            }
+        } else if self.remote.is_none() {
+            self.remote = other.remote;
         }
     }
+}
 
-    let mut result: Vec<ProjectStats> = grouped.into_values().collect();
-    result.sort_by(|a, b| b.prompt_count.cmp(&a.prompt_count));
-    Ok(result)
+/// A session file to tally: its machine, project, path and manifest line
+type SessionFile = (Arc<str>, Arc<str>, PathBuf, Option<ManifestEntry>);
+
+/// Stats keyed by machine and project
+type StatsMap = HashMap<(Arc<str>, Arc<str>), ProjectStats>;
+
+fn merge_stats(mut into: StatsMap, from: StatsMap) -> StatsMap {
+    for (key, stat) in from {
+        match into.get_mut(&key) {
+            Some(entry) => entry.add(stat),
+            None => {
+                into.insert(key, stat);
+            }
+        }
+    }
+    into
 }
 
-fn read_devlog(path: &Path) -> Result<DevlogOutput> {
-    let content = fs::read_to_string(path)?;
-    let devlog: DevlogOutput = serde_json::from_str(&content)?;
-    Ok(devlog)
+/// A stored session, as its manifest line describes it
+#[derive(Clone)]
+pub struct SessionEntry {
+    pub machine: Arc<str>,
+    /// The project's directory in the store
+    pub project_dir: Arc<str>,
+    pub path: PathBuf,
+    pub entry: ManifestEntry,
 }
 
-struct SessionStats {
-    prompts: usize,
-    tool_calls: usize,
-    files_touched: usize,
-    prompt_words: usize,
-    response_words: usize,
-    input_tokens: u64,
-    output_tokens: u64,
-    cache_read_tokens: u64,
-    cache_write_tokens: u64,
+impl SessionEntry {
+    /// The project the session counts towards under `grouping`
+    pub fn project(&self, grouping: ProjectGrouping) -> Arc<str> {
+        match grouping {
+            ProjectGrouping::Directory => Arc::clone(&self.project_dir),
+            ProjectGrouping::Remote => grouping.key(self.entry.project_path(), self.entry.git.as_ref()).into(),
+        }
+    }
+
+    /// Whether the session started at or after `cutoff`; one with no
+    /// readable time is always counted, as in [`session_entries`]
+    pub fn since(&self, cutoff: chrono::DateTime<chrono::Utc>) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.entry.header.timestamp)
+            .map(|dt| dt >= cutoff)
+            .unwrap_or(true)
+    }
 }
 
-fn analyze_session(devlog: &DevlogOutput) -> SessionStats {
-    use crate::parser::ConversationEntry;
-    use std::collections::HashSet;
+/// The stored sessions of the last `days`, with `tag` and `ticket` if given.
+/// `projects` limits them to the project directories those patterns match, as
+/// granted to a server user. Files missing from their manifest are read in
+/// parallel.
+pub fn session_entries(
+    storage_dir: &Path,
+    days: u32,
+    tag: Option<&str>,
+    ticket: Option<&str>,
+    projects: Option<&[String]>,
+) -> Result<Vec<SessionEntry>> {
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
 
-    let mut stats = SessionStats {
-        prompts: 0,
-        tool_calls: 0,
-        files_touched: 0,
-        prompt_words: 0,
-        response_words: 0,
-        input_tokens: 0,
-        output_tokens: 0,
-        cache_read_tokens: 0,
-        cache_write_tokens: 0,
-    };
+    if !storage_dir.exists() {
+        anyhow::bail!("Storage directory does not exist: {}", storage_dir.display());
+    }
 
-    let mut files: HashSet<String> = HashSet::new();
+    // Walk storage directory: storage_dir/machine/project/YYYY/MM/*.json, taking what
+    // each project's manifest already says about its files
+    let mut files: Vec<SessionFile> = Vec::new();
+    let mut manifests = Manifests::default();
+    for machine_entry in fs::read_dir(storage_dir)? {
+        let machine_entry = machine_entry?;
+        let machine_path = machine_entry.path();
+        if !machine_path.is_dir() {
+            continue;
+        }
+        let machine: Arc<str> = machine_entry.file_name().to_string_lossy().into();
 
-    for entry in &devlog.conversation {
-        match entry {
-            ConversationEntry::User { content, .. } => {
-                stats.prompts += 1;
-                stats.prompt_words += count_words(content);
+        for project_entry in fs::read_dir(&machine_path)? {
+            let project_entry = project_entry?;
+            let project_path = project_entry.path();
+            if !project_path.is_dir() {
+                continue;
             }
-            ConversationEntry::Assistant { content, usage, .. } => {
-                stats.response_words += count_words(content);
-                if let Some(ref usage) = usage {
-                    stats.input_tokens += usage.input_tokens.unwrap_or(0);
-                    stats.output_tokens += usage.output_tokens.unwrap_or(0);
-                    stats.cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
-                    stats.cache_write_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
-                }
+            let project_dir_name: Arc<str> = project_entry.file_name().to_string_lossy().into();
+            if projects.map(|p| !access::grants(p, &project_dir_name)).unwrap_or(false) {
+                continue;
             }
-            ConversationEntry::ToolSummary { actions } => {
-                stats.tool_calls += actions.len();
-                // Extract file paths from tool actions
-                for action in actions {
-                    if let Some(file) = extract_file_from_action(action) {
-                        files.insert(file);
-                    }
+
+            // Months before the cutoff are passed over whole
+            for dir in session_dirs(&project_path, Some(cutoff))? {
+                for file_path in session_files(&dir)? {
+                    let entry = manifests.entry(&file_path).cloned();
+                    files.push((Arc::clone(&machine), Arc::clone(&project_dir_name), file_path, entry));
                 }
             }
         }
     }
 
-    stats.files_touched = files.len();
-    stats
+    let wanted = |header: &SessionHeader| {
+        // Check if within date range
+        if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(&header.timestamp) {
+            if dt < cutoff {
+                return false;
+            }
+        }
+        tag.map(|t| header.has_tag(t)).unwrap_or(true) && ticket.map(|t| header.has_ticket(t)).unwrap_or(true)
+    };
+
+    Ok(files
+        .into_par_iter()
+        .filter_map(|(machine, project_dir, path, entry)| {
+            let entry = match entry {
+                Some(entry) => entry,
+                None => {
+                    // Not in a manifest: the conversation is only parsed for
+                    // sessions the filters keep, and only this once, as the
+                    // counts are added to the manifest. A store this user
+                    // can't write to is read again next time.
+                    let session = read_session_header(&path).ok()?;
+                    if !wanted(&session.header) {
+                        return None;
+                    }
+                    let devlog = session.parse().ok()?;
+                    let entry = ManifestEntry::for_file(&path, &devlog).ok()?;
+                    let _ = manifest::remember(&path, &entry);
+                    entry
+                }
+            };
+            if !wanted(&entry.header) {
+                return None;
+            }
+            Some(SessionEntry {
+                machine,
+                project_dir,
+                path,
+                entry,
+            })
+        })
+        .collect())
 }
 
-fn count_words(text: &str) -> usize {
-    text.split_whitespace().count()
+/// Activity per machine and project over the last `days`
+pub fn get_project_stats(
+    storage_dir: &Path,
+    days: u32,
+    grouping: ProjectGrouping,
+    tag: Option<&str>,
+    ticket: Option<&str>,
+    projects: Option<&[String]>,
+) -> Result<Vec<ProjectStats>> {
+    Ok(tally(session_entries(storage_dir, days, tag, ticket, projects)?, grouping))
 }
 
-fn extract_file_from_action(action: &str) -> Option<String> {
-    // Actions look like: "edited src/main.rs", "read config.json", "created foo.txt"
-    let prefixes = ["edited ", "read ", "created "];
-    for prefix in prefixes {
-        if action.starts_with(prefix) {
-            return Some(action[prefix.len()..].to_string());
-        }
-    }
-    None
+/// Sessions tallied per machine and project in parallel, one partial tally
+/// per thread, busiest first
+pub fn tally(sessions: Vec<SessionEntry>, grouping: ProjectGrouping) -> Vec<ProjectStats> {
+    let stats = sessions
+        .into_par_iter()
+        .map(|session| {
+            let project = session.project(grouping);
+            ProjectStats::of_session(session.machine, project, session.entry)
+        })
+        .fold(StatsMap::new, |mut map, stat| {
+            let key = (Arc::clone(&stat.machine), Arc::clone(&stat.project));
+            match map.get_mut(&key) {
+                Some(entry) => entry.add(stat),
+                None => {
+                    map.insert(key, stat);
+                }
+            }
+            map
+        })
+        .reduce(StatsMap::new, merge_stats);
+
+    let mut result: Vec<ProjectStats> = stats.into_values().collect();
+
+    // Sort by prompt count descending
+    result.sort_by_key(|s| std::cmp::Reverse(s.prompt_count));
+
+    result
 }
 
-pub fn print_stats(stats: &[ProjectStats], days: u32) {
-    if stats.is_empty() {
-        println!("No activity in the last {} days", days);
-        return;
-    }
+pub fn get_project_stats_grouped(
+    storage_dir: &Path,
+    days: u32,
+    grouping: ProjectGrouping,
+    tag: Option<&str>,
+    ticket: Option<&str>,
+    projects: Option<&[String]>,
+) -> Result<Vec<ProjectStats>> {
+    let by_machine = get_project_stats(storage_dir, days, grouping, tag, ticket, projects)?;
+    Ok(group_by_project(by_machine))
+}
 
-    println!("Project activity (last {} days):\n", days);
-    println!(
-        "{:<15} {:<25} {:>8} {:>8} {:>10} {:>10}  {}",
-        "Machine", "Project", "Sessions", "Prompts", "In Tokens", "Out Tokens", "Last Activity"
-    );
-    println!("{}", "-".repeat(110));
+/// Per-machine stats added up per project, naming the machines each was on
+pub fn group_by_project(by_machine: Vec<ProjectStats>) -> Vec<ProjectStats> {
+    let mut grouped: HashMap<Arc<str>, ProjectStats> = HashMap::new();
 
-    for stat in stats {
-        let last = if stat.last_activity.is_empty() {
-            "unknown".to_string()
-        } else {
-            chrono::DateTime::parse_from_rfc3339(&stat.last_activity)
-                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-                .unwrap_or_else(|_| stat.last_activity.clone())
-        };
+    for stat in by_machine {
+        let entry = grouped
+            .entry(Arc::clone(&stat.project))
+            .or_insert_with(|| ProjectStats::new(Arc::from(""), Arc::clone(&stat.project)));
 
-        println!(
-            "{:<15} {:<25} {:>8} {:>8} {:>10} {:>10}  {}",
-            truncate(&stat.machine, 15),
-            truncate(&stat.project, 25),
-            stat.session_count,
-            stat.prompt_count,
-            format_tokens(stat.input_tokens + stat.cache_read_tokens),
-            format_tokens(stat.output_tokens),
-            last
-        );
+        // Track machines
+        if entry.machine.is_empty() {
+            entry.machine = Arc::clone(&stat.machine);
+        } else if !entry.machine.contains(&*stat.machine) {
+            entry.machine = format!("{}, {}", entry.machine, stat.machine).into();
+        }
+        entry.add(stat);
     }
 
-    let total_sessions: usize = stats.iter().map(|s| s.session_count).sum();
-    let total_prompts: usize = stats.iter().map(|s| s.prompt_count).sum();
-    let total_in_tokens: u64 = stats.iter().map(|s| s.input_tokens + s.cache_read_tokens).sum();
-    let total_out_tokens: u64 = stats.iter().map(|s| s.output_tokens).sum();
-    let total_cache_read: u64 = stats.iter().map(|s| s.cache_read_tokens).sum();
-    let total_cache_write: u64 = stats.iter().map(|s| s.cache_write_tokens).sum();
-
-    println!("{}", "-".repeat(110));
-    println!(
-        "Total: {} sessions, {} prompts, {} in, {} out across {} projects",
-        total_sessions,
-        total_prompts,
-        format_tokens(total_in_tokens),
-        format_tokens(total_out_tokens),
-        stats.len()
-    );
-    println!(
-        "Cache: {} read, {} written",
-        format_tokens(total_cache_read),
-        format_tokens(total_cache_write)
-    );
+    let mut result: Vec<ProjectStats> = grouped.into_values().collect();
+    result.sort_by_key(|s| std::cmp::Reverse(s.prompt_count));
+    result
 }
 
-fn format_tokens(tokens: u64) -> String {
+pub fn format_tokens(tokens: u64) -> String {
     if tokens >= 1_000_000 {
         format!("{:.1}M", tokens as f64 / 1_000_000.0)
     } else if tokens >= 1_000 {
@@ -303,11 +318,3 @@ fn format_tokens(tokens: u64) -> String {
         tokens.to_string()
     }
 }
-
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max - 3])
-    }
-}
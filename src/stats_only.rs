@@ -0,0 +1,70 @@
+use crate::access;
+use crate::config::{Config, StatsOnlyConfig, StatsOnlyContent};
+use crate::output::DevlogOutput;
+use crate::{redact, skip};
+use anyhow::{Context, Result};
+use devlog_core::counts::content_counts;
+use devlog_core::store::project_name;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Which sessions are captured stats-only, and what is left of their text
+pub struct StatsOnly {
+    config: StatsOnlyConfig,
+}
+
+impl StatsOnly {
+    pub fn new(config: StatsOnlyConfig) -> Self {
+        Self { config }
+    }
+
+    /// The `[stats_only]` section of the user's config; no projects when it has none
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load stats-only projects")?;
+        Ok(Self::new(config.stats_only.unwrap_or_default()))
+    }
+
+    /// Whether the session's project is listed under `[stats_only]` or marks
+    /// itself with `stats_only = true` in its `.devlog/config.toml`
+    pub fn applies(&self, output: &DevlogOutput) -> Result<bool> {
+        Ok(access::grants(&self.config.projects, &project_name(&output.project_dir))
+            || skip::marked_stats_only(Path::new(&output.project_dir))?)
+    }
+
+    /// Strip a session to its metadata and counts when it is stats-only: the
+    /// counts are recorded, every prompt, response, tool summary, note and commit
+    /// subject is hashed or emptied, and the transcript is not kept. Returns
+    /// whether it was stripped.
+    pub fn apply(&self, output: &mut DevlogOutput) -> Result<bool> {
+        if output.stats_only.is_some() || !self.applies(output)? {
+            return Ok(false);
+        }
+        output.stats_only = Some(content_counts(output));
+        let content = self.config.content;
+        redact::for_each_text(output, |_, text| {
+            *text = match content {
+                StatsOnlyContent::Drop => String::new(),
+                // Notes carried over from an earlier ingest are hashed already
+                StatsOnlyContent::Hash if is_hash(text) => std::mem::take(text),
+                StatsOnlyContent::Hash => hash(text),
+            };
+        });
+        // Pushing the raw transcript would send the text anyway
+        output.transcript_path = None;
+        Ok(true)
+    }
+}
+
+fn is_hash(text: &str) -> bool {
+    text.strip_prefix("sha256:")
+        .map(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .unwrap_or(false)
+}
+
+fn hash(text: &str) -> String {
+    let digest: String = Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256:{}", digest)
+}
@@ -0,0 +1,185 @@
+use crate::config::{Config, PushConfig};
+use crate::output::{self, DevlogOutput};
+use crate::{embeddings, git, push, search};
+use anyhow::{Context, Result};
+use devlog_core::forge;
+use devlog_core::store::project_name;
+use reqwest::blocking::Client;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// Sessions asked for per `GET /sessions` request; the receiver's maximum
+const PAGE_SIZE: usize = 1000;
+
+/// A session as listed by the server's `GET /sessions`
+#[derive(serde::Deserialize)]
+struct RemoteSession {
+    session_id: String,
+    machine_id: String,
+    timestamp: String,
+}
+
+/// Bring `devlog_dir` and the push endpoint's server up to date with each other
+/// for the repository in `project_dir`: sessions only here are uploaded,
+/// sessions only on the server (say, captured on another machine) are
+/// downloaded. Sessions are matched by machine and session ID, and where both
+/// sides have one, the later ingest wins.
+pub fn sync(devlog_dir: &Path, project_dir: &Path, dry_run: bool) -> Result<()> {
+    let config = Config::load()?;
+    let push_config = match config.push {
+        Some(ref pc) if pc.enabled => pc,
+        _ => anyhow::bail!("sync needs an enabled [push] section in the config"),
+    };
+    if !push_config.encrypt_to.is_empty() {
+        anyhow::bail!("sync needs a server that can read sessions, and encrypt_to keeps them from it");
+    }
+    let client = push::client(push_config, Duration::from_secs(30))?;
+
+    // The latest devlog of each local session
+    let mut local: BTreeMap<(String, String), DevlogOutput> = BTreeMap::new();
+    for file in search::local_files(devlog_dir)? {
+        let Ok(output) = search::read_devlog(&file.path) else {
+            continue;
+        };
+        let key = (output.machine_id.clone(), output.session_id.clone());
+        if local.get(&key).map(|l| ingested_at(&l.timestamp) < ingested_at(&output.timestamp)).unwrap_or(true) {
+            local.insert(key, output);
+        }
+    }
+
+    let remote: BTreeMap<(String, String), RemoteSession> = list_remote(&client, push_config, project_dir)?
+        .into_iter()
+        .map(|s| ((s.machine_id.clone(), s.session_id.clone()), s))
+        .collect();
+
+    let local_times: BTreeMap<(String, String), i64> = local
+        .iter()
+        .map(|(key, output)| (key.clone(), ingested_at(&output.timestamp)))
+        .collect();
+
+    let mut uploads = Vec::new();
+    for (key, output) in local {
+        let newer = remote
+            .get(&key)
+            .map(|r| ingested_at(&output.timestamp) > ingested_at(&r.timestamp))
+            .unwrap_or(true);
+        if newer {
+            println!("upload    {}  {}", output.machine_id, output.session_id);
+            uploads.push(output);
+        }
+    }
+    let downloads: Vec<&RemoteSession> = remote
+        .iter()
+        .filter(|(key, r)| {
+            local_times
+                .get(*key)
+                .map(|&t| ingested_at(&r.timestamp) > t)
+                .unwrap_or(true)
+        })
+        .map(|(_, r)| r)
+        .collect();
+    for session in &downloads {
+        println!("download  {}  {}", session.machine_id, session.session_id);
+    }
+
+    println!(
+        "{} {} sessions, {} {}",
+        if dry_run { "Would upload" } else { "Uploading" },
+        uploads.len(),
+        if dry_run { "download" } else { "downloading" },
+        downloads.len()
+    );
+    if dry_run {
+        return Ok(());
+    }
+
+    if !uploads.is_empty() {
+        push::push_batch(&uploads)?;
+    }
+    for session in downloads {
+        let output = fetch_session(&client, push_config, session)?;
+        let path = output::write_output_to(devlog_dir, &output)?;
+        if let Err(e) = embeddings::index_session(&output, &path) {
+            eprintln!("Warning: Failed to compute embeddings: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Seconds since the epoch of an ingest timestamp. The receiver lists them to
+/// the second, so finer differences are not compared.
+fn ingested_at(timestamp: &str) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.timestamp())
+        .unwrap_or(0)
+}
+
+/// The server's sessions for this repository: those with the same origin remote,
+/// or those from a directory of the same name when it has none
+fn list_remote(client: &Client, push_config: &PushConfig, project_dir: &Path) -> Result<Vec<RemoteSession>> {
+    let origin = git::get_git_metadata(project_dir, None)
+        .ok()
+        .flatten()
+        .and_then(|info| info.remote);
+    let filter = match origin {
+        Some(url) => ("remote", forge::normalize_remote(&url)),
+        None => ("project", project_name(&project_dir.to_string_lossy())),
+    };
+
+    let url = push_config.sessions_url();
+    let mut sessions = Vec::new();
+    loop {
+        let mut request = client.get(&url).query(&[
+            filter.clone(),
+            ("limit", PAGE_SIZE.to_string()),
+            ("offset", sessions.len().to_string()),
+        ]);
+        if let Some(ref token) = push_config.api_token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .with_context(|| format!("Failed to reach {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Listing sessions failed with status {}: {}",
+                response.status(),
+                response.text().unwrap_or_else(|_| "unknown error".to_string())
+            );
+        }
+        let page: Vec<RemoteSession> = response.json().context("Invalid session list from server")?;
+        let done = page.len() < PAGE_SIZE;
+        sessions.extend(page);
+        if done {
+            return Ok(sessions);
+        }
+    }
+}
+
+fn fetch_session(client: &Client, push_config: &PushConfig, session: &RemoteSession) -> Result<DevlogOutput> {
+    let url = push_config.session_url(&session.machine_id, &session.session_id);
+    let mut request = client.get(&url);
+    if let Some(ref token) = push_config.api_token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to reach {}", url))?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Fetching session {} failed with status {}: {}",
+            session.session_id,
+            response.status(),
+            response.text().unwrap_or_else(|_| "unknown error".to_string())
+        );
+    }
+
+    // devlog-receiver returns its stored row, whose schema_version is null for
+    // sessions stored before it was kept
+    let mut value: serde_json::Value = response.json().context("Invalid session from server")?;
+    if value.get("schema_version").map(|v| v.is_null()).unwrap_or(true) {
+        value["schema_version"] = serde_json::json!("1.0");
+    }
+    serde_json::from_value(value).with_context(|| format!("Invalid session {} from server", session.session_id))
+}
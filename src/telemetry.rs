@@ -0,0 +1,105 @@
+use crate::config::{Config, TelemetryConfig};
+use anyhow::{Context, Result};
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::trace::{Status, TracerProvider as _};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Exports spans while it lives; dropping it sends the ones still queued
+pub struct Telemetry(Option<SdkTracerProvider>);
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.0.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Warning: Failed to export traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Start exporting spans if `[telemetry]` is enabled. Without it no subscriber
+/// is installed, and spans cost next to nothing.
+pub fn init() -> Telemetry {
+    let config = Config::load().ok().and_then(|c| c.telemetry).filter(|t| t.enabled);
+    let Some(config) = config else {
+        return Telemetry(None);
+    };
+    match install(&config) {
+        Ok(provider) => Telemetry(Some(provider)),
+        Err(e) => {
+            eprintln!("Warning: Not exporting traces: {:#}", e);
+            Telemetry(None)
+        }
+    }
+}
+
+fn install(config: &TelemetryConfig) -> Result<SdkTracerProvider> {
+    let mut exporter = SpanExporter::builder().with_http();
+    if let Some(ref endpoint) = config.otlp_endpoint {
+        exporter = exporter.with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')));
+    }
+    let exporter = exporter.build().context("Failed to create the OTLP exporter")?;
+
+    let resource = Resource::builder()
+        .with_service_name(config.service_name.clone().unwrap_or_else(|| "devlog".to_string()))
+        .build();
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build();
+
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer("devlog"));
+    tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))
+        .context("Failed to install the trace subscriber")?;
+    Ok(provider)
+}
+
+/// Headers carrying the current span's trace (W3C `traceparent`), so the
+/// server's spans for a push join the client's trace
+pub fn trace_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    TraceContextPropagator::new().inject_context(&Span::current().context(), &mut HeaderInjector(&mut headers));
+    headers
+}
+
+/// Make `span`, the server's span for a request, continue the trace the
+/// client sent in its headers, if any
+pub fn continue_trace(span: &Span, headers: &HeaderMap) {
+    let parent = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    // Fails only when no trace is being exported
+    let _ = span.set_parent(parent);
+}
+
+/// Mark the current span failed, so the error shows in the trace
+pub fn record_error(error: &anyhow::Error) {
+    Span::current().set_status(Status::error(format!("{:#}", error)));
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::config::{Config, TicketsConfig};
+use crate::output::DevlogOutput;
+use crate::parser::ConversationEntry;
+use anyhow::{Context, Result};
+use devlog_core::{forge, TicketLink};
+use regex::Regex;
+
+/// Prefixes written like ticket keys that name standards, algorithms and versions
+const NOT_KEYS: &[&str] = &[
+    "AES", "CVE", "ECMA", "ES", "GPT", "HTTP", "IPV", "ISO", "MD", "PEP", "RFC", "RSA", "SHA", "TLS",
+    "SSL", "UTF", "WCAG",
+];
+
+/// Finds ticket references such as `PROJ-123` and `#456` in prompts
+pub struct TicketLinker {
+    config: TicketsConfig,
+    key: Regex,
+    number: Regex,
+}
+
+impl TicketLinker {
+    pub fn new(config: TicketsConfig) -> Self {
+        Self {
+            config,
+            key: Regex::new(r"\b([A-Z][A-Z0-9]{1,9})-([0-9]+)\b").expect("valid regex"),
+            // Not `&#123;` or a URL's `/#123`
+            number: Regex::new(r"(?:^|[^\w&/#])#([0-9]+)\b").expect("valid regex"),
+        }
+    }
+
+    /// Recognise tickets as the user's config says
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load ticket settings")?;
+        Ok(Self::new(config.tickets.unwrap_or_default()))
+    }
+
+    /// Set the session's tickets from its prompts, and from its branch name
+    /// (e.g. `feature/PROJ-123-login`), in order of first mention
+    pub fn link(&self, output: &mut DevlogOutput) {
+        let mut ids: Vec<String> = Vec::new();
+        if let Some(ref git) = output.git {
+            self.find_keys(&git.branch, &mut ids);
+        }
+        for entry in &output.conversation {
+            if let ConversationEntry::User { content, .. } = entry {
+                self.find_keys(content, &mut ids);
+                for number in self.number.captures_iter(content) {
+                    push_unique(&mut ids, format!("#{}", &number[1]));
+                }
+            }
+        }
+
+        let remote = output.git.as_ref().and_then(|git| git.remote.as_deref());
+        output.tickets = ids
+            .into_iter()
+            .map(|id| {
+                let url = match id.strip_prefix('#') {
+                    Some(number) => remote.and_then(|r| forge::issue_url(r, number)),
+                    None => self.config.url.as_ref().map(|url| url.replace("{id}", &id)),
+                };
+                TicketLink { id, url }
+            })
+            .collect();
+    }
+
+    fn find_keys(&self, text: &str, ids: &mut Vec<String>) {
+        for key in self.key.captures_iter(text) {
+            let prefix = &key[1];
+            let wanted = if self.config.keys.is_empty() {
+                !NOT_KEYS.contains(&prefix)
+            } else {
+                self.config.keys.iter().any(|k| k == prefix)
+            };
+            if wanted {
+                push_unique(ids, key[0].to_string());
+            }
+        }
+    }
+}
+
+fn push_unique(ids: &mut Vec<String>, id: String) {
+    if !ids.contains(&id) {
+        ids.push(id);
+    }
+}
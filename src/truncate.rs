@@ -0,0 +1,59 @@
+use crate::config::{Config, TruncateConfig};
+use crate::output::DevlogOutput;
+use crate::redact::{self, TextKind};
+use anyhow::{Context, Result};
+
+/// Cuts prompts, responses and tool summaries down to the `[truncate]` limits
+pub struct Truncator {
+    config: TruncateConfig,
+}
+
+impl Truncator {
+    pub fn new(config: TruncateConfig) -> Self {
+        Self { config }
+    }
+
+    /// The limits in the user's config; none when it has no `[truncate]` section
+    pub fn load() -> Result<Self> {
+        let config = Config::load().context("Failed to load truncation limits")?;
+        Ok(Self::new(config.truncate.unwrap_or_default()))
+    }
+
+    fn limit(&self, kind: TextKind) -> Option<usize> {
+        match kind {
+            TextKind::Prompt => self.config.max_prompt_bytes,
+            TextKind::Response => self.config.max_response_bytes,
+            TextKind::ToolSummary => self.config.max_tool_summary_bytes,
+            TextKind::Note | TextKind::CommitSubject => None,
+        }
+    }
+
+    /// Truncate every entry over its limit; returns the number truncated
+    pub fn truncate_output(&self, output: &mut DevlogOutput) -> usize {
+        let mut count = 0;
+        redact::for_each_text(output, |kind, text| {
+            if let Some(limit) = self.limit(kind) {
+                if truncate(text, limit) {
+                    count += 1;
+                }
+            }
+        });
+        count
+    }
+}
+
+/// Keep the first `limit` bytes of `text` (to a character boundary) and mark
+/// the cut with the original size. Returns whether anything was cut.
+pub fn truncate(text: &mut String, limit: usize) -> bool {
+    let original = text.len();
+    if original <= limit {
+        return false;
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(&format!("\n[... truncated {} of {} bytes]", original - end, original));
+    true
+}
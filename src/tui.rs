@@ -0,0 +1,597 @@
+use crate::export::{self, ExportFormat};
+use crate::output::{self, DevlogOutput};
+use crate::parser::ConversationEntry;
+use crate::push;
+use crate::search::{self, DevlogFile};
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const HELP: &str = "q quit · Tab switch pane · / filter or search · n/N next/previous match · e export HTML · t tag · p push";
+
+/// A session in the list, at its latest ingest
+struct Session {
+    machine: String,
+    project: String,
+    /// Every devlog of the session, oldest first; tags are written to all of them
+    paths: Vec<PathBuf>,
+    output: DevlogOutput,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    List,
+    Viewer,
+}
+
+/// What the bottom line is currently editing
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Filter,
+    Search,
+    Tag,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Header,
+    Text,
+    Tools,
+    Note,
+}
+
+struct App {
+    /// Newest first
+    sessions: Vec<Session>,
+    /// Indexes into `sessions` of those matching the filter
+    visible: Vec<usize>,
+    list_state: ListState,
+    focus: Focus,
+    input: Option<Input>,
+    input_text: String,
+    filter: String,
+    search: String,
+    /// The viewer's text, wrapped for `lines_key` (session index, width)
+    lines: Vec<(LineKind, String)>,
+    lines_key: Option<(usize, usize)>,
+    scroll: usize,
+    viewer_height: usize,
+    status: String,
+    /// Set when something may have printed over the screen
+    needs_clear: bool,
+}
+
+/// Browse the sessions of `.devlog`, or of a server storage directory, in the terminal
+pub fn run(files: Vec<DevlogFile>) -> Result<()> {
+    let sessions = load_sessions(files);
+    if sessions.is_empty() {
+        anyhow::bail!("No devlog sessions found");
+    }
+
+    let mut app = App::new(sessions);
+    let mut terminal = ratatui::try_init().context("Failed to set up the terminal")?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+fn load_sessions(files: Vec<DevlogFile>) -> Vec<Session> {
+    let mut by_id: HashMap<(String, String), Session> = HashMap::new();
+    for file in files {
+        let output = match search::read_devlog(&file.path) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Warning: Skipping {}: {}", file.path.display(), e);
+                continue;
+            }
+        };
+        let key = (file.machine.clone(), output.session_id.clone());
+        match by_id.get_mut(&key) {
+            Some(session) => {
+                session.paths.push(file.path);
+                if output.timestamp > session.output.timestamp {
+                    session.output = output;
+                }
+            }
+            None => {
+                by_id.insert(
+                    key,
+                    Session {
+                        machine: file.machine,
+                        project: file.project,
+                        paths: vec![file.path],
+                        output,
+                    },
+                );
+            }
+        }
+    }
+
+    let mut sessions: Vec<Session> = by_id.into_values().collect();
+    for session in &mut sessions {
+        session.paths.sort();
+    }
+    sessions.sort_by(|a, b| b.output.timestamp.cmp(&a.output.timestamp));
+    sessions
+}
+
+impl App {
+    fn new(sessions: Vec<Session>) -> Self {
+        let mut app = Self {
+            visible: Vec::new(),
+            sessions,
+            list_state: ListState::default(),
+            focus: Focus::List,
+            input: None,
+            input_text: String::new(),
+            filter: String::new(),
+            search: String::new(),
+            lines: Vec::new(),
+            lines_key: None,
+            scroll: 0,
+            viewer_height: 0,
+            status: HELP.to_string(),
+            needs_clear: false,
+        };
+        app.apply_filter();
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            if self.needs_clear {
+                terminal.clear()?;
+                self.needs_clear = false;
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list_state.selected().and_then(|i| self.visible.get(i).copied())
+    }
+
+    /// Returns false to quit
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        if let Some(input) = self.input {
+            self.handle_input(input, key);
+            return true;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::List => Focus::Viewer,
+                    Focus::Viewer => Focus::List,
+                }
+            }
+            KeyCode::Char('/') => {
+                self.input = Some(match self.focus {
+                    Focus::List => Input::Filter,
+                    Focus::Viewer => Input::Search,
+                });
+                self.input_text = match self.focus {
+                    Focus::List => self.filter.clone(),
+                    Focus::Viewer => String::new(),
+                };
+            }
+            KeyCode::Char('e') => self.export(),
+            KeyCode::Char('t') => {
+                self.input = Some(Input::Tag);
+                self.input_text.clear();
+            }
+            KeyCode::Char('p') => self.push(),
+            KeyCode::Char('n') => self.next_match(true),
+            KeyCode::Char('N') => self.next_match(false),
+            _ => match self.focus {
+                Focus::List => self.handle_list_key(key.code),
+                Focus::Viewer => self.handle_viewer_key(key.code),
+            },
+        }
+        true
+    }
+
+    fn handle_list_key(&mut self, code: KeyCode) {
+        let count = self.visible.len();
+        if count == 0 {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0);
+        let next = match code {
+            KeyCode::Down | KeyCode::Char('j') => (current + 1).min(count - 1),
+            KeyCode::Up | KeyCode::Char('k') => current.saturating_sub(1),
+            KeyCode::PageDown => (current + 10).min(count - 1),
+            KeyCode::PageUp => current.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => count - 1,
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                self.focus = Focus::Viewer;
+                current
+            }
+            _ => current,
+        };
+        if next != current {
+            self.list_state.select(Some(next));
+            self.scroll = 0;
+        }
+    }
+
+    fn handle_viewer_key(&mut self, code: KeyCode) {
+        let page = self.viewer_height.max(1);
+        match code {
+            KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += page,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(page),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => self.scroll = usize::MAX,
+            KeyCode::Esc | KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::List,
+            _ => {}
+        }
+        // Clamped against the wrapped text when drawn
+    }
+
+    fn handle_input(&mut self, input: Input, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                if input == Input::Filter {
+                    // Restore the filter that was in place before editing
+                    let filter = self.filter.clone();
+                    self.set_filter(filter);
+                }
+                self.input = None;
+            }
+            KeyCode::Enter => {
+                self.input = None;
+                let text = std::mem::take(&mut self.input_text);
+                match input {
+                    Input::Filter => self.set_filter(text),
+                    Input::Search => {
+                        self.search = text;
+                        self.scroll = self.scroll.saturating_sub(1);
+                        self.next_match(true);
+                    }
+                    Input::Tag => self.tag(&text),
+                }
+            }
+            KeyCode::Backspace => {
+                self.input_text.pop();
+                if input == Input::Filter {
+                    self.filter_live();
+                }
+            }
+            KeyCode::Char(c) => {
+                self.input_text.push(c);
+                if input == Input::Filter {
+                    self.filter_live();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Narrow the list while the filter is typed, without committing it
+    fn filter_live(&mut self) {
+        let committed = std::mem::replace(&mut self.filter, self.input_text.clone());
+        self.apply_filter();
+        self.filter = committed;
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.apply_filter();
+    }
+
+    /// Every word of the filter must match the project, machine, date, session ID or a tag
+    fn apply_filter(&mut self) {
+        let previous = self.selected();
+        let terms: Vec<String> = self.filter.split_whitespace().map(str::to_lowercase).collect();
+        self.visible = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| {
+                terms.iter().all(|term| {
+                    s.project.to_lowercase().contains(term.as_str())
+                        || s.machine.to_lowercase().contains(term.as_str())
+                        || s.output.timestamp.starts_with(term.as_str())
+                        || s.output.session_id.starts_with(term.as_str())
+                        || s.output.tags.iter().any(|t| t.to_lowercase() == *term)
+                })
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let position = previous
+            .and_then(|p| self.visible.iter().position(|&i| i == p))
+            .or(if self.visible.is_empty() { None } else { Some(0) });
+        if position.map(|p| self.visible[p]) != previous {
+            self.scroll = 0;
+        }
+        self.list_state.select(position);
+    }
+
+    /// Scroll to the next (or previous) viewer line containing the search text
+    fn next_match(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+        let needle = self.search.to_lowercase();
+        let matches: Vec<usize> = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, text))| text.to_lowercase().contains(&needle))
+            .map(|(i, _)| i)
+            .collect();
+        let found = if forward {
+            matches.iter().find(|&&i| i > self.scroll).or(matches.first())
+        } else {
+            matches.iter().rev().find(|&&i| i < self.scroll).or(matches.last())
+        };
+        match found {
+            Some(&line) => {
+                self.scroll = line;
+                self.focus = Focus::Viewer;
+                self.status = format!("\"{}\": {} matching lines", self.search, matches.len());
+            }
+            None => self.status = format!("\"{}\" not found", self.search),
+        }
+    }
+
+    fn export(&mut self) {
+        let Some(index) = self.selected() else {
+            return;
+        };
+        let session = &self.sessions[index];
+        let latest = session.paths.last().expect("a session has at least one file");
+        let path = PathBuf::from(latest.file_stem().unwrap_or_default()).with_extension("html");
+        let result = export::export_session(&session.output, ExportFormat::Html).and_then(|html| {
+            std::fs::write(&path, html).with_context(|| format!("Failed to write {}", path.display()))
+        });
+        self.status = match result {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Export failed: {:#}", e),
+        };
+    }
+
+    /// Add the given tags, or remove those written as `-tag`
+    fn tag(&mut self, text: &str) {
+        let Some(index) = self.selected() else {
+            return;
+        };
+        let session = &mut self.sessions[index];
+        let change = |output: &mut DevlogOutput| {
+            for word in text.split_whitespace() {
+                match word.strip_prefix('-') {
+                    Some(tag) => output.tags.retain(|t| t != tag),
+                    None if !output.has_tag(word) => output.tags.push(word.to_string()),
+                    None => {}
+                }
+            }
+        };
+
+        let result = session.paths.iter().try_for_each(|path| {
+            let mut output = search::read_devlog(path)?;
+            change(&mut output);
+            output::rewrite_output(path, &output)
+        });
+        change(&mut session.output);
+        self.status = match result {
+            Ok(()) if session.output.tags.is_empty() => "No tags; p pushes the change".to_string(),
+            Ok(()) => format!("Tags: {}; p pushes the change", session.output.tags.join(", ")),
+            Err(e) => format!("Tagging failed: {:#}", e),
+        };
+    }
+
+    fn push(&mut self) {
+        let Some(index) = self.selected() else {
+            return;
+        };
+        // push_session reports progress on stderr, over the screen
+        self.needs_clear = true;
+        self.status = match push::push_session(&self.sessions[index].output, self.sessions[index].paths.last().map(PathBuf::as_path)) {
+            Ok(()) => format!("Pushed session {}", self.sessions[index].output.session_id),
+            Err(e) => format!("Push failed: {:#}", e),
+        };
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(frame.area());
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+            .split(rows[0]);
+
+        self.draw_list(frame, panes[0]);
+        self.draw_viewer(frame, panes[1]);
+
+        let status = match self.input {
+            Some(Input::Filter) => format!("Filter (project, machine, date, session, tag): {}_", self.input_text),
+            Some(Input::Search) => format!("Search: {}_", self.input_text),
+            Some(Input::Tag) => format!("Tags to add (-tag removes): {}_", self.input_text),
+            None => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(status).style(Style::default().fg(Color::DarkGray)), rows[1]);
+    }
+
+    fn pane_block(&self, title: String, pane: Focus) -> Block<'static> {
+        let border = if self.focus == pane {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Block::default().borders(Borders::ALL).border_style(border).title(title)
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&i| {
+                let session = &self.sessions[i];
+                let mut spans = vec![
+                    Span::styled(format_time(&session.output.timestamp), Style::default().fg(Color::DarkGray)),
+                    Span::raw("  "),
+                    Span::styled(session.project.clone(), Style::default().fg(Color::Cyan)),
+                ];
+                for tag in &session.output.tags {
+                    spans.push(Span::styled(format!(" #{}", tag), Style::default().fg(Color::Magenta)));
+                }
+                let prompt = first_prompt(&session.output).unwrap_or_default();
+                ListItem::new(vec![Line::from(spans), Line::from(format!("  {}", prompt))])
+            })
+            .collect();
+
+        let mut title = format!("Sessions ({}/{})", self.visible.len(), self.sessions.len());
+        if !self.filter.is_empty() {
+            title.push_str(&format!(" filter: {}", self.filter));
+        }
+        let list = List::new(items)
+            .block(self.pane_block(title, Focus::List))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.list_state);
+    }
+
+    fn draw_viewer(&mut self, frame: &mut Frame, area: Rect) {
+        let width = area.width.saturating_sub(2) as usize;
+        self.viewer_height = area.height.saturating_sub(2) as usize;
+
+        let Some(index) = self.selected() else {
+            self.lines.clear();
+            self.lines_key = None;
+            frame.render_widget(Paragraph::new("").block(self.pane_block("No session".to_string(), Focus::Viewer)), area);
+            return;
+        };
+        if self.lines_key != Some((index, width)) {
+            self.lines = conversation_lines(&self.sessions[index].output, width.max(10));
+            self.lines_key = Some((index, width));
+        }
+        self.scroll = self.scroll.min(self.lines.len().saturating_sub(self.viewer_height));
+
+        let needle = self.search.to_lowercase();
+        let text: Vec<Line> = self
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(self.viewer_height)
+            .map(|(kind, text)| styled_line(*kind, text, &needle))
+            .collect();
+
+        let session = &self.sessions[index];
+        let short: String = session.output.session_id.chars().take(8).collect();
+        let title = format!("{} · {} · {}", session.project, session.machine, short);
+        frame.render_widget(Paragraph::new(text).block(self.pane_block(title, Focus::Viewer)), area);
+    }
+}
+
+/// The session as lines of at most `width` characters
+fn conversation_lines(output: &DevlogOutput, width: usize) -> Vec<(LineKind, String)> {
+    let mut lines = Vec::new();
+    if let Some(ref git) = output.git {
+        let commit: String = git.commit.chars().take(8).collect();
+        lines.push((LineKind::Tools, format!("Branch {} at {}", git.branch, commit)));
+    }
+    for note in &output.notes {
+        lines.push((LineKind::Header, format!("Note · {}", format_time(&note.timestamp))));
+        push_wrapped(&mut lines, LineKind::Note, &note.text, width);
+    }
+
+    for entry in &output.conversation {
+        match entry {
+            ConversationEntry::User { content, timestamp, .. } => {
+                lines.push((LineKind::Text, String::new()));
+                lines.push((LineKind::Header, format!("You{}", entry_time(timestamp.as_deref()))));
+                push_wrapped(&mut lines, LineKind::Text, content.trim(), width);
+            }
+            ConversationEntry::Assistant { content, timestamp, .. } => {
+                lines.push((LineKind::Text, String::new()));
+                lines.push((LineKind::Header, format!("Claude{}", entry_time(timestamp.as_deref()))));
+                push_wrapped(&mut lines, LineKind::Text, content.trim(), width);
+            }
+            ConversationEntry::ToolSummary { actions } => {
+                push_wrapped(&mut lines, LineKind::Tools, &format!("> {}", actions.join(", ")), width);
+            }
+        }
+    }
+    lines
+}
+
+fn push_wrapped(lines: &mut Vec<(LineKind, String)>, kind: LineKind, text: &str, width: usize) {
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push((kind, String::new()));
+        }
+        for chunk in chars.chunks(width) {
+            lines.push((kind, chunk.iter().collect()));
+        }
+    }
+}
+
+/// A viewer line, with occurrences of the search text highlighted
+fn styled_line<'a>(kind: LineKind, text: &'a str, needle: &str) -> Line<'a> {
+    let style = match kind {
+        LineKind::Header => Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+        LineKind::Text => Style::default(),
+        LineKind::Tools => Style::default().fg(Color::DarkGray),
+        LineKind::Note => Style::default().fg(Color::Yellow),
+    };
+    let highlight = Style::default().fg(Color::Black).bg(Color::Yellow);
+
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths; only highlight when offsets still line up
+    if needle.is_empty() || lower.len() != text.len() || !lower.contains(needle) {
+        return Line::styled(text, style);
+    }
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (pos, _) in lower.match_indices(needle) {
+        if pos < start {
+            continue;
+        }
+        spans.push(Span::styled(&text[start..pos], style));
+        spans.push(Span::styled(&text[pos..pos + needle.len()], highlight));
+        start = pos + needle.len();
+    }
+    spans.push(Span::styled(&text[start..], style));
+    Line::from(spans)
+}
+
+fn first_prompt(output: &DevlogOutput) -> Option<String> {
+    output.conversation.iter().find_map(|entry| match entry {
+        ConversationEntry::User { content, .. } => content.lines().next().map(|l| l.chars().take(80).collect()),
+        _ => None,
+    })
+}
+
+fn entry_time(timestamp: Option<&str>) -> String {
+    timestamp
+        .map(|ts| format!(" · {}", format_time(ts)))
+        .unwrap_or_default()
+}
+
+fn format_time(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}